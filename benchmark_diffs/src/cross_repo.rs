@@ -48,7 +48,7 @@ pub fn windowed_commits_compare(
             (
                 preprocessed
                     .pre_process_with_limit(
-                        &mut x.configured_repo.clone().fetch(),
+                        &mut x.configured_repo.clone().fetch().unwrap(),
                         x.before,
                         x.before,
                         // x.dir_path,
@@ -11,11 +11,14 @@ use tower::ServiceBuilder;
 use tower_http::trace::TraceLayer;
 
 use crate::{
-    commit, fetch, file, pull_requests, querying,
+    commit, fetch, file, pull_requests, querying, sarif,
     scripting::{self, ScriptContent, ScriptContentDepth, ScriptingError, ScriptingParam},
     smells, track, tsg, view, SharedState,
 };
 
+#[cfg(feature = "query_analysis")]
+use crate::query_analysis;
+
 impl IntoResponse for ScriptingError {
     fn into_response(self) -> Response {
         let mut resp = Json(self).into_response();
@@ -76,11 +79,11 @@ pub fn scripting_app(_st: SharedState) -> Router<SharedState> {
         .layer(TraceLayer::new_for_http());
     Router::new()
         .route(
-            "/script/github/:user/:name/:commit",
+            "/script/:forge/:user/:name/:commit",
             post(scripting).layer(scripting_service_config.clone()),
         )
         .route(
-            "/script-depth/github/:user/:name/:commit",
+            "/script-depth/:forge/:user/:name/:commit",
             post(scripting_depth).layer(scripting_service_config.clone()),
         )
         .route("/sharing-scripts/shared-db", get(crate::ws::connect_db))
@@ -149,6 +152,50 @@ async fn querying_streamed(
     querying::streamed(state, path, script)
 }
 
+async fn querying_many(
+    axum::extract::Path(path): axum::extract::Path<querying::Param>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+    axum::extract::Json(script): axum::extract::Json<querying::ContentMany>,
+) -> axum::response::Result<Json<querying::ComputeResultsMany>> {
+    let r = querying::many(script, state, path)?;
+    Ok(Json(r))
+}
+
+async fn querying_sarif(
+    axum::extract::Path(path): axum::extract::Path<querying::Param>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+    axum::extract::Json(script): axum::extract::Json<querying::ContentMany>,
+) -> axum::response::Result<Json<sarif::SarifLog>> {
+    let r = sarif::export(script, state, path)?;
+    Ok(Json(r))
+}
+
+async fn smells_sarif(
+    axum::extract::Path(path): axum::extract::Path<querying::Param>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+    axum::extract::Json(smells): axum::extract::Json<sarif::Smells>,
+) -> axum::response::Result<Json<sarif::SarifLog>> {
+    let r = sarif::export_smells(smells, state, path)?;
+    Ok(Json(r))
+}
+
+#[cfg(feature = "query_analysis")]
+async fn querying_grammar(
+    axum::extract::Path(path): axum::extract::Path<query_analysis::Param>,
+) -> axum::response::Result<Json<&'static polyglote::query_analysis::Grammar>> {
+    let r = query_analysis::grammar(path)?;
+    Ok(Json(r))
+}
+
+#[cfg(feature = "query_analysis")]
+async fn querying_analysis(
+    axum::extract::Path(path): axum::extract::Path<query_analysis::Param>,
+    axum::extract::Json(content): axum::extract::Json<query_analysis::Content>,
+) -> axum::response::Result<Json<query_analysis::Analysis>> {
+    let r = query_analysis::analyse(content, path)?;
+    Ok(Json(r))
+}
+
 async fn querying_differential(
     axum::extract::Path(path): axum::extract::Path<querying::ParamDifferential>,
     axum::extract::State(state): axum::extract::State<SharedState>,
@@ -170,17 +217,29 @@ pub fn querying_app(_st: SharedState) -> Router<SharedState> {
         // .request_body_limit(1024 * 5_000 /* ~5mb */)
         .timeout(Duration::from_secs(10))
         .layer(TraceLayer::new_for_http());
-    Router::new()
+    let router = Router::new()
         .route(
-            "/query/github/:user/:name/*commit",
+            "/query/:forge/:user/:name/*commit",
             post(querying).layer(querying_service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
-            "/query-st/github/:user/:name/*commit",
+            "/query-st/:forge/:user/:name/*commit",
             post(querying_streamed).layer(querying_service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
-            "/query-differential/github/:user/:name/:commit/:baseline",
+            "/query-many/:forge/:user/:name/*commit",
+            post(querying_many).layer(querying_service_config.clone()),
+        )
+        .route(
+            "/query-sarif/:forge/:user/:name/*commit",
+            post(querying_sarif).layer(querying_service_config.clone()),
+        )
+        .route(
+            "/smells-sarif/:forge/:user/:name/*commit",
+            post(smells_sarif).layer(querying_service_config.clone()),
+        )
+        .route(
+            "/query-differential/:forge/:user/:name/:commit/:baseline",
             post(querying_differential).layer(querying_service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
@@ -190,7 +249,18 @@ pub fn querying_app(_st: SharedState) -> Router<SharedState> {
         .route(
             "/sharing-queries/shared/:session",
             get(crate::ws::connect_doc), // .with_state(Arc::clone(&shared_state)),
+        );
+    #[cfg(feature = "query_analysis")]
+    let router = router
+        .route(
+            "/query-grammar/:language",
+            get(querying_grammar).layer(querying_service_config.clone()),
         )
+        .route(
+            "/query-analysis/:language",
+            post(querying_analysis).layer(querying_service_config.clone()),
+        );
+    router
 }
 
 async fn tsg(
@@ -216,7 +286,7 @@ pub fn tsg_app(_st: SharedState) -> Router<SharedState> {
         .layer(TraceLayer::new_for_http());
     Router::new()
         .route(
-            "/tsg/github/:user/:name/:commit",
+            "/tsg/:forge/:user/:name/:commit",
             post(tsg).layer(tsg_service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
@@ -246,6 +316,15 @@ async fn smells_ex_from_diffs(
     Ok(r)
 }
 
+async fn smells_eval(
+    axum::extract::Path(path): axum::extract::Path<smells::evaluation::Param>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+    axum::extract::Json(evaluation): axum::extract::Json<smells::evaluation::Evaluation>,
+) -> axum::response::Result<Json<smells::evaluation::EvaluationResults>> {
+    let r = smells::evaluation::evaluate(evaluation, state, path)?;
+    Ok(r)
+}
+
 pub fn smells_app(_st: SharedState) -> Router<SharedState> {
     let smells_service_config = ServiceBuilder::new()
         .layer(HandleErrorLayer::new(|e: BoxError| async move {
@@ -260,13 +339,17 @@ pub fn smells_app(_st: SharedState) -> Router<SharedState> {
         .layer(TraceLayer::new_for_http());
     Router::new()
         .route(
-            "/smells/github/:user/:name/:commit/:len",
+            "/smells/:forge/:user/:name/:commit/:len",
             post(smells).layer(smells_service_config.clone()),
         )
         .route(
-            "/smells_ex_from_diffs/github/:user/:name/:commit/:len",
+            "/smells_ex_from_diffs/:forge/:user/:name/:commit/:len",
             post(smells_ex_from_diffs).layer(smells_service_config.clone()),
         )
+        .route(
+            "/smells_eval/:forge/:user/:name",
+            post(smells_eval).layer(smells_service_config.clone()),
+        )
 }
 
 pub fn fetch_git_file(_st: SharedState) -> Router<SharedState> {
@@ -282,7 +365,7 @@ pub fn fetch_git_file(_st: SharedState) -> Router<SharedState> {
         .timeout(Duration::from_secs(10))
         .layer(TraceLayer::new_for_http());
     Router::new().route(
-        "/file/github/:user/:name/:commit/*file",
+        "/file/:forge/:user/:name/:commit/*file",
         get(file).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
     )
 }
@@ -310,15 +393,15 @@ pub fn track_code_route(_st: SharedState) -> Router<SharedState> {
         .layer(TraceLayer::new_for_http());
     Router::new()
         .route(
-            "/track/github/:user/:name/:commit/*file",
+            "/track/:forge/:user/:name/:commit/*file",
             get(track_code).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
-            "/track_at_path/github/:user/:name/:commit/*path",
+            "/track_at_path/:forge/:user/:name/:commit/*path",
             get(track_code_at_path).layer(service_config.clone()),
         )
         .route(
-            "/track_at_path_with_changes/github/:user/:name/:commit/*path",
+            "/track_at_path_with_changes/:forge/:user/:name/:commit/*path",
             get(track_code_at_path_with_changes).layer(service_config.clone()),
         )
 }
@@ -366,15 +449,17 @@ pub fn view_code_route(_st: SharedState) -> Router<SharedState> {
         .layer(TraceLayer::new_for_http());
     Router::new()
         .route(
-            "/view/github/:user/:name/:commit/*path",
+            "/view/:forge/:user/:name/:commit/*path",
             get(view_code).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
-            "/view/github/:user/:name/:commit/",
+            "/view/:forge/:user/:name/:commit/",
             get(view_code).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
+        // the node id is named like the forge of the routes above, as axum rejects
+        // differently named parameters at the same position
         .route(
-            "/view/:id",
+            "/view/:forge",
             get(view_code_with_node_id).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
 }
@@ -408,11 +493,11 @@ pub fn fetch_code_route(_st: SharedState) -> Router<SharedState> {
         .layer(TraceLayer::new_for_http());
     Router::new()
         .route(
-            "/fetch/github/:user/:name/:commit/*path",
+            "/fetch/:forge/:user/:name/:commit/*path",
             get(fetch_code).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
-            "/fetch/github/:user/:name/:commit/",
+            "/fetch/:forge/:user/:name/:commit/",
             get(fetch_code).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
@@ -482,15 +567,15 @@ pub fn commit_metadata_route(_st: SharedState) -> Router<SharedState> {
         .layer(TraceLayer::new_for_http());
     Router::new()
         .route(
-            "/commit/github/:user/:name/:version",
+            "/commit/:forge/:user/:name/:version",
             get(commit_metadata).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
-            "/pr/github/:user/:name/:version",
+            "/pr/:forge/:user/:name/:version",
             get(pull_requests::pr_commits).layer(service_config.clone()),
         )
         .route(
-            "/fork/github/:user/:name/:other_user/:other_name/:head",
+            "/fork/:forge/:user/:name/:other_user/:other_name/:head",
             post(add_remote).layer(service_config.clone()),
         )
 }
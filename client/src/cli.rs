@@ -22,8 +22,18 @@ pub(super) struct Options {
     ///
     /// use the following syntax: <forge>/<user>/<name>:<config>
     /// example: github.com/INRIA/spoon:Java
+    ///
+    /// or for a local repository, possibly bare: <path>:<config>
+    /// example: /home/me/spoon:Java
+    ///
+    /// local repositories are served under the `local` forge,
+    /// with the url encoded path of the parent directory as user
     #[clap(short, long)]
     pub repository: Vec<RepoConfig>,
+
+    /// load processed commits from this snapshot at startup, and save new ones to it on shutdown
+    #[clap(long)]
+    pub snapshot: Option<std::path::PathBuf>,
 }
 
 pub(super) struct RepoConfig {
@@ -35,7 +45,7 @@ impl std::str::FromStr for RepoConfig {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (repo, config) = s.split_once(":").ok_or("")?;
+        let (repo, config) = s.rsplit_once(":").ok_or("")?;
         let repo = repo.parse()?;
         let config = config.parse()?;
 
@@ -1,12 +1,13 @@
 use axum::Json;
 // use hyper_ast::types::LabelStore;
-use hyper_ast_cvs_git::git::{fetch_github_repository, retrieve_commit};
+use hyper_ast_cvs_git::git::{retrieve_commit, Forge};
 use serde::{Deserialize, Serialize};
 
 use crate::SharedState;
 
 #[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Param {
+    pub forge: Forge,
     pub user: String,
     pub name: String,
     /// either a commit id or a tag
@@ -33,13 +34,15 @@ pub struct Metadata {
 }
 
 // TODO prefetch a list of parent ids in power of 2 [2,4,8,16,32]
-pub fn commit_metadata(_state: SharedState, path: Param) -> Result<Json<Metadata>, String> {
+pub fn commit_metadata(state: SharedState, path: Param) -> Result<Json<Metadata>, String> {
     let Param {
+        forge,
         user,
         name,
         version,
     } = path.clone();
-    let repo = fetch_github_repository(&format!("{}/{}", user, name));
+    let repo_spec = forge.repo(user.clone(), name.clone());
+    let repo = crate::utils::fetch_registered(&state, repo_spec)?.repo;
     log::debug!("done cloning {user}/{name}");
     let commit = retrieve_commit(&repo, &version);
     if let Err(err) = &commit {
@@ -111,6 +114,7 @@ impl From<BuffOut> for String {
 
 #[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
 pub struct ParamRemote {
+    pub forge: Forge,
     pub user: String,
     pub name: String,
     pub other_user: String,
@@ -118,18 +122,31 @@ pub struct ParamRemote {
     pub head: String,
 }
 
-pub fn add_remote(_state: SharedState, path: ParamRemote) -> Result<(), String> {
+pub fn add_remote(state: SharedState, path: ParamRemote) -> Result<(), String> {
     let ParamRemote {
+        forge,
         user,
         name,
         other_user,
         other_name,
         head,
     } = path.clone();
-    let repo = fetch_github_repository(&format!("{}/{}", user, name));
-    let remote = format!("{}{}/{}", "https://github.com/", user, name);
+    let repo_spec = forge.repo(user, name);
+    let repo = crate::utils::fetch_registered(&state, repo_spec.clone())?.repo;
+    // a local fork is another repository on the same filesystem, that must also be registered
+    let remote = match forge {
+        Forge::Local => {
+            let fork = forge.repo(other_user.clone(), other_name.clone());
+            if state.repositories.read().unwrap().get_config(fork.clone()).is_none() {
+                return Err(format!("{} is not a registered repository", fork));
+            }
+            fork.url()
+        }
+        _ => repo_spec.url(),
+    };
     log::error!("{:?}", &remote);
-    let other = format!("{}_{}", other_user, other_name);
+    // the user of a local repository is a path
+    let other = format!("{}_{}", other_user, other_name).replace('/', "_");
     let r = repo.remote(&other, &remote);
 
     let r = match r {
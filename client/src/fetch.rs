@@ -17,6 +17,7 @@ use crate::{app::Timed, SharedState};
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct Parameters {
+    forge: hyper_ast_cvs_git::git::Forge,
     user: String,
     name: String,
     commit: String,
@@ -53,20 +54,21 @@ pub struct FetchedNodes {
 pub fn fetch(mut state: SharedState, path: Parameters) -> Result<FetchedNodes, String> {
     let now = Instant::now();
     let Parameters {
+        forge,
         user,
         name,
         commit,
         path,
     } = path;
     dbg!(&path);
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_spec = forge.repo(user, name);
     let repo = state
         .repositories
         .read()
         .unwrap()
         .get_config(repo_spec)
         .ok_or_else(|| "missing config for repository".to_string())?;
-    let mut repo = repo.fetch();
+    let mut repo = repo.fetch()?;
     log::info!("done cloning {}", repo.spec);
 
     let commits = crate::utils::handle_pre_processing(&state, &mut repo, "", &commit, 2)
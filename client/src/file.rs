@@ -6,6 +6,7 @@ use crate::{utils, SharedState};
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct FetchFileParam {
+    forge: hyper_ast_cvs_git::git::Forge,
     user: String,
     name: String,
     commit: String,
@@ -15,19 +16,20 @@ pub struct FetchFileParam {
 pub fn from_hyper_ast(state: SharedState, path: FetchFileParam) -> Result<String, String> {
     let now = Instant::now();
     let FetchFileParam {
+        forge,
         user,
         name,
         commit,
         file,
     } = path.clone();
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_spec = forge.repo(user, name);
     let repo = state
         .repositories
         .write()
         .unwrap()
         .get_config(repo_spec)
         .ok_or_else(|| "missing config for repository".to_string())?;
-    let mut repo = repo.fetch();
+    let mut repo = repo.fetch()?;
     log::debug!("done cloning {}", repo.spec);
     let commits = utils::handle_pre_processing(&state, &mut repo, "", &commit, 2).map_err(|e| e.to_string())?;
     // let commits = state
@@ -31,7 +31,10 @@ mod fetch;
 mod file;
 mod matching;
 mod pull_requests;
+#[cfg(feature = "query_analysis")]
+mod query_analysis;
 mod querying;
+mod sarif;
 mod scripting;
 mod smells;
 mod track;
@@ -126,6 +129,19 @@ async fn main() {
     {
         use hyper_ast_cvs_git::processing::RepoConfig;
         let mut repos = shared_state.repositories.write().unwrap();
+        if let Some(path) = opts.snapshot.as_ref().filter(|x| x.exists()) {
+            log::info!("loading snapshot {}", path.display());
+            if let Err(e) = repos.load_snapshot(path) {
+                // appending to it on shutdown would make things worse, let the user move it away
+                log::error!("failed to load snapshot {}: {}", path.display(), e);
+                eprintln!(
+                    "failed to load snapshot {}: {}, remove it or pass another --snapshot path",
+                    path.display(),
+                    e
+                );
+                std::process::exit(1)
+            }
+        }
         repos.register_config(Forge::Github.repo("INRIA", "spoon"), RepoConfig::JavaMaven);
         repos.register_config(Forge::Github.repo("google", "gson"), RepoConfig::JavaMaven);
         repos.register_config(
@@ -172,6 +188,18 @@ async fn main() {
     .with_graceful_shutdown(shutdown_signal())
     .await
     .unwrap();
+    if let Some(path) = &opts.snapshot {
+        let mut repos = shared_state.repositories.write().unwrap();
+        let r = if path.exists() {
+            repos.append_to_snapshot(path)
+        } else {
+            repos.save_snapshot(path)
+        };
+        match r {
+            Ok(()) => log::info!("saved snapshot {}", path.display()),
+            Err(e) => log::error!("failed to save snapshot {}: {}", path.display(), e),
+        }
+    }
 }
 pub(crate) use hyper_ast_cvs_git::no_space;
 /// axum handler for any request that fails to match the router routes.
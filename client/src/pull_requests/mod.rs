@@ -53,6 +53,13 @@ pub(super) async fn pr_commits(
     axum::extract::Path(path): axum::extract::Path<commit::Param>,
     axum::extract::State(state): axum::extract::State<SharedState>,
 ) -> Result<PrData, String> {
+    match path.forge {
+        hyper_ast_cvs_git::git::Forge::Github => (),
+        hyper_ast_cvs_git::git::Forge::Local => return local_pr_commits(&state, &path),
+        hyper_ast_cvs_git::git::Forge::Gitlab => {
+            return Err("pull requests are not retrieved from gitlab".to_string())
+        }
+    }
     if let Some(x) = state.pr_cache.read().unwrap().get(&path) {
         let data = serde_json::from_str(&x.0).map_err(|e| e.to_string())?;
         return format_result(&data);
@@ -99,6 +106,36 @@ pub(super) async fn pr_commits(
     }
 }
 
+/// Without a forge to ask, the pull request is read from the merge commit itself,
+/// its second parent being the head of the merged branch.
+fn local_pr_commits(state: &crate::AppState, path: &commit::Param) -> Result<PrData, String> {
+    let repo = crate::utils::fetch_registered(
+        state,
+        path.forge.repo(path.user.clone(), path.name.clone()),
+    )?
+    .repo;
+    let merge =
+        hyper_ast_cvs_git::git::retrieve_commit(&repo, &path.version).map_err(|e| e.to_string())?;
+    let head = merge
+        .parent_id(1)
+        .map_err(|_| "not a merge commit".to_string())?;
+    Ok(PrData {
+        merge_commit: Some(Commit {
+            id: merge.id().to_string(),
+            user: path.user.clone(),
+            name: path.name.clone(),
+        }),
+        head_commit: Commit {
+            id: head.to_string(),
+            user: path.user.clone(),
+            name: path.name.clone(),
+        },
+        title: merge.summary().unwrap_or_default().to_string(),
+        // local merges are not numbered
+        number: 0,
+    })
+}
+
 fn format_result(data: &repo_view::ResponseData) -> Result<PrData, String> {
     let a: &repo_view::RepoViewSearchNodes =
         data.search.nodes.as_ref().unwrap()[0].as_ref().unwrap();
@@ -0,0 +1,125 @@
+//! Diagnostics and completions for queries being written, see [`polyglote::query_analysis`].
+//!
+//! The [`Grammar`] of a language can also be fetched, to analyse queries directly in an editor.
+use std::sync::OnceLock;
+
+use polyglote::query_analysis::{self, Completion, Diagnostic, Grammar};
+use serde::{Deserialize, Serialize};
+
+use crate::querying::QueryingError;
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Param {
+    language: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Content {
+    pub query: String,
+    /// byte offset where completions are requested
+    pub cursor: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct Analysis {
+    pub diagnostics: Vec<Diagnostic>,
+    pub completions: Vec<Completion>,
+}
+
+/// Grammars are computed on first use, it takes a while.
+///
+/// Covers the languages of [`hyper_ast_cvs_git::resolve_language`].
+pub fn grammar(path: Param) -> Result<&'static Grammar, QueryingError> {
+    static JAVA: OnceLock<Grammar> = OnceLock::new();
+    static CPP: OnceLock<Grammar> = OnceLock::new();
+    static XML: OnceLock<Grammar> = OnceLock::new();
+    static TS: OnceLock<Grammar> = OnceLock::new();
+    static PYTHON: OnceLock<Grammar> = OnceLock::new();
+    static TSQUERY: OnceLock<Grammar> = OnceLock::new();
+    let (cell, language, node_types, name) = match path.language.as_str() {
+        "Java" | "java" => (
+            &JAVA,
+            hyper_ast_gen_ts_java::language(),
+            hyper_ast_gen_ts_java::node_types(),
+            "java",
+        ),
+        "Cpp" | "cpp" => (
+            &CPP,
+            hyper_ast_gen_ts_cpp::language(),
+            hyper_ast_gen_ts_cpp::node_types(),
+            "cpp",
+        ),
+        "Xml" | "xml" => (
+            &XML,
+            hyper_ast_gen_ts_xml::language(),
+            hyper_ast_gen_ts_xml::node_types(),
+            "xml",
+        ),
+        "Ts" | "ts" | "Typescript" | "typescript" => (
+            &TS,
+            hyper_ast_gen_ts_ts::language(),
+            hyper_ast_gen_ts_ts::node_types(),
+            "typescript",
+        ),
+        "Python" | "python" => (
+            &PYTHON,
+            hyper_ast_gen_ts_python::language(),
+            hyper_ast_gen_ts_python::node_types(),
+            "python",
+        ),
+        "Query" | "query" | "tsquery" => (
+            &TSQUERY,
+            hyper_ast_gen_ts_tsquery::language(),
+            hyper_ast_gen_ts_tsquery::node_types(),
+            "tsquery",
+        ),
+        _ => return Err(QueryingError::MissingLanguage(path.language)),
+    };
+    if let Some(grammar) = cell.get() {
+        return Ok(grammar);
+    }
+    let lang = polyglote::Lang {
+        language,
+        name,
+        node_types,
+        highlights: "",
+        tags: "",
+        injects: "",
+    };
+    let grammar =
+        Grammar::from_language(&lang).map_err(|e| QueryingError::ProcessingError(e.to_string()))?;
+    Ok(cell.get_or_init(|| grammar))
+}
+
+pub fn analyse(content: Content, path: Param) -> Result<Analysis, QueryingError> {
+    let grammar = grammar(path)?;
+    let diagnostics = query_analysis::validate(grammar, &content.query);
+    let completions = content
+        .cursor
+        .map(|cursor| query_analysis::complete(grammar, &content.query, cursor))
+        .unwrap_or_default();
+    Ok(Analysis {
+        diagnostics,
+        completions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grammar_of_each_language() {
+        for language in ["Java", "Cpp", "Xml", "Ts", "Python", "tsquery"] {
+            assert!(hyper_ast_cvs_git::resolve_language(language).is_some());
+            let path = Param {
+                language: language.to_string(),
+            };
+            assert!(grammar(path).is_ok(), "{}", language);
+        }
+        let path = Param {
+            language: "Cobol".to_string(),
+        };
+        assert!(grammar(path).is_err());
+    }
+}
@@ -16,6 +16,7 @@ use std::time::Instant;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Param {
+    forge: hyper_ast_cvs_git::git::Forge,
     user: String,
     name: String,
     commit: String,
@@ -27,21 +28,21 @@ pub struct Content {
     pub query: String,
     pub commits: usize,
     // TODO disable the incriminated pattern for subsequent matches
-    /// checked per individual match
+    /// checked per individual match, or per commit when matches are memoized
     /// if triggered on first search (ie. first commit searched) it return directly
     /// if triggered later, divide the numer of commits remaining to analyze by 2 each time (ie. `commits`` field)
     #[serde(default = "default_max_matches")]
     pub max_matches: u64,
-    /// checked each match (in milli seconds)
+    /// checked each match, or per commit when matches are memoized (in milli seconds)
     #[serde(default = "default_timeout")]
     pub timeout: u64,
 }
 
-fn default_max_matches() -> u64 {
+pub(crate) fn default_max_matches() -> u64 {
     500
 }
 
-fn default_timeout() -> u64 {
+pub(crate) fn default_timeout() -> u64 {
     1000
 }
 
@@ -51,6 +52,7 @@ pub enum QueryingError {
     MissingLanguage(String),
     ParsingError(String),
     MatchingErrOnFirst(MatchingError<ComputeResultIdentified>),
+    MatchingErrOnFirstMany(MatchingError<ComputeResultMany>),
     MatchingError(MatchingError<ComputeResult>),
 }
 
@@ -86,6 +88,22 @@ pub struct ComputeResultIdentified {
 pub struct ComputeResult {
     pub compute_time: f64,
     pub result: Vec<u64>,
+    /// values requested with `(#set! @capture key)`, see [`hyper_ast_tsquery::metadata`]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub metadata: Vec<MetadataValue>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MetadataValue {
+    /// index of the enabled pattern, as in [`ComputeResult::result`]
+    pub pattern: u16,
+    /// index of the match among the matches of `pattern`, in the order of the traversal,
+    /// values of the same match share it
+    pub match_index: u64,
+    pub capture: String,
+    pub key: &'static str,
+    /// `None` if the captured node does not store this metadata
+    pub value: Option<u64>,
 }
 impl ComputeResult {
     fn with(self, commit_oid: &Oid) -> ComputeResultIdentified {
@@ -97,6 +115,9 @@ impl ComputeResult {
 }
 
 const INCREMENTAL_QUERIES: bool = true;
+/// reuse the match counts of subtrees that did not change between commits,
+/// unless the query requests metadata values, as they are given per match
+const MEMOIZED_QUERIES: bool = true;
 
 pub fn simple(
     query: Content,
@@ -104,7 +125,7 @@ pub fn simple(
     path: Param,
 ) -> Result<ComputeResults, QueryingError> {
     let now = Instant::now();
-    let Param { user, name, commit } = path.clone();
+    let Param { forge, user, name, commit } = path.clone();
     let mut additional = commit.split("/");
     let commit = additional.next().unwrap();
     let Content {
@@ -120,27 +141,19 @@ pub fn simple(
         hyper_ast_cvs_git::processing::RepoConfig::JavaMaven
     } else if language == "Cpp" {
         hyper_ast_cvs_git::processing::RepoConfig::JavaMaven
+    } else if language == "Ts" {
+        hyper_ast_cvs_git::processing::RepoConfig::TsNpm
+    } else if language == "Python" {
+        hyper_ast_cvs_git::processing::RepoConfig::PythonPip
     } else {
         hyper_ast_cvs_git::processing::RepoConfig::Any
     };
     let language: tree_sitter::Language = hyper_ast_cvs_git::resolve_language(&language)
         .ok_or_else(|| QueryingError::MissingLanguage(language))?;
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
-    let repo = state
-        .repositories
-        .read()
-        .unwrap()
-        .get_config(repo_spec.clone());
-    let repo = match repo {
-        Some(repo) => repo,
-        None => {
-            let configs = &mut state.repositories.write().unwrap();
-            configs.register_config(repo_spec.clone(), config);
-            log::error!("missing config for {}", repo_spec);
-            configs.get_config(repo_spec.clone()).unwrap()
-        }
-    };
-    let mut repo = repo.fetch();
+    let repo_spec = forge.repo(user, name);
+    let repo = crate::utils::get_or_register_config(&state, repo_spec, config)
+        .map_err(QueryingError::ProcessingError)?;
+    let mut repo = repo.fetch().map_err(QueryingError::ProcessingError)?;
     log::warn!("done cloning {}", &repo.spec);
     let commits = crate::utils::handle_pre_processing(&state, &mut repo, "", &commit, commits)
         .map_err(|x| QueryingError::ProcessingError(x.to_string()))?;
@@ -159,6 +172,9 @@ pub fn simple(
     }
     .map_err(|e| QueryingError::ParsingError(e.to_string()))?;
 
+    let memoized = MEMOIZED_QUERIES && !hyper_ast_tsquery::metadata::requests_metadata(&query);
+    let mut query = hyper_ast_tsquery::memoized::MatchCounts::new(query);
+
     log::info!("done query construction");
     let prepare_time = now.elapsed().as_secs_f64();
     let mut results = vec![];
@@ -178,7 +194,11 @@ pub fn simple(
         let commit = repositories.get_commit(&repo.config, commit_oid).unwrap();
         let code = commit.ast_root;
         let stores = &repositories.processor.main_stores;
-        let result = simple_aux(stores, code, &query, timeout, max_matches);
+        let result = if memoized {
+            memoized_aux(stores, code, &mut query, timeout, max_matches)
+        } else {
+            simple_aux(stores, code, query.query(), timeout, max_matches)
+        };
         let result = match result {
             Ok(inner) => Ok(inner.with(commit_oid)),
             Err(err) if results.is_empty() => {
@@ -203,6 +223,225 @@ pub fn simple(
     })
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ContentMany {
+    /// each query only runs on the files of its language
+    pub queries: Vec<LanguageQuery>,
+    pub commits: usize,
+    /// checked per individual match, for each pattern of each query, like [`Content::max_matches`]
+    #[serde(default = "default_max_matches")]
+    pub max_matches: u64,
+    /// checked each match (in milli seconds), like [`Content::timeout`]
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LanguageQuery {
+    pub language: String,
+    pub query: String,
+}
+
+#[derive(Serialize)]
+pub struct ComputeResultsMany {
+    pub prepare_time: f64,
+    pub matching_error_count: usize,
+    pub results: Vec<Result<ComputeResultMany, MatchingError<ComputeResultMany>>>,
+}
+
+#[derive(Serialize)]
+pub struct ComputeResultMany {
+    pub commit: String,
+    pub compute_time: f64,
+    /// matches of all the queries, merged file by file
+    pub matches: Vec<MatchMany>,
+}
+
+#[derive(Serialize)]
+pub struct MatchMany {
+    /// index in [`ContentMany::queries`]
+    pub query: usize,
+    pub pattern: usize,
+    pub captures: Vec<CaptureMany>,
+}
+
+#[derive(Serialize)]
+pub struct CaptureMany {
+    pub name: String,
+    pub file: String,
+    pub start: usize,
+    pub end: usize,
+    /// 0-based
+    pub start_line: usize,
+    pub end_line: usize,
+    pub path: Vec<u16>,
+}
+
+impl From<hyper_ast_cvs_git::search::Match> for MatchMany {
+    fn from(m: hyper_ast_cvs_git::search::Match) -> Self {
+        MatchMany {
+            query: m.query_index,
+            pattern: m.pattern_index,
+            captures: m
+                .captures
+                .into_iter()
+                .map(|c| CaptureMany {
+                    name: c.name,
+                    file: c.file.to_string_lossy().to_string(),
+                    start: c.bytes.start,
+                    end: c.bytes.end,
+                    start_line: c.lines.start,
+                    end_line: c.lines.end,
+                    path: c.path,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<hyper_ast_cvs_git::search::SearchError> for QueryingError {
+    fn from(e: hyper_ast_cvs_git::search::SearchError) -> Self {
+        use hyper_ast_cvs_git::search::SearchError;
+        match e {
+            SearchError::MissingLanguage(l) => QueryingError::MissingLanguage(l),
+            SearchError::ParsingError(e) => QueryingError::ParsingError(e),
+            e @ SearchError::MissingCommit(_) => QueryingError::ProcessingError(e.to_string()),
+        }
+    }
+}
+
+/// Runs a query per language on the same commits, eg. a query on poms with one on java files.
+pub fn many(
+    content: ContentMany,
+    state: SharedState,
+    path: Param,
+) -> Result<ComputeResultsMany, QueryingError> {
+    let now = Instant::now();
+    let ContentMany {
+        queries,
+        commits,
+        max_matches,
+        timeout,
+    } = content;
+    let timeout = std::time::Duration::from_millis(timeout);
+    let mut proc_commit_limit = commits;
+    let (repo, commits) = pre_repo_many(&state, &path, &queries, commits)?;
+    let queries: Vec<_> = queries
+        .iter()
+        .map(|q| (q.language.as_str(), q.query.as_str()))
+        .collect();
+    let prepare_time = now.elapsed().as_secs_f64();
+    let mut results = vec![];
+    let mut matching_error_count = 0;
+    for commit_oid in &commits {
+        if results.len() > proc_commit_limit {
+            break;
+        }
+        let repositories = state.repositories.read().unwrap();
+        let matches =
+            hyper_ast_cvs_git::search::search_many(&repositories, &repo, *commit_oid, &queries)?;
+        let result = many_aux(matches, commit_oid, timeout, max_matches);
+        let result = match result {
+            Ok(inner) => Ok(inner),
+            Err(err) if results.is_empty() => {
+                return Err(QueryingError::MatchingErrOnFirstMany(err));
+            }
+            Err(err) => {
+                matching_error_count += 1;
+                proc_commit_limit /= 2;
+                Err(err)
+            }
+        };
+        results.push(result);
+    }
+    log::info!("done querying of {commits:?} in  {}", repo.spec);
+    Ok(ComputeResultsMany {
+        prepare_time,
+        matching_error_count,
+        results,
+    })
+}
+
+fn many_aux(
+    matches: hyper_ast_cvs_git::search::Matches<'_>,
+    commit_oid: &Oid,
+    timeout: std::time::Duration,
+    max_matches: u64,
+) -> Result<ComputeResultMany, MatchingError<ComputeResultMany>> {
+    let now = Instant::now();
+    // per query and pattern
+    let mut counts: std::collections::HashMap<(usize, usize), u64> = Default::default();
+    let mut result = vec![];
+    for m in matches {
+        let count = counts.entry((m.query_index, m.pattern_index)).or_default();
+        *count += 1;
+        let count = *count;
+        result.push(MatchMany::from(m));
+        let compute_time = now.elapsed();
+        if compute_time >= timeout {
+            return Err(MatchingError::TimeOut(ComputeResultMany {
+                commit: commit_oid.to_string(),
+                compute_time: compute_time.as_secs_f64(),
+                matches: result,
+            }));
+        } else if count > max_matches {
+            return Err(MatchingError::MaxMatches(ComputeResultMany {
+                commit: commit_oid.to_string(),
+                compute_time: compute_time.as_secs_f64(),
+                matches: result,
+            }));
+        }
+    }
+    Ok(ComputeResultMany {
+        commit: commit_oid.to_string(),
+        compute_time: now.elapsed().as_secs_f64(),
+        matches: result,
+    })
+}
+
+/// Checks the languages of `queries`, then processes the `commits` of the repository in `path`.
+pub(crate) fn pre_repo_many(
+    state: &SharedState,
+    path: &Param,
+    queries: &[LanguageQuery],
+    commits: usize,
+) -> Result<(hyper_ast_cvs_git::processing::ConfiguredRepo2, Vec<Oid>), QueryingError> {
+    let Param { forge, user, name, commit } = path.clone();
+    let mut additional = commit.split("/");
+    let commit = additional.next().unwrap();
+    for q in queries {
+        hyper_ast_cvs_git::resolve_language(&q.language)
+            .ok_or_else(|| QueryingError::MissingLanguage(q.language.clone()))?;
+    }
+    let language = queries.first().map(|q| q.language.as_str());
+    // queries on several languages need all the files, whatever the build system
+    let config = if queries
+        .iter()
+        .any(|q| Some(q.language.as_str()) != language)
+    {
+        hyper_ast_cvs_git::processing::RepoConfig::Any
+    } else if language == Some("Java") {
+        hyper_ast_cvs_git::processing::RepoConfig::JavaMaven
+    } else if language == Some("Cpp") {
+        hyper_ast_cvs_git::processing::RepoConfig::CppMake
+    } else if language == Some("Ts") {
+        hyper_ast_cvs_git::processing::RepoConfig::TsNpm
+    } else if language == Some("Python") {
+        hyper_ast_cvs_git::processing::RepoConfig::PythonPip
+    } else {
+        hyper_ast_cvs_git::processing::RepoConfig::Any
+    };
+    let repo_spec = forge.repo(user, name);
+    let repo = crate::utils::get_or_register_config(&state, repo_spec, config)
+        .map_err(QueryingError::ProcessingError)?;
+    let mut repo = repo.fetch().map_err(QueryingError::ProcessingError)?;
+    log::warn!("done cloning {}", &repo.spec);
+    let commits = crate::utils::handle_pre_processing(state, &mut repo, "", &commit, commits)
+        .map_err(|x| QueryingError::ProcessingError(x.to_string()))?;
+    log::info!("done construction of {commits:?} in  {}", repo.spec);
+    Ok((repo, commits))
+}
+
 pub fn streamed(mut state: SharedState, path: Param, content: Content) -> axum::response::Response {
     let now = Instant::now();
 
@@ -266,6 +505,8 @@ pub fn streamed(mut state: SharedState, path: Param, content: Content) -> axum::
         .try_into()
         .unwrap(),
     );
+    let memoized = MEMOIZED_QUERIES && !hyper_ast_tsquery::metadata::requests_metadata(&query);
+    let mut query = hyper_ast_tsquery::memoized::MatchCounts::new(query);
 
     let it = commits
         .into_iter() // TODO use chunks to reduce presure on state.repositories' lock, need some bench before doing this opt ;)
@@ -284,7 +525,11 @@ pub fn streamed(mut state: SharedState, path: Param, content: Content) -> axum::
             let commit = repositories.get_commit(&repo.config, &commit_oid).unwrap();
             let code = commit.ast_root;
             let stores = &repositories.processor.main_stores;
-            let result = simple_aux(stores, code, &query, timeout, max_matches);
+            let result = if memoized {
+                memoized_aux(stores, code, &mut query, timeout, max_matches)
+            } else {
+                simple_aux(stores, code, query.query(), timeout, max_matches)
+            };
             let result = match result {
                 Ok(inner) => Ok(inner.with(&commit_oid)),
                 Err(err) => {
@@ -331,7 +576,7 @@ fn pre_repo(
     content: &Content,
 ) -> Result<(hyper_ast_cvs_git::processing::ConfiguredRepo2, Vec<Oid>), Box<dyn std::error::Error>>
 {
-    let Param { user, name, commit } = path.clone();
+    let Param { forge, user, name, commit } = path.clone();
     let mut additional = commit.split("/");
     let commit = additional.next().unwrap();
     let Content {
@@ -345,25 +590,16 @@ fn pre_repo(
         hyper_ast_cvs_git::processing::RepoConfig::JavaMaven
     } else if language == "Cpp" {
         hyper_ast_cvs_git::processing::RepoConfig::JavaMaven
+    } else if language == "Ts" {
+        hyper_ast_cvs_git::processing::RepoConfig::TsNpm
+    } else if language == "Python" {
+        hyper_ast_cvs_git::processing::RepoConfig::PythonPip
     } else {
         hyper_ast_cvs_git::processing::RepoConfig::Any
     };
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
-    let repo = state
-        .repositories
-        .read()
-        .unwrap()
-        .get_config(repo_spec.clone());
-    let repo = match repo {
-        Some(repo) => repo,
-        None => {
-            let configs = &mut state.repositories.write().unwrap();
-            configs.register_config(repo_spec.clone(), config);
-            log::error!("missing config for {}", repo_spec);
-            configs.get_config(repo_spec.clone()).unwrap()
-        }
-    };
-    let repo = repo.fetch();
+    let repo_spec = forge.repo(user, name);
+    let repo = crate::utils::get_or_register_config(&state, repo_spec, config)?;
+    let repo = repo.fetch()?;
     log::warn!("done cloning {}", &repo.spec);
     let afters = [commit].into_iter().chain(additional.into_iter());
     let rw = crate::utils::walk_commits_multi(&repo, afters)?.take(commits);
@@ -379,7 +615,7 @@ fn pre_query(
     path: &Param,
     content: &Content,
 ) -> Result<hyper_ast_tsquery::Query, QueryingError> {
-    let Param { user, name, commit } = path.clone();
+    let Param { user, name, commit, .. } = path.clone();
     let mut additional = commit.split("/");
     let commit = additional.next().unwrap();
     let Content {
@@ -393,6 +629,10 @@ fn pre_query(
         hyper_ast_cvs_git::processing::RepoConfig::JavaMaven
     } else if language == "Cpp" {
         hyper_ast_cvs_git::processing::RepoConfig::JavaMaven
+    } else if language == "Ts" {
+        hyper_ast_cvs_git::processing::RepoConfig::TsNpm
+    } else if language == "Python" {
+        hyper_ast_cvs_git::processing::RepoConfig::PythonPip
     } else {
         hyper_ast_cvs_git::processing::RepoConfig::Any
     };
@@ -422,19 +662,31 @@ fn simple_aux(
 ) -> Result<ComputeResult, MatchingError<ComputeResult>> {
     let pos = hyper_ast::position::StructuralPosition::new(code);
     let cursor = hyper_ast_tsquery::hyperast::TreeCursor::new(stores, pos);
-    let qcursor = query.matches(cursor);
+    let predicates = hyper_ast_tsquery::metadata::predicates();
+    let qcursor = query.matches_with(cursor, &predicates);
     let now = Instant::now();
     let mut result = vec![0; query.enabled_pattern_count()];
+    let mut metadata = vec![];
     for m in qcursor {
         let i = m.pattern_index;
         let i = query.enabled_pattern_index(i).unwrap();
         result[i as usize] += 1;
+        for (c, key, value) in hyper_ast_tsquery::metadata::requested_metadata(query, &m) {
+            metadata.push(MetadataValue {
+                pattern: i,
+                match_index: result[i as usize] - 1,
+                capture: query.capture_name(c).to_owned(),
+                key: key.as_str(),
+                value,
+            });
+        }
         let compute_time = now.elapsed();
         if compute_time >= timeout {
             let compute_time = now.elapsed().as_secs_f64();
             return Err(MatchingError::TimeOut(ComputeResult {
                 result,
                 compute_time,
+                metadata,
             }));
         } else if result[i as usize] > max_matches {
             // TODO disable the pattern, return the new query
@@ -442,6 +694,7 @@ fn simple_aux(
             return Err(MatchingError::MaxMatches(ComputeResult {
                 result,
                 compute_time,
+                metadata,
             }));
         }
 
@@ -462,9 +715,43 @@ fn simple_aux(
     Ok(ComputeResult {
         result,
         compute_time,
+        metadata,
     })
 }
 
+/// Same as [`simple_aux`], but only goes through the subtrees not already counted in previous commits.
+///
+/// The timeout and the max number of matches are checked after each file.
+fn memoized_aux(
+    stores: &hyper_ast::store::SimpleStores<hyper_ast_cvs_git::TStore>,
+    code: NodeIdentifier,
+    query: &mut hyper_ast_tsquery::memoized::MatchCounts<NodeIdentifier>,
+    timeout: std::time::Duration,
+    max_matches: u64,
+) -> Result<ComputeResult, MatchingError<ComputeResult>> {
+    use hyper_ast_tsquery::memoized::Interrupted;
+    let now = Instant::now();
+    let result = query.count_with_limits(stores, code, timeout, max_matches);
+    let compute_time = now.elapsed().as_secs_f64();
+    match result {
+        Ok(result) => Ok(ComputeResult {
+            result,
+            compute_time,
+            metadata: vec![],
+        }),
+        Err((Interrupted::TimeOut, result)) => Err(MatchingError::TimeOut(ComputeResult {
+            result,
+            compute_time,
+            metadata: vec![],
+        })),
+        Err((Interrupted::MaxMatches, result)) => Err(MatchingError::MaxMatches(ComputeResult {
+            result,
+            compute_time,
+            metadata: vec![],
+        })),
+    }
+}
+
 #[derive(Serialize)]
 pub struct ComputeResultsDifferential {
     pub prepare_time: f64,
@@ -472,6 +759,7 @@ pub struct ComputeResultsDifferential {
 }
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ParamDifferential {
+    forge: hyper_ast_cvs_git::git::Forge,
     user: String,
     name: String,
     commit: String,
@@ -485,6 +773,7 @@ pub fn differential(
 ) -> Result<Json<ComputeResultsDifferential>, QueryingError> {
     let now = Instant::now();
     let ParamDifferential {
+        forge,
         user,
         name,
         commit,
@@ -502,27 +791,19 @@ pub fn differential(
         hyper_ast_cvs_git::processing::RepoConfig::JavaMaven
     } else if language == "Cpp" {
         hyper_ast_cvs_git::processing::RepoConfig::JavaMaven
+    } else if language == "Ts" {
+        hyper_ast_cvs_git::processing::RepoConfig::TsNpm
+    } else if language == "Python" {
+        hyper_ast_cvs_git::processing::RepoConfig::PythonPip
     } else {
         hyper_ast_cvs_git::processing::RepoConfig::Any
     };
     let language: tree_sitter::Language = hyper_ast_cvs_git::resolve_language(&language)
         .ok_or_else(|| QueryingError::MissingLanguage(language))?;
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
-    let repo = state
-        .repositories
-        .read()
-        .unwrap()
-        .get_config(repo_spec.clone());
-    let repo = match repo {
-        Some(repo) => repo,
-        None => {
-            let configs = &mut state.repositories.write().unwrap();
-            configs.register_config(repo_spec.clone(), config);
-            log::error!("missing config for {}", repo_spec);
-            configs.get_config(repo_spec.clone()).unwrap()
-        }
-    };
-    let mut repo = repo.fetch();
+    let repo_spec = forge.repo(user, name);
+    let repo = crate::utils::get_or_register_config(&state, repo_spec, config)
+        .map_err(QueryingError::ProcessingError)?;
+    let mut repo = repo.fetch().map_err(QueryingError::ProcessingError)?;
     log::info!("done cloning {}", &repo.spec);
     let commit = crate::utils::handle_pre_processing(&state, &mut repo, "", &commit, 1)
         .map_err(|x| QueryingError::ProcessingError(x.to_string()))?[0];
@@ -719,7 +1000,8 @@ fn differential_aux(
 > {
     let pos = hyper_ast::position::StructuralPosition::new(code);
     let cursor = hyper_ast_tsquery::hyperast::TreeCursor::new(stores, pos);
-    let qcursor = query.matches(cursor);
+    let predicates = hyper_ast_tsquery::metadata::predicates();
+    let qcursor = query.matches_with(cursor, &predicates);
     let now = Instant::now();
     assert_eq!(
         query.enabled_pattern_count(),
@@ -0,0 +1,466 @@
+//! Export the matches of queries as a [SARIF 2.1.0](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html) log,
+//! the format uploaded to code-scanning tools.
+//!
+//! Each pattern of each query is a rule, each match is a result located at its topmost capture.
+//! The queries of the smells found by [`crate::smells::smells`] can also be exported with [`export_smells`].
+use std::{collections::HashMap, path::PathBuf};
+
+use hyper_ast::{
+    position::row_col::RowCol,
+    store::defaults::NodeIdentifier,
+    types::{Children, HyperAST, HyperType, NodeStore, WithChildren},
+};
+use hyper_ast_cvs_git::{
+    git::Oid,
+    search::{Capture, Match},
+    SimpleStores,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    querying::{pre_repo_many, ContentMany, LanguageQuery, Param, QueryingError},
+    smells::SearchResult,
+    SharedState,
+};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+#[derive(Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    tool: Tool,
+    version_control_provenance: Vec<VersionControlDetails>,
+    /// HyperAST columns are counted in bytes, see [`columns_to_code_points`]
+    column_kind: &'static str,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: ToolComponent,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolComponent {
+    name: &'static str,
+    information_uri: &'static str,
+    rules: Vec<Rule>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: String,
+    short_description: Message,
+    full_description: Message,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionControlDetails {
+    repository_uri: String,
+    revision_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    rule_index: usize,
+    message: Message,
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+}
+
+#[derive(Serialize, Clone)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+/// Lines and columns are 1-based
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+    byte_offset: usize,
+    byte_length: usize,
+}
+
+/// The rules of `queries`, with the index of the first rule of each query
+fn rules(queries: &[LanguageQuery]) -> Result<(Vec<Rule>, Vec<usize>), QueryingError> {
+    let mut rules = vec![];
+    let mut firsts = vec![];
+    for (i, q) in queries.iter().enumerate() {
+        let language = hyper_ast_cvs_git::resolve_language(&q.language)
+            .ok_or_else(|| QueryingError::MissingLanguage(q.language.clone()))?;
+        let query = hyper_ast_tsquery::Query::new(&q.query, language)
+            .map_err(|e| QueryingError::ParsingError(e.to_string()))?;
+        firsts.push(rules.len());
+        let starts = query.get_each_pat_start_byte();
+        for (j, start) in starts.iter().enumerate() {
+            let end = starts.get(j + 1).copied().unwrap_or(q.query.len());
+            rules.push(Rule {
+                id: rule_id(i, j),
+                short_description: Message {
+                    text: format!("pattern {} of the {} query {}", j, q.language, i),
+                },
+                full_description: Message {
+                    text: q.query[*start..end].trim().to_string(),
+                },
+            });
+        }
+    }
+    Ok((rules, firsts))
+}
+
+fn rule_id(query: usize, pattern: usize) -> String {
+    format!("query{}/pattern{}", query, pattern)
+}
+
+/// Converts the columns of `c` from bytes to code points, `text` being the content of its file
+fn columns_to_code_points(c: &mut Capture, text: &str) {
+    let convert = |byte: usize, col: usize| {
+        byte.checked_sub(col)
+            .and_then(|line_start| text.get(line_start..byte))
+            .map_or(col, |line| line.chars().count())
+    };
+    c.start = RowCol::new(c.start.row(), convert(c.bytes.start, c.start.col()));
+    c.end = RowCol::new(c.end.row(), convert(c.bytes.end, c.end.col()));
+}
+
+/// Text of the file containing the node at `path`, starting from the `root` of a commit
+fn file_text(stores: &SimpleStores, root: NodeIdentifier, path: &[u16]) -> Option<String> {
+    let mut x = root;
+    for o in path {
+        if stores.resolve_type(&x).is_file() {
+            break;
+        }
+        let n = stores.node_store().resolve(&x);
+        x = *n.children()?.get(*o)?;
+    }
+    if !stores.resolve_type(&x).is_file() {
+        return None;
+    }
+    Some(hyper_ast::nodes::TextSerializer::new(stores, x).to_string())
+}
+
+fn result(m: Match, firsts: &[usize]) -> SarifResult {
+    let location = |c: Capture| Location {
+        physical_location: PhysicalLocation {
+            artifact_location: ArtifactLocation {
+                uri: c.file.to_string_lossy().to_string(),
+            },
+            region: Region {
+                start_line: c.start.row() + 1,
+                start_column: c.start.col() + 1,
+                end_line: c.end.row() + 1,
+                end_column: c.end.col() + 1,
+                byte_offset: c.bytes.start,
+                byte_length: c.bytes.len(),
+            },
+        },
+        message: Some(Message { text: c.name }),
+    };
+    let mut captures = m.captures;
+    // the topmost capture is the one with the shortest path
+    let top = (0..captures.len()).min_by_key(|i| captures[*i].path.len());
+    let locations: Vec<_> = top
+        .map(|i| captures.remove(i))
+        .into_iter()
+        .map(location)
+        .collect();
+    let text = match locations.first().and_then(|l| l.message.as_ref()) {
+        Some(name) => format!("matched @{}", name.text),
+        None => "matched".to_string(),
+    };
+    SarifResult {
+        rule_id: rule_id(m.query_index, m.pattern_index),
+        rule_index: firsts[m.query_index] + m.pattern_index,
+        message: Message { text },
+        locations,
+        related_locations: captures.into_iter().map(location).collect(),
+    }
+}
+
+/// Runs the queries of `content` like [`crate::querying::many`], with a SARIF run per commit.
+///
+/// `max_matches` and `timeout` are not checked, a run lists all the results of its commit.
+pub fn export(
+    content: ContentMany,
+    state: SharedState,
+    path: Param,
+) -> Result<SarifLog, QueryingError> {
+    let ContentMany {
+        queries, commits, ..
+    } = content;
+    let (rules, firsts) = self::rules(&queries)?;
+    let (repo, commits) = pre_repo_many(&state, &path, &queries, commits)?;
+    let search_queries: Vec<_> = queries
+        .iter()
+        .map(|q| (q.language.as_str(), q.query.as_str()))
+        .collect();
+    let mut runs = vec![];
+    for commit_oid in &commits {
+        let results = {
+            let repositories = state.repositories.read().unwrap();
+            let stores = &repositories.processor.main_stores;
+            let matches = hyper_ast_cvs_git::search::search_many(
+                &repositories,
+                &repo,
+                *commit_oid,
+                &search_queries,
+            )?;
+            let root = repositories
+                .get_commit(&repo.config, commit_oid)
+                .unwrap()
+                .ast_root;
+            // files are only serialized once per commit
+            let mut texts: HashMap<PathBuf, Option<String>> = HashMap::new();
+            matches
+                .map(|mut m| {
+                    for c in &mut m.captures {
+                        let text = texts
+                            .entry(c.file.clone())
+                            .or_insert_with(|| file_text(stores, root, &c.path));
+                        if let Some(text) = text {
+                            columns_to_code_points(c, text);
+                        }
+                    }
+                    result(m, &firsts)
+                })
+                .collect()
+        };
+        runs.push(run(&repo.spec, commit_oid, &rules, results));
+    }
+    Ok(SarifLog {
+        schema: SCHEMA,
+        version: "2.1.0",
+        runs,
+    })
+}
+
+/// Body of [`export_smells`]
+#[derive(Deserialize, Clone)]
+pub struct Smells {
+    /// the language of the queries of the smells
+    pub language: String,
+    pub smells: Vec<SearchResult>,
+    pub commits: usize,
+}
+
+/// Locates the smells found by [`crate::smells::smells`] like [`export`], each smell query being a rule.
+pub fn export_smells(
+    content: Smells,
+    state: SharedState,
+    path: Param,
+) -> Result<SarifLog, QueryingError> {
+    let Smells {
+        language,
+        smells,
+        commits,
+    } = content;
+    let queries = smells
+        .into_iter()
+        .map(|smell| LanguageQuery {
+            language: language.clone(),
+            query: smell.query,
+        })
+        .collect();
+    let content = ContentMany {
+        queries,
+        commits,
+        max_matches: crate::querying::default_max_matches(),
+        timeout: crate::querying::default_timeout(),
+    };
+    export(content, state, path)
+}
+
+fn run(
+    repo: &hyper_ast_cvs_git::git::Repo,
+    commit: &Oid,
+    rules: &[Rule],
+    results: Vec<SarifResult>,
+) -> Run {
+    Run {
+        tool: Tool {
+            driver: ToolComponent {
+                name: "HyperAST",
+                information_uri: "https://github.com/HyperAST/HyperAST",
+                rules: rules.to_vec(),
+            },
+        },
+        version_control_provenance: vec![VersionControlDetails {
+            repository_uri: repo.url(),
+            revision_id: commit.to_string(),
+        }],
+        column_kind: "unicodeCodePoints",
+        results,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_hand_built_match() {
+        let text = "/* é */ int x;\n";
+        let capture = |name: &str, bytes: std::ops::Range<usize>, path: Vec<u16>| Capture {
+            name: name.to_string(),
+            file: "src/A.java".into(),
+            lines: 0..1,
+            start: RowCol::new(0, bytes.start),
+            end: RowCol::new(0, bytes.end),
+            bytes,
+            path,
+        };
+        let mut m = Match {
+            query_index: 1,
+            pattern_index: 0,
+            captures: vec![
+                capture("name", 13..14, vec![0, 1, 2]),
+                capture("decl", 9..14, vec![0, 1]),
+            ],
+        };
+        for c in &mut m.captures {
+            columns_to_code_points(c, text);
+        }
+        let repo = hyper_ast_cvs_git::git::Forge::Github.repo("user", "name");
+        let oid = Oid::from_str("0123456789abcdef0123456789abcdef01234567").unwrap();
+        let run = run(&repo, &oid, &[], vec![result(m, &[0, 2])]);
+        let run = serde_json::to_value(&run).unwrap();
+
+        let provenance = &run["versionControlProvenance"][0];
+        assert_eq!(provenance["repositoryUri"], "https://github.com/user/name");
+        assert_eq!(provenance["revisionId"], oid.to_string());
+        assert_eq!(run["columnKind"], "unicodeCodePoints");
+        let result = &run["results"][0];
+        assert_eq!(result["ruleId"], "query1/pattern0");
+        assert_eq!(result["ruleIndex"], 2);
+        // the topmost capture, `é` takes 2 bytes but 1 code point
+        let location = &result["locations"][0];
+        assert_eq!(location["message"]["text"], "decl");
+        let region = &location["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 1);
+        assert_eq!(region["startColumn"], 9);
+        assert_eq!(region["endLine"], 1);
+        assert_eq!(region["endColumn"], 14);
+        assert_eq!(region["byteOffset"], 9);
+        assert_eq!(region["byteLength"], 5);
+        assert_eq!(
+            location["physicalLocation"]["artifactLocation"]["uri"],
+            "src/A.java"
+        );
+        let related = &result["relatedLocations"][0];
+        assert_eq!(related["message"]["text"], "name");
+        assert_eq!(related["physicalLocation"]["region"]["startColumn"], 13);
+    }
+
+    #[test]
+    fn export_fixture_commit() {
+        use hyper_ast_cvs_git::fixtures::{commit_files, fixture_dir, init_fixture};
+        let path = fixture_dir("sarif_export");
+        init_fixture(&path);
+        let b = "package fixture;\n\nclass B {\n    /* é */ void g() {}\n}\n";
+        let oid = commit_files(&path, &[("src/main/java/fixture/B.java", b.as_bytes())]);
+        let repo = hyper_ast_cvs_git::git::Repo::local(&path).unwrap();
+        let param: Param = serde_json::from_value(serde_json::json!({
+            "forge": "local",
+            "user": repo.user,
+            "name": repo.name,
+            "commit": oid.to_string(),
+        }))
+        .unwrap();
+        let content = ContentMany {
+            queries: vec![LanguageQuery {
+                language: "Java".to_string(),
+                query: "(method_declaration) @m".to_string(),
+            }],
+            commits: 1,
+            max_matches: 500,
+            timeout: 1000,
+        };
+        let Ok(log) = export(content, SharedState::default(), param) else {
+            panic!("the fixture should be processed and queried")
+        };
+        let log = serde_json::to_value(&log).unwrap();
+        let runs = log["runs"].as_array().unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(
+            runs[0]["versionControlProvenance"][0]["revisionId"],
+            oid.to_string()
+        );
+
+        let mut matched = vec![];
+        for result in runs[0]["results"].as_array().unwrap() {
+            let location = &result["locations"][0]["physicalLocation"];
+            let uri = location["artifactLocation"]["uri"].as_str().unwrap();
+            let text = std::fs::read_to_string(path.join(uri)).unwrap();
+            let region = &location["region"];
+            let start = region["byteOffset"].as_u64().unwrap() as usize;
+            let end = start + region["byteLength"].as_u64().unwrap() as usize;
+            // 1-based lines and columns, in code points
+            let line_col = |byte: usize| {
+                let line_start = text[..byte].rfind('\n').map_or(0, |i| i + 1);
+                let line = text[..byte].matches('\n').count() + 1;
+                (line, text[line_start..byte].chars().count() + 1)
+            };
+            let region_start = (region["startLine"].as_u64(), region["startColumn"].as_u64());
+            let region_end = (region["endLine"].as_u64(), region["endColumn"].as_u64());
+            let as_u64 = |(l, c): (usize, usize)| (Some(l as u64), Some(c as u64));
+            assert_eq!(region_start, as_u64(line_col(start)));
+            assert_eq!(region_end, as_u64(line_col(end)));
+            matched.push((uri.to_string(), text[start..end].to_string(), region_start));
+        }
+        matched.sort();
+        assert_eq!(
+            matched,
+            vec![
+                (
+                    "src/main/java/fixture/A.java".to_string(),
+                    "int f(int x) {\n        return x + 1;\n    }".to_string(),
+                    (Some(4), Some(5)),
+                ),
+                (
+                    "src/main/java/fixture/B.java".to_string(),
+                    "void g() {}".to_string(),
+                    // `é` takes 2 bytes but 1 code point
+                    (Some(4), Some(13)),
+                ),
+            ]
+        );
+    }
+}
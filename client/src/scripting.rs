@@ -27,6 +27,7 @@ use std::fmt::Display;
 
 #[derive(Deserialize, Clone)]
 pub struct ScriptingParam {
+    forge: hyper_ast_cvs_git::git::Forge,
     user: String,
     name: String,
     commit: String,
@@ -124,7 +125,7 @@ pub fn simple_depth(
         commits,
     } = script;
     let now = Instant::now();
-    let ScriptingParam { user, name, commit } = path.clone();
+    let ScriptingParam { forge, user, name, commit } = path.clone();
     let mut engine = Engine::new();
     engine.disable_symbol("/");
     add_utils(&mut engine);
@@ -137,26 +138,14 @@ pub fn simple_depth(
     let accumulate_script = engine.compile(script.accumulate.clone()).map_err(|x| {
         ScriptingError::AtCompilation(format!("Acc: {}, {}", x, script.accumulate.clone()))
     })?;
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
-    let repo = state
-        .repositories
-        .write()
-        .unwrap()
-        .get_config(repo_spec.clone());
-    let repo = match repo {
-        Some(repo) => repo,
-        None => {
-            let configs = &mut state.repositories.write().unwrap();
-            configs.register_config(
-                repo_spec.clone(),
-                hyper_ast_cvs_git::processing::RepoConfig::JavaMaven,
-            );
-            log::error!("missing config for {}", repo_spec);
-            configs.get_config(repo_spec.clone()).unwrap()
-        }
-    };
-    // .ok_or_else(|| ScriptingError::Other("missing config for repository".to_string()))?;
-    let mut repo = repo.fetch();
+    let repo_spec = forge.repo(user, name);
+    let repo = crate::utils::get_or_register_config(
+        &state,
+        repo_spec,
+        hyper_ast_cvs_git::processing::RepoConfig::JavaMaven,
+    )
+    .map_err(ScriptingError::Other)?;
+    let mut repo = repo.fetch().map_err(ScriptingError::Other)?;
     log::warn!("done cloning {}", &repo.spec);
     let commits = state
         .repositories
@@ -209,7 +198,7 @@ fn simple_prepare(
     ),
     ScriptingError,
 > {
-    let ScriptingParam { user, name, commit } = path.clone();
+    let ScriptingParam { forge, user, name, commit } = path.clone();
     let mut engine = Engine::new();
     engine.disable_symbol("/");
     add_utils(&mut engine);
@@ -222,14 +211,14 @@ fn simple_prepare(
     let accumulate_script = engine.compile(script.accumulate.clone()).map_err(|x| {
         ScriptingError::AtCompilation(format!("Acc: {}, {}", x, script.accumulate.clone()))
     })?;
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_spec = forge.repo(user, name);
     let repo = state
         .repositories
         .write()
         .unwrap()
         .get_config(repo_spec)
         .ok_or_else(|| ScriptingError::Other("missing config for repository".to_string()))?;
-    let repo = repo.fetch();
+    let repo = repo.fetch().map_err(ScriptingError::Other)?;
     log::warn!("done cloning {}", &repo.spec);
     Ok((
         commit,
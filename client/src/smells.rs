@@ -4,6 +4,7 @@ use axum::Json;
 use code2query::QueryLattice;
 use hashbrown::HashSet;
 use hyper_ast::position::position_accessors::SolvedPosition;
+use hyper_ast::store::defaults::NodeIdentifier;
 use hyper_ast::{
     position::{
         position_accessors::{RootedPosition, WithPreOrderOffsets},
@@ -11,6 +12,7 @@ use hyper_ast::{
     },
     types::Children,
 };
+use hyper_ast_cvs_git::processing::RepoConfig;
 use hyper_diff::actions::Actions;
 use serde::{Deserialize, Serialize};
 use tokio::time::Instant;
@@ -23,10 +25,13 @@ mod code2query;
 
 mod diffing;
 
+pub(crate) mod evaluation;
+
 type Idx = u16;
 
 #[derive(Deserialize, Clone)]
 pub struct Param {
+    forge: hyper_ast_cvs_git::git::Forge,
     user: String,
     name: String,
     commit: String,
@@ -35,6 +40,7 @@ pub struct Param {
 
 #[derive(Deserialize, Clone)]
 pub struct Diffs {
+    forge: hyper_ast_cvs_git::git::Forge,
     user: String,
     name: String,
     commit: String,
@@ -119,6 +125,67 @@ enum QueryGenKind {
 }
 const QUERY_GENERATOR: QueryGenKind = QueryGenKind::Advanced2;
 
+/// The languages in which smells can be searched, given by the config of the repository.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SmellsLanguage {
+    Java,
+    Cpp,
+}
+
+impl SmellsLanguage {
+    pub(crate) fn of(config: &RepoConfig) -> Result<Self, String> {
+        match config {
+            RepoConfig::JavaMaven | RepoConfig::JavaGradle => Ok(Self::Java),
+            RepoConfig::CppMake | RepoConfig::CppCMake => Ok(Self::Cpp),
+            config => Err(format!(
+                "smells are not supported for {:?} repositories",
+                config
+            )),
+        }
+    }
+
+    pub(crate) fn language(self) -> tree_sitter::Language {
+        match self {
+            Self::Java => hyper_ast_gen_ts_java::language(),
+            Self::Cpp => hyper_ast_gen_ts_cpp::language(),
+        }
+    }
+
+    /// the queries precomputed while generating the code
+    pub(crate) fn precomputeds(self) -> &'static [&'static str] {
+        match self {
+            Self::Java => hyper_ast_cvs_git::java_processor::sub_queries(),
+            Self::Cpp => &[],
+        }
+    }
+
+    /// Generates the queries matching the `examples`, the node types being those of the language.
+    pub(crate) fn query_lattice(
+        self,
+        with_spaces_stores: &hyper_ast::store::SimpleStores<hyper_ast_cvs_git::TStore>,
+        examples: impl Iterator<Item = NodeIdentifier>,
+        meta_gen: &hyper_ast_tsquery::Query,
+        meta_simp: &hyper_ast_tsquery::Query,
+    ) -> QueryLattice<NodeIdentifier> {
+        // the type store of the repositories resolves the roles of each language
+        type TS = hyper_ast_cvs_git::TStore;
+        match self {
+            Self::Java => QueryLattice::with_examples::<hyper_ast_gen_ts_java::types::TIdN<_>, TS>(
+                with_spaces_stores,
+                examples,
+                meta_gen,
+                meta_simp,
+            ),
+            Self::Cpp => QueryLattice::with_examples::<hyper_ast_gen_ts_cpp::types::TIdN<_>, TS>(
+                with_spaces_stores,
+                examples,
+                meta_gen,
+                meta_simp,
+            ),
+        }
+    }
+}
+
 pub(crate) fn smells(
     examples: Examples,
     state: SharedState,
@@ -126,6 +193,7 @@ pub(crate) fn smells(
 ) -> Result<Json<SearchResults>, String> {
     let now = Instant::now();
     let Param {
+        forge,
         user,
         name,
         commit,
@@ -146,15 +214,22 @@ pub(crate) fn smells(
         true
     };
 
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_spec = forge.repo(user, name);
     let configs = state.clone();
+    let lang = state
+        .repositories
+        .read()
+        .unwrap()
+        .get_repo_config(&repo_spec)
+        .ok_or_else(|| "missing config for repository".to_string())
+        .and_then(|config| SmellsLanguage::of(&config))?;
     let repo_handle = state
         .repositories
         .write()
         .unwrap()
         .get_config(repo_spec)
         .ok_or_else(|| "missing config for repository".to_string())?;
-    let mut repository = repo_handle.fetch();
+    let mut repository = repo_handle.fetch()?;
     log::warn!("done cloning {}", repository.spec);
     let commits = state
         .repositories
@@ -184,15 +259,8 @@ pub(crate) fn smells(
     let with_spaces_stores: &hyper_ast::store::SimpleStores<hyper_ast_cvs_git::TStore> =
         &repositories.processor.main_stores;
     let stores = &hyper_ast_cvs_git::no_space::as_nospaces(with_spaces_stores);
-
-    // NOTE temporary bypass, will be fixed when adding polyglote facilities
-    // SAFETY for now TStores are identical enough to be transmuted
-    // TODO use a proper wrapper
-    // TODO alternatively rework the type store and node types entirely with some compile time links/macros
-    let sss: &hyper_ast::store::SimpleStores<hyper_ast_gen_ts_java::types::TStore> =
-        unsafe { std::mem::transmute(with_spaces_stores) };
-    let meta_gen = hyper_ast_tsquery::Query::new(&meta_gen, hyper_ast_gen_ts_java::language())
-        .map_err(|x| x.to_string())?;
+    let meta_gen =
+        hyper_ast_tsquery::Query::new(&meta_gen, lang.language()).map_err(|x| x.to_string())?;
     let meta_simp = hyper_ast_tsquery::Query::new(&meta_simp, hyper_ast_gen_ts_tsquery::language())
         .map_err(|x| x.to_string())?;
 
@@ -213,18 +281,28 @@ pub(crate) fn smells(
             acc.entry(x.0).or_default().push(x.1);
             acc
         });
-    let query_lattice =
-        QueryLattice::with_examples(sss, ex_map.keys().copied(), &meta_gen, &meta_simp);
+    let query_lattice = lang.query_lattice(
+        with_spaces_stores,
+        ex_map.keys().copied(),
+        &meta_gen,
+        &meta_simp,
+    );
     let bad: Vec<_> = query_lattice
         .iter()
         .filter(|x| 5 < x.1.len() && x.1.len() * 2 < ex_map.len())
         .collect();
     dbg!(bad.len());
     let matches = if simple_matching {
-        matching::matches_default(with_spaces_stores, dst_tr, bad.iter().map(|x| x.0.as_str()))?
+        matching::matches_default(
+            with_spaces_stores,
+            lang,
+            dst_tr,
+            bad.iter().map(|x| x.0.as_str()),
+        )?
     } else if prepro_matching {
         matching::matches_with_precomputeds(
             with_spaces_stores,
+            lang,
             dst_tr,
             bad.iter().map(|x| x.0.as_str()),
         )?
@@ -278,12 +356,13 @@ pub(crate) fn smells_ex_from_diffs(
 ) -> Result<Json<ExamplesResults>, String> {
     let now = Instant::now();
     let Diffs {
+        forge,
         user,
         name,
         commit,
         len,
     } = path;
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_spec = forge.repo(user, name);
     let configs = state.clone();
     let repo_handle = state
         .repositories
@@ -291,7 +370,7 @@ pub(crate) fn smells_ex_from_diffs(
         .unwrap()
         .get_config(repo_spec)
         .ok_or_else(|| "missing config for repository".to_string())?;
-    let mut repository = repo_handle.fetch();
+    let mut repository = repo_handle.fetch()?;
     log::warn!("done cloning {}", repository.spec);
     let commits = state
         .repositories
@@ -1,7 +1,7 @@
 use std::cmp::Ordering;
 use std::collections::HashSet;
 
-use hyper_ast::types::{HyperAST, WithSerialization};
+use hyper_ast::types::{HyperAST, RoleStore, TypedNodeId, WithSerialization};
 use hyper_ast_gen_ts_tsquery::auto::tsq_ser_meta::Converter;
 
 use hyper_ast::position::position_accessors::{SolvedPosition, WithPreOrderOffsets};
@@ -11,8 +11,8 @@ use hyper_ast_tsquery::Node as _;
 use num::integer::Average;
 
 type QStore = hyper_ast::store::SimpleStores<hyper_ast_gen_ts_tsquery::types::TStore>;
-// TODO use a polyglote code store, snd prio c++ after java
-type JStore = hyper_ast::store::SimpleStores<hyper_ast_gen_ts_java::types::TStore>;
+/// The stores of the code given as examples, eg. with `hyper_ast_gen_ts_cpp::types::TStore`
+type CStore<TS> = hyper_ast::store::SimpleStores<TS>;
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 enum TR {
@@ -65,31 +65,56 @@ impl QueryLattice<NodeIdentifier> {
         QueryId(query)
     }
 
-    fn generate_query(&mut self, stores: &JStore, from: NodeIdentifier) -> QueryId {
-        QueryId(generate_query(&mut self.query_store, stores, from))
+    fn generate_query<TIdN, TS, C>(&mut self, stores: &CStore<TS>, from: NodeIdentifier) -> QueryId
+    where
+        TIdN: 'static + TypedNodeId<IdN = NodeIdentifier>,
+        TS: hyper_ast::types::TypeStore,
+        C: Converter<Ty = TIdN::Ty>,
+    {
+        QueryId(generate_query::<TIdN, TS, C>(
+            &mut self.query_store,
+            stores,
+            from,
+        ))
     }
 
     /// meta_simp: `(predicate (identifier) (#EQ? "EQ") (parameters (string) @label )) @pred (named_node (identifier) (#EQ "expression_statement")) @rm`
-    fn generate_query2(
+    fn generate_query2<TIdN, TS>(
         &mut self,
-        stores: &JStore,
+        stores: &CStore<TS>,
         from: NodeIdentifier,
         meta_gen: &hyper_ast_tsquery::Query,
         meta_simp: &hyper_ast_tsquery::Query,
-    ) -> QueryId {
-        let q = generate_query2(&mut self.query_store, stores, from, meta_gen, meta_simp).unwrap();
+    ) -> QueryId
+    where
+        TIdN: 'static + TypedNodeId<IdN = NodeIdentifier>,
+        TS: RoleStore,
+        TS::IdF: Into<u16> + From<u16>,
+    {
+        let q =
+            generate_query2::<TIdN, TS>(&mut self.query_store, stores, from, meta_gen, meta_simp)
+                .unwrap();
         QueryId(q)
     }
 
     fn pp(&self, query: QueryId) -> String {
         hyper_ast::nodes::TextSerializer::<_, _>::new(&self.query_store, query.0).to_string()
     }
-    pub fn with_examples(
-        stores: &JStore,
+    /// Generates and generalizes queries from the subtrees in `from`,
+    /// `TIdN` being the typed ids of their language, eg. `hyper_ast_gen_ts_cpp::types::TIdN<_>`.
+    ///
+    /// `meta_gen` must be a query of the language of the examples.
+    pub fn with_examples<TIdN, TS>(
+        stores: &CStore<TS>,
         from: impl Iterator<Item = NodeIdentifier>,
         meta_gen: &hyper_ast_tsquery::Query,
         meta_simp: &hyper_ast_tsquery::Query,
-    ) -> Self {
+    ) -> Self
+    where
+        TIdN: 'static + TypedNodeId<IdN = NodeIdentifier>,
+        TS: RoleStore,
+        TS::IdF: Into<u16> + From<u16>,
+    {
         let mut s = Self::new();
         macro_rules! sort {
             ($v:expr) => {
@@ -120,7 +145,7 @@ impl QueryLattice<NodeIdentifier> {
         for from in from {
             // TODO add variant with immediates
             let Some((query, label_h)) =
-                generate_query2_aux(&mut s.query_store, stores, from, meta_gen)
+                generate_query2_aux::<TIdN, TS>(&mut s.query_store, stores, from, meta_gen)
             else {
                 continue;
             };
@@ -267,25 +292,23 @@ impl<E> Default for QueryLattice<E> {
     }
 }
 
-fn generate_query(
+/// `C` converts kinds of the language of `TIdN`,
+/// eg. one using `hyper_ast_gen_ts_java::types::Type::from_str` for java
+fn generate_query<TIdN, TS, C>(
     query_store: &mut QStore,
-    stores: &JStore,
+    stores: &CStore<TS>,
     from: NodeIdentifier,
-) -> NodeIdentifier {
-    #[derive(Default)]
-    struct Conv;
-    impl Converter for Conv {
-        type Ty = hyper_ast_gen_ts_java::types::Type;
-
-        fn conv(s: &str) -> Option<Self::Ty> {
-            hyper_ast_gen_ts_java::types::Type::from_str(s)
-        }
-    }
-    let _query = hyper_ast_gen_ts_tsquery::auto::tsq_ser_meta::TreeToQuery::<
-        _,
-        hyper_ast_gen_ts_java::types::TIdN<_>,
-        Conv,
-    >::with_pred(stores, from, "(identifier) (type_identifier)");
+) -> NodeIdentifier
+where
+    TIdN: 'static + TypedNodeId<IdN = NodeIdentifier>,
+    TS: hyper_ast::types::TypeStore,
+    C: Converter<Ty = TIdN::Ty>,
+{
+    let _query = hyper_ast_gen_ts_tsquery::auto::tsq_ser_meta::TreeToQuery::<_, TIdN, C>::with_pred(
+        stores,
+        from,
+        "(identifier) (type_identifier)",
+    );
     let _query = _query.to_string();
     let (mut query_store, query) = hyper_ast_gen_ts_tsquery::search::ts_query(_query.as_bytes());
     const M0: &str = r#"(predicate (identifier) @op (#eq? @op "eq") (parameters (capture (identifier) @id ) (string) @label ))"#;
@@ -361,14 +384,19 @@ fn generate_query(
     query
 }
 
-fn generate_query2(
+fn generate_query2<TIdN, TS>(
     query_store: &mut QStore,
-    stores: &JStore,
+    stores: &CStore<TS>,
     from: NodeIdentifier,
     meta_gen: &hyper_ast_tsquery::Query,
     meta_simp: &hyper_ast_tsquery::Query,
-) -> Option<NodeIdentifier> {
-    let query = generate_query2_aux(query_store, stores, from, meta_gen)?.0;
+) -> Option<NodeIdentifier>
+where
+    TIdN: 'static + TypedNodeId<IdN = NodeIdentifier>,
+    TS: RoleStore,
+    TS::IdF: Into<u16> + From<u16>,
+{
+    let query = generate_query2_aux::<TIdN, TS>(query_store, stores, from, meta_gen)?.0;
 
     let query = simp_rms(query_store, query, meta_simp, |len| {
         0.average_floor(&len).clamp(0, len)
@@ -429,16 +457,22 @@ fn simp_rms2<'a>(
     })
 }
 
-fn generate_query2_aux(
+fn generate_query2_aux<TIdN, TS>(
     query_store: &mut QStore,
-    stores: &JStore,
+    stores: &CStore<TS>,
     from: NodeIdentifier,
     meta_gen: &hyper_ast_tsquery::Query,
-) -> Option<(NodeIdentifier, LableH)> {
-    let query = hyper_ast_gen_ts_tsquery::auto::tsq_ser_meta2::TreeToQuery::<
-        _,
-        hyper_ast_gen_ts_java::types::TIdN<_>,
-    >::new(stores, from, meta_gen.clone());
+) -> Option<(NodeIdentifier, LableH)>
+where
+    TIdN: 'static + TypedNodeId<IdN = NodeIdentifier>,
+    TS: RoleStore,
+    TS::IdF: Into<u16> + From<u16>,
+{
+    let query = hyper_ast_gen_ts_tsquery::auto::tsq_ser_meta2::TreeToQuery::<_, TIdN>::new(
+        stores,
+        from,
+        meta_gen.clone(),
+    );
     let query = format!("{} @_root", query);
     hyper_ast_gen_ts_tsquery::search::ts_query2_with_label_hash(query_store, query.as_bytes())
 }
@@ -586,3 +620,114 @@ impl<P> std::fmt::Display for PerLabel<P> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const META_SIMP: &str = r#"(predicate
+    (identifier) (#EQ? "EQ")
+    (parameters
+        (string) @label
+    )
+) @pred
+(_
+    (named_node
+        (identifier) (#EQ "expression_statement")
+    ) @rm
+    .
+)"#;
+
+    /// examples only differing by the name of a variable
+    fn check_lattice(lattice: &QueryLattice<NodeIdentifier>, examples: &[NodeIdentifier]) {
+        let queries: Vec<_> = lattice.iter().collect();
+        assert!(!queries.is_empty());
+        for e in examples {
+            assert!(queries.iter().any(|(_, x)| x.contains(e)));
+        }
+        assert!(
+            queries.iter().any(|(_, x)| x.len() == examples.len()),
+            "the name of the variable should have been generalized"
+        );
+    }
+
+    /// Generates the queries of `codes`, examples only differing by the name of a variable,
+    /// each code being parsed and added to the stores by `generate`.
+    fn check_examples<TIdN, TS>(
+        language: tree_sitter::Language,
+        codes: &[&str],
+        mut generate: impl FnMut(&mut CStore<TS>, &str) -> NodeIdentifier,
+    ) where
+        TIdN: 'static + TypedNodeId<IdN = NodeIdentifier>,
+        TS: RoleStore,
+        TS::IdF: Into<u16> + From<u16>,
+        CStore<TS>: Default,
+    {
+        let mut stores = CStore::<TS>::default();
+        let examples: Vec<_> = codes
+            .iter()
+            .map(|text| generate(&mut stores, text))
+            .collect();
+        let meta_gen = r#"(identifier) @label
+["{" ";" "(" ")" "}"] @skip"#;
+        let meta_gen = hyper_ast_tsquery::Query::new(meta_gen, language.clone()).unwrap();
+        let meta_simp =
+            hyper_ast_tsquery::Query::new(META_SIMP, hyper_ast_gen_ts_tsquery::language()).unwrap();
+        let lattice = QueryLattice::with_examples::<TIdN, _>(
+            &stores,
+            examples.iter().copied(),
+            &meta_gen,
+            &meta_simp,
+        );
+        check_lattice(&lattice, &examples);
+        for (q, _) in lattice.iter() {
+            hyper_ast_tsquery::Query::new(&q, language.clone()).unwrap();
+        }
+    }
+
+    #[test]
+    fn cpp_examples() {
+        use hyper_ast_gen_ts_cpp::types::{TIdN, TStore};
+        type CppTreeGen<'store, 'cache> =
+            hyper_ast_gen_ts_cpp::legion::CppTreeGen<'store, 'cache, TStore>;
+        const CODES: [&str; 3] = [
+            "void f(int* p) { if (p == nullptr) return; g(); }",
+            "void f(int* q) { if (q == nullptr) return; g(); }",
+            "void f(int* r) { if (r == nullptr) return; g(); }",
+        ];
+        let mut md_cache = Default::default();
+        check_examples::<TIdN<NodeIdentifier>, TStore>(
+            hyper_ast_gen_ts_cpp::language(),
+            &CODES,
+            |stores, text| {
+                let (Ok(tree) | Err(tree)) = CppTreeGen::tree_sitter_parse(text.as_bytes());
+                let mut tree_gen = CppTreeGen::new(stores, &mut md_cache);
+                let file = tree_gen.generate_file(b"", text.as_bytes(), tree.walk());
+                file.local.compressed_node
+            },
+        );
+    }
+
+    #[test]
+    fn typescript_examples() {
+        use hyper_ast_gen_ts_ts::types::{TIdN, TStore};
+        type TsTreeGen<'store, 'cache> =
+            hyper_ast_gen_ts_ts::legion::TsTreeGen<'store, 'cache, TStore>;
+        const CODES: [&str; 3] = [
+            "function f(p) { if (p === null) { return; } g(); }",
+            "function f(q) { if (q === null) { return; } g(); }",
+            "function f(r) { if (r === null) { return; } g(); }",
+        ];
+        let mut md_cache = Default::default();
+        check_examples::<TIdN<NodeIdentifier>, TStore>(
+            hyper_ast_gen_ts_ts::language(),
+            &CODES,
+            |stores, text| {
+                let (Ok(tree) | Err(tree)) = TsTreeGen::tree_sitter_parse(text.as_bytes());
+                let mut tree_gen = TsTreeGen::new(stores, &mut md_cache);
+                let file = tree_gen.generate_file(b"", text.as_bytes(), tree.walk());
+                file.local.compressed_node
+            },
+        );
+    }
+}
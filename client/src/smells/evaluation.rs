@@ -0,0 +1,420 @@
+//! Evaluation of the queries synthesized from a batch of fix examples.
+//!
+//! Each candidate of the [`QueryLattice`] is matched on the buggy and fixed versions of every example.
+//! A query should match the buggy code (the `before` of an example) and no longer match once fixed (the `after`).
+//! Candidates are then scored, deduplicated and ranked, the result being a ready-to-use rule pack.
+use std::collections::BTreeSet;
+
+use axum::Json;
+use hashbrown::HashSet;
+use hyper_ast::store::defaults::NodeIdentifier;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use super::{matching, ExamplesValue, SmellsLanguage};
+use crate::SharedState;
+
+#[derive(Deserialize, Clone)]
+pub struct Param {
+    forge: hyper_ast_cvs_git::git::Forge,
+    user: String,
+    name: String,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Evaluation {
+    /// the query configuring the query generation from examples, see [`super::Examples`]
+    meta_gen: String,
+    /// the query configuring the query simplification/generalization, see [`super::Examples`]
+    meta_simp: String,
+    /// the labeled examples, possibly coming from different fix commits
+    examples: Vec<ExamplesValue>,
+}
+
+#[derive(Serialize)]
+pub struct EvaluationResults {
+    pub prepare_time: f64,
+    pub search_time: f64,
+    /// ranked from the best rule to the worst one
+    rules: Vec<RankedQuery>,
+    /// all the rules in a single query, in the same order
+    pack: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RankedQuery {
+    pub query: String,
+    /// examples matched in their buggy version, ie. true positives
+    pub examples: Vec<usize>,
+    /// examples still matched once fixed, ie. false positives
+    pub fixed: Vec<usize>,
+    /// matches in the whole buggy commits, labeled or not
+    pub matches: usize,
+    pub precision: f64,
+    pub recall: f64,
+}
+
+impl RankedQuery {
+    fn f1(&self) -> f64 {
+        if self.precision + self.recall == 0.0 {
+            return 0.0;
+        }
+        2.0 * self.precision * self.recall / (self.precision + self.recall)
+    }
+
+    /// at least as good on every example while being at least as general,
+    /// `pattern` and `other_pattern` being the parsed queries
+    fn subsumes(
+        &self,
+        pattern: &tree_sitter::Tree,
+        other: &Self,
+        other_pattern: &tree_sitter::Tree,
+    ) -> bool {
+        other.examples.iter().all(|x| self.examples.contains(x))
+            && self.fixed.iter().all(|x| other.fixed.contains(x))
+            && generalizes(
+                (pattern.root_node(), self.query.as_bytes()),
+                (other_pattern.root_node(), other.query.as_bytes()),
+            )
+    }
+}
+
+fn query_parser() -> tree_sitter::Parser {
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&hyper_ast_gen_ts_tsquery::language())
+        .unwrap();
+    parser
+}
+
+/// A node of a parsed query with the text of the query.
+type Pattern<'a> = (tree_sitter::Node<'a>, &'a [u8]);
+
+/// `general` matches at least what `specific` matches, compared on the structure of the queries:
+/// the patterns of `general` are found in the same order in `specific`, `_` matching any node,
+/// and the predicates of `general` also constrain `specific`.
+/// Captures are ignored, they do not change what is matched.
+///
+/// Conservative, eg. a list of alternatives is only as general as an identical one.
+fn generalizes(general: Pattern, specific: Pattern) -> bool {
+    let mut specific_predicates = vec![];
+    predicates(specific, &mut specific_predicates);
+    let mut general_predicates = vec![];
+    predicates(general, &mut general_predicates);
+    general_predicates
+        .iter()
+        .all(|p| specific_predicates.contains(p))
+        && embeds(general, specific)
+}
+
+fn predicates<'a>((node, text): Pattern<'a>, out: &mut Vec<&'a str>) {
+    if node.kind() == "predicate" {
+        out.push(node.utf8_text(text).unwrap_or_default());
+        return;
+    }
+    for i in 0..node.child_count() {
+        predicates((node.child(i).unwrap(), text), out);
+    }
+}
+
+fn embeds((g, g_text): Pattern, (s, s_text): Pattern) -> bool {
+    if g.kind() != s.kind() {
+        return false;
+    }
+    let text = |node: tree_sitter::Node, text| node.utf8_text(text).unwrap_or_default();
+    if g.child_count() == 0 || g.kind() == "list" {
+        return text(g, g_text) == text(s, s_text);
+    }
+    if let (Some(g_head), Some(s_head)) = (head(g), head(s)) {
+        let g_head = text(g_head, g_text);
+        if g_head != "_" && g_head != text(s_head, s_text) {
+            return false;
+        }
+    }
+    let quantifier = |node: tree_sitter::Node, t| {
+        (0..node.child_count())
+            .filter_map(|i| node.child(i))
+            .find(|x| x.kind() == "quantifier")
+            .map(|x| text(x, t))
+    };
+    if quantifier(g, g_text) != quantifier(s, s_text) {
+        return false;
+    }
+    let mut s_children = sub_patterns(s);
+    sub_patterns(g).all(|g| s_children.any(|s| embeds((g, g_text), (s, s_text))))
+}
+
+/// the name of a node or of a field
+fn head(node: tree_sitter::Node) -> Option<tree_sitter::Node> {
+    match node.kind() {
+        "named_node" | "negated_field" => node.child(1),
+        "anonymous_node" | "field_definition" => node.child(0),
+        _ => None,
+    }
+}
+
+/// the patterns and anchors directly in `node`
+fn sub_patterns(node: tree_sitter::Node) -> impl Iterator<Item = tree_sitter::Node> {
+    let head = head(node).map(|x| x.id());
+    (0..node.child_count())
+        .filter_map(move |i| node.child(i))
+        .filter(move |x| Some(x.id()) != head)
+        .filter(|x| x.is_named() || x.kind() == ".")
+        .filter(|x| !matches!(x.kind(), "capture" | "predicate" | "comment" | "quantifier"))
+}
+
+pub(crate) fn evaluate(
+    evaluation: Evaluation,
+    state: SharedState,
+    path: Param,
+) -> Result<Json<EvaluationResults>, String> {
+    let now = Instant::now();
+    let Param { forge, user, name } = path;
+    let Evaluation {
+        meta_gen,
+        meta_simp,
+        examples,
+    } = evaluation;
+    if examples.is_empty() {
+        return Err("no examples to evaluate queries on".to_string());
+    }
+
+    let repo_spec = forge.repo(user, name);
+    let lang = state
+        .repositories
+        .read()
+        .unwrap()
+        .get_repo_config(&repo_spec)
+        .ok_or_else(|| "missing config for repository".to_string())
+        .and_then(|config| SmellsLanguage::of(&config))?;
+    let repo_handle = state
+        .repositories
+        .write()
+        .unwrap()
+        .get_config(repo_spec)
+        .ok_or_else(|| "missing config for repository".to_string())?;
+    let mut repository = repo_handle.fetch()?;
+    log::warn!("done cloning {}", repository.spec);
+    // the fix commits, each one with its parent holding the buggy code
+    let fixes: BTreeSet<&str> = examples.iter().map(|e| e.after.commit.as_str()).collect();
+    for commit in &fixes {
+        let commits = state
+            .repositories
+            .write()
+            .unwrap()
+            .pre_process_with_limit(&mut repository, "", commit, 2)
+            .map_err(|e| e.to_string())?;
+        log::warn!(
+            "done construction of {commits:?} in {}",
+            repository.spec.user
+        );
+    }
+    let prepare_time = now.elapsed().as_secs_f64();
+    let now = Instant::now();
+    use hyper_ast_cvs_git::processing::ConfiguredRepoTrait;
+    let repositories = state.repositories.read().unwrap();
+    let root = |commit: &str| -> Result<NodeIdentifier, String> {
+        let oid = hyper_ast_cvs_git::git::Oid::from_str(commit).map_err(|e| e.to_string())?;
+        repositories
+            .get_commit(repository.config(), &oid)
+            .map(|c| c.ast_root)
+            .ok_or_else(|| format!("missing commit {commit}"))
+    };
+    let with_spaces_stores: &hyper_ast::store::SimpleStores<hyper_ast_cvs_git::TStore> =
+        &repositories.processor.main_stores;
+    let locate = |tr: NodeIdentifier, path: &[super::Idx]| {
+        let (_, node) = hyper_ast::position::compute_position(
+            tr,
+            &mut path.iter().copied(),
+            with_spaces_stores,
+        );
+        node
+    };
+
+    let mut buggy_roots = HashSet::new();
+    let mut buggy = Vec::with_capacity(examples.len());
+    let mut fixed = Vec::with_capacity(examples.len());
+    for e in &examples {
+        if e.before.path.is_empty() {
+            return Err("an example is missing the path to its buggy code".to_string());
+        }
+        let tr = root(&e.before.commit)?;
+        buggy_roots.insert(tr);
+        buggy.push(locate(tr, &e.before.path));
+        // nothing left to match if the buggy code was only removed
+        if !e.after.path.is_empty() {
+            fixed.push(Some(locate(root(&e.after.commit)?, &e.after.path)));
+        } else {
+            fixed.push(None);
+        }
+    }
+
+    let meta_gen =
+        hyper_ast_tsquery::Query::new(&meta_gen, lang.language()).map_err(|x| x.to_string())?;
+    let meta_simp = hyper_ast_tsquery::Query::new(&meta_simp, hyper_ast_gen_ts_tsquery::language())
+        .map_err(|x| x.to_string())?;
+    let query_lattice = lang.query_lattice(
+        with_spaces_stores,
+        buggy.iter().copied().collect::<HashSet<_>>().into_iter(),
+        &meta_gen,
+        &meta_simp,
+    );
+    let candidates: Vec<String> = query_lattice.iter().map(|x| x.0).collect();
+    log::info!("evaluating {} candidate queries", candidates.len());
+
+    let queries = || candidates.iter().map(|x| x.as_str());
+    let on_buggy =
+        matching::matches_per_root(with_spaces_stores, lang, buggy.iter().copied(), queries())?;
+    let on_fixed = matching::matches_per_root(
+        with_spaces_stores,
+        lang,
+        fixed.iter().flatten().copied(),
+        queries(),
+    )?;
+    let on_commits =
+        matching::matches_per_root(with_spaces_stores, lang, buggy_roots.into_iter(), queries())?;
+
+    let fixed_examples: Vec<usize> = (0..examples.len())
+        .filter(|i| fixed[*i].is_some())
+        .collect();
+    let candidates = candidates.into_iter().enumerate().map(|(q, query)| {
+        let examples = (0..examples.len())
+            .filter(|i| on_buggy[*i][q] > 0)
+            .collect();
+        let fixed = (0..fixed_examples.len())
+            .filter(|i| on_fixed[*i][q] > 0)
+            .map(|i| fixed_examples[i])
+            .collect();
+        let matches = on_commits.iter().map(|x| x[q]).sum();
+        score(query, examples, fixed, matches, buggy.len())
+    });
+    let rules = rank(candidates);
+    let pack = rules
+        .iter()
+        .map(|x| x.query.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let search_time = now.elapsed().as_secs_f64();
+    Ok(Json::from(EvaluationResults {
+        prepare_time,
+        search_time,
+        rules,
+        pack,
+    }))
+}
+
+fn score(
+    query: String,
+    examples: Vec<usize>,
+    fixed: Vec<usize>,
+    matches: usize,
+    total: usize,
+) -> RankedQuery {
+    let tp = examples.len() as f64;
+    let fp = fixed.len() as f64;
+    let precision = if tp + fp == 0.0 { 0.0 } else { tp / (tp + fp) };
+    let recall = if total == 0 { 0.0 } else { tp / total as f64 };
+    RankedQuery {
+        query,
+        examples,
+        fixed,
+        matches,
+        precision,
+        recall,
+    }
+}
+
+/// Remove the queries matching no buggy example and the ones subsumed by a more general query,
+/// then sort the remaining ones by F1 score, recall and generality.
+fn rank(candidates: impl Iterator<Item = RankedQuery>) -> Vec<RankedQuery> {
+    let mut candidates: Vec<_> = candidates.filter(|x| !x.examples.is_empty()).collect();
+    // the most general queries first, so they are the ones kept among equivalent ones,
+    // a query being at least as long as the ones generalizing it
+    candidates.sort_by(|a, b| {
+        a.query
+            .len()
+            .cmp(&b.query.len())
+            .then(a.query.cmp(&b.query))
+    });
+    // TODO merge candidates into more general queries, eg. replacing differing nodes by `_`
+    let mut parser = query_parser();
+    let mut rules: Vec<(RankedQuery, Option<tree_sitter::Tree>)> = vec![];
+    for c in candidates {
+        // unparsable queries are neither subsuming nor subsumed
+        let pattern = parser
+            .parse(&c.query, None)
+            .filter(|x| !x.root_node().has_error());
+        if let Some(pattern) = &pattern {
+            let subsumed = rules
+                .iter()
+                .any(|(r, p)| p.as_ref().map_or(false, |p| r.subsumes(p, &c, pattern)));
+            if subsumed {
+                continue;
+            }
+        }
+        rules.push((c, pattern));
+    }
+    let mut rules: Vec<_> = rules.into_iter().map(|(r, _)| r).collect();
+    rules.sort_by(|a, b| {
+        b.f1()
+            .total_cmp(&a.f1())
+            .then(b.recall.total_cmp(&a.recall))
+            .then(a.query.len().cmp(&b.query.len()))
+    });
+    rules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rank_dedup_and_order() {
+        let total = 4;
+        let candidates = vec![
+            // matches everything, even fixed code
+            score("(_)".into(), vec![0, 1, 2], vec![0, 1, 2, 3], 40, total),
+            score("(a (b))".into(), vec![0, 1], vec![], 2, total),
+            // equivalent to the previous one but more specific
+            score("(a (b (c)))".into(), vec![0, 1], vec![], 2, total),
+            // dominated by the previous one
+            score("(a (b (d)))".into(), vec![0], vec![1], 1, total),
+            score("(x)".into(), vec![1, 2, 3], vec![], 5, total),
+            score("(y (z))".into(), vec![], vec![], 3, total),
+        ];
+        let rules = rank(candidates.into_iter());
+        let queries: Vec<_> = rules.iter().map(|x| x.query.as_str()).collect();
+        assert_eq!(queries, vec!["(x)", "(a (b))", "(_)"]);
+        assert_eq!(rules[0].precision, 1.0);
+        assert_eq!(rules[0].recall, 0.75);
+        assert_eq!(rules[2].precision, 3.0 / 7.0);
+    }
+
+    #[test]
+    fn structural_generality() {
+        let generalizes = |general: &str, specific: &str| {
+            let mut parser = query_parser();
+            let g = parser.parse(general, None).unwrap();
+            let s = parser.parse(specific, None).unwrap();
+            assert!(!g.root_node().has_error() && !s.root_node().has_error());
+            super::generalizes(
+                (g.root_node(), general.as_bytes()),
+                (s.root_node(), specific.as_bytes()),
+            )
+        };
+        assert!(generalizes("(a (b))", "(a (b))"));
+        assert!(generalizes("(a (b))", "(a (c) (b (d)))"));
+        // as long, but matching other code
+        assert!(!generalizes("(a (b))", "(a (c))"));
+        assert!(generalizes("(_ (b))", "(a (b))"));
+        assert!(!generalizes("(a (b))", "(_ (b))"));
+        assert!(!generalizes("(a (b) (c))", "(a (c) (b))"));
+        assert!(generalizes("(a (b))", "(a . (b))"));
+        assert!(!generalizes("(a . (b))", "(a (b))"));
+        assert!(generalizes("(a f: (b))", "(a f: (b) g: (c))"));
+        assert!(!generalizes("(a f: (b))", "(a g: (b))"));
+        assert!(generalizes("(a) @x", "(a (b)) @y"));
+        assert!(generalizes("((a) @x)", "((a (b)) @x (#eq? @x \"y\"))"));
+        assert!(!generalizes("((a (b)) @x (#eq? @x \"y\"))", "((a) @x)"));
+    }
+}
@@ -1,10 +1,10 @@
-use super::SearchResult;
+use super::{SearchResult, SmellsLanguage};
 
 use hyper_ast::store::defaults::NodeIdentifier;
-use hyper_ast_cvs_git::java_processor::sub_queries;
 
 pub(crate) fn matches_default<'a>(
     with_spaces_stores: &hyper_ast::store::SimpleStores<hyper_ast_cvs_git::TStore>,
+    lang: SmellsLanguage,
     tr: NodeIdentifier,
     queries: impl Iterator<Item = &'a str>,
 ) -> Result<Vec<usize>, String> {
@@ -15,8 +15,8 @@ pub(crate) fn matches_default<'a>(
             format!("{}\n\n", x)
         })
         .collect::<String>();
-    let qqq = hyper_ast_tsquery::Query::new(&collect, hyper_ast_gen_ts_java::language())
-        .map_err(|e| e.to_string())?;
+    let qqq =
+        hyper_ast_tsquery::Query::new(&collect, lang.language()).map_err(|e| e.to_string())?;
     if qqq.enabled_pattern_count() != len {
         dbg!(qqq.enabled_pattern_count(), len);
         let mut count = 0;
@@ -67,6 +67,7 @@ pub(crate) fn matches_default<'a>(
 
 pub(crate) fn matches_with_precomputeds<'a>(
     with_spaces_stores: &hyper_ast::store::SimpleStores<hyper_ast_cvs_git::TStore>,
+    lang: SmellsLanguage,
     tr: NodeIdentifier,
     queries: impl Iterator<Item = &'a str>,
 ) -> Result<Vec<usize>, String> {
@@ -78,8 +79,8 @@ pub(crate) fn matches_with_precomputeds<'a>(
                 format!("{}\n", x)
             })
             .collect::<String>(),
-        hyper_ast_gen_ts_java::language(),
-        &sub_queries(),
+        lang.language(),
+        lang.precomputeds(),
     )
     .map_err(|e| e.to_string())?;
     if qqq.enabled_pattern_count() != len {
@@ -101,16 +102,17 @@ pub(crate) fn matches_with_precomputeds<'a>(
 
 pub(crate) fn matches_with_precomputed(
     with_spaces_stores: &hyper_ast::store::SimpleStores<hyper_ast_cvs_git::TStore>,
+    lang: SmellsLanguage,
     tr: NodeIdentifier,
     result: &mut SearchResult,
 ) -> Result<(), String> {
     let (_, qqq) = hyper_ast_tsquery::Query::with_precomputed(
         &result.query,
-        hyper_ast_gen_ts_java::language(),
-        sub_queries(),
+        lang.language(),
+        lang.precomputeds(),
     )
     .map_err(|e| e.to_string())?;
-    if qqq.pattern_count() != 1 + sub_queries().len() {
+    if qqq.pattern_count() != 1 + lang.precomputeds().len() {
         dbg!(qqq.pattern_count());
         return Err("different number of patterns".to_string());
     }
@@ -126,3 +128,43 @@ pub(crate) fn matches_with_precomputed(
     }
     Ok(())
 }
+
+/// Count the matches of each query under each root, compiling the queries only once.
+///
+/// `res[r][q]` is the number of matches of `queries[q]` under `roots[r]`.
+pub(crate) fn matches_per_root<'a>(
+    with_spaces_stores: &hyper_ast::store::SimpleStores<hyper_ast_cvs_git::TStore>,
+    lang: SmellsLanguage,
+    roots: impl Iterator<Item = NodeIdentifier>,
+    queries: impl Iterator<Item = &'a str>,
+) -> Result<Vec<Vec<usize>>, String> {
+    let mut len = 0;
+    let collect = queries
+        .map(|x| {
+            len += 1;
+            format!("{}\n\n", x)
+        })
+        .collect::<String>();
+    let qqq =
+        hyper_ast_tsquery::Query::new(&collect, lang.language()).map_err(|e| e.to_string())?;
+    if qqq.enabled_pattern_count() != len {
+        log::debug!("{} patterns for {} queries", qqq.enabled_pattern_count(), len);
+        return Err("different number of patterns".to_string());
+    }
+    let res = roots
+        .map(|tr| {
+            let qcursor = qqq.matches(hyper_ast_tsquery::hyperast_opt::TreeCursor::new(
+                with_spaces_stores,
+                hyper_ast::position::structural_pos::CursorWithPersistance::new(tr),
+            ));
+            let mut res = vec![0; len];
+            for m in qcursor {
+                let i = m.pattern_index;
+                let i = qqq.enabled_pattern_index(i).unwrap();
+                res[i as usize] += 1;
+            }
+            res
+        })
+        .collect();
+    Ok(res)
+}
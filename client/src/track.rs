@@ -37,6 +37,7 @@ use crate::{
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct TrackingParam {
+    forge: hyper_ast_cvs_git::git::Forge,
     user: String,
     name: String,
     commit: String,
@@ -45,6 +46,7 @@ pub struct TrackingParam {
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct TrackingAtPathParam {
+    forge: hyper_ast_cvs_git::git::Forge,
     user: String,
     name: String,
     commit: String,
@@ -257,6 +259,7 @@ pub fn track_code(
 ) -> Result<TrackingResult<IdN, Idx>, TrackingError> {
     let now = Instant::now();
     let TrackingParam {
+        forge,
         user,
         name,
         commit,
@@ -268,7 +271,7 @@ pub fn track_code(
         before,
         flags,
     } = query;
-    let repo_specifier = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_specifier = forge.repo(user, name);
     let repo_handle = state
         .repositories
         .write()
@@ -280,7 +283,12 @@ pub fn track_code(
             node_processed: 0,
             message: "missing config for repository".to_string(),
         })?;
-    let mut repository = repo_handle.fetch();
+    let mut repository = repo_handle.fetch().map_err(|message| TrackingError {
+        compute_time: now.elapsed().as_secs_f64(),
+        commits_processed: 0,
+        node_processed: 0,
+        message,
+    })?;
     log::warn!("done cloning {}", repository.spec);
     // let mut get_mut = state.write().unwrap();
     // let state = get_mut.deref_mut();
@@ -400,12 +408,13 @@ pub(crate) fn track_code_at_path(
         flags,
     } = query;
     let TrackingAtPathParam {
+        forge,
         user,
         name,
         commit,
         path,
     } = path;
-    let repo_specifier = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_specifier = forge.repo(user, name);
     let repository = state
         .repositories
         .write()
@@ -417,7 +426,12 @@ pub(crate) fn track_code_at_path(
             node_processed: 0,
             message: "missing config for repository".to_string(),
         })?;
-    let mut repository = repository.fetch();
+    let mut repository = repository.fetch().map_err(|message| TrackingError {
+        compute_time: now.elapsed().as_secs_f64(),
+        commits_processed: 0,
+        node_processed: 0,
+        message,
+    })?;
     log::warn!("done cloning {}", repository.spec);
     // let mut get_mut = state.write().unwrap();
     // let state = get_mut.deref_mut();
@@ -557,12 +571,13 @@ pub(crate) fn track_code_at_path_with_changes(
         flags,
     } = query;
     let TrackingAtPathParam {
+        forge,
         user,
         name,
         commit,
         path,
     } = path;
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_spec = forge.repo(user, name);
     let configs = state.clone();
     let repo_handle = state
         .repositories
@@ -575,7 +590,12 @@ pub(crate) fn track_code_at_path_with_changes(
             node_processed: 0,
             message: "missing config for repository".to_string(),
         })?;
-    let mut repository = repo_handle.fetch();
+    let mut repository = repo_handle.fetch().map_err(|message| TrackingError {
+        compute_time: now.elapsed().as_secs_f64(),
+        commits_processed: 0,
+        node_processed: 0,
+        message,
+    })?;
     log::warn!("done cloning {}", repository.spec);
     let mut ori_oid = None;
     let mut commit = commit.clone();
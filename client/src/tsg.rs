@@ -11,6 +11,7 @@ pub type Functions<Node> =
 
 #[derive(Deserialize, Clone)]
 pub struct Param {
+    forge: hyper_ast_cvs_git::git::Forge,
     user: String,
     name: String,
     commit: String,
@@ -25,6 +26,7 @@ pub struct Content {
 
 #[derive(Debug, Serialize, Clone)]
 pub enum QueryingError {
+    ProcessingError(String),
     MissingLanguage(String),
     TsgParsing(String),
 }
@@ -54,7 +56,7 @@ pub fn simple(
     path: Param,
 ) -> Result<Json<ComputeResults>, QueryingError> {
     let now = Instant::now();
-    let Param { user, name, commit } = path.clone();
+    let Param { forge, user, name, commit } = path.clone();
     let Content {
         language,
         query,
@@ -62,26 +64,14 @@ pub fn simple(
     } = query;
     let language: tree_sitter::Language = hyper_ast_cvs_git::resolve_language(&language)
         .ok_or_else(|| QueryingError::MissingLanguage(language))?;
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
-    let repo = state
-        .repositories
-        .write()
-        .unwrap()
-        .get_config(repo_spec.clone());
-    let repo = match repo {
-        Some(repo) => repo,
-        None => {
-            let configs = &mut state.repositories.write().unwrap();
-            configs.register_config(
-                repo_spec.clone(),
-                hyper_ast_cvs_git::processing::RepoConfig::JavaMaven,
-            );
-            log::error!("missing config for {}", repo_spec);
-            configs.get_config(repo_spec.clone()).unwrap()
-        }
-    };
-    // .ok_or_else(|| ScriptingError::Other("missing config for repository".to_string()))?;
-    let mut repo = repo.fetch();
+    let repo_spec = forge.repo(user, name);
+    let repo = crate::utils::get_or_register_config(
+        &state,
+        repo_spec,
+        hyper_ast_cvs_git::processing::RepoConfig::JavaMaven,
+    )
+    .map_err(QueryingError::ProcessingError)?;
+    let mut repo = repo.fetch().map_err(QueryingError::ProcessingError)?;
     log::warn!("done cloning {}", &repo.spec);
     let commits = state
         .repositories
@@ -130,6 +130,39 @@ fn bi_sharding<'a>(
     (shard1, shard2)
 }
 
+/// The registered handle of `repo`, a repository of a forge is registered with `config` on its first use,
+/// but a local repository must have been registered on startup, to not expose the filesystem of the server.
+pub(crate) fn get_or_register_config(
+    state: &crate::AppState,
+    repo: hyper_ast_cvs_git::git::Repo,
+    config: hyper_ast_cvs_git::processing::RepoConfig,
+) -> Result<hyper_ast_cvs_git::processing::ConfiguredRepoHandle2, String> {
+    let registered = state.repositories.read().unwrap().get_config(repo.clone());
+    if let Some(handle) = registered {
+        return Ok(handle);
+    }
+    if repo.local_path().is_some() {
+        return Err(format!("{} is not a registered local repository", repo));
+    }
+    log::error!("missing config for {}", repo);
+    let configs = &mut state.repositories.write().unwrap();
+    Ok(configs.register_config(repo, config))
+}
+
+/// Opens a registered repository, the requests cannot make the server open any other path.
+pub(crate) fn fetch_registered(
+    state: &crate::AppState,
+    repo: hyper_ast_cvs_git::git::Repo,
+) -> Result<hyper_ast_cvs_git::processing::ConfiguredRepo2, String> {
+    let handle = state
+        .repositories
+        .read()
+        .unwrap()
+        .get_config(repo.clone())
+        .ok_or_else(|| format!("{} is not a registered repository", repo))?;
+    handle.fetch()
+}
+
 /// Ensures the range is preprocessed --doing it if needed-- while avoiding to lock global state
 pub(crate) fn handle_pre_processing(
     state: &std::sync::Arc<crate::AppState>,
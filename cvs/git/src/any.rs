@@ -0,0 +1,142 @@
+use crate::{
+    opaque::make_text_leaf, processing::ObjectName, Accumulator, BasicDirAcc, DefaultMetrics,
+    ParseErr, PROPAGATE_ERROR_ON_BAD_CST_NODE,
+};
+use hyper_ast::store::defaults::{LabelIdentifier, NodeIdentifier};
+use hyper_ast_gen_ts_tsquery::legion::TsQueryTreeGen;
+use hyper_ast_gen_ts_xml::legion::XmlTreeGen;
+
+pub(crate) fn handle_xml_file<'a>(
+    tree_gen: &mut XmlTreeGen<'a, hyper_ast_gen_ts_xml::types::TStore>,
+    name: &ObjectName,
+    text: &'a [u8],
+) -> Result<(NodeIdentifier, DefaultMetrics), ParseErr> {
+    let tree = match hyper_ast_gen_ts_xml::legion::tree_sitter_parse_xml(text) {
+        Ok(tree) => tree,
+        Err(tree) => {
+            log::warn!("bad CST: {:?}", name.try_str());
+            log::debug!("{}", tree.root_node().to_sexp());
+            if PROPAGATE_ERROR_ON_BAD_CST_NODE {
+                return Err(ParseErr::IllFormed);
+            } else {
+                tree
+            }
+        }
+    };
+    let x = tree_gen
+        .generate_file(name.as_bytes(), text, tree.walk())
+        .local;
+    Ok((x.compressed_node, x.metrics))
+}
+
+pub(crate) fn handle_tsquery_file<'stores, 'cache, 'b: 'stores>(
+    tree_gen: &mut TsQueryTreeGen<'stores, 'cache, hyper_ast_gen_ts_tsquery::types::TStore>,
+    name: &ObjectName,
+    text: &'b [u8],
+) -> Result<(NodeIdentifier, DefaultMetrics), ParseErr> {
+    let tree = match hyper_ast_gen_ts_tsquery::legion::tree_sitter_parse(text) {
+        Ok(tree) => tree,
+        Err(tree) => {
+            log::warn!("bad CST: {:?}", name.try_str());
+            log::debug!("{}", tree.root_node().to_sexp());
+            if PROPAGATE_ERROR_ON_BAD_CST_NODE {
+                return Err(ParseErr::IllFormed);
+            } else {
+                tree
+            }
+        }
+    };
+    let x = tree_gen
+        .generate_file(name.as_bytes(), text, tree.walk())
+        .local;
+    Ok((x.compressed_node, x.metrics))
+}
+
+/// Whether the blob parses without syntax errors with `parse`,
+/// unlike the build system processors the any processor keeps ill-formed sources as text.
+pub(crate) fn is_well_formed<T>(
+    repository: &git2::Repository,
+    oid: git2::Oid,
+    parse: impl Fn(&[u8]) -> Result<T, T>,
+) -> bool {
+    repository
+        .find_blob(oid)
+        .map_or(false, |blob| parse(blob.content()).is_ok())
+}
+
+/// Files without a generator, or that could not be generated, are kept as opaque leafs,
+/// identified by their raw bytes, so binary files are handled like any other file.
+pub(crate) fn handle_text_file(
+    stores: &mut crate::opaque::SimpleStores,
+    text: &[u8],
+) -> (NodeIdentifier, DefaultMetrics) {
+    make_text_leaf(stores, text)
+}
+
+/// A directory of any repository, whatever the languages and build systems it uses.
+pub struct AnyAcc {
+    pub(crate) primary: BasicDirAcc<NodeIdentifier, LabelIdentifier, DefaultMetrics>,
+}
+
+impl From<String> for AnyAcc {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl AnyAcc {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            primary: BasicDirAcc::new(name),
+        }
+    }
+    pub(crate) fn push_file(
+        &mut self,
+        name: LabelIdentifier,
+        (compressed_node, metrics): (NodeIdentifier, DefaultMetrics),
+    ) {
+        assert!(!self.primary.children_names.contains(&name));
+        self.primary.push(name, compressed_node, metrics);
+    }
+}
+
+impl hyper_ast::tree_gen::Accumulator for AnyAcc {
+    type Node = (LabelIdentifier, (NodeIdentifier, DefaultMetrics));
+    fn push(&mut self, (name, full_node): Self::Node) {
+        self.push_file(name, full_node);
+    }
+}
+
+impl Accumulator for AnyAcc {
+    type Unlabeled = (NodeIdentifier, DefaultMetrics);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper_ast::types::LabelStore as _;
+
+    #[test]
+    fn text_files_in_directory() {
+        let mut stores = crate::opaque::SimpleStores {
+            label_store: Default::default(),
+            type_store: Default::default(),
+            node_store: Default::default(),
+        };
+        let readme = handle_text_file(&mut stores, b"# Demo\n");
+        let copy = handle_text_file(&mut stores, b"# Demo\n");
+        assert_eq!(readme.0, copy.0);
+        let binary = handle_text_file(&mut stores, &[0x89, b'P', b'N', b'G', 0xff]);
+        assert_ne!(readme.0, binary.0);
+        // would be the same once made valid utf8
+        let other = handle_text_file(&mut stores, &[0x89, b'P', b'N', b'G', 0xfe]);
+        assert_ne!(binary.0, other.0);
+        let mut acc = AnyAcc::new("docs".to_string());
+        let name = stores.label_store.get_or_insert("README.md");
+        acc.push_file(name, readme);
+        let name = stores.label_store.get_or_insert("logo.png");
+        acc.push_file(name, binary);
+        assert_eq!(acc.primary.children.len(), 2);
+        assert_eq!(acc.primary.metrics.line_count, 1);
+    }
+}
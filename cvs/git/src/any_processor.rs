@@ -0,0 +1,556 @@
+use crate::{
+    any::AnyAcc,
+    cpp_processor::{CppProc, CppProcessorHolder},
+    git::BasicGitObject,
+    java_processor::{JavaProc, JavaProcessorHolder},
+    npm_processor::{NpmProc, NpmProcessorHolder},
+    preprocessed::RepositoryProcessor,
+    processing::{
+        caches,
+        erased::{CommitProcExt, ParametrizedCommitProc2, ParametrizedCommitProcessor2Handle},
+        file_sys, CacheHolding, InFiles, ObjectName,
+    },
+    DefaultMetrics, Processor,
+};
+use git2::{Oid, Repository};
+use hyper_ast::{
+    hashed::{IndexingHashBuilder, MetaDataHashsBuilder},
+    store::{defaults::NodeIdentifier, nodes::legion::eq_node},
+    types::LabelStore,
+};
+use hyper_ast_gen_ts_tsquery::legion::TsQueryTreeGen;
+use hyper_ast_gen_ts_xml::{
+    legion::XmlTreeGen,
+    types::{Type, XmlEnabledTypeStore as _},
+};
+use std::{
+    iter::Peekable,
+    path::{Components, PathBuf},
+};
+
+pub type SimpleStores = hyper_ast::store::SimpleStores<hyper_ast_gen_ts_xml::types::TStore>;
+
+/// Processes the directories of any repository, without looking for a build system,
+/// each file is dispatched to a generator by its extension,
+/// and files without a generator are kept as text leafs.
+pub struct AnyProcessor<'repo, 'prepro, 'd, 'c, Acc> {
+    repository: &'repo Repository,
+    prepro: &'prepro mut RepositoryProcessor,
+    stack: Vec<(Oid, Vec<BasicGitObject>, Acc)>,
+    pub dir_path: &'d mut Peekable<Components<'c>>,
+    handles: Handles,
+}
+
+/// The processors whose caches are shared with the processing of any repository,
+/// so the same source files are only generated once.
+#[derive(Clone, Copy)]
+pub(crate) struct Handles {
+    any: ParametrizedCommitProcessor2Handle<AnyProc>,
+    java: ParametrizedCommitProcessor2Handle<JavaProc>,
+    cpp: ParametrizedCommitProcessor2Handle<CppProc>,
+    ts: ParametrizedCommitProcessor2Handle<NpmProc>,
+}
+
+/// Why a directory of any repository could not be explored.
+#[derive(Debug)]
+pub enum TreeErr {
+    Git(git2::Error),
+    NotUtf8(std::str::Utf8Error),
+}
+
+impl From<git2::Error> for TreeErr {
+    fn from(value: git2::Error) -> Self {
+        TreeErr::Git(value)
+    }
+}
+
+impl From<std::str::Utf8Error> for TreeErr {
+    fn from(value: std::str::Utf8Error) -> Self {
+        TreeErr::NotUtf8(value)
+    }
+}
+
+impl<'repo, 'b, 'd, 'c, Acc: From<String>> AnyProcessor<'repo, 'b, 'd, 'c, Acc> {
+    pub fn new(
+        repository: &'repo Repository,
+        prepro: &'b mut RepositoryProcessor,
+        dir_path: &'d mut Peekable<Components<'c>>,
+        name: &[u8],
+        oid: git2::Oid,
+    ) -> Result<Self, TreeErr> {
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<AnyProcessorHolder>();
+        let any = <AnyProc as CommitProcExt>::register_param(h, Parameter);
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<JavaProcessorHolder>();
+        let java = <JavaProc as CommitProcExt>::register_param(
+            h,
+            crate::java_processor::Parameter { query: None },
+        );
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<CppProcessorHolder>();
+        let cpp = <CppProc as CommitProcExt>::register_param(h, crate::cpp_processor::Parameter);
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<NpmProcessorHolder>();
+        let ts = <NpmProc as CommitProcExt>::register_param(h, crate::npm_processor::Parameter);
+        let stack = vec![prepare_tree(repository, oid, name)?];
+        Ok(Self {
+            stack,
+            repository,
+            prepro,
+            dir_path,
+            handles: Handles { any, java, cpp, ts },
+        })
+    }
+}
+
+impl<'repo, 'b, 'd, 'c> Processor<AnyAcc> for AnyProcessor<'repo, 'b, 'd, 'c, AnyAcc> {
+    fn pre(&mut self, current_object: BasicGitObject) {
+        match current_object {
+            BasicGitObject::Tree(oid, name) => {
+                if let Some(s) = self.dir_path.peek() {
+                    if name
+                        .as_bytes()
+                        .eq(std::ffi::OsStr::as_encoded_bytes(s.as_os_str()))
+                    {
+                        self.dir_path.next();
+                        self.stack.last_mut().expect("never empty").1.clear();
+                        self.push_tree(oid, &name);
+                    }
+                    return;
+                }
+                self.handle_tree_cached(oid, name);
+            }
+            BasicGitObject::Blob(oid, name) => {
+                if self.dir_path.peek().is_some() {
+                    return;
+                }
+                if let Err(err) = self.prepro.help_handle_any_file(
+                    oid,
+                    &mut self.stack.last_mut().unwrap().2,
+                    &name,
+                    self.repository,
+                    self.handles,
+                ) {
+                    log::warn!("skipped file {:?}: {:?}", name.try_str(), err);
+                }
+            }
+        }
+    }
+    fn post(&mut self, oid: Oid, acc: AnyAcc) -> Option<(NodeIdentifier, DefaultMetrics)> {
+        let name = acc.primary.name.clone();
+        let key = (oid, name.as_bytes().into());
+        let full_node = make(acc, self.prepro.main_stores_mut().mut_with_ts());
+        CacheHolding::<caches::Any>::get_caches_mut(
+            self.prepro
+                .processing_systems
+                .mut_or_default::<AnyProcessorHolder>(),
+        )
+        .object_map
+        .insert(key, full_node.clone());
+        let name = self.prepro.main_stores.label_store.get_or_insert(name);
+        if self.stack.is_empty() {
+            Some(full_node)
+        } else {
+            let w = &mut self.stack.last_mut().unwrap().2;
+            w.push_file(name, full_node);
+            None
+        }
+    }
+
+    fn stack(&mut self) -> &mut Vec<(Oid, Vec<BasicGitObject>, AnyAcc)> {
+        &mut self.stack
+    }
+}
+
+impl<'repo, 'prepro, 'd, 'c> AnyProcessor<'repo, 'prepro, 'd, 'c, AnyAcc> {
+    fn handle_tree_cached(&mut self, oid: Oid, name: ObjectName) {
+        if let Some(already) = CacheHolding::<caches::Any>::get_caches_mut(
+            self.prepro
+                .processing_systems
+                .mut_or_default::<AnyProcessorHolder>(),
+        )
+        .object_map
+        .get(&(oid, name.clone()))
+        {
+            // reinit already computed node for post order
+            let full_node = already.clone();
+            let w = &mut self.stack.last_mut().unwrap().2;
+            let name = self.prepro.intern_object_name(&name);
+            w.push_file(name, full_node);
+            return;
+        }
+        log::debug!("tree {:?}", name.try_str());
+        self.push_tree(oid, &name);
+    }
+
+    /// Directories that cannot be explored are skipped, the rest of the commit is still processed.
+    fn push_tree(&mut self, oid: Oid, name: &ObjectName) {
+        match prepare_tree(self.repository, oid, name.as_bytes()) {
+            Ok(x) => self.stack.push(x),
+            Err(err) => log::warn!("skipped tree {:?}: {:?}", name.try_str(), err),
+        }
+    }
+}
+
+fn prepare_tree<Acc: From<String>>(
+    repository: &Repository,
+    oid: Oid,
+    name: &[u8],
+) -> Result<(Oid, Vec<BasicGitObject>, Acc), TreeErr> {
+    let tree = repository.find_tree(oid)?;
+    let name = std::str::from_utf8(name)?.to_string();
+    Ok((oid, prepare_dir_exploration(tree), Acc::from(name)))
+}
+
+pub(crate) fn prepare_dir_exploration(tree: git2::Tree) -> Vec<BasicGitObject> {
+    tree.iter()
+        .rev()
+        .map(TryInto::try_into)
+        .filter_map(|x| x.ok())
+        .collect()
+}
+
+/// All directories have the same type, whatever the files they contain.
+pub(crate) fn make(acc: AnyAcc, stores: &mut SimpleStores) -> (NodeIdentifier, DefaultMetrics) {
+    let kind = Type::Directory;
+    let interned_kind = hyper_ast_gen_ts_xml::types::TStore::intern(kind);
+    let label_id = stores.label_store.get_or_insert(acc.primary.name.clone());
+
+    let primary = acc
+        .primary
+        .map_metrics(|m| m.finalize(&interned_kind, &label_id, 0));
+    let hashable = primary.metrics.hashs.most_discriminating();
+    let eq = eq_node(&interned_kind, Some(&label_id), &primary.children);
+    assert_eq!(primary.children_names.len(), primary.children.len());
+
+    let insertion = stores.node_store.prepare_insertion(&hashable, eq);
+    if let Some(id) = insertion.occupied_id() {
+        let metrics = primary
+            .metrics
+            .map_hashs(|h| MetaDataHashsBuilder::build(h));
+        return (id, metrics);
+    }
+
+    log::info!("make dir {} {}", &primary.name, primary.children.len());
+
+    let mut dyn_builder = hyper_ast::store::nodes::legion::dyn_builder::EntityBuilder::new();
+    let children_is_empty = primary.children.is_empty();
+    let metrics = primary.persist(&mut dyn_builder, interned_kind, label_id);
+    let metrics = metrics.map_hashs(|h| h.build());
+    let hashs = metrics.add_md_metrics(&mut dyn_builder, children_is_empty);
+    hashs.persist(&mut dyn_builder);
+
+    let vacant = insertion.vacant();
+    let node_id = hyper_ast::store::nodes::legion::NodeStore::insert_built_after_prepare(
+        vacant,
+        dyn_builder.build(),
+    );
+
+    (node_id, metrics)
+}
+
+fn line_break(text: &[u8]) -> Vec<u8> {
+    if text.contains(&b'\r') {
+        "\r\n".as_bytes().to_vec()
+    } else {
+        "\n".as_bytes().to_vec()
+    }
+}
+
+impl RepositoryProcessor {
+    /// The outcome is cached by blob, so a source is only checked for syntax errors once.
+    fn help_handle_any_file(
+        &mut self,
+        oid: Oid,
+        parent: &mut AnyAcc,
+        name: &ObjectName,
+        repository: &Repository,
+        handles: Handles,
+    ) -> Result<(), crate::ParseErr> {
+        let key = (oid, name.clone());
+        let already = CacheHolding::<caches::Any>::get_caches_mut(
+            self.processing_systems
+                .mut_or_default::<AnyProcessorHolder>(),
+        )
+        .object_map
+        .get(&key)
+        .cloned();
+        let full_node = if let Some(already) = already {
+            already
+        } else {
+            let full_node = self.handle_any_blob(oid, name, repository, handles)?;
+            CacheHolding::<caches::Any>::get_caches_mut(
+                self.processing_systems
+                    .mut_or_default::<AnyProcessorHolder>(),
+            )
+            .object_map
+            .insert(key, full_node.clone());
+            full_node
+        };
+        let name = self.intern_object_name(name);
+        parent.push_file(name, full_node);
+        Ok(())
+    }
+
+    /// Sources of the languages with a dedicated processor go through their caches,
+    /// the remaining files, and the sources with syntax errors or that could not be generated,
+    /// are handled as text.
+    fn handle_any_blob(
+        &mut self,
+        oid: Oid,
+        name: &ObjectName,
+        repository: &Repository,
+        handles: Handles,
+    ) -> Result<(NodeIdentifier, DefaultMetrics), crate::ParseErr> {
+        use crate::any::is_well_formed;
+        let ill_formed = Err(crate::ParseErr::IllFormed);
+        let source = if file_sys::Java::matches(name) {
+            use hyper_ast_gen_ts_java::legion_with_refs::tree_sitter_parse;
+            Some(if is_well_formed(repository, oid, tree_sitter_parse) {
+                self.handle_java_blob(oid, name, repository, handles.java)
+                    .map(|(x, _)| (x.compressed_node, x.metrics))
+            } else {
+                ill_formed
+            })
+        } else if file_sys::Cpp::matches(name) {
+            use hyper_ast_gen_ts_cpp::{legion::CppTreeGen, types::TStore};
+            Some(
+                if is_well_formed(repository, oid, CppTreeGen::<TStore>::tree_sitter_parse) {
+                    self.handle_cpp_blob(oid, name, repository, handles.cpp)
+                        .map(|(x, _)| (x.compressed_node, x.metrics))
+                } else {
+                    ill_formed
+                },
+            )
+        } else if file_sys::Ts::matches(name) {
+            Some(
+                if is_well_formed(repository, oid, |text| {
+                    crate::npm::tree_sitter_parse(name, text)
+                }) {
+                    self.handle_ts_blob(oid, name, repository, handles.ts)
+                        .map(|(x, _)| (x.compressed_node, x.metrics))
+                } else {
+                    ill_formed
+                },
+            )
+        } else {
+            None
+        };
+        match source {
+            Some(Ok(full_node)) => Ok(full_node),
+            Some(Err(err)) => {
+                log::warn!("kept as text {:?}: {:?}", name.try_str(), err);
+                self.handle_text_blob(oid, name, repository, handles.any)
+            }
+            None => self.handle_text_blob(oid, name, repository, handles.any),
+        }
+    }
+
+    fn handle_text_blob(
+        &mut self,
+        oid: Oid,
+        name: &ObjectName,
+        repository: &Repository,
+        parameters: ParametrizedCommitProcessor2Handle<AnyProc>,
+    ) -> Result<(NodeIdentifier, DefaultMetrics), crate::ParseErr> {
+        self.processing_systems
+            .caching_blob_handler::<file_sys::Text>()
+            .handle2(oid, repository, name, parameters, |c, n, t| {
+                let generated = if file_sys::Xml::matches(n) {
+                    crate::any::handle_xml_file(
+                        &mut XmlTreeGen {
+                            line_break: line_break(t),
+                            stores: self.main_stores.mut_with_ts(),
+                        },
+                        n,
+                        t,
+                    )
+                    .ok()
+                } else if file_sys::TsQuery::matches(n) {
+                    crate::any::handle_tsquery_file(
+                        &mut TsQueryTreeGen {
+                            line_break: line_break(t),
+                            stores: self.main_stores.mut_with_ts(),
+                            md_cache: &mut CacheHolding::<caches::Text>::get_caches_mut(
+                                c.mut_or_default::<AnyProcessorHolder>(),
+                            )
+                            .query_md_cache,
+                        },
+                        n,
+                        t,
+                    )
+                    .ok()
+                } else {
+                    None
+                };
+                Ok::<_, crate::ParseErr>(generated.unwrap_or_else(|| {
+                    crate::any::handle_text_file(self.main_stores.mut_with_ts(), t)
+                }))
+            })
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Parameter;
+#[derive(Default)]
+pub(crate) struct AnyProcessorHolder(Option<AnyProc>);
+pub(crate) struct AnyProc {
+    parameter: Parameter,
+    cache: caches::Any,
+    text_cache: caches::Text,
+    commits: std::collections::HashMap<git2::Oid, crate::Commit>,
+}
+impl crate::processing::erased::Parametrized for AnyProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(AnyProc {
+                    parameter: t,
+                    cache: Default::default(),
+                    text_cache: Default::default(),
+                    commits: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+struct PreparedAnyCommitProc<'repo> {
+    repository: &'repo git2::Repository,
+    commit_builder: crate::preprocessed::CommitBuilder,
+}
+impl<'repo> crate::processing::erased::PreparedCommitProc for PreparedAnyCommitProc<'repo> {
+    fn process(
+        self: Box<PreparedAnyCommitProc<'repo>>,
+        prepro: &mut RepositoryProcessor,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        let name = b"";
+        let root_full_node = AnyProcessor::<AnyAcc>::new(
+            self.repository,
+            prepro,
+            &mut dir_path,
+            name,
+            self.commit_builder.tree_oid(),
+        )
+        .expect("the tree of the commit")
+        .process();
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<AnyProcessorHolder>();
+        let handle = <AnyProc as CommitProcExt>::register_param(h, Parameter);
+        let commit_oid = self.commit_builder.commit_oid();
+        let commit = self.commit_builder.finish(root_full_node.0);
+        h.with_parameters_mut(handle.0)
+            .commits
+            .insert(commit_oid, commit);
+        root_full_node.0
+    }
+}
+
+impl crate::processing::erased::CommitProc for AnyProc {
+    fn prepare_processing<'repo>(
+        &self,
+        repository: &'repo git2::Repository,
+        commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc + 'repo> {
+        Box::new(PreparedAnyCommitProc {
+            repository,
+            commit_builder,
+        })
+    }
+
+    fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        self.commits.get(&commit_oid)
+    }
+
+    fn commits(&self) -> Box<dyn Iterator<Item = (&git2::Oid, &crate::Commit)> + '_> {
+        Box::new(self.commits.iter())
+    }
+
+    fn restore_commit(
+        &mut self,
+        commit_oid: git2::Oid,
+        commit: crate::Commit,
+    ) -> std::io::Result<()> {
+        self.commits.insert(commit_oid, commit);
+        Ok(())
+    }
+}
+
+impl CommitProcExt for AnyProc {
+    type Holder = AnyProcessorHolder;
+}
+
+impl ParametrizedCommitProc2 for AnyProcessorHolder {
+    type Proc = AnyProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+}
+
+impl CacheHolding<caches::Any> for AnyProc {
+    fn get_caches_mut(&mut self) -> &mut caches::Any {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &caches::Any {
+        &self.cache
+    }
+}
+
+impl CacheHolding<caches::Any> for AnyProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut caches::Any {
+        &mut self.0.as_mut().unwrap().cache
+    }
+    fn get_caches(&self) -> &caches::Any {
+        &self.0.as_ref().unwrap().cache
+    }
+}
+
+impl CacheHolding<caches::Text> for AnyProc {
+    fn get_caches_mut(&mut self) -> &mut caches::Text {
+        &mut self.text_cache
+    }
+    fn get_caches(&self) -> &caches::Text {
+        &self.text_cache
+    }
+}
+
+impl CacheHolding<caches::Text> for AnyProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut caches::Text {
+        &mut self.0.as_mut().unwrap().text_cache
+    }
+    fn get_caches(&self) -> &caches::Text {
+        &self.0.as_ref().unwrap().text_cache
+    }
+}
@@ -0,0 +1,312 @@
+use crate::{
+    opaque::{make_text_leaf, SimpleStores},
+    preprocessed::IsSkippedAna,
+    Accumulator, BasicDirAcc, DefaultMetrics,
+};
+use enumset::EnumSet;
+use hyper_ast::store::defaults::{LabelIdentifier, NodeIdentifier};
+use hyper_ast_gen_ts_cpp::legion as cpp_tree_gen;
+use std::path::PathBuf;
+
+/// There is no CMake grammar yet, so a CMakeLists.txt is kept as a single text leaf,
+/// and the commands needed to find modules and targets are extracted from its text.
+pub(crate) fn handle_cmakelists(stores: &mut SimpleStores, text: &[u8]) -> Result<CMakeLists, ()> {
+    let text = std::str::from_utf8(text).map_err(|_| ())?;
+    let (compressed_node, metrics) = make_text_leaf(stores, text.as_bytes());
+    let mut x = CMakeLists {
+        compressed_node,
+        metrics,
+        subdirectories: vec![],
+        targets: vec![],
+    };
+    for (command, args) in commands(text) {
+        let Some(first) = args.into_iter().next() else {
+            continue;
+        };
+        match command.as_str() {
+            "add_subdirectory" => x.subdirectories.push(first),
+            "add_executable" | "add_library" | "add_custom_target" => x.targets.push(first),
+            _ => (),
+        }
+    }
+    Ok(x)
+}
+
+/// The commands of a CMake script, ie. `name(args...)`, with lowercased names
+/// and their arguments split on spaces, quoted arguments being unquoted.
+pub(crate) fn commands(text: &str) -> Vec<(String, Vec<String>)> {
+    let mut result = vec![];
+    let mut chars = text.chars().peekable();
+    let mut ident = String::new();
+    while let Some(c) = chars.next() {
+        if c == '#' {
+            ident.clear();
+            skip_comment(&mut chars);
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            ident.push(c);
+        } else if c == '(' && !ident.is_empty() {
+            let args = arguments(&mut chars);
+            result.push((ident.to_ascii_lowercase(), args));
+            ident.clear();
+        } else if !(c == ' ' || c == '\t') {
+            ident.clear();
+        }
+    }
+    result
+}
+
+fn skip_comment(chars: &mut std::iter::Peekable<std::str::Chars>) {
+    if chars.peek() == Some(&'[') {
+        // bracket comment, eg. #[[ ... ]]
+        let mut previous = ' ';
+        while let Some(c) = chars.next() {
+            if previous == ']' && c == ']' {
+                return;
+            }
+            previous = c;
+        }
+    }
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            return;
+        }
+    }
+}
+
+fn arguments(chars: &mut std::iter::Peekable<std::str::Chars>) -> Vec<String> {
+    let mut args = vec![];
+    let mut arg = String::new();
+    let mut depth = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => arg.extend(chars.next()),
+                        c => arg.push(c),
+                    }
+                }
+            }
+            '#' => skip_comment(chars),
+            '(' => {
+                depth += 1;
+                arg.push(c);
+            }
+            ')' if depth == 0 => break,
+            ')' => {
+                depth -= 1;
+                arg.push(c);
+            }
+            c if c.is_whitespace() => {
+                if !arg.is_empty() {
+                    args.push(std::mem::take(&mut arg));
+                }
+            }
+            c => arg.push(c),
+        }
+    }
+    if !arg.is_empty() {
+        args.push(arg);
+    }
+    args
+}
+
+#[derive(Debug, Clone)]
+pub struct CMakeLists {
+    pub compressed_node: NodeIdentifier,
+    pub metrics: DefaultMetrics,
+    subdirectories: Vec<String>,
+    targets: Vec<String>,
+}
+
+/// Module metadata attached to the directories holding a CMakeLists.txt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CMakeModule {
+    /// names of the targets, ie. executables, libraries and custom targets
+    pub targets: Box<[LabelIdentifier]>,
+    /// paths given to add_subdirectory
+    pub subdirectories: Box<[LabelIdentifier]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MD {
+    pub(crate) metrics: DefaultMetrics,
+    pub(crate) status: EnumSet<SemFlags>,
+}
+
+#[derive(enumset::EnumSetType, Debug)]
+pub enum SemFlags {
+    /// holds a CMakeLists.txt
+    IsCMakeModule,
+    /// added with add_subdirectory by the CMakeLists.txt of a parent module
+    IsCMakeSubdirectory,
+    HoldCMakeSubModule,
+}
+
+pub struct CMakeModuleAcc {
+    pub(crate) primary: BasicDirAcc<NodeIdentifier, LabelIdentifier, DefaultMetrics>,
+    /// paths of the subdirectories still to be found inside this directory
+    pub(crate) sub_modules: Option<Vec<PathBuf>>,
+    /// sub_modules come from a parent module, so the directory cannot be reused elsewhere
+    pub(crate) inherited: bool,
+    pub(crate) targets: Vec<String>,
+    pub(crate) subdirectories: Vec<String>,
+    pub(crate) status: EnumSet<SemFlags>,
+}
+
+impl From<String> for CMakeModuleAcc {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl CMakeModuleAcc {
+    pub(crate) fn new(name: String) -> Self {
+        Self::with_content(name, vec![], false)
+    }
+    pub(crate) fn with_content(name: String, sub_modules: Vec<PathBuf>, declared: bool) -> Self {
+        let mut status = EnumSet::new();
+        if declared {
+            status |= SemFlags::IsCMakeSubdirectory;
+        }
+        Self {
+            primary: BasicDirAcc::new(name),
+            inherited: declared || !sub_modules.is_empty(),
+            sub_modules: if sub_modules.is_empty() {
+                None
+            } else {
+                Some(sub_modules)
+            },
+            targets: vec![],
+            subdirectories: vec![],
+            status,
+        }
+    }
+}
+
+impl CMakeModuleAcc {
+    pub(crate) fn push_cmakelists(&mut self, name: LabelIdentifier, full_node: CMakeLists) {
+        self.status |= SemFlags::IsCMakeModule;
+        assert!(!self.primary.children_names.contains(&name));
+        self.primary.children.push(full_node.compressed_node);
+        self.primary.children_names.push(name);
+        self.sub_modules
+            .get_or_insert_with(Default::default)
+            .extend(full_node.subdirectories.iter().map(PathBuf::from));
+        self.subdirectories = full_node.subdirectories;
+        self.targets = full_node.targets;
+        self.primary.metrics.acc(full_node.metrics);
+    }
+    pub(crate) fn push_source_file(
+        &mut self,
+        name: LabelIdentifier,
+        full_node: cpp_tree_gen::Local,
+        _skiped_ana: IsSkippedAna,
+    ) {
+        self.primary
+            .push(name, full_node.compressed_node, full_node.metrics);
+    }
+    pub fn push_submodule(&mut self, name: LabelIdentifier, full_node: (NodeIdentifier, MD)) {
+        if full_node.1.status.contains(SemFlags::HoldCMakeSubModule)
+            || full_node.1.status.contains(SemFlags::IsCMakeModule)
+        {
+            self.status |= SemFlags::HoldCMakeSubModule;
+        }
+        self.primary.push(name, full_node.0, full_node.1.metrics);
+    }
+    /// Splits the subdirectories declared by parents between this directory and its children.
+    ///
+    /// Returns if the directory `name` was declared and the paths to search inside it.
+    pub(crate) fn match_subdirectories(&self, name: &str) -> (bool, Vec<PathBuf>) {
+        let mut declared = false;
+        let mut inside = vec![];
+        for x in self.sub_modules.iter().flatten() {
+            let Ok(rest) = x.strip_prefix(name) else {
+                continue;
+            };
+            if rest.as_os_str().is_empty() {
+                declared = true;
+            } else {
+                inside.push(rest.to_owned());
+            }
+        }
+        (declared, inside)
+    }
+}
+
+impl hyper_ast::tree_gen::Accumulator for CMakeModuleAcc {
+    type Node = (LabelIdentifier, (NodeIdentifier, MD));
+    fn push(&mut self, (name, full_node): Self::Node) {
+        self.push_submodule(name, full_node);
+    }
+}
+
+impl Accumulator for CMakeModuleAcc {
+    type Unlabeled = (NodeIdentifier, MD);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper_ast::types::LabelStore as _;
+
+    #[test]
+    fn commands_of_cmakelists() {
+        let text = r#"cmake_minimum_required(VERSION 3.10)
+project(Demo CXX) # the project
+#[[ add_subdirectory(commented)
+]]
+add_subdirectory(src)
+ADD_SUBDIRECTORY( "third party/lib" EXCLUDE_FROM_ALL)
+if(BUILD_TESTS AND (NOT WIN32))
+  add_subdirectory(tests)
+endif()
+add_library(core STATIC a.cc b.hh)
+add_executable(${PROJECT_NAME}_cli
+    main.cc # entry point
+)
+"#;
+        let commands = commands(text);
+        let names: Vec<_> = commands.iter().map(|x| x.0.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "cmake_minimum_required",
+                "project",
+                "add_subdirectory",
+                "add_subdirectory",
+                "if",
+                "add_subdirectory",
+                "endif",
+                "add_library",
+                "add_executable",
+            ]
+        );
+        assert_eq!(commands[3].1, vec!["third party/lib", "EXCLUDE_FROM_ALL"]);
+        assert_eq!(commands[4].1, vec!["BUILD_TESTS", "AND", "(NOT", "WIN32)"]);
+        assert_eq!(commands[8].1, vec!["${PROJECT_NAME}_cli", "main.cc"]);
+    }
+
+    #[test]
+    fn subdirectories_of_cmakelists() {
+        let mut stores = SimpleStores {
+            label_store: Default::default(),
+            type_store: Default::default(),
+            node_store: Default::default(),
+        };
+        let text = b"add_subdirectory(src)\nadd_subdirectory(libs/a)\nadd_library(a a.cc)\n";
+        let x = handle_cmakelists(&mut stores, text).unwrap();
+        assert_eq!(x.targets, vec!["a"]);
+        let mut acc = CMakeModuleAcc::new("".to_string());
+        let name = stores.label_store.get_or_insert("CMakeLists.txt");
+        acc.push_cmakelists(name, x);
+        assert!(acc.status.contains(SemFlags::IsCMakeModule));
+        assert_eq!(acc.match_subdirectories("src"), (true, vec![]));
+        assert_eq!(
+            acc.match_subdirectories("libs"),
+            (false, vec![PathBuf::from("a")])
+        );
+        assert_eq!(acc.match_subdirectories("docs"), (false, vec![]));
+    }
+}
@@ -0,0 +1,117 @@
+//! Diff each pair of consecutive commits of a range, file by file.
+//!
+//! Diffs are computed on demand, so results can be consumed one commit pair at a time.
+use std::fmt::Display;
+
+use git2::Oid;
+use hyper_ast::store::{defaults::LabelIdentifier, nodes::DefaultNodeIdentifier as NodeIdentifier};
+use hyper_diff::{
+    actions::script_generator2::SimpleAction,
+    algorithms::per_file::{self, FileDiff},
+    tree::tree_path::CompressedTreePath,
+};
+
+use crate::{no_space::as_nospaces, preprocessed::PreProcessedRepository};
+
+pub type FileDiffs = Vec<
+    FileDiff<
+        SimpleAction<LabelIdentifier, CompressedTreePath<u16>, NodeIdentifier>,
+        NodeIdentifier,
+    >,
+>;
+
+/// The files changed from `src` to `dst`.
+pub struct CommitPairDiff {
+    pub src: Oid,
+    pub dst: Oid,
+    pub files: FileDiffs,
+}
+
+/// A commit of the range that was not processed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingCommit(pub Oid);
+
+impl Display for MissingCommit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "commit not processed: {}", self.0)
+    }
+}
+
+impl std::error::Error for MissingCommit {}
+
+impl PreProcessedRepository {
+    /// Diffs the files of two already processed commits, skipping unchanged subtrees.
+    ///
+    /// Fails if one of the commits was not processed.
+    pub fn diff_commits(&self, src: Oid, dst: Oid) -> Result<CommitPairDiff, MissingCommit> {
+        let root = |oid| {
+            self.commits
+                .get(&oid)
+                .map(|c| c.ast_root)
+                .ok_or(MissingCommit(oid))
+        };
+        let src_tr = root(src)?;
+        let dst_tr = root(dst)?;
+        let hyperast = as_nospaces(&self.processor.main_stores);
+        let files = per_file::diff(&hyperast, &src_tr, &dst_tr);
+        Ok(CommitPairDiff { src, dst, files })
+    }
+}
+
+// like the other `pre_process*` methods of [`PreProcessedRepository`],
+// commits are processed as maven projects, thus the feature gate
+#[cfg(feature = "maven_java")]
+impl PreProcessedRepository {
+    /// Processes the first parents between `before` and `after` (at most `limit` commits),
+    /// then lazily diffs each of them with its parent, from the most recent one.
+    pub fn diff_commit_range(
+        &mut self,
+        repository: &mut git2::Repository,
+        before: &str,
+        after: &str,
+        dir_path: &str,
+        limit: usize,
+    ) -> CommitRangeDiffs<'_> {
+        let commits =
+            self.pre_process_first_parents_with_limit(repository, before, after, dir_path, limit);
+        CommitRangeDiffs::new(self, commits)
+    }
+}
+
+/// Iterator over the diffs of consecutive commits,
+/// each commit being diffed with the one that follows it.
+pub struct CommitRangeDiffs<'a> {
+    repository: &'a PreProcessedRepository,
+    commits: Vec<Oid>,
+    next: usize,
+}
+
+impl<'a> CommitRangeDiffs<'a> {
+    /// `commits` must be ordered from child to parent,
+    /// as returned by the `pre_process*` methods of [`PreProcessedRepository`].
+    /// A pair with a commit that was not processed gives a [`MissingCommit`].
+    pub fn new(repository: &'a PreProcessedRepository, commits: Vec<Oid>) -> Self {
+        Self {
+            repository,
+            commits,
+            next: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for CommitRangeDiffs<'a> {
+    type Item = Result<CommitPairDiff, MissingCommit>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let dst = *self.commits.get(self.next)?;
+        let src = *self.commits.get(self.next + 1)?;
+        self.next += 1;
+        log::info!("diff of {src} and {dst}");
+        Some(self.repository.diff_commits(src, dst))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.commits.len().saturating_sub(self.next + 1);
+        (len, Some(len))
+    }
+}
@@ -188,6 +188,19 @@ impl crate::processing::erased::CommitProc for CppProc {
     fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
         self.commits.get(&commit_oid)
     }
+
+    fn commits(&self) -> Box<dyn Iterator<Item = (&git2::Oid, &crate::Commit)> + '_> {
+        Box::new(self.commits.iter())
+    }
+
+    fn restore_commit(
+        &mut self,
+        commit_oid: git2::Oid,
+        commit: crate::Commit,
+    ) -> std::io::Result<()> {
+        self.commits.insert(commit_oid, commit);
+        Ok(())
+    }
 }
 
 impl crate::processing::erased::CommitProcExt for CppProc {
@@ -231,7 +244,7 @@ impl CacheHolding<crate::processing::caches::Cpp> for CppProcessorHolder {
 
 #[cfg(feature = "cpp")]
 impl RepositoryProcessor {
-    fn handle_cpp_blob(
+    pub(crate) fn handle_cpp_blob(
         &mut self,
         oid: Oid,
         name: &ObjectName,
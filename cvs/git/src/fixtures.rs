@@ -0,0 +1,74 @@
+//! Small git repositories written in the temporary directory, for the tests of this crate and of its dependents.
+use std::path::{Path, PathBuf};
+
+static POM: &str = r#"<project>
+    <modelVersion>4.0.0</modelVersion>
+    <groupId>fixture</groupId>
+    <artifactId>fixture</artifactId>
+    <version>1.0</version>
+</project>
+"#;
+
+static JAVA: &str = r#"package fixture;
+
+class A {
+    int f(int x) {
+        return x + 1;
+    }
+}
+"#;
+
+pub fn fixture_dir(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("hyperast_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    path
+}
+
+/// `git init` a maven project with a single commit
+pub fn init_fixture(path: &Path) -> git2::Oid {
+    git2::Repository::init(path).unwrap();
+    commit_files(
+        path,
+        &[
+            ("pom.xml", POM.as_bytes()),
+            ("src/main/java/fixture/A.java", JAVA.as_bytes()),
+        ],
+    )
+}
+
+/// Writes the files and commits them on top of HEAD
+pub fn commit_files(path: &Path, files: &[(&str, &[u8])]) -> git2::Oid {
+    let changes: Vec<_> = files
+        .iter()
+        .map(|&(name, text)| (name, Some(text)))
+        .collect();
+    commit_changes(path, &changes)
+}
+
+/// Commits the changes on top of HEAD, a file without content is removed
+pub fn commit_changes(path: &Path, changes: &[(&str, Option<&[u8]>)]) -> git2::Oid {
+    let repo = git2::Repository::open(path).unwrap();
+    for (name, text) in changes {
+        let file = path.join(name);
+        match text {
+            Some(text) => {
+                std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+                std::fs::write(file, text).unwrap();
+            }
+            None => std::fs::remove_file(file).unwrap(),
+        }
+    }
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    index.update_all(["*"].iter(), None).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = git2::Signature::now("fixture", "fixture@example.com").unwrap();
+    let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+    let parents: Vec<_> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, "fixture", &tree, &parents)
+        .unwrap()
+}
@@ -204,6 +204,9 @@ where
 pub enum Forge {
     Github,
     Gitlab,
+    /// An existing repository on the local filesystem, possibly bare, that is never fetched.
+    /// The `user` of its [`Repo`] is the path of the parent directory.
+    Local,
 }
 
 impl std::str::FromStr for Forge {
@@ -211,18 +214,37 @@ impl std::str::FromStr for Forge {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
-            "github.com" => Self::Github,
-            "gitlab.com" => Self::Gitlab,
+            "github.com" | "github" => Self::Github,
+            "gitlab.com" | "gitlab" => Self::Gitlab,
+            "local" => Self::Local,
             x => return Err(format!("'{}' is not an authorize forge", x)),
         })
     }
 }
 
+impl<'de> serde::Deserialize<'de> for Forge {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl serde::Serialize for Forge {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            Forge::Github => "github",
+            Forge::Gitlab => "gitlab",
+            Forge::Local => "local",
+        })
+    }
+}
+
 impl Forge {
     fn url(&self) -> &str {
         match self {
             Forge::Github => "https://github.com/",
             Forge::Gitlab => "https://gitlab.com/",
+            Forge::Local => "file://",
         }
     }
     pub fn repo(self, user: impl Into<String>, name: impl Into<String>) -> Repo {
@@ -248,10 +270,39 @@ impl Repo {
     pub fn url(&self) -> String {
         format!("{}{}/{}", self.forge.url(), self.user, self.name)
     }
-    pub fn fetch(&self) -> Repository {
-        let url = self.url();
+    /// Opens the repository, cloning or fetching it first if it is on a forge.
+    pub fn fetch(&self) -> Result<Repository, String> {
+        if let Some(path) = self.local_path() {
+            return Repository::open(&path)
+                .map_err(|e| format!("failed to open {:?}: {}", path, e));
+        }
+        let url: Url = self
+            .url()
+            .parse()
+            .map_err(|()| format!("{} is not a valid url", self.url()))?;
         let path = format!("{}", "/tmp/hyperastgitresources/repo/");
-        fetch_repository(url, path)
+        Ok(fetch_repository(url, path))
+    }
+
+    /// A local repository given its path, the path must exist.
+    pub fn local(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path
+            .as_ref()
+            .canonicalize()
+            .map_err(|e| format!("{:?} is not a valid path: {}", path.as_ref(), e))?;
+        let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+            return Err(format!("{:?} cannot be a repository", path));
+        };
+        let user = parent.to_str().ok_or("the path should be valid utf8")?;
+        let name = name.to_str().ok_or("the path should be valid utf8")?;
+        Ok(Forge::Local.repo(user, name))
+    }
+
+    pub fn local_path(&self) -> Option<PathBuf> {
+        match self.forge {
+            Forge::Local => Some(Path::new(&self.user).join(&self.name)),
+            _ => None,
+        }
     }
 }
 
@@ -265,6 +316,11 @@ impl std::str::FromStr for Repo {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = s.strip_prefix("file://") {
+            return Self::local(path);
+        } else if s.starts_with("/") || s.starts_with(".") {
+            return Self::local(s);
+        }
         let (forge, repo) = s
             .split_once("/")
             .ok_or("give a valid repository address without 'https://' and '.git'")?;
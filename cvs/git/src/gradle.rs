@@ -0,0 +1,121 @@
+use crate::{
+    maven::{MavenModuleAcc, SemFlags},
+    opaque::{make_text_leaf, SimpleStores},
+    processing::{file_sys, InFiles, ObjectName},
+    DefaultMetrics,
+};
+use hyper_ast::store::defaults::{LabelIdentifier, NodeIdentifier};
+
+/// There is no groovy nor kotlin grammar yet, so gradle scripts are kept as text leafs,
+/// the projects included by a settings script are extracted from its text.
+pub(crate) fn handle_gradle_script(
+    stores: &mut SimpleStores,
+    name: &ObjectName,
+    text: &[u8],
+) -> Result<GradleScript, ()> {
+    let text = std::str::from_utf8(text).map_err(|_| ())?;
+    let (compressed_node, metrics) = make_text_leaf(stores, text.as_bytes());
+    let submodules = if file_sys::GradleSettings::matches(name) {
+        includes(text)
+    } else {
+        vec![]
+    };
+    Ok(GradleScript {
+        compressed_node,
+        metrics,
+        submodules,
+    })
+}
+
+/// The projects included by a settings.gradle(.kts), as paths relative to the root project,
+/// eg. `include ':lib:core'` or `include("lib:core")` gives `lib/core`.
+pub(crate) fn includes(text: &str) -> Vec<String> {
+    let mut result = vec![];
+    let mut continued = false;
+    for line in text.lines() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        let args = if continued {
+            line
+        } else if let Some(rest) = line.strip_prefix("include") {
+            if !rest.starts_with(|c: char| c.is_whitespace() || c == '(') {
+                // eg. includeBuild, composite builds are separate projects
+                continue;
+            }
+            rest
+        } else {
+            continue;
+        };
+        let mut quote = None;
+        let mut current = String::new();
+        for c in args.chars() {
+            match quote {
+                Some(q) if c == q => {
+                    let path = current.trim_start_matches(':').replace(':', "/");
+                    result.push(path);
+                    current.clear();
+                    quote = None;
+                }
+                Some(_) => current.push(c),
+                None if c == '"' || c == '\'' => quote = Some(c),
+                None => (),
+            }
+        }
+        continued =
+            args.ends_with(',') || ((continued || args.contains('(')) && !args.contains(')'));
+    }
+    result
+}
+
+#[derive(Debug, Clone)]
+pub struct GradleScript {
+    pub compressed_node: NodeIdentifier,
+    pub metrics: DefaultMetrics,
+    submodules: Vec<String>,
+}
+
+/// Gradle projects are represented like maven modules,
+/// so the module level analysis, eg. [`crate::maven::IterMavenModules`], works the same.
+impl MavenModuleAcc {
+    /// A gradle project follows the maven layout for its sources by default.
+    pub(crate) fn mark_gradle_project(&mut self) {
+        self.status |= SemFlags::IsMavenModule;
+        self.main_dirs
+            .get_or_insert_with(|| vec!["src/main/java".into()]);
+        self.test_dirs
+            .get_or_insert_with(|| vec!["src/test/java".into()]);
+    }
+    pub(crate) fn push_gradle_script(&mut self, name: LabelIdentifier, full_node: GradleScript) {
+        self.mark_gradle_project();
+        assert!(!self.primary.children_names.contains(&name));
+        self.primary.children.push(full_node.compressed_node);
+        self.primary.children_names.push(name);
+        self.sub_modules
+            .get_or_insert_with(Default::default)
+            .extend(full_node.submodules.iter().map(|x| x.into()));
+        self.primary.metrics.acc(full_node.metrics);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn includes_of_settings() {
+        let text = r#"rootProject.name = 'demo'
+include ':app', ':lib:core'
+include(
+    ':lib:ui', // the ui
+    ':tools'
+)
+// include ':old'
+includeBuild '../plugins'
+"#;
+        assert_eq!(includes(text), vec!["app", "lib/core", "lib/ui", "tools"]);
+        let text = r#"rootProject.name = "demo"
+include("app")
+include("lib:core", "lib:ui")
+"#;
+        assert_eq!(includes(text), vec!["app", "lib/core", "lib/ui"]);
+    }
+}
@@ -0,0 +1,378 @@
+use crate::processing::erased::ParametrizedCommitProcessor2Handle as PCP2Handle;
+use crate::{
+    git::BasicGitObject,
+    maven::{MavenModuleAcc, SemFlags, MD},
+    maven_processor::MavenModuleHelper,
+    preprocessed::RepositoryProcessor,
+    processing::{
+        caches, erased::ParametrizedCommitProc2, file_sys, CacheHolding, InFiles, ObjectName,
+    },
+    Processor,
+};
+use git2::{Oid, Repository};
+use hyper_ast::{store::defaults::NodeIdentifier, tree_gen::Accumulator, types::LabelStore};
+use std::{
+    iter::Peekable,
+    path::{Components, PathBuf},
+};
+
+/// Processes the projects of a gradle build,
+/// they are found from the includes of the settings script and mapped like maven modules,
+/// ie. with java sources in src/main/java and src/test/java.
+pub struct GradleProcessor<'repo, 'prepro, 'd, 'c, Acc> {
+    repository: &'repo Repository,
+    prepro: &'prepro mut RepositoryProcessor,
+    stack: Vec<(Oid, Vec<BasicGitObject>, Acc)>,
+    pub dir_path: &'d mut Peekable<Components<'c>>,
+    handle: PCP2Handle<GradleProc>,
+}
+
+impl<'repo, 'b, 'd, 'c, Acc: From<String>> GradleProcessor<'repo, 'b, 'd, 'c, Acc> {
+    pub fn new(
+        repository: &'repo Repository,
+        prepro: &'b mut RepositoryProcessor,
+        dir_path: &'d mut Peekable<Components<'c>>,
+        name: &[u8],
+        oid: git2::Oid,
+    ) -> Self {
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<GradleProcessorHolder>();
+        let handle =
+            <GradleProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let tree = repository.find_tree(oid).unwrap();
+        let prepared = prepare_dir_exploration(tree, dir_path);
+        let name = std::str::from_utf8(&name).unwrap().to_string();
+        let stack = vec![(oid, prepared, Acc::from(name))];
+        Self {
+            stack,
+            repository,
+            prepro,
+            dir_path,
+            handle,
+        }
+    }
+}
+
+impl<'repo, 'b, 'd, 'c> Processor<MavenModuleAcc>
+    for GradleProcessor<'repo, 'b, 'd, 'c, MavenModuleAcc>
+{
+    fn pre(&mut self, current_object: BasicGitObject) {
+        match current_object {
+            BasicGitObject::Tree(oid, name) => {
+                self.handle_tree_cached(oid, name);
+            }
+            BasicGitObject::Blob(oid, name)
+                if self.dir_path.peek().is_none() && file_sys::GradleScript::matches(&name) =>
+            {
+                let parent_acc = &mut self.stack.last_mut().unwrap().2;
+                if let Err(err) = self.prepro.help_handle_gradle_script(
+                    oid,
+                    parent_acc,
+                    name,
+                    self.repository,
+                    self.handle,
+                ) {
+                    log::debug!("{:?}", err);
+                }
+            }
+            _ => {}
+        }
+    }
+    fn post(&mut self, oid: Oid, acc: MavenModuleAcc) -> Option<(NodeIdentifier, MD)> {
+        let name = acc.primary.name.clone();
+        let full_node =
+            crate::maven_processor::make(acc, self.prepro.main_stores_mut().mut_with_ts());
+        CacheHolding::<caches::Gradle>::get_caches_mut(
+            self.prepro
+                .processing_systems
+                .mut_or_default::<GradleProcessorHolder>(),
+        )
+        .object_map
+        .insert(oid, full_node.clone());
+        let name = self.prepro.main_stores.label_store.get_or_insert(name);
+        if self.stack.is_empty() {
+            Some(full_node)
+        } else {
+            let w = &mut self.stack.last_mut().unwrap().2;
+            assert!(
+                !w.primary.children_names.contains(&name),
+                "{:?} {:?}",
+                w.primary.children_names,
+                name
+            );
+            if full_node.1.status.contains(SemFlags::IsMavenModule) {
+                w.push_submodule(name, full_node);
+            } else {
+                w.push((name, full_node));
+            }
+            None
+        }
+    }
+
+    fn stack(&mut self) -> &mut Vec<(Oid, Vec<BasicGitObject>, MavenModuleAcc)> {
+        &mut self.stack
+    }
+}
+
+impl<'repo, 'prepro, 'd, 'c> GradleProcessor<'repo, 'prepro, 'd, 'c, MavenModuleAcc> {
+    fn handle_tree_cached(&mut self, oid: Oid, name: ObjectName) {
+        if let Some(s) = self.dir_path.peek() {
+            if name
+                .as_bytes()
+                .eq(std::ffi::OsStr::as_encoded_bytes(s.as_os_str()))
+            {
+                self.dir_path.next();
+                self.stack.last_mut().expect("never empty").1.clear();
+                let tree = self.repository.find_tree(oid).unwrap();
+                let prepared = prepare_dir_exploration(tree, self.dir_path);
+                self.stack
+                    .push((oid, prepared, MavenModuleAcc::new(name.try_into().unwrap())));
+            }
+            return;
+        }
+        if let Some(already) = CacheHolding::<caches::Gradle>::get_caches_mut(
+            self.prepro
+                .processing_systems
+                .mut_or_default::<GradleProcessorHolder>(),
+        )
+        .object_map
+        .get(&oid)
+        {
+            // reinit already computed node for post order
+            let full_node = already.clone();
+            let w = &mut self.stack.last_mut().unwrap().2;
+            let name = self.prepro.intern_object_name(&name);
+            assert!(!w.primary.children_names.contains(&name));
+            if full_node.1.status.contains(SemFlags::IsMavenModule) {
+                w.push_submodule(name, full_node);
+            } else {
+                w.push((name, full_node));
+            }
+            return;
+        }
+        log::debug!("gradle tree {:?}", name.try_str());
+        let parent_acc = &mut self.stack.last_mut().unwrap().2;
+        let helper = MavenModuleHelper::from((parent_acc, &name));
+        if helper.source_directories.0 || helper.test_source_directories.0 {
+            let (name, (full_node, _)) =
+                self.prepro
+                    .help_handle_java_folder(self.repository, self.dir_path, oid, &name);
+            let parent_acc = &mut self.stack.last_mut().unwrap().2;
+            assert!(!parent_acc.primary.children_names.contains(&name));
+            if helper.source_directories.0 {
+                parent_acc.push_source_directory(name, full_node);
+            } else {
+                parent_acc.push_test_source_directory(name, full_node);
+            }
+            return;
+        }
+        // projects do not always have a build script, included ones are projects anyway
+        let included = helper.submodules.0;
+        let mut acc: MavenModuleAcc = helper.into();
+        if included {
+            acc.mark_gradle_project();
+        }
+        let tree = self.repository.find_tree(oid).unwrap();
+        let prepared = prepare_dir_exploration(tree, self.dir_path);
+        self.stack.push((oid, prepared, acc));
+    }
+}
+
+/// The settings script first, so the included projects are known before exploring the directories,
+/// then the build script.
+pub(crate) fn prepare_dir_exploration(
+    tree: git2::Tree,
+    dir_path: &mut Peekable<Components>,
+) -> Vec<BasicGitObject> {
+    let mut children_objects: Vec<BasicGitObject> = tree
+        .iter()
+        .map(TryInto::try_into)
+        .filter_map(|x| x.ok())
+        .collect();
+    if dir_path.peek().is_none() {
+        children_objects.sort_by_key(|x| match x {
+            BasicGitObject::Blob(_, n) if file_sys::GradleSettings::matches(n) => 0,
+            BasicGitObject::Blob(_, n) if file_sys::GradleScript::matches(n) => 1,
+            _ => 2,
+        });
+    }
+    children_objects.reverse(); // we use it like a stack
+    children_objects
+}
+
+impl RepositoryProcessor {
+    fn help_handle_gradle_script(
+        &mut self,
+        oid: Oid,
+        parent_acc: &mut MavenModuleAcc,
+        name: ObjectName,
+        repository: &Repository,
+        parameters: PCP2Handle<GradleProc>,
+    ) -> Result<(), crate::ParseErr> {
+        let x = self
+            .processing_systems
+            .caching_blob_handler::<file_sys::GradleScript>()
+            .handle2(oid, repository, &name, parameters, |_, n, t| {
+                crate::gradle::handle_gradle_script(self.main_stores.mut_with_ts(), n, t)
+                    .map_err(|_| crate::ParseErr::IllFormed)
+            })?;
+        let name = self.intern_object_name(&name);
+        assert!(!parent_acc.primary.children_names.contains(&name));
+        parent_acc.push_gradle_script(name, x);
+        Ok(())
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Parameter;
+#[derive(Default)]
+pub struct GradleProcessorHolder(Option<GradleProc>);
+pub struct GradleProc {
+    parameter: Parameter,
+    cache: caches::Gradle,
+    scripts_cache: caches::GradleScript,
+    commits: std::collections::HashMap<git2::Oid, crate::Commit>,
+}
+impl crate::processing::erased::Parametrized for GradleProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(GradleProc {
+                    parameter: t,
+                    cache: Default::default(),
+                    scripts_cache: Default::default(),
+                    commits: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+
+struct PreparedGradleCommitProc<'repo> {
+    repository: &'repo git2::Repository,
+    commit_builder: crate::preprocessed::CommitBuilder,
+}
+
+impl<'repo> crate::processing::erased::PreparedCommitProc for PreparedGradleCommitProc<'repo> {
+    fn process(
+        self: Box<PreparedGradleCommitProc<'repo>>,
+        prepro: &mut RepositoryProcessor,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        let name = b"";
+        let root_full_node = GradleProcessor::<MavenModuleAcc>::new(
+            self.repository,
+            prepro,
+            &mut dir_path,
+            name,
+            self.commit_builder.tree_oid(),
+        )
+        .process();
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<GradleProcessorHolder>();
+        let handle =
+            <GradleProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let commit_oid = self.commit_builder.commit_oid();
+        let commit = self.commit_builder.finish(root_full_node.0);
+        h.with_parameters_mut(handle.0)
+            .commits
+            .insert(commit_oid, commit);
+        root_full_node.0
+    }
+}
+
+impl crate::processing::erased::CommitProc for GradleProc {
+    fn prepare_processing<'repo>(
+        &self,
+        repository: &'repo git2::Repository,
+        commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc + 'repo> {
+        Box::new(PreparedGradleCommitProc {
+            repository,
+            commit_builder,
+        })
+    }
+
+    fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        self.commits.get(&commit_oid)
+    }
+
+    fn commits(&self) -> Box<dyn Iterator<Item = (&git2::Oid, &crate::Commit)> + '_> {
+        Box::new(self.commits.iter())
+    }
+
+    fn restore_commit(
+        &mut self,
+        commit_oid: git2::Oid,
+        commit: crate::Commit,
+    ) -> std::io::Result<()> {
+        self.commits.insert(commit_oid, commit);
+        Ok(())
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for GradleProc {
+    type Holder = GradleProcessorHolder;
+}
+
+impl ParametrizedCommitProc2 for GradleProcessorHolder {
+    type Proc = GradleProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+}
+
+impl CacheHolding<caches::Gradle> for GradleProc {
+    fn get_caches_mut(&mut self) -> &mut caches::Gradle {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &caches::Gradle {
+        &self.cache
+    }
+}
+
+impl CacheHolding<caches::Gradle> for GradleProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut caches::Gradle {
+        &mut self.0.as_mut().unwrap().cache
+    }
+    fn get_caches(&self) -> &caches::Gradle {
+        &self.0.as_ref().unwrap().cache
+    }
+}
+
+impl CacheHolding<caches::GradleScript> for GradleProc {
+    fn get_caches_mut(&mut self) -> &mut caches::GradleScript {
+        &mut self.scripts_cache
+    }
+    fn get_caches(&self) -> &caches::GradleScript {
+        &self.scripts_cache
+    }
+}
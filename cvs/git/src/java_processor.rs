@@ -342,6 +342,19 @@ impl crate::processing::erased::CommitProc for JavaProc {
     ) -> Box<dyn crate::processing::erased::PreparedCommitProc + 'repo> {
         unimplemented!("required for processing java at the root of project")
     }
+
+    fn commits(&self) -> Box<dyn Iterator<Item = (&git2::Oid, &crate::Commit)> + '_> {
+        Box::new(self.commits.iter())
+    }
+
+    fn restore_commit(
+        &mut self,
+        commit_oid: git2::Oid,
+        commit: crate::Commit,
+    ) -> std::io::Result<()> {
+        self.commits.insert(commit_oid, commit);
+        Ok(())
+    }
 }
 
 impl crate::processing::erased::CommitProcExt for JavaProc {
@@ -477,7 +490,7 @@ impl RepositoryProcessor {
         (name, full_node)
     }
 
-    fn handle_java_blob(
+    pub(crate) fn handle_java_blob(
         &mut self,
         oid: Oid,
         name: &ObjectName,
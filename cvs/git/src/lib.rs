@@ -1,14 +1,31 @@
 #![feature(test)]
 #![feature(extract_if)]
 pub mod allrefs;
+#[cfg(feature = "any")]
+pub mod any;
+#[cfg(feature = "cmake")]
+pub mod cmake;
+pub mod commit_range;
 pub mod cpp;
 pub mod git;
+#[cfg(feature = "gradle")]
+pub mod gradle;
 pub mod java;
 pub mod make;
 pub mod maven;
+#[cfg(feature = "npm")]
+pub mod npm;
+#[cfg(feature = "python")]
+pub mod python;
 
+#[cfg(feature = "any")]
+pub mod any_processor;
+#[cfg(feature = "cmake")]
+pub mod cmake_processor;
 #[cfg(feature = "cpp")]
 pub mod cpp_processor;
+#[cfg(feature = "gradle")]
+pub mod gradle_processor;
 #[cfg(feature = "java")]
 pub mod java_processor;
 #[cfg(feature = "make")]
@@ -17,14 +34,25 @@ pub mod make_processor;
 pub mod maven_processor;
 pub mod multi_preprocessed;
 pub mod no_space;
+#[cfg(feature = "npm")]
+pub mod npm_processor;
+#[cfg(any(feature = "maven", feature = "cmake", feature = "any"))]
+mod opaque;
 /// for now only tested on maven repositories with a pom in root.
 pub mod preprocessed;
 pub mod processing;
+#[cfg(feature = "python")]
+pub mod python_processor;
+pub mod search;
+pub mod snapshot;
 mod utils;
 
 #[cfg(test)]
 pub mod tests;
 
+#[cfg(any(test, feature = "fixtures"))]
+pub mod fixtures;
+
 use git::BasicGitObject;
 use git2::Oid;
 use hyper_ast::{store::defaults::LabelIdentifier, utils::Bytes};
@@ -115,11 +143,47 @@ fn ts_lang_java() -> Option<tree_sitter::Language> {
 fn ts_lang_java() -> Option<tree_sitter::Language> {
     None
 }
+#[cfg(any(feature = "maven", feature = "any"))]
+fn ts_lang_xml() -> Option<tree_sitter::Language> {
+    Some(hyper_ast_gen_ts_xml::language())
+}
+#[cfg(not(any(feature = "maven", feature = "any")))]
+fn ts_lang_xml() -> Option<tree_sitter::Language> {
+    None
+}
+#[cfg(feature = "ts")]
+fn ts_lang_ts() -> Option<tree_sitter::Language> {
+    Some(hyper_ast_gen_ts_ts::language())
+}
+#[cfg(not(feature = "ts"))]
+fn ts_lang_ts() -> Option<tree_sitter::Language> {
+    None
+}
+#[cfg(feature = "python")]
+fn ts_lang_python() -> Option<tree_sitter::Language> {
+    Some(hyper_ast_gen_ts_python::language())
+}
+#[cfg(not(feature = "python"))]
+fn ts_lang_python() -> Option<tree_sitter::Language> {
+    None
+}
+#[cfg(feature = "tsquery")]
+fn ts_lang_tsquery() -> Option<tree_sitter::Language> {
+    Some(hyper_ast_gen_ts_tsquery::language())
+}
+#[cfg(not(feature = "tsquery"))]
+fn ts_lang_tsquery() -> Option<tree_sitter::Language> {
+    None
+}
 
 pub fn resolve_language(language: &str) -> Option<tree_sitter::Language> {
     match language {
         "Java" | "java" => ts_lang_java(),
         "Cpp" | "cpp" => ts_lang_cpp(),
+        "Xml" | "xml" => ts_lang_xml(),
+        "Ts" | "ts" | "Typescript" | "typescript" => ts_lang_ts(),
+        "Python" | "python" => ts_lang_python(),
+        "Query" | "query" | "tsquery" => ts_lang_tsquery(),
         _ => None,
     }
 }
@@ -180,7 +180,7 @@ impl<'a> IterMavenModules2<'a> {
             })
             .is_some();
 
-        if contains_pom {
+        if contains_pom || is_module(&b) {
             Some(x)
         } else {
             while !self.remaining.is_empty() {
@@ -455,6 +455,12 @@ impl<'a, T: TreePathMut<NodeIdentifier, u16> + Debug + Clone> Iterator for IterM
 type XmlIdN = hyper_ast_gen_ts_xml::types::TIdN<NodeIdentifier>;
 type XmlNode<'a> = hyper_ast::store::nodes::legion::HashedNodeRef<'a, XmlIdN>;
 
+/// Modules without a pom.xml, eg. gradle projects, are flagged by their processor
+fn is_module(b: &XmlNode) -> bool {
+    b.get_component::<EnumSet<SemFlags>>()
+        .map_or(false, |x| x.contains(SemFlags::IsMavenModule))
+}
+
 impl<'a, T: TreePath<NodeIdentifier>> IterMavenModules<'a, T> {
     pub fn new(stores: &'a SimpleStores, path: T, root: NodeIdentifier) -> Self {
         let stack = vec![(root, 0, None)];
@@ -505,7 +511,7 @@ impl<'a, T: TreePath<NodeIdentifier>> IterMavenModules<'a, T> {
                 }
             })
             .is_some();
-        contains_pom
+        contains_pom || is_module(b)
     }
 }
 
@@ -345,11 +345,11 @@ impl RepositoryProcessor {
     }
 }
 
-struct MavenModuleHelper {
+pub(crate) struct MavenModuleHelper {
     name: String,
-    submodules: (bool, Vec<PathBuf>),
-    source_directories: (bool, Vec<PathBuf>),
-    test_source_directories: (bool, Vec<PathBuf>),
+    pub(crate) submodules: (bool, Vec<PathBuf>),
+    pub(crate) source_directories: (bool, Vec<PathBuf>),
+    pub(crate) test_source_directories: (bool, Vec<PathBuf>),
 }
 
 impl From<(&mut MavenModuleAcc, &ObjectName)> for MavenModuleHelper {
@@ -628,6 +628,19 @@ impl crate::processing::erased::CommitProc for MavenProc {
     fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
         self.commits.get(&commit_oid)
     }
+
+    fn commits(&self) -> Box<dyn Iterator<Item = (&git2::Oid, &crate::Commit)> + '_> {
+        Box::new(self.commits.iter())
+    }
+
+    fn restore_commit(
+        &mut self,
+        commit_oid: git2::Oid,
+        commit: crate::Commit,
+    ) -> std::io::Result<()> {
+        self.commits.insert(commit_oid, commit);
+        Ok(())
+    }
 }
 
 impl crate::processing::erased::CommitProcExt for MavenProc {
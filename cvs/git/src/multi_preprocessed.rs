@@ -22,14 +22,18 @@ pub struct PreProcessedRepositories {
     // pub commits: HashMap<RepoConfig, HashMap<git2::Oid, Commit>>,
     pub processor: RepositoryProcessor,
     // pub processing_ordered_commits: HashMap<String,Vec<git2::Oid>>,
-    configs: HashMap<Repo, ParametrizedCommitProcessorHandle>,
+    configs: HashMap<Repo, (RepoConfig, ParametrizedCommitProcessorHandle)>,
+    pub(crate) snapshot: crate::snapshot::Snapshotted,
 }
 
 #[derive(Default)]
 pub struct CommitsPerSys {
     pub maven: HashMap<git2::Oid, Commit>,
+    pub gradle: HashMap<git2::Oid, Commit>,
     pub make: HashMap<git2::Oid, Commit>,
+    pub cmake: HashMap<git2::Oid, Commit>,
     pub npm: HashMap<git2::Oid, Commit>,
+    pub pip: HashMap<git2::Oid, Commit>,
     pub any: HashMap<git2::Oid, Commit>,
 }
 
@@ -45,8 +49,11 @@ impl CommitsPerSys {
     pub fn accessCommits<'a>(&'a self, sys: &RepoConfig) -> &'a HashMap<git2::Oid, Commit> {
         match sys {
             RepoConfig::JavaMaven => &self.maven,
+            RepoConfig::JavaGradle => &self.gradle,
             RepoConfig::CppMake => &self.make,
+            RepoConfig::CppCMake => &self.cmake,
             RepoConfig::TsNpm => &self.npm,
+            RepoConfig::PythonPip => &self.pip,
             RepoConfig::Any => &self.any,
         }
     }
@@ -121,39 +128,85 @@ impl PreProcessedRepositories {
     }
 
     pub fn register_config(&mut self, repo: Repo, config: RepoConfig) -> ConfiguredRepoHandle2 {
+        let r = ConfiguredRepoHandle2 {
+            spec: repo,
+            config: self.processor_handle(config),
+        };
+        self.configs.insert(r.spec.clone(), (config, r.config));
+        r
+    }
+
+    pub(crate) fn processor_handle(
+        &mut self,
+        config: RepoConfig,
+    ) -> ParametrizedCommitProcessorHandle {
         use crate::processing::erased::Parametrized;
-        let r = match config {
+        match config {
             RepoConfig::JavaMaven => {
                 let h = self
                     .processor
                     .processing_systems
                     .mut_or_default::<crate::maven_processor::MavenProcessorHolder>();
-                ConfiguredRepoHandle2 {
-                    spec: repo,
-                    config: h.register_param(crate::maven_processor::Parameter),
-                }
+                h.register_param(crate::maven_processor::Parameter)
+            }
+            RepoConfig::JavaGradle => {
+                let h = self
+                    .processor
+                    .processing_systems
+                    .mut_or_default::<crate::gradle_processor::GradleProcessorHolder>();
+                h.register_param(crate::gradle_processor::Parameter)
             }
             RepoConfig::CppMake => {
                 let h = self
                     .processor
                     .processing_systems
                     .mut_or_default::<crate::make_processor::MakeProcessorHolder>();
-                ConfiguredRepoHandle2 {
-                    spec: repo,
-                    config: h.register_param(crate::make_processor::Parameter),
-                }
+                h.register_param(crate::make_processor::Parameter)
             }
-            _ => todo!(),
-        };
+            RepoConfig::CppCMake => {
+                let h = self
+                    .processor
+                    .processing_systems
+                    .mut_or_default::<crate::cmake_processor::CMakeProcessorHolder>();
+                h.register_param(crate::cmake_processor::Parameter)
+            }
+            RepoConfig::TsNpm => {
+                let h = self
+                    .processor
+                    .processing_systems
+                    .mut_or_default::<crate::npm_processor::NpmProcessorHolder>();
+                h.register_param(crate::npm_processor::Parameter)
+            }
+            RepoConfig::PythonPip => {
+                let h = self
+                    .processor
+                    .processing_systems
+                    .mut_or_default::<crate::python_processor::PythonProcessorHolder>();
+                h.register_param(crate::python_processor::Parameter)
+            }
+            RepoConfig::Any => {
+                let h = self
+                    .processor
+                    .processing_systems
+                    .mut_or_default::<crate::any_processor::AnyProcessorHolder>();
+                h.register_param(crate::any_processor::Parameter)
+            }
+        }
+    }
 
-        self.configs.insert(r.spec.clone(), r.config);
-        r
+    pub(crate) fn configs(&self) -> impl Iterator<Item = (&Repo, &RepoConfig)> {
+        self.configs.iter().map(|(repo, (config, _))| (repo, config))
     }
 
     pub fn get_config(&self, repo: Repo) -> Option<ConfiguredRepoHandle2> {
         self.configs
             .get(&repo)
-            .map(|&config| ConfiguredRepoHandle2 { config, spec: repo })
+            .map(|&(_, config)| ConfiguredRepoHandle2 { config, spec: repo })
+    }
+
+    /// The config registered for `repo`, eg. to know the language of its code.
+    pub fn get_repo_config(&self, repo: &Repo) -> Option<RepoConfig> {
+        self.configs.get(repo).map(|&(config, _)| config)
     }
 
     pub fn pre_process_with_limit(
@@ -0,0 +1,282 @@
+use crate::{
+    preprocessed::IsSkippedAna, processing::ObjectName, Accumulator, BasicDirAcc, DefaultMetrics,
+    PROPAGATE_ERROR_ON_BAD_CST_NODE,
+};
+use enumset::EnumSet;
+use hyper_ast::store::defaults::{LabelIdentifier, NodeIdentifier};
+use hyper_ast_gen_ts_ts::{legion as ts_tree_gen, types::TStore};
+use std::path::{Component, PathBuf};
+
+pub(crate) fn handle_ts_file<'stores, 'cache, 'b: 'stores>(
+    tree_gen: &mut ts_tree_gen::TsTreeGen<'stores, 'cache, TStore>,
+    name: &ObjectName,
+    text: &'b [u8],
+) -> Result<ts_tree_gen::FNode, ()> {
+    let tree = match tree_sitter_parse(name, text) {
+        Ok(tree) => tree,
+        Err(tree) => {
+            log::warn!("bad CST: {:?}", name.try_str());
+            log::debug!("{}", tree.root_node().to_sexp());
+            if PROPAGATE_ERROR_ON_BAD_CST_NODE {
+                return Err(());
+            } else {
+                tree
+            }
+        }
+    };
+    Ok(tree_gen.generate_file(name.as_bytes(), text, tree.walk()))
+}
+
+/// tsx and jsx files need the tsx grammar, the other sources are parsed with the typescript one.
+pub(crate) fn tree_sitter_parse(
+    name: &ObjectName,
+    text: &[u8],
+) -> Result<tree_sitter::Tree, tree_sitter::Tree> {
+    let name = name.as_bytes();
+    if name.ends_with(b".tsx") || name.ends_with(b".jsx") {
+        ts_tree_gen::TsTreeGen::<TStore>::tree_sitter_parse_tsx(text)
+    } else {
+        ts_tree_gen::TsTreeGen::<TStore>::tree_sitter_parse(text)
+    }
+}
+
+/// The content of a package.json is kept in the HyperAST by the typescript generator,
+/// for now there is no json grammar and the typescript one is the closest.
+/// The module description is read with a proper json parser.
+pub(crate) fn handle_package_json<'stores, 'cache, 'b: 'stores>(
+    tree_gen: &mut ts_tree_gen::TsTreeGen<'stores, 'cache, TStore>,
+    name: &ObjectName,
+    text: &'b [u8],
+) -> Result<PackageJson, ()> {
+    let tree = match ts_tree_gen::TsTreeGen::<TStore>::tree_sitter_parse(text) {
+        Ok(tree) => tree,
+        Err(tree) => {
+            log::debug!("package.json as ts: {}", tree.root_node().to_sexp());
+            tree
+        }
+    };
+    let descr = match serde_json::from_slice::<PackageDescr>(text) {
+        Ok(descr) => descr,
+        Err(err) => {
+            log::warn!("bad package.json {:?}: {}", name.try_str(), err);
+            if PROPAGATE_ERROR_ON_BAD_CST_NODE {
+                return Err(());
+            }
+            PackageDescr::default()
+        }
+    };
+    let x = tree_gen
+        .generate_file(name.as_bytes(), text, tree.walk())
+        .local;
+    Ok(PackageJson {
+        compressed_node: x.compressed_node,
+        metrics: x.metrics,
+        name: descr.name,
+        workspaces: descr.workspaces.into_paths(),
+    })
+}
+
+/// The few fields of a package.json needed to find the modules of a repository
+#[derive(serde::Deserialize, Default)]
+struct PackageDescr {
+    name: Option<String>,
+    #[serde(default)]
+    workspaces: Workspaces,
+}
+
+/// npm and yarn declare workspaces as a list of paths,
+/// yarn also accepts them in a `packages` field.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum Workspaces {
+    Paths(Vec<String>),
+    Packages {
+        #[serde(default)]
+        packages: Vec<String>,
+    },
+}
+
+impl Default for Workspaces {
+    fn default() -> Self {
+        Workspaces::Paths(vec![])
+    }
+}
+
+impl Workspaces {
+    fn into_paths(self) -> Vec<PathBuf> {
+        let (Workspaces::Paths(x) | Workspaces::Packages { packages: x }) = self;
+        x.into_iter()
+            .map(|x| {
+                PathBuf::from(x)
+                    .components()
+                    .filter(|x| x != &Component::CurDir)
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PackageJson {
+    pub compressed_node: NodeIdentifier,
+    pub metrics: DefaultMetrics,
+    pub name: Option<String>,
+    /// paths to the workspaces, they can contain `*` wildcards
+    pub workspaces: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MD {
+    pub(crate) metrics: DefaultMetrics,
+    pub(crate) status: EnumSet<SemFlags>,
+}
+
+#[derive(enumset::EnumSetType, Debug)]
+pub enum SemFlags {
+    /// holds a `package.json`
+    IsNpmPackage,
+    /// declared as a workspace by the package.json of a parent package
+    IsNpmWorkspace,
+    HoldNpmSubPackage,
+}
+
+pub struct NpmModuleAcc {
+    pub(crate) primary: BasicDirAcc<NodeIdentifier, LabelIdentifier, DefaultMetrics>,
+    /// the paths of the workspaces still to be found inside this directory
+    pub(crate) sub_modules: Option<Vec<PathBuf>>,
+    /// sub_modules come from a parent package, so the directory cannot be reused elsewhere
+    pub(crate) inherited: bool,
+    pub(crate) status: EnumSet<SemFlags>,
+}
+
+impl From<String> for NpmModuleAcc {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl NpmModuleAcc {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            primary: BasicDirAcc::new(name),
+            sub_modules: None,
+            inherited: false,
+            status: Default::default(),
+        }
+    }
+    pub(crate) fn with_content(name: String, sub_modules: Vec<PathBuf>, workspace: bool) -> Self {
+        let mut status = EnumSet::new();
+        if workspace {
+            status |= SemFlags::IsNpmWorkspace;
+        }
+        Self {
+            primary: BasicDirAcc::new(name),
+            inherited: workspace || !sub_modules.is_empty(),
+            sub_modules: if sub_modules.is_empty() {
+                None
+            } else {
+                Some(sub_modules)
+            },
+            status,
+        }
+    }
+}
+
+impl NpmModuleAcc {
+    pub(crate) fn push_package_json(&mut self, name: LabelIdentifier, full_node: PackageJson) {
+        self.status |= SemFlags::IsNpmPackage;
+        assert!(!self.primary.children_names.contains(&name));
+        self.primary.children.push(full_node.compressed_node);
+        self.primary.children_names.push(name);
+        self.sub_modules
+            .get_or_insert_with(Default::default)
+            .extend(full_node.workspaces);
+        self.primary.metrics.acc(full_node.metrics);
+    }
+    pub(crate) fn push_source_file(
+        &mut self,
+        name: LabelIdentifier,
+        full_node: ts_tree_gen::Local,
+        _skiped_ana: IsSkippedAna,
+    ) {
+        self.primary
+            .push(name, full_node.compressed_node, full_node.metrics);
+    }
+    pub fn push_submodule(&mut self, name: LabelIdentifier, full_node: (NodeIdentifier, MD)) {
+        if full_node.1.status.contains(SemFlags::HoldNpmSubPackage)
+            || full_node.1.status.contains(SemFlags::IsNpmPackage)
+        {
+            self.status |= SemFlags::HoldNpmSubPackage;
+        }
+        self.primary.push(name, full_node.0, full_node.1.metrics);
+    }
+}
+
+impl hyper_ast::tree_gen::Accumulator for NpmModuleAcc {
+    type Node = (LabelIdentifier, (NodeIdentifier, MD));
+    fn push(&mut self, (name, full_node): Self::Node) {
+        self.push_submodule(name, full_node);
+    }
+}
+
+impl Accumulator for NpmModuleAcc {
+    type Unlabeled = (NodeIdentifier, MD);
+}
+
+/// Match the workspaces of a parent package against a child directory,
+/// a `*` component matching any directory.
+///
+/// Returns if the directory is itself a workspace and the paths to search inside it.
+pub(crate) fn match_workspaces(
+    sub_modules: &Option<Vec<PathBuf>>,
+    name: &str,
+) -> (bool, Vec<PathBuf>) {
+    let mut matched = false;
+    let mut inside = vec![];
+    for x in sub_modules.iter().flatten() {
+        let mut components = x.components();
+        let Some(first) = components.next() else {
+            continue;
+        };
+        let first = first.as_os_str();
+        if first != "*" && first != name {
+            continue;
+        }
+        let rest = components.as_path();
+        if rest.as_os_str().is_empty() {
+            matched = true;
+        } else {
+            inside.push(rest.to_owned());
+        }
+    }
+    (matched, inside)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workspaces_from_package_json() {
+        let text = br#"{"name": "root", "private": true, "workspaces": ["packages/*", "./tools"]}"#;
+        let descr: PackageDescr = serde_json::from_slice(text).unwrap();
+        assert_eq!(descr.name.as_deref(), Some("root"));
+        let sub_modules = Some(descr.workspaces.into_paths());
+        assert_eq!(
+            match_workspaces(&sub_modules, "packages"),
+            (false, vec![PathBuf::from("*")])
+        );
+        assert_eq!(match_workspaces(&sub_modules, "tools"), (true, vec![]));
+        assert_eq!(match_workspaces(&sub_modules, "docs"), (false, vec![]));
+        let sub_modules = Some(vec![PathBuf::from("*")]);
+        assert_eq!(match_workspaces(&sub_modules, "core"), (true, vec![]));
+
+        let text = br#"{"workspaces": {"packages": ["libs/a"], "nohoist": []}}"#;
+        let descr: PackageDescr = serde_json::from_slice(text).unwrap();
+        assert_eq!(descr.name, None);
+        assert_eq!(descr.workspaces.into_paths(), vec![PathBuf::from("libs/a")]);
+
+        let descr: PackageDescr = serde_json::from_slice(br#"{"version": "1.0.0"}"#).unwrap();
+        assert!(descr.workspaces.into_paths().is_empty());
+    }
+}
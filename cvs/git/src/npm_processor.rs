@@ -0,0 +1,507 @@
+use crate::{
+    git::BasicGitObject,
+    npm::{match_workspaces, NpmModuleAcc, MD},
+    preprocessed::{IsSkippedAna, RepositoryProcessor},
+    processing::{
+        caches, erased::ParametrizedCommitProc2, file_sys, CacheHolding, InFiles, ObjectName,
+    },
+    Processor,
+};
+use git2::{Oid, Repository};
+use hyper_ast::{
+    hashed::{IndexingHashBuilder, MetaDataHashsBuilder},
+    store::{defaults::NodeIdentifier, nodes::legion::eq_node},
+    types::LabelStore,
+};
+use hyper_ast_gen_ts_ts::{
+    legion as ts_gen,
+    types::{TsEnabledTypeStore as _, Type},
+};
+use std::{
+    iter::Peekable,
+    path::{Components, PathBuf},
+};
+
+pub type SimpleStores = hyper_ast::store::SimpleStores<hyper_ast_gen_ts_ts::types::TStore>;
+
+/// Processes the directories of an npm package,
+/// sources are parsed with the typescript generator,
+/// and the workspaces declared in a package.json are searched like maven submodules.
+pub struct NpmProcessor<'repo, 'prepro, 'd, 'c, Acc> {
+    repository: &'repo Repository,
+    prepro: &'prepro mut RepositoryProcessor,
+    stack: Vec<(Oid, Vec<BasicGitObject>, Acc)>,
+    pub dir_path: &'d mut Peekable<Components<'c>>,
+    handle: crate::processing::erased::ParametrizedCommitProcessor2Handle<NpmProc>,
+}
+
+impl<'repo, 'b, 'd, 'c, Acc: From<String>> NpmProcessor<'repo, 'b, 'd, 'c, Acc> {
+    pub fn new(
+        repository: &'repo Repository,
+        prepro: &'b mut RepositoryProcessor,
+        dir_path: &'d mut Peekable<Components<'c>>,
+        name: &[u8],
+        oid: git2::Oid,
+    ) -> Self {
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<NpmProcessorHolder>();
+        let handle =
+            <NpmProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let tree = repository.find_tree(oid).unwrap();
+        let prepared = prepare_dir_exploration(tree, dir_path);
+        let name = std::str::from_utf8(&name).unwrap().to_string();
+        let stack = vec![(oid, prepared, Acc::from(name))];
+        Self {
+            stack,
+            repository,
+            prepro,
+            dir_path,
+            handle,
+        }
+    }
+}
+
+impl<'repo, 'b, 'd, 'c> Processor<NpmModuleAcc> for NpmProcessor<'repo, 'b, 'd, 'c, NpmModuleAcc> {
+    fn pre(&mut self, current_object: BasicGitObject) {
+        match current_object {
+            BasicGitObject::Tree(oid, name) => {
+                if let Some(s) = self.dir_path.peek() {
+                    if name
+                        .as_bytes()
+                        .eq(std::ffi::OsStr::as_encoded_bytes(s.as_os_str()))
+                    {
+                        self.dir_path.next();
+                        self.stack.last_mut().expect("never empty").1.clear();
+                        let tree = self.repository.find_tree(oid).unwrap();
+                        let prepared = prepare_dir_exploration(tree, self.dir_path);
+                        self.stack.push((
+                            oid,
+                            prepared,
+                            NpmModuleAcc::new(name.try_into().unwrap()),
+                        ));
+                    }
+                    return;
+                }
+                if name.as_bytes() == b"node_modules" {
+                    // installed dependencies, they are not part of the project
+                    return;
+                }
+                self.handle_tree_cached(oid, name);
+            }
+            BasicGitObject::Blob(oid, name) => {
+                if self.dir_path.peek().is_some() {
+                    return;
+                }
+                if file_sys::PackageJson::matches(&name) {
+                    self.prepro
+                        .help_handle_package_json(
+                            oid,
+                            &mut self.stack.last_mut().unwrap().2,
+                            name,
+                            self.repository,
+                            self.handle,
+                        )
+                        .unwrap();
+                } else if file_sys::Ts::matches(&name) {
+                    self.prepro
+                        .help_handle_ts_file(
+                            oid,
+                            &mut self.stack.last_mut().unwrap().2,
+                            &name,
+                            self.repository,
+                            self.handle,
+                        )
+                        .unwrap();
+                } else {
+                    log::debug!("not ts source file {:?}", name.try_str());
+                }
+            }
+        }
+    }
+    fn post(&mut self, oid: Oid, acc: NpmModuleAcc) -> Option<(NodeIdentifier, MD)> {
+        let name = acc.primary.name.clone();
+        let key = (oid, name.as_bytes().into());
+        let inherited = acc.inherited;
+        let full_node = make(acc, self.prepro.main_stores_mut().mut_with_ts());
+        if !inherited {
+            CacheHolding::<caches::Npm>::get_caches_mut(
+                self.prepro
+                    .processing_systems
+                    .mut_or_default::<NpmProcessorHolder>(),
+            )
+            .object_map
+            .insert(key, full_node.clone());
+        }
+        let name = self.prepro.main_stores.label_store.get_or_insert(name);
+        if self.stack.is_empty() {
+            Some(full_node)
+        } else {
+            let w = &mut self.stack.last_mut().unwrap().2;
+            assert!(
+                !w.primary.children_names.contains(&name),
+                "{:?} {:?}",
+                w.primary.children_names,
+                name
+            );
+            w.push_submodule(name, full_node);
+            None
+        }
+    }
+
+    fn stack(&mut self) -> &mut Vec<(Oid, Vec<BasicGitObject>, NpmModuleAcc)> {
+        &mut self.stack
+    }
+}
+
+impl<'repo, 'prepro, 'd, 'c> NpmProcessor<'repo, 'prepro, 'd, 'c, NpmModuleAcc> {
+    fn handle_tree_cached(&mut self, oid: Oid, name: ObjectName) {
+        let parent_acc = &mut self.stack.last_mut().unwrap().2;
+        let (workspace, sub_modules) =
+            match_workspaces(&parent_acc.sub_modules, name.try_str().unwrap_or_default());
+        // directories scoped by the workspaces of a parent are not cached
+        if !workspace && sub_modules.is_empty() {
+            if let Some(already) = CacheHolding::<caches::Npm>::get_caches_mut(
+                self.prepro
+                    .processing_systems
+                    .mut_or_default::<NpmProcessorHolder>(),
+            )
+            .object_map
+            .get(&(oid, name.clone()))
+            {
+                // reinit already computed node for post order
+                let full_node = already.clone();
+                let w = &mut self.stack.last_mut().unwrap().2;
+                let name = self.prepro.intern_object_name(&name);
+                assert!(!w.primary.children_names.contains(&name));
+                w.push_submodule(name, full_node);
+                return;
+            }
+        }
+        log::debug!("npm tree {:?}", name.try_str());
+        let tree = self.repository.find_tree(oid).unwrap();
+        let prepared = prepare_dir_exploration(tree, self.dir_path);
+        let acc = NpmModuleAcc::with_content(name.try_into().unwrap(), sub_modules, workspace);
+        self.stack.push((oid, prepared, acc));
+    }
+}
+
+/// The package.json first, so the workspaces are known before exploring the directories.
+pub(crate) fn prepare_dir_exploration(
+    tree: git2::Tree,
+    dir_path: &mut Peekable<Components>,
+) -> Vec<BasicGitObject> {
+    let mut children_objects: Vec<BasicGitObject> = tree
+        .iter()
+        .map(TryInto::try_into)
+        .filter_map(|x| x.ok())
+        .collect();
+    if dir_path.peek().is_none() {
+        let p = children_objects.iter().position(|x| match x {
+            BasicGitObject::Blob(_, n) => file_sys::PackageJson::matches(n),
+            _ => false,
+        });
+        if let Some(p) = p {
+            children_objects.swap(0, p); // priority to config file processing
+        }
+    }
+    children_objects.reverse(); // we use it like a stack
+    children_objects
+}
+
+pub(crate) fn make(acc: NpmModuleAcc, stores: &mut SimpleStores) -> (NodeIdentifier, MD) {
+    let kind = Type::Directory;
+    let interned_kind = hyper_ast_gen_ts_ts::types::TStore::intern(kind);
+    let label_id = stores.label_store.get_or_insert(acc.primary.name.clone());
+
+    let primary = acc
+        .primary
+        .map_metrics(|m| m.finalize(&interned_kind, &label_id, 0));
+    let hashable = primary.metrics.hashs.most_discriminating();
+    let eq = eq_node(&interned_kind, Some(&label_id), &primary.children);
+    assert_eq!(primary.children_names.len(), primary.children.len());
+    let status = acc.status;
+
+    let insertion = stores.node_store.prepare_insertion(&hashable, eq);
+    if let Some(id) = insertion.occupied_id() {
+        let metrics = primary
+            .metrics
+            .map_hashs(|h| MetaDataHashsBuilder::build(h));
+        return (id, MD { metrics, status });
+    }
+
+    log::info!("make npm {} {}", &primary.name, primary.children.len());
+
+    let mut dyn_builder = hyper_ast::store::nodes::legion::dyn_builder::EntityBuilder::new();
+    let children_is_empty = primary.children.is_empty();
+    if !status.is_empty() {
+        dyn_builder.add(status);
+    }
+    let metrics = primary.persist(&mut dyn_builder, interned_kind, label_id);
+    let metrics = metrics.map_hashs(|h| h.build());
+    let hashs = metrics.add_md_metrics(&mut dyn_builder, children_is_empty);
+    hashs.persist(&mut dyn_builder);
+
+    let vacant = insertion.vacant();
+    let node_id = hyper_ast::store::nodes::legion::NodeStore::insert_built_after_prepare(
+        vacant,
+        dyn_builder.build(),
+    );
+
+    (node_id, MD { metrics, status })
+}
+
+fn line_break(text: &[u8]) -> Vec<u8> {
+    if text.contains(&b'\r') {
+        "\r\n".as_bytes().to_vec()
+    } else {
+        "\n".as_bytes().to_vec()
+    }
+}
+
+impl RepositoryProcessor {
+    pub(crate) fn handle_ts_blob(
+        &mut self,
+        oid: Oid,
+        name: &ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<NpmProc>,
+    ) -> Result<(ts_gen::Local, IsSkippedAna), crate::ParseErr> {
+        self.processing_systems
+            .caching_blob_handler::<file_sys::Ts>()
+            .handle2(oid, repository, &name, parameters, |c, n, t| {
+                crate::npm::handle_ts_file(
+                    &mut ts_gen::TsTreeGen {
+                        line_break: line_break(t),
+                        stores: self.main_stores.mut_with_ts(),
+                        md_cache: &mut CacheHolding::<caches::Ts>::get_caches_mut(
+                            c.mut_or_default::<NpmProcessorHolder>(),
+                        )
+                        .md_cache,
+                    },
+                    n,
+                    t,
+                )
+                .map_err(|_| crate::ParseErr::IllFormed)
+                .map(|x| (x.local.clone(), false))
+            })
+    }
+
+    pub(crate) fn help_handle_ts_file(
+        &mut self,
+        oid: Oid,
+        parent: &mut NpmModuleAcc,
+        name: &ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<NpmProc>,
+    ) -> Result<(), crate::ParseErr> {
+        let (full_node, skiped_ana) = self.handle_ts_blob(oid, name, repository, parameters)?;
+        let name = self.intern_object_name(name);
+        assert!(!parent.primary.children_names.contains(&name));
+        parent.push_source_file(name, full_node, skiped_ana);
+        Ok(())
+    }
+
+    fn help_handle_package_json(
+        &mut self,
+        oid: Oid,
+        parent: &mut NpmModuleAcc,
+        name: ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<NpmProc>,
+    ) -> Result<(), crate::ParseErr> {
+        let x = self
+            .processing_systems
+            .caching_blob_handler::<file_sys::PackageJson>()
+            .handle(oid, repository, &name, parameters, |c, n, t| {
+                crate::npm::handle_package_json(
+                    &mut ts_gen::TsTreeGen {
+                        line_break: line_break(t),
+                        stores: self.main_stores.mut_with_ts(),
+                        md_cache: &mut CacheHolding::<caches::Ts>::get_caches_mut(
+                            c.mut_or_default::<NpmProcessorHolder>(),
+                        )
+                        .md_cache,
+                    },
+                    n,
+                    t,
+                )
+                .map_err(|_| crate::ParseErr::IllFormed)
+            })?;
+        let name = self.intern_object_name(&name);
+        assert!(!parent.primary.children_names.contains(&name));
+        parent.push_package_json(name, x);
+        Ok(())
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Parameter;
+#[derive(Default)]
+pub(crate) struct NpmProcessorHolder(Option<NpmProc>);
+pub(crate) struct NpmProc {
+    parameter: Parameter,
+    cache: caches::Npm,
+    files_cache: caches::Ts,
+    package_cache: caches::PackageJson,
+    commits: std::collections::HashMap<git2::Oid, crate::Commit>,
+}
+impl crate::processing::erased::Parametrized for NpmProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(NpmProc {
+                    parameter: t,
+                    cache: Default::default(),
+                    files_cache: Default::default(),
+                    package_cache: Default::default(),
+                    commits: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+struct PreparedNpmCommitProc<'repo> {
+    repository: &'repo git2::Repository,
+    commit_builder: crate::preprocessed::CommitBuilder,
+}
+impl<'repo> crate::processing::erased::PreparedCommitProc for PreparedNpmCommitProc<'repo> {
+    fn process(
+        self: Box<PreparedNpmCommitProc<'repo>>,
+        prepro: &mut RepositoryProcessor,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        let name = b"";
+        let root_full_node = NpmProcessor::<NpmModuleAcc>::new(
+            self.repository,
+            prepro,
+            &mut dir_path,
+            name,
+            self.commit_builder.tree_oid(),
+        )
+        .process();
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<NpmProcessorHolder>();
+        let handle =
+            <NpmProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let commit_oid = self.commit_builder.commit_oid();
+        let commit = self.commit_builder.finish(root_full_node.0);
+        h.with_parameters_mut(handle.0)
+            .commits
+            .insert(commit_oid, commit);
+        root_full_node.0
+    }
+}
+
+impl crate::processing::erased::CommitProc for NpmProc {
+    fn prepare_processing<'repo>(
+        &self,
+        repository: &'repo git2::Repository,
+        commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc + 'repo> {
+        Box::new(PreparedNpmCommitProc {
+            repository,
+            commit_builder,
+        })
+    }
+
+    fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        self.commits.get(&commit_oid)
+    }
+
+    fn commits(&self) -> Box<dyn Iterator<Item = (&git2::Oid, &crate::Commit)> + '_> {
+        Box::new(self.commits.iter())
+    }
+
+    fn restore_commit(
+        &mut self,
+        commit_oid: git2::Oid,
+        commit: crate::Commit,
+    ) -> std::io::Result<()> {
+        self.commits.insert(commit_oid, commit);
+        Ok(())
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for NpmProc {
+    type Holder = NpmProcessorHolder;
+}
+
+impl crate::processing::erased::ParametrizedCommitProc2 for NpmProcessorHolder {
+    type Proc = NpmProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+}
+
+impl CacheHolding<caches::Npm> for NpmProc {
+    fn get_caches_mut(&mut self) -> &mut caches::Npm {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &caches::Npm {
+        &self.cache
+    }
+}
+
+impl CacheHolding<caches::Npm> for NpmProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut caches::Npm {
+        &mut self.0.as_mut().unwrap().cache
+    }
+    fn get_caches(&self) -> &caches::Npm {
+        &self.0.as_ref().unwrap().cache
+    }
+}
+
+impl CacheHolding<caches::Ts> for NpmProc {
+    fn get_caches_mut(&mut self) -> &mut caches::Ts {
+        &mut self.files_cache
+    }
+    fn get_caches(&self) -> &caches::Ts {
+        &self.files_cache
+    }
+}
+
+impl CacheHolding<caches::Ts> for NpmProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut caches::Ts {
+        &mut self.0.as_mut().unwrap().files_cache
+    }
+    fn get_caches(&self) -> &caches::Ts {
+        &self.0.as_ref().unwrap().files_cache
+    }
+}
+
+impl CacheHolding<caches::PackageJson> for NpmProc {
+    fn get_caches_mut(&mut self) -> &mut caches::PackageJson {
+        &mut self.package_cache
+    }
+    fn get_caches(&self) -> &caches::PackageJson {
+        &self.package_cache
+    }
+}
@@ -0,0 +1,72 @@
+//! Files without a grammar in the HyperAST,
+//! their content is kept as a single opaque leaf,
+//! thus they still take part in the hashing and the metrics of their directory.
+use crate::DefaultMetrics;
+use hyper_ast::{
+    filter::BloomSize,
+    hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder, SyntaxNodeHashs},
+    store::{
+        defaults::{LabelIdentifier, NodeIdentifier},
+        nodes::legion::{compo, EntryRef, NodeStore},
+    },
+    tree_gen::SubTreeMetrics,
+    types::LabelStore as _,
+};
+use hyper_ast_gen_ts_xml::types::{TStore, TType, Type, XmlEnabledTypeStore as _};
+use num::ToPrimitive as _;
+
+pub type SimpleStores = hyper_ast::store::SimpleStores<TStore>;
+
+/// Makes a leaf for the raw bytes of `text`, deduplicated like any other node.
+/// It is labeled with the git blob oid of the bytes, and keeps their length,
+/// so contents that are not valid utf8 are neither altered nor confused.
+pub(crate) fn make_text_leaf(
+    stores: &mut SimpleStores,
+    text: &[u8],
+) -> (NodeIdentifier, DefaultMetrics) {
+    let interned_kind = TStore::intern(Type::CharData);
+    let line_count = text
+        .iter()
+        .filter(|x| **x == b'\n')
+        .count()
+        .to_u16()
+        .expect("too many newlines");
+    let len: u32 = text.len().try_into().unwrap();
+    let label = git2::Oid::hash_object(git2::ObjectType::Blob, text)
+        .expect("hashing in memory")
+        .to_string();
+    let label_id = stores.label_store.get_or_insert(label.as_str());
+    let hbuilder: hashed::HashesBuilder<SyntaxNodeHashs<u32>> =
+        hashed::HashesBuilder::new(Default::default(), &interned_kind, &label, 1);
+    let hashable = &hbuilder.most_discriminating();
+    let eq = |x: EntryRef| {
+        x.get_component::<TType>() == Ok(&interned_kind)
+            && x.get_component::<LabelIdentifier>() == Ok(&label_id)
+            && x.get_component::<compo::BytesLen>().map(|x| x.0) == Ok(len)
+    };
+    let insertion = stores.node_store.prepare_insertion(hashable, eq);
+    let hashs = hbuilder.build();
+    let compressed_node = if let Some(id) = insertion.occupied_id() {
+        id
+    } else {
+        let vacant = insertion.vacant();
+        NodeStore::insert_after_prepare(
+            vacant,
+            (
+                interned_kind,
+                label_id,
+                compo::BytesLen(len),
+                hashs,
+                BloomSize::None,
+            ),
+        )
+    };
+    let metrics = SubTreeMetrics {
+        hashs,
+        size: 1,
+        height: 1,
+        size_no_spaces: 1,
+        line_count,
+    };
+    (compressed_node, metrics)
+}
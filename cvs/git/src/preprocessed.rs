@@ -651,23 +651,6 @@ impl CommitBuilder {
     }
 }
 
-#[cfg(feature = "any")]
-impl CommitProcessor<file_sys::Any> for RepositoryProcessor {
-    fn handle_module<'a, 'b, const RMS: bool>(
-        &mut self,
-        repository: &'a Repository,
-        dir_path: &'b mut Peekable<Components<'b>>,
-        name: &[u8],
-        oid: git2::Oid,
-    ) -> NodeIdentifier {
-        let root_full_node = MavenProcessor::<RMS, false, MavenModuleAcc>::new(
-            repository, self, dir_path, name, oid,
-        )
-        .process();
-        root_full_node.0
-    }
-}
-
 impl<H: IdHolder, T> IdHolder for (H, T) {
     type Id = H::Id;
     fn id(&self) -> Self::Id {
@@ -737,44 +720,47 @@ impl CommitProcessor<file_sys::Make> for RepositoryProcessor {
     }
 }
 
+#[cfg(feature = "any")]
 impl CommitProcessor<file_sys::Any> for RepositoryProcessor {
     type Module = (NodeIdentifier, DefaultMetrics);
     fn handle_module<'a, 'b, const RMS: bool>(
         &mut self,
-        _repository: &'a Repository,
-        _dir_path: &'b mut Peekable<Components<'b>>,
-        _name: &[u8],
-        _oid: git2::Oid,
+        repository: &'a Repository,
+        dir_path: &'b mut Peekable<Components<'b>>,
+        name: &[u8],
+        oid: git2::Oid,
     ) -> Self::Module {
-        todo!("still not sure how to dispatch")
+        crate::any_processor::AnyProcessor::<crate::any::AnyAcc>::new(
+            repository, self, dir_path, name, oid,
+        )
+        .expect("the tree of the module")
+        .process()
     }
 }
 
 /// plan to work on all languges of the family of typesript ie. ts, js, tsx, jsx
-/// - [ ] ts
-/// - [ ] js
-/// - [ ] tsx
-/// - [ ] jsx
-/// - [ ] d.ts
+/// - [x] ts
+/// - [x] js
+/// - [x] tsx
+/// - [x] jsx
+/// - [x] d.ts
 /// - [ ] various transpiler configs
 ///   - [ ] babel
 ///   - [ ] ts
 #[cfg(feature = "npm")]
 impl CommitProcessor<file_sys::Npm> for RepositoryProcessor {
-    type Module = (NodeIdentifier, DefaultMetrics);
+    type Module = (NodeIdentifier, crate::npm::MD);
     fn handle_module<'a, 'b, const RMS: bool>(
         &mut self,
-        _repository: &'a Repository,
-        _dir_path: &'b mut Peekable<Components<'b>>,
-        _name: &[u8],
-        _oid: git2::Oid,
+        repository: &'a Repository,
+        dir_path: &'b mut Peekable<Components<'b>>,
+        name: &[u8],
+        oid: git2::Oid,
     ) -> Self::Module {
-        todo!("need to implement NpmProcessor")
-        // let root_full_node = NpmProcessor::<RMS, FFWD, NpmModuleAcc>::new(repository, self, dir_path, name, oid)
-        //     .process();
-        // // self.object_map_make
-        // //     .insert(commit_oid, root_full_node.clone());
-        // root_full_node.0
+        crate::npm_processor::NpmProcessor::<crate::npm::NpmModuleAcc>::new(
+            repository, self, dir_path, name, oid,
+        )
+        .process()
     }
 }
 
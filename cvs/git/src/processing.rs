@@ -9,15 +9,21 @@ pub use erased::ParametrizedCommitProcessorHandle;
 
 pub enum BuildSystem {
     Maven,
+    Gradle,
     Make,
+    CMake,
     Npm,
+    Pip,
     None,
 }
 
 pub enum ProcessingConfig<P> {
     JavaMaven { limit: usize, dir_path: P },
+    JavaGradle { limit: usize, dir_path: P },
     CppMake { limit: usize, dir_path: P },
+    CppCMake { limit: usize, dir_path: P },
     TsNpm { limit: usize, dir_path: P },
+    PythonPip { limit: usize, dir_path: P },
     Any { limit: usize, dir_path: P },
 }
 
@@ -27,7 +33,10 @@ pub enum ProcessingConfig<P> {
 pub enum RepoConfig {
     CppMake,
     JavaMaven,
+    CppCMake,
     TsNpm,
+    PythonPip,
+    JavaGradle,
     Any,
 }
 
@@ -38,12 +47,20 @@ impl std::str::FromStr for RepoConfig {
         Ok(match s {
             "Cpp" => Self::CppMake,
             "cpp" => Self::CppMake,
+            "CMake" => Self::CppCMake,
+            "cmake" => Self::CppCMake,
             "Java" => Self::JavaMaven,
             "java" => Self::JavaMaven,
+            "Gradle" => Self::JavaGradle,
+            "gradle" => Self::JavaGradle,
             "typescript" => Self::TsNpm,
             "javascript" => Self::TsNpm,
             "Ts" => Self::TsNpm,
             "ts" => Self::TsNpm,
+            "Python" => Self::PythonPip,
+            "python" => Self::PythonPip,
+            "py" => Self::PythonPip,
+            "Any" => Self::Any,
             "any" => Self::Any,
             x => return Err(format!("'{}' is not anvailable config", x)),
         })
@@ -61,8 +78,26 @@ impl From<&RepoConfig> for ProcessingConfig<&'static str> {
                 limit: 3,
                 dir_path: "",
             },
-            RepoConfig::TsNpm => todo!(),
-            RepoConfig::Any => todo!(),
+            RepoConfig::CppCMake => Self::CppCMake {
+                limit: 3,
+                dir_path: "",
+            },
+            RepoConfig::TsNpm => Self::TsNpm {
+                limit: 3,
+                dir_path: "",
+            },
+            RepoConfig::PythonPip => Self::PythonPip {
+                limit: 3,
+                dir_path: "",
+            },
+            RepoConfig::JavaGradle => Self::JavaGradle {
+                limit: 3,
+                dir_path: "",
+            },
+            RepoConfig::Any => Self::Any {
+                limit: 3,
+                dir_path: "",
+            },
         }
     }
 }
@@ -95,12 +130,12 @@ impl ConfiguredRepoTrait for ConfiguredRepoHandle {
 }
 
 impl ConfiguredRepoHandle {
-    pub fn fetch(self) -> ConfiguredRepo {
-        ConfiguredRepo {
-            repo: self.spec.fetch(),
+    pub fn fetch(self) -> Result<ConfiguredRepo, String> {
+        Ok(ConfiguredRepo {
+            repo: self.spec.fetch()?,
             spec: self.spec,
             config: self.config,
-        }
+        })
     }
 }
 
@@ -122,12 +157,12 @@ impl ConfiguredRepoTrait for ConfiguredRepoHandle2 {
     }
 }
 impl ConfiguredRepoHandle2 {
-    pub fn fetch(self) -> ConfiguredRepo2 {
-        ConfiguredRepo2 {
-            repo: self.spec.fetch(),
+    pub fn fetch(self) -> Result<ConfiguredRepo2, String> {
+        Ok(ConfiguredRepo2 {
+            repo: self.spec.fetch()?,
             spec: self.spec,
             config: self.config,
-        }
+        })
     }
 }
 
@@ -318,6 +353,30 @@ pub(crate) mod caches {
         }
     }
 
+    #[derive(Default)]
+    pub struct Gradle {
+        pub object_map: OidMap<(NodeIdentifier, crate::maven::MD)>,
+    }
+
+    #[derive(Default)]
+    pub struct GradleScript {
+        pub object_map: NamedMap<crate::gradle::GradleScript>,
+    }
+
+    impl super::ObjectMapper for GradleScript {
+        type K = (git2::Oid, ObjectName);
+
+        type V = crate::gradle::GradleScript;
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
     #[derive(Default)]
     pub struct Make {
         pub object_map: OidMap<(NodeIdentifier, crate::make::MD)>,
@@ -342,6 +401,124 @@ pub(crate) mod caches {
         }
     }
 
+    #[derive(Default)]
+    pub struct CMake {
+        pub object_map: NamedMap<(NodeIdentifier, crate::cmake::MD)>,
+    }
+
+    #[derive(Default)]
+    pub struct CMakeLists {
+        pub object_map: OidMap<crate::cmake::CMakeLists>,
+    }
+
+    impl super::ObjectMapper for CMakeLists {
+        type K = git2::Oid;
+
+        type V = crate::cmake::CMakeLists;
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
+    #[derive(Default)]
+    pub struct Ts {
+        pub(crate) md_cache: hyper_ast_gen_ts_ts::legion::MDCache,
+        pub object_map: NamedMap<(hyper_ast_gen_ts_ts::legion::Local, IsSkippedAna)>,
+    }
+
+    impl super::ObjectMapper for Ts {
+        type K = (git2::Oid, ObjectName);
+
+        type V = (hyper_ast_gen_ts_ts::legion::Local, IsSkippedAna);
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
+    #[derive(Default)]
+    pub struct Npm {
+        pub object_map: NamedMap<(NodeIdentifier, crate::npm::MD)>,
+    }
+
+    #[derive(Default)]
+    pub struct PackageJson {
+        pub object_map: OidMap<crate::npm::PackageJson>,
+    }
+
+    impl super::ObjectMapper for PackageJson {
+        type K = git2::Oid;
+
+        type V = crate::npm::PackageJson;
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
+    #[derive(Default)]
+    pub struct Python {
+        pub(crate) md_cache: hyper_ast_gen_ts_python::legion::MDCache,
+        pub object_map: NamedMap<(hyper_ast_gen_ts_python::legion::Local, IsSkippedAna)>,
+    }
+
+    impl super::ObjectMapper for Python {
+        type K = (git2::Oid, ObjectName);
+
+        type V = (hyper_ast_gen_ts_python::legion::Local, IsSkippedAna);
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
+    #[derive(Default)]
+    pub struct Pip {
+        pub object_map: NamedMap<(NodeIdentifier, crate::python::MD)>,
+    }
+
+    #[derive(Default)]
+    pub struct Any {
+        pub object_map: NamedMap<(NodeIdentifier, crate::DefaultMetrics)>,
+    }
+
+    #[derive(Default)]
+    pub struct Text {
+        pub(crate) query_md_cache: hyper_ast_gen_ts_tsquery::legion::MDCache,
+        pub object_map: NamedMap<(NodeIdentifier, crate::DefaultMetrics)>,
+    }
+
+    impl super::ObjectMapper for Text {
+        type K = (git2::Oid, ObjectName);
+
+        type V = (NodeIdentifier, crate::DefaultMetrics);
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
     // // any
     // pub object_map_any: OidMap<(NodeIdentifier, DefaultMetrics)>,
     // // maven
@@ -383,6 +560,10 @@ pub mod file_sys {
     /// The default file system, directories and files
     pub struct Any;
 
+    impl CachesHolding for Any {
+        type Caches = super::caches::Any;
+    }
+
     /// The maven scheme https://maven.apache.org/guides/introduction/introduction-to-the-standard-directory-layout.html ,
     /// made of nested maven modules.
     /// Each maven module has a config file (often a pom.xml),
@@ -426,6 +607,42 @@ pub mod file_sys {
         }
     }
 
+    /// The gradle scheme,
+    /// a settings script includes the projects of the build,
+    /// and each project can have a build script,
+    /// sources follow the maven layout.
+    #[cfg(feature = "gradle")]
+    pub struct Gradle;
+
+    impl CachesHolding for Gradle {
+        type Caches = super::caches::Gradle;
+    }
+
+    /// The groovy or kotlin scripts configuring a gradle build
+    #[cfg(feature = "gradle")]
+    pub struct GradleScript;
+
+    impl CachesHolding for GradleScript {
+        type Caches = super::caches::GradleScript;
+    }
+
+    impl super::InFiles for GradleScript {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.eq(b"build.gradle")
+                || name.0.eq(b"build.gradle.kts")
+                || <GradleSettings as super::InFiles>::matches(name)
+        }
+    }
+
+    #[cfg(feature = "gradle")]
+    pub struct GradleSettings;
+
+    impl super::InFiles for GradleSettings {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.eq(b"settings.gradle") || name.0.eq(b"settings.gradle.kts")
+        }
+    }
+
     /// The make scheme,
     /// It contains a Makefile and different directories, often src/ or lib/, tests/ or tests/, and also third-party/ docs/ script/,
     /// but it is mostly community and programming language dependent.
@@ -463,6 +680,33 @@ pub mod file_sys {
                 || name.0.ends_with(b".cxx")
                 || name.0.ends_with(b".h")
                 || name.0.ends_with(b".hpp")
+                || name.0.ends_with(b".cc")
+                || name.0.ends_with(b".hh")
+                || name.0.ends_with(b".ipp")
+                || name.0.ends_with(b".inl")
+        }
+    }
+
+    /// The CMake scheme,
+    /// each module contains a CMakeLists.txt,
+    /// that can include other modules with add_subdirectory and declare targets.
+    #[cfg(feature = "cmake")]
+    pub struct CMake;
+
+    impl CachesHolding for CMake {
+        type Caches = super::caches::CMake;
+    }
+
+    #[cfg(feature = "cmake")]
+    pub struct CMakeLists;
+
+    impl CachesHolding for CMakeLists {
+        type Caches = super::caches::CMakeLists;
+    }
+
+    impl super::InFiles for CMakeLists {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.eq(b"CMakeLists.txt")
         }
     }
 
@@ -472,6 +716,115 @@ pub mod file_sys {
     /// or is a collection of packages that contains a packages/ directory where each package is located
     #[cfg(feature = "npm")]
     pub struct Npm;
+
+    impl CachesHolding for Npm {
+        type Caches = super::caches::Npm;
+    }
+
+    /// The module descriptor of npm,
+    /// it can declare workspaces, ie. paths to nested packages.
+    #[cfg(feature = "npm")]
+    pub struct PackageJson;
+
+    impl CachesHolding for PackageJson {
+        type Caches = super::caches::PackageJson;
+    }
+
+    impl super::InFiles for PackageJson {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.eq(b"package.json")
+        }
+    }
+
+    /// Typescript and javascript sources,
+    /// tsx and jsx files are parsed with the tsx grammar, the others with the typescript one
+    #[cfg(feature = "ts")]
+    pub struct Ts;
+
+    impl CachesHolding for Ts {
+        type Caches = super::caches::Ts;
+    }
+
+    impl super::InFiles for Ts {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.ends_with(b".ts")
+                || name.0.ends_with(b".js")
+                || name.0.ends_with(b".mjs")
+                || name.0.ends_with(b".cjs")
+                || name.0.ends_with(b".tsx")
+                || name.0.ends_with(b".jsx")
+        }
+    }
+
+    /// The pip scheme,
+    /// a project is configured by a pyproject.toml or a setup.py,
+    /// and it is made of packages, ie. directories containing an __init__.py,
+    /// often located in a src/ directory or directly at the root of the project.
+    #[cfg(feature = "pip")]
+    pub struct Pip;
+
+    impl CachesHolding for Pip {
+        type Caches = super::caches::Pip;
+    }
+
+    #[cfg(feature = "pip")]
+    pub struct PyProject;
+
+    impl super::InFiles for PyProject {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.eq(b"pyproject.toml") || name.0.eq(b"setup.py")
+        }
+    }
+
+    #[cfg(feature = "pip")]
+    pub struct PyPackage;
+
+    impl super::InFiles for PyPackage {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.eq(b"__init__.py")
+        }
+    }
+
+    #[cfg(feature = "python")]
+    pub struct Python;
+
+    impl CachesHolding for Python {
+        type Caches = super::caches::Python;
+    }
+
+    impl super::InFiles for Python {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.ends_with(b".py") || name.0.ends_with(b".pyi")
+        }
+    }
+
+    #[cfg(feature = "any")]
+    pub struct Xml;
+
+    impl super::InFiles for Xml {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.ends_with(b".xml")
+        }
+    }
+
+    /// Tree-sitter queries, eg. the highlights.scm of a grammar
+    #[cfg(feature = "tsquery")]
+    pub struct TsQuery;
+
+    impl super::InFiles for TsQuery {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.ends_with(b".scm")
+        }
+    }
+
+    /// Any file not handled by a specific generator,
+    /// it includes xml and tree-sitter queries that do not need a dedicated scheme.
+    #[cfg(feature = "any")]
+    pub struct Text;
+
+    impl CachesHolding for Text {
+        type Caches = super::caches::Text;
+    }
 }
 
 impl crate::preprocessed::RepositoryProcessor {
@@ -496,9 +849,17 @@ make_multi! {
     MakeFile,
     Ts,
     Js,
+    Python,
+    Xml,
+    TsQuery,
+    Text,
     ;
     Maven [Java] Xml => crate::maven::Md,
+    Gradle [Java] GradleScript => crate::maven::Md,
     Make [Cpp] MakeFile => crate::make::Md,
-    Npm [Ts, Js] Xml => crate::make::Md,
+    CMake [Cpp] CMakeLists => crate::cmake::MD,
+    Npm [Ts, Js] PackageJson => crate::npm::MD,
+    Pip [Python] PyProject => crate::python::MD,
+    Any [Java, Cpp, Ts, Js, Xml, TsQuery] Text => crate::DefaultMetrics,
     None => crate::make::Md,
 }
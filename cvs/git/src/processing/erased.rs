@@ -52,6 +52,24 @@ pub trait CommitProc {
     ) -> Box<dyn PreparedCommitProc + 'repo>;
 
     fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit>;
+
+    /// Processed commits, e.g. to write them in a snapshot
+    fn commits(&self) -> Box<dyn Iterator<Item = (&git2::Oid, &crate::Commit)> + '_> {
+        Box::new(std::iter::empty())
+    }
+
+    /// Registers a commit processed in a previous session, e.g. read from a snapshot,
+    /// only processors working at the root of a project hold commits.
+    fn restore_commit(
+        &mut self,
+        _commit_oid: git2::Oid,
+        _commit: crate::Commit,
+    ) -> std::io::Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "this processor does not hold processed commits",
+        ))
+    }
 }
 pub trait PreparedCommitProc {
     fn process(
@@ -0,0 +1,103 @@
+use crate::{
+    preprocessed::IsSkippedAna, processing::ObjectName, Accumulator, BasicDirAcc, DefaultMetrics,
+    PROPAGATE_ERROR_ON_BAD_CST_NODE,
+};
+use enumset::EnumSet;
+use hyper_ast::store::defaults::{LabelIdentifier, NodeIdentifier};
+use hyper_ast_gen_ts_python::{legion as python_tree_gen, types::TStore};
+
+pub(crate) fn handle_python_file<'stores, 'cache, 'b: 'stores>(
+    tree_gen: &mut python_tree_gen::PythonTreeGen<'stores, 'cache, TStore>,
+    name: &ObjectName,
+    text: &'b [u8],
+) -> Result<python_tree_gen::FNode, ()> {
+    let tree = match python_tree_gen::PythonTreeGen::<TStore>::tree_sitter_parse(text) {
+        Ok(tree) => tree,
+        Err(tree) => {
+            log::warn!("bad CST: {:?}", name.try_str());
+            log::debug!("{}", tree.root_node().to_sexp());
+            if PROPAGATE_ERROR_ON_BAD_CST_NODE {
+                return Err(());
+            } else {
+                tree
+            }
+        }
+    };
+    Ok(tree_gen.generate_file(name.as_bytes(), text, tree.walk()))
+}
+
+#[derive(Debug, Clone)]
+pub struct MD {
+    pub(crate) metrics: DefaultMetrics,
+    pub(crate) status: EnumSet<SemFlags>,
+}
+
+#[derive(enumset::EnumSetType, Debug)]
+pub enum SemFlags {
+    /// holds an `__init__.py`
+    IsPackage,
+    /// holds a `pyproject.toml` or a `setup.py`
+    IsProject,
+    /// a package is nested somewhere inside
+    HoldPackage,
+}
+
+pub struct PythonAcc {
+    pub(crate) primary: BasicDirAcc<NodeIdentifier, LabelIdentifier, DefaultMetrics>,
+    pub(crate) status: EnumSet<SemFlags>,
+}
+
+impl PythonAcc {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            primary: BasicDirAcc::new(name),
+            status: Default::default(),
+        }
+    }
+}
+
+impl From<String> for PythonAcc {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl PythonAcc {
+    pub(crate) fn push_file(
+        &mut self,
+        name: LabelIdentifier,
+        full_node: python_tree_gen::Local,
+        _skiped_ana: IsSkippedAna,
+    ) {
+        self.primary
+            .push(name, full_node.compressed_node, full_node.metrics);
+    }
+    pub(crate) fn mark_package(&mut self) {
+        self.status |= SemFlags::IsPackage;
+    }
+    pub(crate) fn mark_project(&mut self) {
+        self.status |= SemFlags::IsProject;
+    }
+    pub(crate) fn push_directory(
+        &mut self,
+        name: LabelIdentifier,
+        full_node: (NodeIdentifier, MD),
+    ) {
+        let status = full_node.1.status;
+        if status.contains(SemFlags::IsPackage) || status.contains(SemFlags::HoldPackage) {
+            self.status |= SemFlags::HoldPackage;
+        }
+        self.primary.push(name, full_node.0, full_node.1.metrics);
+    }
+}
+
+impl hyper_ast::tree_gen::Accumulator for PythonAcc {
+    type Node = (LabelIdentifier, (NodeIdentifier, MD));
+    fn push(&mut self, (name, full_node): Self::Node) {
+        self.push_directory(name, full_node);
+    }
+}
+
+impl Accumulator for PythonAcc {
+    type Unlabeled = (NodeIdentifier, MD);
+}
@@ -0,0 +1,443 @@
+use crate::{
+    git::BasicGitObject,
+    preprocessed::{IsSkippedAna, RepositoryProcessor},
+    processing::{
+        caches, erased::ParametrizedCommitProc2, file_sys, CacheHolding, InFiles, ObjectName,
+    },
+    python::{PythonAcc, MD},
+    Processor,
+};
+use git2::{Oid, Repository};
+use hyper_ast::{
+    hashed::{IndexingHashBuilder, MetaDataHashsBuilder},
+    store::{defaults::NodeIdentifier, nodes::legion::eq_node},
+    types::LabelStore,
+};
+use hyper_ast_gen_ts_python::{
+    legion as python_gen,
+    types::{PythonEnabledTypeStore as _, Type},
+};
+use std::{
+    iter::Peekable,
+    path::{Components, PathBuf},
+};
+
+pub type SimpleStores = hyper_ast::store::SimpleStores<hyper_ast_gen_ts_python::types::TStore>;
+
+/// Processes the directories of a python project,
+/// each source file is parsed while packages (`__init__.py`)
+/// and project configurations (`pyproject.toml`, `setup.py`) are recorded in the status of their directory.
+pub struct PythonProcessor<'repo, 'prepro, 'd, 'c, Acc> {
+    repository: &'repo Repository,
+    prepro: &'prepro mut RepositoryProcessor,
+    stack: Vec<(Oid, Vec<BasicGitObject>, Acc)>,
+    pub dir_path: &'d mut Peekable<Components<'c>>,
+    handle: crate::processing::erased::ParametrizedCommitProcessor2Handle<PythonProc>,
+}
+
+impl<'repo, 'b, 'd, 'c, Acc: From<String>> PythonProcessor<'repo, 'b, 'd, 'c, Acc> {
+    pub fn new(
+        repository: &'repo Repository,
+        prepro: &'b mut RepositoryProcessor,
+        dir_path: &'d mut Peekable<Components<'c>>,
+        name: &[u8],
+        oid: git2::Oid,
+    ) -> Self {
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<PythonProcessorHolder>();
+        let handle =
+            <PythonProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let tree = repository.find_tree(oid).unwrap();
+        let prepared = prepare_dir_exploration(tree, dir_path);
+        let name = std::str::from_utf8(&name).unwrap().to_string();
+        let stack = vec![(oid, prepared, Acc::from(name))];
+        Self {
+            stack,
+            repository,
+            prepro,
+            dir_path,
+            handle,
+        }
+    }
+}
+
+impl<'repo, 'b, 'd, 'c> Processor<PythonAcc> for PythonProcessor<'repo, 'b, 'd, 'c, PythonAcc> {
+    fn pre(&mut self, current_object: BasicGitObject) {
+        match current_object {
+            BasicGitObject::Tree(oid, name) => {
+                if let Some(s) = self.dir_path.peek() {
+                    if name
+                        .as_bytes()
+                        .eq(std::ffi::OsStr::as_encoded_bytes(s.as_os_str()))
+                    {
+                        self.dir_path.next();
+                        self.stack.last_mut().expect("never empty").1.clear();
+                        let tree = self.repository.find_tree(oid).unwrap();
+                        let prepared = prepare_dir_exploration(tree, self.dir_path);
+                        self.stack
+                            .push((oid, prepared, PythonAcc::new(name.try_into().unwrap())));
+                    }
+                    return;
+                }
+                self.handle_tree_cached(oid, name);
+            }
+            BasicGitObject::Blob(oid, name) => {
+                if self.dir_path.peek().is_some() {
+                    return;
+                }
+                let acc = &mut self.stack.last_mut().unwrap().2;
+                if file_sys::PyPackage::matches(&name) {
+                    acc.mark_package();
+                }
+                if file_sys::PyProject::matches(&name) {
+                    acc.mark_project();
+                }
+                // NOTE a setup.py is also a python file
+                if file_sys::Python::matches(&name) {
+                    self.prepro
+                        .help_handle_python_file(
+                            oid,
+                            &mut self.stack.last_mut().unwrap().2,
+                            &name,
+                            self.repository,
+                            self.handle,
+                        )
+                        .unwrap();
+                } else {
+                    log::debug!("not python source file {:?}", name.try_str());
+                }
+            }
+        }
+    }
+    fn post(&mut self, oid: Oid, acc: PythonAcc) -> Option<(NodeIdentifier, MD)> {
+        let name = acc.primary.name.clone();
+        let key = (oid, name.as_bytes().into());
+        let full_node = make(acc, self.prepro.main_stores_mut().mut_with_ts());
+        CacheHolding::<caches::Pip>::get_caches_mut(
+            self.prepro
+                .processing_systems
+                .mut_or_default::<PythonProcessorHolder>(),
+        )
+        .object_map
+        .insert(key, full_node.clone());
+        let name = self.prepro.main_stores.label_store.get_or_insert(name);
+        if self.stack.is_empty() {
+            Some(full_node)
+        } else {
+            let w = &mut self.stack.last_mut().unwrap().2;
+            assert!(
+                !w.primary.children_names.contains(&name),
+                "{:?} {:?}",
+                w.primary.children_names,
+                name
+            );
+            w.push_directory(name, full_node);
+            None
+        }
+    }
+
+    fn stack(&mut self) -> &mut Vec<(Oid, Vec<BasicGitObject>, PythonAcc)> {
+        &mut self.stack
+    }
+}
+
+impl<'repo, 'prepro, 'd, 'c> PythonProcessor<'repo, 'prepro, 'd, 'c, PythonAcc> {
+    fn handle_tree_cached(&mut self, oid: Oid, name: ObjectName) {
+        if let Some(already) = CacheHolding::<caches::Pip>::get_caches_mut(
+            self.prepro
+                .processing_systems
+                .mut_or_default::<PythonProcessorHolder>(),
+        )
+        .object_map
+        .get(&(oid, name.clone()))
+        {
+            // reinit already computed node for post order
+            let full_node = already.clone();
+            let w = &mut self.stack.last_mut().unwrap().2;
+            let name = self.prepro.intern_object_name(&name);
+            assert!(!w.primary.children_names.contains(&name));
+            w.push_directory(name, full_node);
+        } else {
+            log::debug!("python tree {:?}", name.try_str());
+            let tree = self.repository.find_tree(oid).unwrap();
+            let prepared = prepare_dir_exploration(tree, self.dir_path);
+            self.stack
+                .push((oid, prepared, PythonAcc::new(name.try_into().unwrap())));
+        }
+    }
+}
+
+/// Project configurations first, so the status of a directory is known before its content is pushed.
+pub(crate) fn prepare_dir_exploration(
+    tree: git2::Tree,
+    dir_path: &mut Peekable<Components>,
+) -> Vec<BasicGitObject> {
+    let mut children_objects: Vec<BasicGitObject> = tree
+        .iter()
+        .map(TryInto::try_into)
+        .filter_map(|x| x.ok())
+        .collect();
+    if dir_path.peek().is_none() {
+        let p = children_objects.iter().position(|x| match x {
+            BasicGitObject::Blob(_, n) => file_sys::PyProject::matches(n),
+            _ => false,
+        });
+        if let Some(p) = p {
+            children_objects.swap(0, p); // priority to config file processing
+        }
+    }
+    children_objects.reverse(); // we use it like a stack
+    children_objects
+}
+
+pub(crate) fn make(acc: PythonAcc, stores: &mut SimpleStores) -> (NodeIdentifier, MD) {
+    let kind = Type::Directory;
+    let interned_kind = hyper_ast_gen_ts_python::types::TStore::intern(kind);
+    let label_id = stores.label_store.get_or_insert(acc.primary.name.clone());
+
+    let primary = acc
+        .primary
+        .map_metrics(|m| m.finalize(&interned_kind, &label_id, 0));
+    let hashable = primary.metrics.hashs.most_discriminating();
+    let eq = eq_node(&interned_kind, Some(&label_id), &primary.children);
+    assert_eq!(primary.children_names.len(), primary.children.len());
+    let status = acc.status;
+
+    let insertion = stores.node_store.prepare_insertion(&hashable, eq);
+    if let Some(id) = insertion.occupied_id() {
+        let metrics = primary
+            .metrics
+            .map_hashs(|h| MetaDataHashsBuilder::build(h));
+        return (id, MD { metrics, status });
+    }
+
+    log::info!("make py {} {}", &primary.name, primary.children.len());
+
+    let mut dyn_builder = hyper_ast::store::nodes::legion::dyn_builder::EntityBuilder::new();
+    let children_is_empty = primary.children.is_empty();
+    if !status.is_empty() {
+        dyn_builder.add(status);
+    }
+    let metrics = primary.persist(&mut dyn_builder, interned_kind, label_id);
+    let metrics = metrics.map_hashs(|h| h.build());
+    let hashs = metrics.add_md_metrics(&mut dyn_builder, children_is_empty);
+    hashs.persist(&mut dyn_builder);
+
+    let vacant = insertion.vacant();
+    let node_id = hyper_ast::store::nodes::legion::NodeStore::insert_built_after_prepare(
+        vacant,
+        dyn_builder.build(),
+    );
+
+    (node_id, MD { metrics, status })
+}
+
+impl RepositoryProcessor {
+    fn handle_python_blob(
+        &mut self,
+        oid: Oid,
+        name: &ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<PythonProc>,
+    ) -> Result<(python_gen::Local, IsSkippedAna), crate::ParseErr> {
+        self.processing_systems
+            .caching_blob_handler::<file_sys::Python>()
+            .handle2(oid, repository, &name, parameters, |c, n, t| {
+                let line_break = if t.contains(&b'\r') {
+                    "\r\n".as_bytes().to_vec()
+                } else {
+                    "\n".as_bytes().to_vec()
+                };
+                crate::python::handle_python_file(
+                    &mut python_gen::PythonTreeGen {
+                        line_break,
+                        stores: self.main_stores.mut_with_ts(),
+                        md_cache: &mut CacheHolding::<caches::Python>::get_caches_mut(
+                            c.mut_or_default::<PythonProcessorHolder>(),
+                        )
+                        .md_cache,
+                    },
+                    n,
+                    t,
+                )
+                .map_err(|_| crate::ParseErr::IllFormed)
+                .map(|x| (x.local.clone(), false))
+            })
+    }
+
+    pub(crate) fn help_handle_python_file(
+        &mut self,
+        oid: Oid,
+        parent: &mut PythonAcc,
+        name: &ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<PythonProc>,
+    ) -> Result<(), crate::ParseErr> {
+        let (full_node, skiped_ana) = self.handle_python_blob(oid, name, repository, parameters)?;
+        let name = self.intern_object_name(name);
+        assert!(!parent.primary.children_names.contains(&name));
+        parent.push_file(name, full_node, skiped_ana);
+        Ok(())
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Parameter;
+#[derive(Default)]
+pub(crate) struct PythonProcessorHolder(Option<PythonProc>);
+pub(crate) struct PythonProc {
+    parameter: Parameter,
+    cache: caches::Pip,
+    files_cache: caches::Python,
+    commits: std::collections::HashMap<git2::Oid, crate::Commit>,
+}
+impl crate::processing::erased::Parametrized for PythonProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(PythonProc {
+                    parameter: t,
+                    cache: Default::default(),
+                    files_cache: Default::default(),
+                    commits: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+struct PreparedPythonCommitProc<'repo> {
+    repository: &'repo git2::Repository,
+    commit_builder: crate::preprocessed::CommitBuilder,
+}
+impl<'repo> crate::processing::erased::PreparedCommitProc for PreparedPythonCommitProc<'repo> {
+    fn process(
+        self: Box<PreparedPythonCommitProc<'repo>>,
+        prepro: &mut RepositoryProcessor,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        let name = b"";
+        let root_full_node = PythonProcessor::<PythonAcc>::new(
+            self.repository,
+            prepro,
+            &mut dir_path,
+            name,
+            self.commit_builder.tree_oid(),
+        )
+        .process();
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<PythonProcessorHolder>();
+        let handle =
+            <PythonProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let commit_oid = self.commit_builder.commit_oid();
+        let commit = self.commit_builder.finish(root_full_node.0);
+        h.with_parameters_mut(handle.0)
+            .commits
+            .insert(commit_oid, commit);
+        root_full_node.0
+    }
+}
+
+impl crate::processing::erased::CommitProc for PythonProc {
+    fn prepare_processing<'repo>(
+        &self,
+        repository: &'repo git2::Repository,
+        commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc + 'repo> {
+        Box::new(PreparedPythonCommitProc {
+            repository,
+            commit_builder,
+        })
+    }
+
+    fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        self.commits.get(&commit_oid)
+    }
+
+    fn commits(&self) -> Box<dyn Iterator<Item = (&git2::Oid, &crate::Commit)> + '_> {
+        Box::new(self.commits.iter())
+    }
+
+    fn restore_commit(
+        &mut self,
+        commit_oid: git2::Oid,
+        commit: crate::Commit,
+    ) -> std::io::Result<()> {
+        self.commits.insert(commit_oid, commit);
+        Ok(())
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for PythonProc {
+    type Holder = PythonProcessorHolder;
+}
+
+impl crate::processing::erased::ParametrizedCommitProc2 for PythonProcessorHolder {
+    type Proc = PythonProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+}
+
+impl CacheHolding<caches::Pip> for PythonProc {
+    fn get_caches_mut(&mut self) -> &mut caches::Pip {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &caches::Pip {
+        &self.cache
+    }
+}
+
+impl CacheHolding<caches::Pip> for PythonProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut caches::Pip {
+        &mut self.0.as_mut().unwrap().cache
+    }
+    fn get_caches(&self) -> &caches::Pip {
+        &self.0.as_ref().unwrap().cache
+    }
+}
+
+impl CacheHolding<caches::Python> for PythonProc {
+    fn get_caches_mut(&mut self) -> &mut caches::Python {
+        &mut self.files_cache
+    }
+    fn get_caches(&self) -> &caches::Python {
+        &self.files_cache
+    }
+}
+
+impl CacheHolding<caches::Python> for PythonProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut caches::Python {
+        &mut self.0.as_mut().unwrap().files_cache
+    }
+    fn get_caches(&self) -> &caches::Python {
+        &self.0.as_ref().unwrap().files_cache
+    }
+}
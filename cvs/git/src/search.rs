@@ -0,0 +1,305 @@
+//! Run tree-sitter queries on processed commits, without going through the `client` server.
+//!
+//! ```ignore
+//! for m in search(&repositories, &repo, oid, "Java", "(method_declaration) @m")? {
+//!     for c in m.captures {
+//!         println!("{}:{:?} {}", c.file.display(), c.lines, c.name);
+//!     }
+//! }
+//! ```
+//!
+//! With [`search_many`], queries of different languages are run in the same pass over a commit,
+//! eg. to find a dependency declared in the poms and imported in the java files.
+use std::{
+    fmt::Display,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use git2::Oid;
+use hyper_ast::{
+    position::{
+        position_accessors::WithPreOrderOffsets, row_col::RowCol, PositionConverter,
+        StructuralPosition, TreePath,
+    },
+    store::defaults::NodeIdentifier,
+    types::{
+        Children, HyperAST, HyperType, IterableChildren, LabelStore, Labeled, LangRef, NodeStore,
+        WithChildren, WithSerialization, WithStats,
+    },
+};
+use hyper_ast_tsquery::{hyperast::Node, CustomPredicates, Query};
+
+use crate::{
+    multi_preprocessed::PreProcessedRepositories,
+    processing::{erased::ParametrizedCommitProcessorHandle, ConfiguredRepoTrait},
+    SimpleStores,
+};
+
+#[derive(Debug, Clone)]
+pub struct Match {
+    /// index of the query in the ones given to [`search_many`]
+    pub query_index: usize,
+    pub pattern_index: usize,
+    pub captures: Vec<Capture>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Capture {
+    pub name: String,
+    /// relative to the root of the commit
+    pub file: PathBuf,
+    pub bytes: Range<usize>,
+    /// 0-based, the last line is excluded
+    pub lines: Range<usize>,
+    /// 0-based, columns are in bytes
+    pub start: RowCol<usize>,
+    /// position right after the capture
+    pub end: RowCol<usize>,
+    /// offsets of the children to go through, starting from the root of the commit
+    pub path: Vec<u16>,
+}
+
+#[derive(Debug)]
+pub enum SearchError {
+    MissingLanguage(String),
+    /// the commit was not processed with the configuration of the repository
+    MissingCommit(Oid),
+    ParsingError(String),
+}
+
+impl Display for SearchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchError::MissingLanguage(l) => write!(f, "missing language: {}", l),
+            SearchError::MissingCommit(oid) => write!(f, "commit not processed: {}", oid),
+            SearchError::ParsingError(e) => write!(f, "query parsing error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SearchError {}
+
+/// Name of the HyperAST language of the files queried with `language`,
+/// as given by [`LangRef::name`].
+fn lang_name(language: &str) -> Option<&'static str> {
+    match language {
+        #[cfg(feature = "java")]
+        "Java" | "java" => Some(std::any::type_name::<hyper_ast_gen_ts_java::types::Lang>()),
+        #[cfg(feature = "cpp")]
+        "Cpp" | "cpp" => Some(std::any::type_name::<hyper_ast_gen_ts_cpp::types::Lang>()),
+        #[cfg(any(feature = "maven", feature = "any"))]
+        "Xml" | "xml" => Some(std::any::type_name::<hyper_ast_gen_ts_xml::types::Lang>()),
+        #[cfg(feature = "ts")]
+        "Ts" | "ts" | "Typescript" | "typescript" => {
+            Some(std::any::type_name::<hyper_ast_gen_ts_ts::types::Lang>())
+        }
+        #[cfg(feature = "python")]
+        "Python" | "python" => Some(std::any::type_name::<hyper_ast_gen_ts_python::types::Lang>()),
+        #[cfg(feature = "tsquery")]
+        "Query" | "query" | "tsquery" => {
+            Some(std::any::type_name::<hyper_ast_gen_ts_tsquery::types::Lang>())
+        }
+        _ => None,
+    }
+}
+
+/// Matches `query` on the files of `language` in an already processed `commit` of `repo`.
+///
+/// Patterns are matched within each file, so they cannot capture directories.
+/// The [`hyper_ast_tsquery::metadata::predicates`] are available, eg. `(#is? @m cyclomatic ">10")`.
+/// Matches are computed lazily, one file at a time.
+pub fn search<'a>(
+    repositories: &'a PreProcessedRepositories,
+    repo: &impl ConfiguredRepoTrait<Config = ParametrizedCommitProcessorHandle>,
+    commit: Oid,
+    language: &str,
+    query: &str,
+) -> Result<Matches<'a>, SearchError> {
+    search_many(repositories, repo, commit, &[(language, query)])
+}
+
+/// Same as [`search`], with one query per language given as `(language, query)`.
+///
+/// Each query only runs on the files of its language, all in the same pass over the commit.
+/// Matches of a file are given in the order of `queries`.
+pub fn search_many<'a>(
+    repositories: &'a PreProcessedRepositories,
+    repo: &impl ConfiguredRepoTrait<Config = ParametrizedCommitProcessorHandle>,
+    commit: Oid,
+    queries: &[(&str, &str)],
+) -> Result<Matches<'a>, SearchError> {
+    let queries = queries
+        .iter()
+        .map(|(language, query)| {
+            let (ts_language, lang) = resolve(language)?;
+            let query = Query::new(query, ts_language)
+                .map_err(|e| SearchError::ParsingError(e.to_string()))?;
+            Ok((lang, query))
+        })
+        .collect::<Result<_, _>>()?;
+    let root = repositories
+        .get_commit(repo.config(), &commit)
+        .ok_or(SearchError::MissingCommit(commit))?
+        .ast_root;
+    Ok(Matches::new(
+        &repositories.processor.main_stores,
+        root,
+        queries,
+    ))
+}
+
+fn resolve(language: &str) -> Result<(tree_sitter::Language, &'static str), SearchError> {
+    let missing = || SearchError::MissingLanguage(language.to_string());
+    let ts_language = crate::resolve_language(language).ok_or_else(missing)?;
+    let lang = lang_name(language).ok_or_else(missing)?;
+    Ok((ts_language, lang))
+}
+
+/// Iterator over the matches of a [`search`], file by file in the order of the commit.
+pub struct Matches<'a> {
+    stores: &'a SimpleStores,
+    /// each query with the name of the language of the files it runs on
+    queries: Vec<(&'static str, Query)>,
+    predicates: CustomPredicates<Node<'a, SimpleStores>>,
+    /// directories and files still to visit, with their path
+    stack: Vec<(NodeIdentifier, PathBuf, Vec<u16>)>,
+    pending: std::vec::IntoIter<Match>,
+}
+
+impl<'a> Matches<'a> {
+    fn new(
+        stores: &'a SimpleStores,
+        root: NodeIdentifier,
+        queries: Vec<(&'static str, Query)>,
+    ) -> Self {
+        Self {
+            stores,
+            queries,
+            predicates: hyper_ast_tsquery::metadata::predicates(),
+            stack: vec![(root, PathBuf::new(), vec![])],
+            pending: vec![].into_iter(),
+        }
+    }
+
+    fn file_matches<'b>(
+        &'b self,
+        query_index: usize,
+        file: NodeIdentifier,
+        path: &'b Path,
+        prefix: &'b [u16],
+    ) -> impl Iterator<Item = Match> + 'b {
+        let query = &self.queries[query_index].1;
+        let cursor = hyper_ast_tsquery::hyperast::TreeCursor::new(
+            self.stores,
+            StructuralPosition::new(file),
+        );
+        query
+            .matches_with(cursor, &self.predicates)
+            .map(move |m| Match {
+                query_index,
+                pattern_index: m.pattern_index.to_usize(),
+                captures: (&m.captures)
+                    .into_iter()
+                    .map(|c| {
+                        let offsets: Vec<u16> = c.node.pos.iter_offsets().collect();
+                        let (bytes, lines) = ranges(self.stores, file, &offsets);
+                        let start: RowCol<usize> = PositionConverter::new(&c.node.pos)
+                            .with_stores(self.stores)
+                            .compute_pos_post_order::<_, RowCol<usize>, _>();
+                        let end = end_position(self.stores, *c.node.pos.node().unwrap(), &start);
+                        Capture {
+                            name: query.capture_name(c.index).to_string(),
+                            file: path.to_owned(),
+                            bytes,
+                            lines,
+                            start,
+                            end,
+                            path: prefix.iter().copied().chain(offsets).collect(),
+                        }
+                    })
+                    .collect(),
+            })
+    }
+}
+
+impl<'a> Iterator for Matches<'a> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(m) = self.pending.next() {
+                return Some(m);
+            }
+            let (id, path, offsets) = self.stack.pop()?;
+            let stores = self.stores;
+            let t = stores.resolve_type(&id);
+            if t.is_file() {
+                let n = stores.node_store().resolve(&id);
+                let lang = stores.resolve_lang(&n).name();
+                let matches: Vec<_> = (0..self.queries.len())
+                    .filter(|i| self.queries[*i].0 == lang)
+                    .flat_map(|i| self.file_matches(i, id, &path, &offsets))
+                    .collect();
+                self.pending = matches.into_iter();
+            } else if t.is_directory() {
+                let n = stores.node_store().resolve(&id);
+                let Some(cs) = n.children() else {
+                    continue;
+                };
+                let cs: Vec<_> = cs.iter_children().copied().enumerate().collect();
+                // reversed to visit children in order
+                for (o, child) in cs.into_iter().rev() {
+                    let t = stores.resolve_type(&child);
+                    if !t.is_file() && !t.is_directory() {
+                        continue;
+                    }
+                    let c = stores.node_store().resolve(&child);
+                    let name = stores.label_store().resolve(c.get_label_unchecked());
+                    let mut offsets = offsets.clone();
+                    offsets.push(o as u16);
+                    self.stack.push((child, path.join(name), offsets));
+                }
+            }
+        }
+    }
+}
+
+/// Byte and line ranges of the node at `offsets` in `file`
+fn ranges(
+    stores: &SimpleStores,
+    file: NodeIdentifier,
+    offsets: &[u16],
+) -> (Range<usize>, Range<usize>) {
+    let mut bytes = 0;
+    let mut lines = 0;
+    let mut x = file;
+    for &o in offsets {
+        let n = stores.node_store().resolve(&x);
+        let cs = n
+            .children()
+            .expect("offsets should follow existing children");
+        for c in cs.before(o).iter_children() {
+            let c = stores.node_store().resolve(c);
+            bytes += c.try_bytes_len().unwrap_or_default();
+            lines += c.line_count();
+        }
+        x = *cs.get(o).expect("offsets should follow existing children");
+    }
+    let n = stores.node_store().resolve(&x);
+    let len = n.try_bytes_len().unwrap_or_default();
+    (bytes..bytes + len, lines..lines + n.line_count() + 1)
+}
+
+/// Row and column right after the node `id` that starts at `start`
+fn end_position(stores: &SimpleStores, id: NodeIdentifier, start: &RowCol<usize>) -> RowCol<usize> {
+    let n = stores.node_store().resolve(&id);
+    let len = n.try_bytes_len().unwrap_or_default();
+    let lines = n.line_count();
+    if lines == 0 {
+        return RowCol::new(start.row(), start.col() + len);
+    }
+    let text = hyper_ast::nodes::TextSerializer::new(stores, id).to_string();
+    let col = text.rfind('\n').map_or(len, |i| text.len() - i - 1);
+    RowCol::new(start.row() + lines, col)
+}
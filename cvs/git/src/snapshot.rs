@@ -0,0 +1,301 @@
+//! Saving and loading [`PreProcessedRepositories`] as snapshots,
+//! see [`hyper_ast::store::persist`] for the layout of the subtrees.
+//!
+//! The payload of each segment lists the repositories registered
+//! and the commits processed since the previous segment,
+//! thus a restarted server does not need to process them again.
+use std::{
+    collections::HashSet,
+    fs::{File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use hyper_ast::store::persist::{
+    ComponentRegistry, Persist, SnapshotError, SnapshotReader, SnapshotWriter, Watermark,
+};
+
+use crate::{
+    git::{Forge, Repo},
+    multi_preprocessed::PreProcessedRepositories,
+    processing::RepoConfig,
+    Commit,
+};
+
+/// What the snapshot of a [`PreProcessedRepositories`] already contains.
+#[derive(Default)]
+pub(crate) struct Snapshotted {
+    watermark: Watermark,
+    repos: HashSet<Repo>,
+    commits: HashSet<(RepoConfig, git2::Oid)>,
+}
+
+/// Components attached to nodes by the processors enabled in this build.
+pub fn component_registry() -> ComponentRegistry {
+    let mut registry = ComponentRegistry::new();
+    #[cfg(feature = "java")]
+    registry.register::<hyper_ast_gen_ts_java::types::TType>("type_java");
+    #[cfg(feature = "cpp")]
+    registry.register::<hyper_ast_gen_ts_cpp::types::TType>("type_cpp");
+    #[cfg(any(feature = "maven", feature = "cmake", feature = "any"))]
+    registry.register::<hyper_ast_gen_ts_xml::types::TType>("type_xml");
+    #[cfg(feature = "maven")]
+    register_status::<crate::maven::SemFlags>(&mut registry, "maven_status");
+    #[cfg(feature = "cmake")]
+    {
+        registry.register::<crate::cmake::CMakeModule>("cmake_module");
+        register_status::<crate::cmake::SemFlags>(&mut registry, "cmake_status");
+    }
+    #[cfg(feature = "npm")]
+    {
+        registry.register::<hyper_ast_gen_ts_ts::types::TType>("type_ts");
+        register_status::<crate::npm::SemFlags>(&mut registry, "npm_status");
+    }
+    #[cfg(feature = "python")]
+    {
+        registry.register::<hyper_ast_gen_ts_python::types::TType>("type_python");
+        register_status::<crate::python::SemFlags>(&mut registry, "python_status");
+    }
+    #[cfg(feature = "tsquery")]
+    registry.register::<hyper_ast::types::TypeU16<hyper_ast_gen_ts_tsquery::types::TsQuery>>(
+        "type_tsquery",
+    );
+    registry
+}
+
+/// Registers the flags that the processor of a build system puts on directories, eg. `IsMavenModule`.
+#[allow(unused)]
+fn register_status<F>(registry: &mut ComponentRegistry, name: &'static str)
+where
+    F: enumset::EnumSetType + 'static,
+    enumset::EnumSet<F>: Send + Sync,
+{
+    use enumset::EnumSet;
+    use hyper_ast::store::nodes::{
+        legion::{dyn_builder, EntryRef},
+        EntityBuilder as _,
+    };
+    fn write<F>(e: &EntryRef, w: &mut dyn Write) -> io::Result<()>
+    where
+        F: enumset::EnumSetType + 'static,
+        EnumSet<F>: Send + Sync,
+    {
+        let status = e
+            .get_component::<EnumSet<F>>()
+            .map_err(|_| invalid_data("missing status"))?;
+        status.as_u64().persist(w)
+    }
+    fn read<F>(r: &mut dyn Read, builder: &mut dyn_builder::EntityBuilder) -> io::Result<()>
+    where
+        F: enumset::EnumSetType + 'static,
+        EnumSet<F>: Send + Sync,
+    {
+        let status = EnumSet::<F>::try_from_u64(u64::restore(r)?)
+            .ok_or_else(|| invalid_data("bad status"))?;
+        builder.add(status);
+        Ok(())
+    }
+    registry.register_raw::<EnumSet<F>>(name, write::<F>, read::<F>);
+}
+
+#[cfg(feature = "cmake")]
+impl Persist for crate::cmake::CMakeModule {
+    fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+        self.targets.persist(w)?;
+        self.subdirectories.persist(w)
+    }
+    fn restore(r: &mut dyn Read) -> io::Result<Self> {
+        Ok(crate::cmake::CMakeModule {
+            targets: Persist::restore(r)?,
+            subdirectories: Persist::restore(r)?,
+        })
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn persist_oid(oid: &git2::Oid, w: &mut dyn Write) -> io::Result<()> {
+    w.write_all(oid.as_bytes())
+}
+
+fn restore_oid(r: &mut dyn Read) -> io::Result<git2::Oid> {
+    let mut b = [0; 20];
+    r.read_exact(&mut b)?;
+    git2::Oid::from_bytes(&b).map_err(|_| invalid_data("bad oid"))
+}
+
+impl Persist for RepoConfig {
+    fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+        let t: u8 = match self {
+            RepoConfig::CppMake => 0,
+            RepoConfig::JavaMaven => 1,
+            RepoConfig::TsNpm => 2,
+            RepoConfig::Any => 3,
+            RepoConfig::PythonPip => 4,
+            RepoConfig::CppCMake => 5,
+            RepoConfig::JavaGradle => 6,
+        };
+        t.persist(w)
+    }
+    fn restore(r: &mut dyn Read) -> io::Result<Self> {
+        Ok(match u8::restore(r)? {
+            0 => RepoConfig::CppMake,
+            1 => RepoConfig::JavaMaven,
+            2 => RepoConfig::TsNpm,
+            3 => RepoConfig::Any,
+            4 => RepoConfig::PythonPip,
+            5 => RepoConfig::CppCMake,
+            6 => RepoConfig::JavaGradle,
+            _ => return Err(invalid_data("bad repository config")),
+        })
+    }
+}
+
+impl Persist for Repo {
+    fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+        let forge: u8 = match self.forge {
+            Forge::Github => 0,
+            Forge::Gitlab => 1,
+            Forge::Local => 2,
+        };
+        forge.persist(w)?;
+        self.user.persist(w)?;
+        self.name.persist(w)
+    }
+    fn restore(r: &mut dyn Read) -> io::Result<Self> {
+        let forge = match u8::restore(r)? {
+            0 => Forge::Github,
+            1 => Forge::Gitlab,
+            2 => Forge::Local,
+            _ => return Err(invalid_data("bad forge")),
+        };
+        Ok(forge.repo(String::restore(r)?, String::restore(r)?))
+    }
+}
+
+impl Persist for Commit {
+    fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+        (self.parents.len() as u32).persist(w)?;
+        self.parents.iter().try_for_each(|x| persist_oid(x, w))?;
+        self.processing_time.persist(w)?;
+        self.memory_used.persist(w)?;
+        self.ast_root.persist(w)?;
+        persist_oid(&self.tree_oid, w)
+    }
+    fn restore(r: &mut dyn Read) -> io::Result<Self> {
+        let len = u32::restore(r)?;
+        let parents = (0..len).map(|_| restore_oid(r)).collect::<io::Result<_>>()?;
+        Ok(Commit {
+            parents,
+            processing_time: Persist::restore(r)?,
+            memory_used: Persist::restore(r)?,
+            ast_root: Persist::restore(r)?,
+            tree_oid: restore_oid(r)?,
+        })
+    }
+}
+
+impl PreProcessedRepositories {
+    /// Writes everything processed so far in a new snapshot at `path`.
+    pub fn save_snapshot(&mut self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        let file = BufWriter::new(File::create(path)?);
+        self.snapshot = Default::default();
+        self.write_segment(SnapshotWriter::create(file)?)
+    }
+
+    /// Appends to the snapshot at `path` what was processed since it was saved or loaded.
+    pub fn append_to_snapshot(&mut self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        let file = BufWriter::new(OpenOptions::new().append(true).open(path)?);
+        self.write_segment(SnapshotWriter::append(file, self.snapshot.watermark))
+    }
+
+    /// Loads the snapshot at `path`, it must be done before processing anything.
+    pub fn load_snapshot(&mut self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        let registry = component_registry();
+        let mut reader = SnapshotReader::open(BufReader::new(File::open(path)?))?;
+        while let Some(payload) =
+            reader.read_segment(&mut self.processor.main_stores, &registry)?
+        {
+            self.restore_payload(&mut &payload[..])?;
+        }
+        self.snapshot.watermark = reader.watermark();
+        Ok(())
+    }
+
+    fn write_segment<W: Write>(
+        &mut self,
+        mut writer: SnapshotWriter<W>,
+    ) -> Result<(), SnapshotError> {
+        let repos: Vec<(Repo, RepoConfig)> = self
+            .configs()
+            .filter(|(repo, _)| !self.snapshot.repos.contains(repo))
+            .map(|(repo, config)| (repo.clone(), *config))
+            .collect();
+        let configs: HashSet<RepoConfig> = self.configs().map(|(_, config)| *config).collect();
+        let mut commits = vec![];
+        for config in configs {
+            let handle = self.processor_handle(config);
+            let proc = self
+                .processor
+                .processing_systems
+                .by_id(&handle.0)
+                .unwrap()
+                .get(handle.1);
+            commits.extend(
+                proc.commits()
+                    .filter(|(oid, _)| !self.snapshot.commits.contains(&(config, **oid)))
+                    .map(|(oid, commit)| (config, *oid, commit.clone())),
+            );
+        }
+
+        let mut payload = vec![];
+        let w = &mut payload as &mut dyn Write;
+        (repos.len() as u32).persist(w)?;
+        for (repo, config) in &repos {
+            repo.persist(w)?;
+            config.persist(w)?;
+        }
+        (commits.len() as u32).persist(w)?;
+        for (config, oid, commit) in &commits {
+            config.persist(w)?;
+            persist_oid(oid, w)?;
+            commit.persist(w)?;
+        }
+
+        let registry = component_registry();
+        writer.write_segment(&self.processor.main_stores, &registry, &payload)?;
+        self.snapshot.watermark = writer.watermark();
+        self.snapshot.repos.extend(repos.into_iter().map(|(repo, _)| repo));
+        self.snapshot
+            .commits
+            .extend(commits.into_iter().map(|(config, oid, _)| (config, oid)));
+        Ok(())
+    }
+
+    fn restore_payload(&mut self, r: &mut dyn Read) -> io::Result<()> {
+        let len = u32::restore(r)?;
+        for _ in 0..len {
+            let repo = Repo::restore(r)?;
+            let config = RepoConfig::restore(r)?;
+            self.register_config(repo.clone(), config);
+            self.snapshot.repos.insert(repo);
+        }
+        let len = u32::restore(r)?;
+        for _ in 0..len {
+            let config = RepoConfig::restore(r)?;
+            let oid = restore_oid(r)?;
+            let commit = Commit::restore(r)?;
+            let handle = self.processor_handle(config);
+            self.processor
+                .processing_systems
+                .by_id_mut(&handle.0)
+                .unwrap()
+                .get_mut(handle.1)
+                .restore_commit(oid, commit)?;
+            self.snapshot.commits.insert((config, oid));
+        }
+        Ok(())
+    }
+}
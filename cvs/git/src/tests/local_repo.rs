@@ -0,0 +1,164 @@
+use std::path::Path;
+
+pub(crate) use crate::fixtures::{commit_changes, commit_files, fixture_dir, init_fixture};
+use crate::{
+    git::{Forge, Repo},
+    multi_preprocessed::PreProcessedRepositories,
+    processing::RepoConfig,
+};
+
+#[test]
+fn parse_local_repo() {
+    let path = fixture_dir("parse_local_repo");
+    init_fixture(&path);
+    let repo: Repo = path.to_str().unwrap().parse().unwrap();
+    assert_eq!(repo.forge, Forge::Local);
+    assert_eq!(repo.local_path(), Some(path.canonicalize().unwrap()));
+    let url = format!("file://{}", path.to_str().unwrap());
+    assert_eq!(repo, url.parse().unwrap());
+    assert!("/does/not/exist".parse::<Repo>().is_err());
+}
+
+#[test]
+fn process_local_and_bare_repo() {
+    let path = fixture_dir("process_local_repo");
+    let commit_oid = init_fixture(&path);
+    let bare_path = fixture_dir("process_bare_repo");
+    git2::build::RepoBuilder::new()
+        .bare(true)
+        .clone(&format!("file://{}", path.to_str().unwrap()), &bare_path)
+        .unwrap();
+
+    let mut repos = PreProcessedRepositories::default();
+    let mut process = |path: &Path| {
+        let repo = repos
+            .register_config(Repo::local(path).unwrap(), RepoConfig::JavaMaven)
+            .fetch()
+            .unwrap();
+        let oids = repos.pre_process_with_limit(&repo, "", "", 2).unwrap();
+        assert_eq!(oids, vec![commit_oid]);
+        repos
+            .get_commit(&repo.config, &commit_oid)
+            .unwrap()
+            .ast_root
+    };
+    let root = process(&path);
+    let bare_root = process(&bare_path);
+    // same content, thus same subtrees
+    assert_eq!(root, bare_root);
+}
+
+#[test]
+fn save_load_and_append_snapshot() {
+    let path = fixture_dir("snapshot_repo");
+    let snapshot = fixture_dir("snapshot_file").join("repos.snapshot");
+    let first = init_fixture(&path);
+    let spec = Repo::local(&path).unwrap();
+
+    let mut repos = PreProcessedRepositories::default();
+    let repo = repos
+        .register_config(spec.clone(), RepoConfig::JavaMaven)
+        .fetch()
+        .unwrap();
+    repos.pre_process_with_limit(&repo, "", "", 2).unwrap();
+    let first_root = repos.get_commit(&repo.config, &first).unwrap().ast_root;
+    repos.save_snapshot(&snapshot).unwrap();
+
+    let mut loaded = PreProcessedRepositories::default();
+    loaded.load_snapshot(&snapshot).unwrap();
+    let repo = loaded.get_config(spec.clone()).unwrap().fetch().unwrap();
+    let commit = loaded.get_commit(&repo.config, &first).unwrap();
+    assert_eq!(commit.ast_root, first_root);
+
+    let second = commit_files(
+        &path,
+        &[(
+            "src/main/java/fixture/B.java",
+            b"package fixture;\n\nclass B extends A {}\n",
+        )],
+    );
+    let oids = loaded.pre_process_with_limit(&repo, "", "", 1).unwrap();
+    assert_eq!(oids, vec![second]);
+    let second_root = loaded.get_commit(&repo.config, &second).unwrap().ast_root;
+    assert_ne!(second_root, first_root);
+    loaded.append_to_snapshot(&snapshot).unwrap();
+
+    let mut reloaded = PreProcessedRepositories::default();
+    reloaded.load_snapshot(&snapshot).unwrap();
+    let repo = reloaded.get_config(spec).unwrap();
+    let commit = reloaded.get_commit(&repo.config, &second).unwrap();
+    assert_eq!(commit.ast_root, second_root);
+    assert_eq!(commit.parents, vec![first]);
+    let commit = reloaded.get_commit(&repo.config, &first).unwrap();
+    assert_eq!(commit.ast_root, first_root);
+}
+
+#[cfg(feature = "maven_java")]
+#[test]
+fn diff_commit_range_of_local_repo() {
+    use crate::{commit_range::CommitRangeDiffs, preprocessed::PreProcessedRepository};
+    use hyper_diff::algorithms::per_file::FileChange;
+
+    let path = fixture_dir("commit_range_repo");
+    let first = init_fixture(&path);
+    let second = commit_files(
+        &path,
+        &[(
+            "src/main/java/fixture/Old.java",
+            b"package fixture;\n\nclass Old {}\n",
+        )],
+    );
+    let third = commit_changes(
+        &path,
+        &[
+            (
+                "src/main/java/fixture/A.java",
+                Some(b"package fixture;\n\nclass A {\n    int f(int x) {\n        return x + 2;\n    }\n}\n"),
+            ),
+            ("src/main/java/fixture/Old.java", None),
+            (
+                "src/main/java/fixture/B.java",
+                Some(b"package fixture;\n\nclass B extends A {}\n"),
+            ),
+        ],
+    );
+
+    let mut repository = git2::Repository::open(&path).unwrap();
+    let mut preprocessed = PreProcessedRepository::new("fixture");
+    let diffs: Vec<_> = preprocessed
+        .diff_commit_range(&mut repository, "", "", "", 2)
+        .collect();
+    assert_eq!(diffs.len(), 1);
+    let diff = diffs.into_iter().next().unwrap().unwrap();
+    assert_eq!((diff.src, diff.dst), (second, third));
+    // pom.xml did not change
+    let mut files: Vec<_> = diff.files.iter().collect();
+    files.sort_by(|a, b| a.change.path().cmp(b.change.path()));
+    let changes: Vec<_> = files
+        .iter()
+        .map(|f| match &f.change {
+            FileChange::Added { path, .. } => ("added", path.as_str()),
+            FileChange::Deleted { path, .. } => ("deleted", path.as_str()),
+            FileChange::Modified { path, .. } => ("modified", path.as_str()),
+        })
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            ("modified", "src/main/java/fixture/A.java"),
+            ("added", "src/main/java/fixture/B.java"),
+            ("deleted", "src/main/java/fixture/Old.java"),
+        ]
+    );
+    // only modified files have an edit script
+    assert!(!files[0].actions.as_ref().unwrap().iter().next().is_none());
+    assert!(files[1].actions.is_none());
+    assert!(files[2].actions.is_none());
+
+    // the first commit was not processed
+    let mut diffs = CommitRangeDiffs::new(&preprocessed, vec![second, first]);
+    assert_eq!(diffs.size_hint(), (1, Some(1)));
+    let err = diffs.next().unwrap().err().unwrap();
+    assert_eq!(err.0, first);
+    assert!(diffs.next().is_none());
+}
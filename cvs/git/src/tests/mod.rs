@@ -1,4 +1,7 @@
 pub mod direct_type_ref;
+pub mod local_repo;
+pub mod processors;
+pub mod search;
 #[cfg(test)]
 pub mod extends_package_local;
 pub mod obj_creation;
@@ -0,0 +1,353 @@
+//! Processing small repositories with the processor of each build system.
+use enumset::EnumSet;
+use hyper_ast::{
+    store::defaults::NodeIdentifier,
+    types::{HyperAST as _, WithChildren as _},
+};
+
+use super::local_repo::{commit_files, fixture_dir};
+use crate::{
+    git::Repo, multi_preprocessed::PreProcessedRepositories, preprocessed::child_at_path,
+    processing::RepoConfig,
+};
+
+/// `git init` a repository with a single commit of `files`, then processes it with `config`
+fn process(
+    name: &str,
+    config: RepoConfig,
+    files: &[(&str, &[u8])],
+) -> (PreProcessedRepositories, NodeIdentifier) {
+    let path = fixture_dir(name);
+    git2::Repository::init(&path).unwrap();
+    let oid = commit_files(&path, files);
+    let mut repos = PreProcessedRepositories::default();
+    let repo = repos
+        .register_config(Repo::local(&path).unwrap(), config)
+        .fetch()
+        .unwrap();
+    repos.pre_process_with_limit(&repo, "", "", 1).unwrap();
+    let root = repos.get_commit(&repo.config, &oid).unwrap().ast_root;
+    (repos, root)
+}
+
+fn node_at(repos: &PreProcessedRepositories, root: NodeIdentifier, path: &str) -> NodeIdentifier {
+    let names = path.split('/').filter(|x| !x.is_empty());
+    child_at_path(&repos.processor.main_stores, root, names)
+        .unwrap_or_else(|| panic!("nothing at {:?}", path))
+}
+
+/// the flags put by the processor on the directory at `path`
+fn status_at<F>(repos: &PreProcessedRepositories, root: NodeIdentifier, path: &str) -> EnumSet<F>
+where
+    F: enumset::EnumSetType + 'static,
+    EnumSet<F>: Send + Sync,
+{
+    let id = node_at(repos, root, path);
+    let n = repos.processor.main_stores.node_store.resolve(id);
+    n.get_component::<EnumSet<F>>()
+        .map_or(EnumSet::empty(), |x| *x)
+}
+
+/// the type of the node at `path`, if it is one of the types of the language of `T`
+fn type_at<T: 'static + Copy>(
+    repos: &PreProcessedRepositories,
+    root: NodeIdentifier,
+    path: &str,
+) -> Option<T> {
+    let id = node_at(repos, root, path);
+    let t = repos.processor.main_stores.resolve_type(&id);
+    t.as_any().downcast_ref::<T>().copied()
+}
+
+#[cfg(feature = "pip_python")]
+#[test]
+fn python_packages_and_projects() {
+    use crate::python::SemFlags;
+    use hyper_ast_gen_ts_python::types::Type;
+    let (repos, root) = process(
+        "python_repo",
+        RepoConfig::PythonPip,
+        &[
+            ("pyproject.toml", b"[project]\nname = \"fixture\"\n"),
+            ("pkg/__init__.py", b""),
+            ("pkg/mod.py", b"def f(x):\n    return x + 1\n"),
+            ("pkg/sub/__init__.py", b"from .. import mod\n"),
+            ("tools/setup.py", b"from setuptools import setup\nsetup()\n"),
+            ("scripts/run.py", b"print('run')\n"),
+            ("scripts/README.md", b"# Scripts\n"),
+        ],
+    );
+    let status = |path| status_at::<SemFlags>(&repos, root, path);
+    assert_eq!(status(""), SemFlags::IsProject | SemFlags::HoldPackage);
+    assert_eq!(status("pkg"), SemFlags::IsPackage | SemFlags::HoldPackage);
+    assert_eq!(status("pkg/sub"), EnumSet::only(SemFlags::IsPackage));
+    assert_eq!(status("tools"), EnumSet::only(SemFlags::IsProject));
+    assert_eq!(status("scripts"), EnumSet::empty());
+    // configurations written in python are also parsed
+    assert_eq!(type_at(&repos, root, "tools/setup.py"), Some(Type::Module));
+    assert_eq!(type_at(&repos, root, "pkg/mod.py"), Some(Type::Module));
+    // only python files are kept
+    let scripts = node_at(&repos, root, "scripts");
+    let scripts = repos.processor.main_stores.node_store.resolve(scripts);
+    assert_eq!(scripts.child_count(), 1);
+}
+
+#[cfg(feature = "npm_ts")]
+#[test]
+fn npm_packages_and_workspaces() {
+    use crate::npm::SemFlags;
+    use hyper_ast_gen_ts_ts::types::Type;
+    let (repos, root) = process(
+        "npm_repo",
+        RepoConfig::TsNpm,
+        &[
+            (
+                "package.json",
+                br#"{"name": "root", "private": true, "workspaces": ["packages/*"]}"#,
+            ),
+            ("src/index.ts", b"export const x: number = 1;\n"),
+            ("src/legacy.js", b"module.exports = { y: 2 };\n"),
+            ("src/App.tsx", b"export const App = () => <div />;\n"),
+            ("packages/a/package.json", br#"{"name": "a"}"#),
+            (
+                "packages/a/lib.ts",
+                b"export function f(x: number) { return x; }\n",
+            ),
+            ("node_modules/dep/index.js", b"exports.dep = 1;\n"),
+        ],
+    );
+    let status = |path| status_at::<SemFlags>(&repos, root, path);
+    assert_eq!(
+        status(""),
+        SemFlags::IsNpmPackage | SemFlags::HoldNpmSubPackage
+    );
+    assert_eq!(
+        status("packages"),
+        EnumSet::only(SemFlags::HoldNpmSubPackage)
+    );
+    assert_eq!(
+        status("packages/a"),
+        SemFlags::IsNpmPackage | SemFlags::IsNpmWorkspace
+    );
+    assert_eq!(status("src"), EnumSet::empty());
+    assert_eq!(type_at(&repos, root, "src/index.ts"), Some(Type::Program));
+    assert_eq!(type_at(&repos, root, "src/legacy.js"), Some(Type::Program));
+    assert_eq!(
+        type_at(&repos, root, "packages/a/lib.ts"),
+        Some(Type::Program)
+    );
+    assert_eq!(type_at(&repos, root, "src/App.tsx"), Some(Type::Program));
+    let stores = &repos.processor.main_stores;
+    assert_eq!(
+        child_at_path(stores, root, ["node_modules"].into_iter()),
+        None
+    );
+}
+
+#[cfg(feature = "cmake_cpp")]
+#[test]
+fn cmake_modules_and_targets() {
+    use crate::cmake::{CMakeModule, SemFlags};
+    use hyper_ast::types::LabelStore as _;
+    use hyper_ast_gen_ts_cpp::types::Type;
+    let (repos, root) = process(
+        "cmake_repo",
+        RepoConfig::CppCMake,
+        &[
+            (
+                "CMakeLists.txt",
+                b"cmake_minimum_required(VERSION 3.10)\nproject(Demo CXX)\nadd_subdirectory(src)\nadd_subdirectory(libs/core)\nadd_executable(demo main.cc)\n",
+            ),
+            ("main.cc", b"int main() { return 0; }\n"),
+            ("src/CMakeLists.txt", b"add_library(app app.cc)\n"),
+            ("src/app.cc", b"#include \"app.hh\"\nint app() { return g(); }\n"),
+            ("src/app.hh", b"#pragma once\nint app();\n"),
+            ("src/detail.ipp", b"inline int g() { return 1; }\n"),
+            ("src/detail.inl", b"inline int h() { return 2; }\n"),
+            (
+                "libs/core/CMakeLists.txt",
+                b"add_library(core STATIC core.cc)\nadd_custom_target(docs)\n",
+            ),
+            ("libs/core/core.cc", b"int core() { return 0; }\n"),
+            ("docs/notes.txt", b"not a source file\n"),
+        ],
+    );
+    let status = |path| status_at::<SemFlags>(&repos, root, path);
+    assert_eq!(
+        status(""),
+        SemFlags::IsCMakeModule | SemFlags::HoldCMakeSubModule
+    );
+    assert_eq!(
+        status("src"),
+        SemFlags::IsCMakeModule | SemFlags::IsCMakeSubdirectory
+    );
+    assert_eq!(status("libs"), EnumSet::only(SemFlags::HoldCMakeSubModule));
+    assert_eq!(
+        status("libs/core"),
+        SemFlags::IsCMakeModule | SemFlags::IsCMakeSubdirectory
+    );
+    assert_eq!(status("docs"), EnumSet::empty());
+
+    let stores = &repos.processor.main_stores;
+    let module = |path| {
+        let n = stores.node_store.resolve(node_at(&repos, root, path));
+        let module = n.get_component::<CMakeModule>().ok().cloned();
+        let names =
+            |x: &[_]| -> Vec<_> { x.iter().map(|x| stores.label_store.resolve(x)).collect() };
+        module.map(|x| (names(&x.targets), names(&x.subdirectories)))
+    };
+    assert_eq!(module(""), Some((vec!["demo"], vec!["src", "libs/core"])));
+    assert_eq!(module("src"), Some((vec!["app"], vec![])));
+    assert_eq!(module("libs/core"), Some((vec!["core", "docs"], vec![])));
+    assert_eq!(module("libs"), None);
+
+    for path in [
+        "main.cc",
+        "src/app.cc",
+        "src/app.hh",
+        "src/detail.ipp",
+        "src/detail.inl",
+        "libs/core/core.cc",
+    ] {
+        assert_eq!(
+            type_at(&repos, root, path),
+            Some(Type::TranslationUnit),
+            "{} should be generated as C++",
+            path
+        );
+    }
+    assert_eq!(
+        child_at_path(stores, root, ["docs", "notes.txt"].into_iter()),
+        None
+    );
+}
+
+#[cfg(feature = "gradle_java")]
+#[test]
+fn gradle_included_projects() {
+    use crate::maven::SemFlags;
+    use hyper_ast_gen_ts_java::types::Type;
+    let (repos, root) = process(
+        "gradle_repo",
+        RepoConfig::JavaGradle,
+        &[
+            (
+                "settings.gradle",
+                b"rootProject.name = 'fixture'\ninclude ':app', ':lib:core'\n",
+            ),
+            ("build.gradle", b"plugins { id 'java' }\n"),
+            // included without a build script of its own
+            (
+                "app/src/main/java/app/Main.java",
+                b"package app;\n\nclass Main {}\n",
+            ),
+            (
+                "app/src/test/java/app/MainTest.java",
+                b"package app;\n\nclass MainTest {}\n",
+            ),
+            ("lib/core/build.gradle", b"plugins { id 'java-library' }\n"),
+            (
+                "lib/core/src/main/java/core/Core.java",
+                b"package core;\n\npublic class Core {}\n",
+            ),
+            ("docs/src/main/java/Doc.java", b"class Doc {}\n"),
+        ],
+    );
+    let status = |path| status_at::<SemFlags>(&repos, root, path);
+    assert!(status("").contains(SemFlags::IsMavenModule));
+    assert!(status("").contains(SemFlags::HoldMavenSubModule));
+    let app = status("app");
+    assert!(app.contains(SemFlags::IsMavenModule));
+    assert!(app.contains(SemFlags::HoldMainFolder));
+    assert!(app.contains(SemFlags::HoldTestFolder));
+    assert!(status("app/src/main").contains(SemFlags::HoldMainFolder));
+    assert!(status("app/src/test").contains(SemFlags::HoldTestFolder));
+    assert!(status("lib/core").contains(SemFlags::IsMavenModule));
+    assert!(!status("lib").contains(SemFlags::IsMavenModule));
+    assert!(status("lib").contains(SemFlags::HoldMavenSubModule));
+    // not included, thus not a project
+    assert_eq!(status("docs"), EnumSet::empty());
+
+    // the source directories are generated as java
+    for path in [
+        "app/src/main/java",
+        "app/src/test/java",
+        "lib/core/src/main/java",
+    ] {
+        assert_eq!(
+            type_at(&repos, root, path),
+            Some(Type::Directory),
+            "{}",
+            path
+        );
+    }
+    assert_eq!(
+        type_at(&repos, root, "app/src/main/java/app/Main.java"),
+        Some(Type::Program)
+    );
+    assert_eq!(
+        type_at(&repos, root, "app/src/test/java/app/MainTest.java"),
+        Some(Type::Program)
+    );
+}
+
+#[cfg(feature = "any")]
+#[test]
+fn any_files_of_mixed_tree() {
+    use hyper_ast::store::nodes::legion::compo;
+    use hyper_ast::types::{LabelStore as _, Labeled as _};
+    use hyper_ast_gen_ts_xml::types::Type as Xml;
+    let binary: &[u8] = &[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0xff, 0x00];
+    let (repos, root) = process(
+        "any_repo",
+        RepoConfig::Any,
+        &[
+            (
+                "pom.xml",
+                b"<project><artifactId>a</artifactId></project>\n",
+            ),
+            ("src/Main.java", b"class Main {}\n"),
+            ("src/Broken.java", b"class Broken {\n"),
+            ("native/lib.cpp", b"int f() { return 0; }\n"),
+            ("web/index.ts", b"export const x: number = 1;\n"),
+            ("queries/highlights.scm", b"(identifier) @variable\n"),
+            ("assets/logo.png", binary),
+            ("README.md", b"# Mixed\n"),
+        ],
+    );
+    assert_eq!(
+        type_at(&repos, root, "src/Main.java"),
+        Some(hyper_ast_gen_ts_java::types::Type::Program)
+    );
+    assert_eq!(
+        type_at(&repos, root, "native/lib.cpp"),
+        Some(hyper_ast_gen_ts_cpp::types::Type::TranslationUnit)
+    );
+    assert_eq!(
+        type_at(&repos, root, "web/index.ts"),
+        Some(hyper_ast_gen_ts_ts::types::Type::Program)
+    );
+    assert_eq!(type_at(&repos, root, "pom.xml"), Some(Xml::Document));
+    assert_eq!(
+        type_at(&repos, root, "queries/highlights.scm"),
+        Some(hyper_ast_gen_ts_tsquery::types::Type::Program)
+    );
+
+    // the remaining files, including the sources with syntax errors, are opaque leafs
+    // labeled with the blob oid of their content
+    let stores = &repos.processor.main_stores;
+    let opaque_at = |path| {
+        assert_eq!(type_at(&repos, root, path), Some(Xml::CharData), "{}", path);
+        let n = stores.node_store.resolve(node_at(&repos, root, path));
+        let label = stores.label_store.resolve(n.get_label_unchecked());
+        let len = n.get_component::<compo::BytesLen>().unwrap().0;
+        (label.to_string(), len as usize)
+    };
+    let blob = |text: &[u8]| {
+        let oid = git2::Oid::hash_object(git2::ObjectType::Blob, text).unwrap();
+        (oid.to_string(), text.len())
+    };
+    assert_eq!(opaque_at("src/Broken.java"), blob(b"class Broken {\n"));
+    assert_eq!(opaque_at("README.md"), blob(b"# Mixed\n"));
+    assert_eq!(opaque_at("assets/logo.png"), blob(binary));
+}
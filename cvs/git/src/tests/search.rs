@@ -0,0 +1,86 @@
+use std::path::Path;
+
+use super::local_repo::{fixture_dir, init_fixture};
+use crate::{
+    git::Repo,
+    multi_preprocessed::PreProcessedRepositories,
+    processing::RepoConfig,
+    search::{search, search_many},
+};
+
+static JAVA_FILE: &str = "src/main/java/fixture/A.java";
+
+#[test]
+fn search_processed_commit() {
+    let path = fixture_dir("search_repo");
+    let oid = init_fixture(&path);
+    let mut repos = PreProcessedRepositories::default();
+    let repo = repos
+        .register_config(Repo::local(&path).unwrap(), RepoConfig::JavaMaven)
+        .fetch()
+        .unwrap();
+    repos.pre_process_with_limit(&repo, "", "", 1).unwrap();
+    let text = std::fs::read_to_string(path.join(JAVA_FILE)).unwrap();
+
+    // on a single line
+    let ms: Vec<_> = search(&repos, &repo, oid, "Java", "(formal_parameter) @p")
+        .unwrap()
+        .collect();
+    assert_eq!(ms.len(), 1);
+    assert_eq!(ms[0].captures.len(), 1);
+    let c = &ms[0].captures[0];
+    assert_eq!(c.name, "p");
+    assert_eq!(c.file, Path::new(JAVA_FILE));
+    assert_eq!(&text[c.bytes.clone()], "int x");
+    assert_eq!(c.lines, 3..4);
+    assert_eq!((c.start.row(), c.start.col()), (3, 10));
+    assert_eq!((c.end.row(), c.end.col()), (3, 15));
+
+    // on many lines
+    let ms: Vec<_> = search(&repos, &repo, oid, "Java", "(method_declaration) @m")
+        .unwrap()
+        .collect();
+    assert_eq!(ms.len(), 1);
+    let c = &ms[0].captures[0];
+    assert_eq!(c.file, Path::new(JAVA_FILE));
+    assert_eq!(
+        &text[c.bytes.clone()],
+        "int f(int x) {\n        return x + 1;\n    }"
+    );
+    assert_eq!(c.lines, 3..6);
+    assert_eq!((c.start.row(), c.start.col()), (3, 4));
+    assert_eq!((c.end.row(), c.end.col()), (5, 5));
+}
+
+#[test]
+fn search_many_languages() {
+    let path = fixture_dir("search_many_repo");
+    let oid = init_fixture(&path);
+    let mut repos = PreProcessedRepositories::default();
+    let repo = repos
+        .register_config(Repo::local(&path).unwrap(), RepoConfig::JavaMaven)
+        .fetch()
+        .unwrap();
+    repos.pre_process_with_limit(&repo, "", "", 1).unwrap();
+    let pom = std::fs::read_to_string(path.join("pom.xml")).unwrap();
+
+    let queries = [
+        ("Java", "(method_declaration) @m"),
+        ("Xml", "(element) @e"),
+        ("Java", "(formal_parameter) @p"),
+    ];
+    let ms: Vec<_> = search_many(&repos, &repo, oid, &queries).unwrap().collect();
+    // files in the order of the commit, where the pom comes first,
+    // then the matches of each file in the order of the queries
+    let order: Vec<_> = ms
+        .iter()
+        .map(|m| (m.query_index, m.captures[0].file.to_str().unwrap()))
+        .collect();
+    let mut expected = vec![(1, "pom.xml"); 5];
+    expected.push((0, JAVA_FILE));
+    expected.push((2, JAVA_FILE));
+    assert_eq!(order, expected);
+    let project = &ms[0].captures[0];
+    assert!(pom[project.bytes.clone()].starts_with("<project>"));
+    assert!(pom[project.bytes.clone()].ends_with("</project>"));
+}
@@ -9,8 +9,14 @@ pub struct TStore;
 impl hyper_ast::store::TyDown<hyper_ast_gen_ts_cpp::types::TStore> for TStore {}
 #[cfg(feature = "java")]
 impl hyper_ast::store::TyDown<hyper_ast_gen_ts_java::types::TStore> for TStore {}
-#[cfg(feature = "maven")]
+#[cfg(any(feature = "maven", feature = "cmake", feature = "any"))]
 impl hyper_ast::store::TyDown<hyper_ast_gen_ts_xml::types::TStore> for TStore {}
+#[cfg(feature = "ts")]
+impl hyper_ast::store::TyDown<hyper_ast_gen_ts_ts::types::TStore> for TStore {}
+#[cfg(feature = "python")]
+impl hyper_ast::store::TyDown<hyper_ast_gen_ts_python::types::TStore> for TStore {}
+#[cfg(feature = "tsquery")]
+impl hyper_ast::store::TyDown<hyper_ast_gen_ts_tsquery::types::TStore> for TStore {}
 
 impl Default for TStore {
     fn default() -> Self {
@@ -54,13 +60,34 @@ impl<'a> hyper_ast::types::RoleStore for TStore {
                 );
                 hyper_ast_gen_ts_cpp::types::TStore::resolve_field(t.get_lang(), field_id)
             }
-            #[cfg(feature = "maven")]
+            #[cfg(any(feature = "maven", feature = "cmake", feature = "any"))]
             "hyper_ast_gen_ts_xml::types::Lang" => {
                 let t = hyper_ast_gen_ts_xml::types::TType::new(
                     hyper_ast_gen_ts_xml::types::Type::Spaces,
                 );
                 hyper_ast_gen_ts_xml::types::TStore::resolve_field(t.get_lang(), field_id)
             }
+            #[cfg(feature = "python")]
+            "hyper_ast_gen_ts_python::types::Lang" => {
+                let t = hyper_ast_gen_ts_python::types::TType::new(
+                    hyper_ast_gen_ts_python::types::Type::Spaces,
+                );
+                hyper_ast_gen_ts_python::types::TStore::resolve_field(t.get_lang(), field_id)
+            }
+            #[cfg(feature = "ts")]
+            "hyper_ast_gen_ts_ts::types::Lang" => {
+                let t = hyper_ast_gen_ts_ts::types::TType::new(
+                    hyper_ast_gen_ts_ts::types::Type::Spaces,
+                );
+                hyper_ast_gen_ts_ts::types::TStore::resolve_field(t.get_lang(), field_id)
+            }
+            #[cfg(feature = "tsquery")]
+            "hyper_ast_gen_ts_tsquery::types::Lang" => {
+                let t = hyper_ast::types::TypeU16::<hyper_ast_gen_ts_tsquery::types::TsQuery>::new(
+                    hyper_ast_gen_ts_tsquery::types::Type::Spaces,
+                );
+                hyper_ast_gen_ts_tsquery::types::TStore::resolve_field(t.get_lang(), field_id)
+            }
             x => panic!("{}", x),
         }
     }
@@ -82,13 +109,34 @@ impl<'a> hyper_ast::types::RoleStore for TStore {
                 );
                 hyper_ast_gen_ts_cpp::types::TStore::intern_role(t.get_lang(), role)
             }
-            #[cfg(feature = "maven")]
+            #[cfg(any(feature = "maven", feature = "cmake", feature = "any"))]
             "hyper_ast_gen_ts_xml::types::Lang" => {
                 let t = hyper_ast_gen_ts_xml::types::TType::new(
                     hyper_ast_gen_ts_xml::types::Type::Spaces,
                 );
                 hyper_ast_gen_ts_xml::types::TStore::intern_role(t.get_lang(), role)
             }
+            #[cfg(feature = "python")]
+            "hyper_ast_gen_ts_python::types::Lang" => {
+                let t = hyper_ast_gen_ts_python::types::TType::new(
+                    hyper_ast_gen_ts_python::types::Type::Spaces,
+                );
+                hyper_ast_gen_ts_python::types::TStore::intern_role(t.get_lang(), role)
+            }
+            #[cfg(feature = "ts")]
+            "hyper_ast_gen_ts_ts::types::Lang" => {
+                let t = hyper_ast_gen_ts_ts::types::TType::new(
+                    hyper_ast_gen_ts_ts::types::Type::Spaces,
+                );
+                hyper_ast_gen_ts_ts::types::TStore::intern_role(t.get_lang(), role)
+            }
+            #[cfg(feature = "tsquery")]
+            "hyper_ast_gen_ts_tsquery::types::Lang" => {
+                let t = hyper_ast::types::TypeU16::<hyper_ast_gen_ts_tsquery::types::TsQuery>::new(
+                    hyper_ast_gen_ts_tsquery::types::Type::Spaces,
+                );
+                hyper_ast_gen_ts_tsquery::types::TStore::intern_role(t.get_lang(), role)
+            }
             x => panic!("{}", x),
         }
     }
@@ -123,6 +171,27 @@ impl TypeStore for TStore {
             }
             .map(|t| t.as_static().into())
         })
+        .or_else(|| {
+            unsafe {
+                erazed.unerase_ref::<hyper_ast_gen_ts_python::types::TType>(std::any::TypeId::of::<
+                    hyper_ast_gen_ts_python::types::TType,
+                >())
+            }
+            .map(|t| t.as_static().into())
+        })
+        .or_else(|| {
+            unsafe {
+                erazed.unerase_ref::<hyper_ast_gen_ts_ts::types::TType>(std::any::TypeId::of::<
+                    hyper_ast_gen_ts_ts::types::TType,
+                >())
+            }
+            .map(|t| t.as_static().into())
+        })
+        .or_else(|| {
+            type TType = hyper_ast::types::TypeU16<hyper_ast_gen_ts_tsquery::types::TsQuery>;
+            unsafe { erazed.unerase_ref::<TType>(std::any::TypeId::of::<TType>()) }
+                .map(|t| t.as_static().into())
+        })
         .unwrap_or_else(|| {
             dbg!(tid);
             dbg!(std::any::type_name::<Self::Ty>());
@@ -167,7 +167,8 @@ impl<L: Default> CodeEditor<L> {
     pub fn code(&self) -> &str {
         self.code.as_str()
     }
-    pub fn ui(&mut self, ui: &mut egui::Ui) {
+    /// Returns the response of the text edit, if it is shown
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<Response> {
         let Self {
             code, lang, info, ..
         } = self;
@@ -188,29 +189,33 @@ impl<L: Default> CodeEditor<L> {
                 });
         });
         col.show_body_indented(&header_res.response, ui, |ui| {
-            egui::ScrollArea::vertical().show(ui, |ui| {
-                if TREE_SITTER {
-                    let _layouter = |ui: &egui::Ui, code: &EditAwareString, wrap_width: f32| {
-                        dbg!(&lang);
-                        let mut layout_job =
-                            crate::syntax_highlighting::syntax_highlighting_ts::highlight(
-                                ui.ctx(),
-                                &theme,
-                                code,
-                                &lang.as_ref().unwrap(),
-                            );
-                        layout_job.wrap.max_width = wrap_width;
-                        ui.fonts(|f| f.layout_job(layout_job))
-                    };
-                } else {
-                    show_edit_syntect(ui, code);
-                }
-            });
-        });
+            egui::ScrollArea::vertical()
+                .show(ui, |ui| {
+                    if TREE_SITTER {
+                        let _layouter = |ui: &egui::Ui, code: &EditAwareString, wrap_width: f32| {
+                            dbg!(&lang);
+                            let mut layout_job =
+                                crate::syntax_highlighting::syntax_highlighting_ts::highlight(
+                                    ui.ctx(),
+                                    &theme,
+                                    code,
+                                    &lang.as_ref().unwrap(),
+                                );
+                            layout_job.wrap.max_width = wrap_width;
+                            ui.fonts(|f| f.layout_job(layout_job))
+                        };
+                        None
+                    } else {
+                        Some(show_edit_syntect(ui, code))
+                    }
+                })
+                .inner
+        })
+        .and_then(|x| x.inner)
     }
 }
 
-pub fn show_edit_syntect(ui: &mut egui::Ui, code: &mut EditAwareString) {
+pub fn show_edit_syntect(ui: &mut egui::Ui, code: &mut EditAwareString) -> Response {
     let language = "rs";
     let theme = egui_extras::syntax_highlighting::CodeTheme::from_memory(ui.ctx());
 
@@ -231,7 +236,7 @@ pub fn show_edit_syntect(ui: &mut egui::Ui, code: &mut EditAwareString) {
             .desired_rows(1)
             .lock_focus(true)
             .layouter(&mut layouter),
-    );
+    )
 }
 
 fn checkbox_heading(
@@ -167,6 +167,14 @@ impl Type {
         assert!(t < COUNT);
         unsafe { std::mem::transmute(t) }
     }
+
+    pub fn is_named(&self) -> bool {
+        match self {
+            Type::Spaces | Type::Directory => false,
+            Type::ERROR => true,
+            t => id_for_node_kind(t.to_str(), true) != 0,
+        }
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -497,7 +505,7 @@ impl HyperType for Type {
     }
 
     fn is_named(&self) -> bool {
-        todo!()
+        self.is_named()
     }
     fn get_lang(&self) -> hyper_ast::types::LangWrapper<Self>
     where
@@ -391,6 +391,252 @@ fn _compare_all<'a>(
     assert_eq!(bad.len(), 0)
 }
 
+/// Memoized counts must be the same as counting the matches of a whole traversal
+#[test]
+fn memoized_counts() {
+    for text in CODES {
+        for query in QUERIES {
+            let (query, stores, code) = prep_stepped(query, text.as_bytes());
+            let mut expected = vec![0; query.enabled_pattern_count()];
+            for m in query.matches(hyper_ast_tsquery::hyperast::TreeCursor::new(
+                &stores,
+                hyper_ast::position::StructuralPosition::new(code),
+            )) {
+                let i = query.enabled_pattern_index(m.pattern_index).unwrap();
+                expected[i as usize] += 1;
+            }
+            let mut counts = hyper_ast_tsquery::memoized::MatchCounts::new(query);
+            assert_eq!(expected, counts.count(&stores, code));
+            // now from the cache
+            assert_eq!(expected, counts.count(&stores, code));
+        }
+    }
+}
+
+/// Limits are checked once a file is counted, its counts stay memoized
+#[test]
+fn memoized_counts_with_limits() {
+    use hyper_ast_tsquery::memoized::{Interrupted, MatchCounts};
+    use std::time::Duration;
+    let text = "class A { void f() {} void g() {} }".as_bytes();
+    let (query, stores, code) = prep_stepped("(method_declaration) @m", text);
+    let mut counts = MatchCounts::new(query);
+    assert_eq!(
+        counts.count_with_limits(&stores, code, Duration::MAX, 1),
+        Err((Interrupted::MaxMatches, vec![2]))
+    );
+    assert_eq!(
+        counts.count_with_limits(&stores, code, Duration::ZERO, u64::MAX),
+        Err((Interrupted::TimeOut, vec![2]))
+    );
+    assert_eq!(
+        counts.count_with_limits(&stores, code, Duration::MAX, 2),
+        Ok(vec![2])
+    );
+    assert_eq!(counts.cached_count(), 1);
+}
+
+fn differential_versions<const N: usize>(
+    texts: [&str; N],
+) -> (SimpleStores<crate::types::TStore>, [NodeIdentifier; N]) {
+    use crate::legion_with_refs;
+    let mut stores = hyper_ast::store::SimpleStores::default();
+    let mut md_cache = Default::default();
+    let mut java_tree_gen = legion_with_refs::JavaTreeGen::new(&mut stores, &mut md_cache);
+    let roots = texts.map(|text| {
+        let tree = match legion_with_refs::tree_sitter_parse(text.as_bytes()) {
+            Ok(t) => t,
+            Err(t) => t,
+        };
+        let full_node = java_tree_gen.generate_file(b"", text.as_bytes(), tree.walk());
+        full_node.local.compressed_node
+    });
+    (stores, roots)
+}
+
+/// Counts the (added, removed, moved) changes
+fn differential_kinds<Idx>(
+    changes: &[hyper_ast_tsquery::differential::MatchChange<Idx>],
+) -> (usize, usize, usize) {
+    use hyper_ast_tsquery::differential::MatchChange;
+    changes.iter().fold((0, 0, 0), |(a, r, m), x| match x {
+        MatchChange::Added(_) => (a + 1, r, m),
+        MatchChange::Removed(_) => (a, r + 1, m),
+        MatchChange::Moved { .. } => (a, r, m + 1),
+    })
+}
+
+/// Adding a method before another one only adds a match, the other one is just shifted
+#[test]
+fn differential_matches() {
+    use hyper_ast_tsquery::differential::match_changes;
+    let query =
+        hyper_ast_tsquery::Query::new("(method_declaration) @method", tree_sitter_java::language())
+            .unwrap();
+    let (stores, [src, dst]) = differential_versions([
+        "class A { void f() {} }",
+        "class A { void g() {} void f() {} }",
+    ]);
+    let changes = match_changes(&query, &stores, src, dst);
+    assert_eq!(differential_kinds(&changes), (1, 0, 0));
+    assert!(match_changes(&query, &stores, dst, dst).is_empty());
+}
+
+/// Deleting a method removes its match
+#[test]
+fn differential_removed_matches() {
+    use hyper_ast_tsquery::differential::{located_matches, match_changes, MatchChange};
+    let query =
+        hyper_ast_tsquery::Query::new("(method_declaration) @method", tree_sitter_java::language())
+            .unwrap();
+    let (stores, [src, dst]) = differential_versions([
+        "class A { void f() {} void g(int a) { a++; } }",
+        "class A { void f() {} }",
+    ]);
+    let changes = match_changes(&query, &stores, src, dst);
+    assert_eq!(differential_kinds(&changes), (0, 1, 0));
+    let MatchChange::Removed(removed) = &changes[0] else {
+        unreachable!()
+    };
+    assert_eq!(removed, &located_matches(&query, &stores, src)[1]);
+}
+
+/// Swapping two methods moves one of them
+#[test]
+fn differential_moved_matches() {
+    use hyper_ast_tsquery::differential::match_changes;
+    let query =
+        hyper_ast_tsquery::Query::new("(method_declaration) @method", tree_sitter_java::language())
+            .unwrap();
+    let (stores, [src, dst]) = differential_versions([
+        "class A { void f() { f(); } void g(int a) { a++; } }",
+        "class A { void g(int a) { a++; } void f() { f(); } }",
+    ]);
+    let changes = match_changes(&query, &stores, src, dst);
+    assert_eq!(differential_kinds(&changes), (0, 0, 1));
+}
+
+/// Matches sharing their topmost captured node are all paired
+#[test]
+fn differential_matches_on_same_node() {
+    use hyper_ast_tsquery::differential::match_changes;
+    let query = hyper_ast_tsquery::Query::new(
+        "(class_declaration body: (class_body (method_declaration) @m)) @c",
+        tree_sitter_java::language(),
+    )
+    .unwrap();
+    let (stores, [src, dst]) = differential_versions([
+        "class A { void f() {} void g() {} }",
+        "class A { void f() {} void g() {} void h() {} }",
+    ]);
+    let changes = match_changes(&query, &stores, src, dst);
+    assert_eq!(differential_kinds(&changes), (1, 0, 0));
+}
+
+/// Each version is compared with the previous one
+#[test]
+fn differential_commits() {
+    use hyper_ast_tsquery::differential::differential;
+    let query =
+        hyper_ast_tsquery::Query::new("(method_declaration) @method", tree_sitter_java::language())
+            .unwrap();
+    let (stores, roots) = differential_versions([
+        "class A { void f() {} }",
+        "class A { void f() {} void g(int a) { a++; } }",
+        "class A { void f() {} void g(int a) { a++; } }",
+        "class A { void g(int a) { a++; } }",
+    ]);
+    let kinds: Vec<_> = differential(&query, &stores, &roots)
+        .map(|changes| differential_kinds(&changes))
+        .collect();
+    assert_eq!(kinds, vec![(1, 0, 0), (0, 0, 0), (0, 1, 0)]);
+}
+
+/// Registered predicates filter matches using the HyperAST
+#[test]
+fn custom_predicates() {
+    use hyper_ast::position::TreePath;
+    use hyper_ast::types::{NodeStore, WithStats};
+    use hyper_ast_tsquery::{CustomPredicates, PredicateArg};
+    let text = "class A { void f() {} void g() { int a = 0; a++; } }".as_bytes();
+    let (query, stores, code) = prep_stepped(r#"((method_declaration) @m (#is-public? @m))"#, text);
+    type Node<'a> = hyper_ast_tsquery::hyperast::Node<'a, SimpleStores<crate::types::TStore>>;
+    let mut predicates = CustomPredicates::<Node>::default();
+    predicates.register("size-gt?", |args| {
+        let [PredicateArg::Capture(nodes), PredicateArg::String(min)] = args else {
+            return false;
+        };
+        let min: usize = min.parse().unwrap();
+        nodes.iter().all(|n| {
+            let id = n.pos.node().unwrap();
+            n.stores.node_store().resolve(id).size() > min
+        })
+    });
+    assert_eq!(predicates.missing(&query), vec!["is-public?"]);
+    let count = |query: &str| {
+        let query = hyper_ast_tsquery::Query::new(query, tree_sitter_java::language()).unwrap();
+        assert!(predicates.missing(&query).is_empty());
+        let cursor = hyper_ast_tsquery::hyperast::TreeCursor::new(
+            &stores,
+            hyper_ast::position::StructuralPosition::new(code),
+        );
+        query.matches_with(cursor, &predicates).count()
+    };
+    assert_eq!(count(r#"((method_declaration) @m (#size-gt? @m "0"))"#), 2);
+    assert_eq!(
+        count(r#"((method_declaration) @m (#size-gt? @m "1000"))"#),
+        0
+    );
+}
+
+#[test]
+fn metadata_properties() {
+    use hyper_ast_tsquery::metadata::{self, Metadata};
+    let text = "class A { void f() {} void g(int a) { if (a > 0) { a++; } } }".as_bytes();
+    let (query, stores, code) = prep_stepped(r#"((method_declaration) @m (#set! @m size))"#, text);
+    let predicates = metadata::predicates();
+    let matches = |query: &hyper_ast_tsquery::Query| {
+        let cursor = hyper_ast_tsquery::hyperast::TreeCursor::new(
+            &stores,
+            hyper_ast::position::StructuralPosition::new(code),
+        );
+        query.matches_with(cursor, &predicates).collect::<Vec<_>>()
+    };
+    assert!(metadata::requests_metadata(&query));
+    let ms = matches(&query);
+    assert_eq!(ms.len(), 2);
+    for m in &ms {
+        let values = metadata::requested_metadata(&query, m);
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].1, Metadata::Size);
+        assert!(values[0].2.unwrap() > 1);
+    }
+    let count = |query: &str| {
+        let query = hyper_ast_tsquery::Query::new(query, tree_sitter_java::language()).unwrap();
+        matches(&query).len()
+    };
+    assert_eq!(count(r#"((method_declaration) @m (#is? @m size ">0"))"#), 2);
+    assert_eq!(
+        count(r#"((method_declaration) @m (#is? @m size ">100000"))"#),
+        0
+    );
+    assert_eq!(
+        count(r#"((method_declaration) @m (#is? @m cyclomatic ">1"))"#),
+        1
+    );
+    assert_eq!(
+        count(r#"((method_declaration) @m (#is-not? @m cyclomatic ">1"))"#),
+        1
+    );
+    assert_eq!(count(r#"((block) @b (#is? @b cyclomatic))"#), 0);
+    let query = hyper_ast_tsquery::Query::new(
+        r#"((method_declaration) @m (#is? @m size ">0"))"#,
+        tree_sitter_java::language(),
+    )
+    .unwrap();
+    assert!(!metadata::requests_metadata(&query));
+}
+
 #[test]
 fn compare_prepro() {
     log::set_logger(&LOGGER)
@@ -0,0 +1,64 @@
+use hyper_ast::types::HyperAST;
+
+use crate::types::{PythonEnabledTypeStore, TStore, Type};
+
+type PythonTreeGen<'store, 'cache> = crate::legion::PythonTreeGen<'store, 'cache, TStore>;
+type SimpleStores = hyper_ast::store::SimpleStores<TStore>;
+
+#[test]
+pub(crate) fn python_simple_test() {
+    let text = {
+        let source_code1 = r#"from typing import List
+
+
+@dataclass
+class A(B):
+    """doc"""
+
+    def f(self, x: int = 0) -> List[int]:
+        if x > 0:
+            return [await g(y) for y in range(x)]
+        else:
+            h = lambda z: (yield z)
+        return f"{x!r:>10}"
+"#;
+        source_code1.as_bytes()
+    };
+    let tree = match PythonTreeGen::tree_sitter_parse(text) {
+        Ok(t) => t,
+        Err(t) => panic!("{}", t.root_node().to_sexp()),
+    };
+    println!("{}", tree.root_node().to_sexp());
+    let mut stores = SimpleStores::default();
+    let mut md_cache = Default::default();
+    let mut tree_gen = PythonTreeGen::new(&mut stores, &mut md_cache);
+    let x = tree_gen.generate_file(b"", text, tree.walk()).local;
+    println!(
+        "{}",
+        hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
+    );
+    assert_eq!(
+        Type::Module,
+        TStore::resolve(stores.resolve_type(&x.compressed_node))
+    );
+    assert_eq!(
+        std::str::from_utf8(text).unwrap(),
+        hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node).to_string()
+    );
+}
+
+#[test]
+fn keywords_sharing_kind_with_named_nodes() {
+    use hyper_ast::types::HyperType;
+    assert_eq!(Some(Type::Lambda), Type::from_kind("lambda", true));
+    assert_eq!(Some(Type::TS1), Type::from_kind("lambda", false));
+    assert_eq!(Some(Type::Identifier), Type::from_kind("identifier", true));
+    assert!(HyperType::is_named(&Type::Yield));
+    assert!(!HyperType::is_named(&Type::TS2));
+    assert!(HyperType::is_supertype(&Type::PrimaryExpression));
+    assert!(HyperType::is_hidden(&Type::_SimpleStatements));
+    for t in 0..Type::Spaces as u16 {
+        let t = Type::from_u16(t);
+        assert_eq!(Some(t), Type::from_kind(t.to_str(), t.is_named()));
+    }
+}
@@ -0,0 +1,1805 @@
+use std::fmt::Display;
+
+use hyper_ast::types::{
+    AnyType, HyperType, LangRef, NodeId, TypeStore, TypeTrait, TypeU16, TypedNodeId,
+};
+
+#[cfg(feature = "legion")]
+mod legion_impls {
+    use super::*;
+
+    use crate::TNode;
+
+    impl<'a> TNode<'a> {
+        pub fn obtain_type(&self) -> Type {
+            let t = self.0.kind_id();
+            from_symbol(t)
+        }
+    }
+
+    /// Types indexed by tree-sitter symbols,
+    /// kinds are matched by name to stay independent of the grammar's symbol ordering.
+    fn from_symbol(t: u16) -> Type {
+        static SYMBOLS: std::sync::OnceLock<Vec<Type>> = std::sync::OnceLock::new();
+        let symbols = SYMBOLS.get_or_init(|| {
+            let language = tree_sitter_python::language();
+            (0..language.node_kind_count() as u16)
+                .map(|t| {
+                    let kind = language.node_kind_for_id(t).unwrap_or_default();
+                    Type::from_kind(kind, language.node_kind_is_named(t)).unwrap_or_else(|| {
+                        log::debug!("unknown python kind {:?}", kind);
+                        Type::ERROR
+                    })
+                })
+                .collect()
+        });
+        symbols.get(t as usize).copied().unwrap_or(Type::ERROR)
+    }
+
+    impl TypeStore for TStore {
+        type Ty = TypeU16<Py>;
+    }
+    impl PythonEnabledTypeStore for TStore {
+        fn intern(t: Type) -> Self::Ty {
+            t.into()
+        }
+        fn resolve(t: Self::Ty) -> Type {
+            t.e()
+        }
+    }
+
+    use hyper_ast::types::{LangWrapper, RoleStore};
+
+    impl RoleStore for TStore {
+        type IdF = u16;
+
+        type Role = hyper_ast::types::Role;
+
+        fn resolve_field(_lang: LangWrapper<Self::Ty>, field_id: Self::IdF) -> Self::Role {
+            let s = tree_sitter_python::language()
+                .field_name_for_id(field_id)
+                .ok_or_else(|| format!("{}", field_id))
+                .unwrap();
+            hyper_ast::types::Role::try_from(s).expect(s)
+        }
+
+        fn intern_role(_lang: LangWrapper<Self::Ty>, role: Self::Role) -> Self::IdF {
+            let field_name = role.to_string();
+            tree_sitter_python::language()
+                .field_id_for_name(field_name)
+                .unwrap()
+                .into()
+        }
+    }
+}
+
+pub trait PythonEnabledTypeStore: TypeStore {
+    fn intern(t: Type) -> Self::Ty;
+    fn resolve(t: Self::Ty) -> Type;
+}
+
+impl Type {
+    pub fn resolve(t: u16) -> Self {
+        assert!(t < COUNT);
+        unsafe { std::mem::transmute(t) }
+    }
+
+    /// hidden rules start with an underscore, auxiliary ones end with eg. `_repeat1`
+    pub fn is_hidden(&self) -> bool {
+        let s = self.to_str();
+        let aux = s.rsplit_once('_').map_or(false, |(_, x)| {
+            let x = x.trim_end_matches(|c: char| c.is_ascii_digit());
+            x == "repeat" || x == "token"
+        });
+        self == &Type::End || s.starts_with('_') || aux
+    }
+
+    pub fn is_supertype(&self) -> bool {
+        match self {
+            Type::_CompoundStatement => true,
+            Type::_SimpleStatement => true,
+            Type::Expression => true,
+            Type::Parameter => true,
+            Type::Pattern => true,
+            Type::PrimaryExpression => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_named(&self) -> bool {
+        match self {
+            Type::Spaces | Type::Directory => false,
+            // keywords sharing their kind with a named node
+            Type::TS0 | Type::TS1 | Type::TS2 | Type::TS3 => false,
+            Type::ERROR => true,
+            t => id_for_node_kind(t.to_str(), true) != 0,
+        }
+    }
+
+    /// Some keywords have the same kind as a named node, eg. `lambda` or `yield`.
+    pub fn from_kind(kind: &str, named: bool) -> Option<Type> {
+        match (kind, named) {
+            ("type", false) => Some(Type::TS0),
+            ("lambda", false) => Some(Type::TS1),
+            ("yield", false) => Some(Type::TS2),
+            ("await", false) => Some(Type::TS3),
+            (kind, _) => Type::from_str(kind),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TIdN<IdN>(IdN);
+
+impl<IdN: Clone + Eq + NodeId> NodeId for TIdN<IdN> {
+    type IdN = IdN;
+
+    fn as_id(&self) -> &Self::IdN {
+        &self.0
+    }
+
+    unsafe fn from_id(id: Self::IdN) -> Self {
+        Self(id)
+    }
+
+    unsafe fn from_ref_id(_id: &Self::IdN) -> &Self {
+        todo!()
+    }
+}
+
+#[cfg(feature = "impl")]
+fn id_for_node_kind(kind: &str, named: bool) -> u16 {
+    tree_sitter_python::language().id_for_node_kind(kind, named)
+}
+#[cfg(not(feature = "impl"))]
+fn id_for_node_kind(kind: &str, named: bool) -> u16 {
+    unimplemented!("need treesitter grammar")
+}
+
+impl<IdN: Clone + Eq + NodeId> TypedNodeId for TIdN<IdN> {
+    type Ty = Type;
+}
+
+pub struct TStore;
+
+impl Default for TStore {
+    fn default() -> Self {
+        Self
+    }
+}
+
+type TypeInternalSize = u16;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct T(TypeInternalSize);
+
+#[derive(Debug)]
+pub struct Lang;
+pub type Py = Lang;
+
+impl LangRef<AnyType> for Py {
+    fn make(&self, _t: u16) -> &'static AnyType {
+        panic!()
+        // &From::<&'static dyn HyperType>::from(&S_T_L[t as usize])
+    }
+    fn to_u16(&self, t: AnyType) -> u16 {
+        // t as u16
+        let t = t.as_any().downcast_ref::<Type>().unwrap();
+        *t as u16
+    }
+
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Py>()
+    }
+
+    fn ts_symbol(&self, t: AnyType) -> u16 {
+        id_for_node_kind(t.as_static_str(), t.is_named())
+    }
+}
+
+impl LangRef<Type> for Py {
+    fn make(&self, t: u16) -> &'static Type {
+        &S_T_L[t as usize]
+    }
+    fn to_u16(&self, t: Type) -> u16 {
+        t as u16
+    }
+
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Py>()
+    }
+
+    fn ts_symbol(&self, t: Type) -> u16 {
+        id_for_node_kind(t.as_static_str(), t.is_named())
+    }
+}
+
+impl LangRef<TType> for Lang {
+    fn make(&self, t: u16) -> &'static TType {
+        // TODO could make one safe, but not priority
+        unsafe { std::mem::transmute(&S_T_L[t as usize]) }
+    }
+    fn to_u16(&self, t: TType) -> u16 {
+        t.e() as u16
+    }
+
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Lang>()
+    }
+
+    fn ts_symbol(&self, t: TType) -> u16 {
+        id_for_node_kind(t.as_static_str(), t.is_named())
+    }
+}
+
+impl hyper_ast::types::Lang<Type> for Py {
+    fn make(t: u16) -> &'static Type {
+        Lang.make(t)
+    }
+    fn to_u16(t: Type) -> u16 {
+        Lang.to_u16(t)
+    }
+}
+
+impl HyperType for Type {
+    fn generic_eq(&self, other: &dyn HyperType) -> bool
+    where
+        Self: 'static + PartialEq + Sized,
+    {
+        // Do a type-safe casting. If the types are different,
+        // return false, otherwise test the values for equality.
+        other
+            .as_any()
+            .downcast_ref::<Self>()
+            .map_or(false, |a| self == a)
+    }
+
+    fn is_directory(&self) -> bool {
+        self == &Type::Directory
+    }
+
+    fn is_file(&self) -> bool {
+        self == &Type::Module
+    }
+
+    fn is_spaces(&self) -> bool {
+        self == &Type::Spaces
+    }
+
+    fn is_syntax(&self) -> bool {
+        self == &Type::LParen // "(",
+        || self == &Type::RParen // ")",
+        || self == &Type::LBracket // "[",
+        || self == &Type::RBracket // "]",
+        || self == &Type::LBrace // "{",
+        || self == &Type::RBrace // "}",
+        || self == &Type::Comma // ",",
+        || self == &Type::Colon // ":",
+        || self == &Type::SemiColon // ";",
+        || self == &Type::Dot // ".",
+        || self == &Type::DashGt // "->",
+        || self == &Type::Eq // "=",
+        || self == &Type::At // "@",
+    }
+
+    fn as_shared(&self) -> hyper_ast::types::Shared {
+        use hyper_ast::types::Shared;
+
+        match self {
+            Type::ClassDefinition => Shared::TypeDeclaration,
+            Type::Comment => Shared::Comment,
+            Type::Identifier => Shared::Identifier,
+            _ => Shared::Other,
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_static(&self) -> &'static dyn HyperType {
+        let t = <Py as hyper_ast::types::Lang<Type>>::to_u16(*self);
+        let t = <Py as hyper_ast::types::Lang<Type>>::make(t);
+        t
+    }
+
+    fn as_static_str(&self) -> &'static str {
+        self.to_str()
+    }
+
+    fn is_hidden(&self) -> bool {
+        self.is_hidden()
+    }
+
+    fn is_supertype(&self) -> bool {
+        self.is_supertype()
+    }
+
+    fn is_named(&self) -> bool {
+        self.is_named()
+    }
+
+    fn get_lang(&self) -> hyper_ast::types::LangWrapper<Self>
+    where
+        Self: Sized,
+    {
+        From::<&'static (dyn LangRef<Self>)>::from(&Lang)
+    }
+    fn lang_ref(&self) -> hyper_ast::types::LangWrapper<AnyType> {
+        hyper_ast::types::LangWrapper::from(&Lang as &(dyn LangRef<AnyType> + 'static))
+    }
+}
+impl TypeTrait for Type {
+    type Lang = Py;
+
+    fn is_fork(&self) -> bool {
+        todo!()
+    }
+
+    fn is_literal(&self) -> bool {
+        todo!()
+    }
+
+    fn is_primitive(&self) -> bool {
+        todo!()
+    }
+
+    fn is_type_declaration(&self) -> bool {
+        todo!()
+    }
+
+    fn is_identifier(&self) -> bool {
+        todo!()
+    }
+
+    fn is_instance_ref(&self) -> bool {
+        todo!()
+    }
+
+    fn is_type_body(&self) -> bool {
+        todo!()
+    }
+
+    fn is_value_member(&self) -> bool {
+        todo!()
+    }
+
+    fn is_executable_member(&self) -> bool {
+        todo!()
+    }
+
+    fn is_statement(&self) -> bool {
+        todo!()
+    }
+
+    fn is_declarative_statement(&self) -> bool {
+        todo!()
+    }
+
+    fn is_structural_statement(&self) -> bool {
+        todo!()
+    }
+
+    fn is_block_related(&self) -> bool {
+        todo!()
+    }
+
+    fn is_simple_statement(&self) -> bool {
+        todo!()
+    }
+
+    fn is_local_declare(&self) -> bool {
+        todo!()
+    }
+
+    fn is_parameter(&self) -> bool {
+        todo!()
+    }
+
+    fn is_parameter_list(&self) -> bool {
+        todo!()
+    }
+
+    fn is_argument_list(&self) -> bool {
+        todo!()
+    }
+
+    fn is_expression(&self) -> bool {
+        todo!()
+    }
+
+    fn is_comment(&self) -> bool {
+        todo!()
+    }
+}
+
+// 264 + directory  + spaces
+const COUNT: u16 = 266;
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.to_str())
+    }
+}
+
+pub type TType = hyper_ast::types::TypeU16<Lang>;
+
+impl hyper_ast::types::LLang<TType> for Py {
+    type I = u16;
+
+    type E = Type;
+
+    const TE: &[Self::E] = S_T_L;
+
+    fn as_lang_wrapper() -> hyper_ast::types::LangWrapper<TType> {
+        From::<&'static (dyn LangRef<_>)>::from(&Lang)
+    }
+}
+
+impl From<u16> for Type {
+    fn from(value: u16) -> Self {
+        debug_assert_eq!(Self::from_u16(value), S_T_L[value as usize]);
+        S_T_L[value as usize]
+    }
+}
+impl Into<TypeU16<Py>> for Type {
+    fn into(self) -> TypeU16<Py> {
+        TypeU16::new(self)
+    }
+}
+
+impl Into<u16> for Type {
+    fn into(self) -> u16 {
+        self as u16
+    }
+}
+
+#[repr(u16)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Type {
+    End,
+    Identifier,
+    Import,
+    Dot,
+    From,
+    Future,
+    LParen,
+    RParen,
+    Comma,
+    As,
+    Star,
+    Print,
+    GtGt,
+    Assert,
+    ColonEq,
+    Return,
+    Del,
+    Raise,
+    Pass,
+    Break,
+    Continue,
+    If,
+    Colon,
+    Elif,
+    Else,
+    Match,
+    Case,
+    Async,
+    For,
+    In,
+    While,
+    Try,
+    Except,
+    ExceptStar,
+    Finally,
+    With,
+    Def,
+    DashGt,
+    StarStar,
+    Global,
+    Nonlocal,
+    Exec,
+    TS0,
+    Eq,
+    Class,
+    LBracket,
+    RBracket,
+    At,
+    Dash,
+    Underscore,
+    Pipe,
+    LBrace,
+    RBrace,
+    Plus,
+    Not,
+    And,
+    Or,
+    Slash,
+    Percent,
+    SlashSlash,
+    Amp,
+    Caret,
+    LtLt,
+    Tilde,
+    Is,
+    LT,
+    LTEq,
+    EqEq,
+    BangEq,
+    GTEq,
+    GT,
+    LTGT,
+    NotIn,
+    IsNot,
+    TS1,
+    PlusEq,
+    DashEq,
+    StarEq,
+    SlashEq,
+    AtEq,
+    SlashSlashEq,
+    PercentEq,
+    StarStarEq,
+    GtGtEq,
+    LtLtEq,
+    AmpEq,
+    CaretEq,
+    PipeEq,
+    TS2,
+    SemiColon,
+    TS3,
+    EscapeSequence,
+    Integer,
+    Float,
+    True,
+    False,
+    None,
+    Comment,
+    LineContinuation,
+    StringStart,
+    StringContent,
+    EscapeInterpolation,
+    StringEnd,
+    Module,
+    _Newline,
+    _Indent,
+    _Dedent,
+    _Statement,
+    _SimpleStatements,
+    _SimpleStatement,
+    _CompoundStatement,
+    _ImportList,
+    _Expressions,
+    _LeftHandSide,
+    _RightHandSide,
+    _CollectionElements,
+    _ComprehensionClauses,
+    _Parameters,
+    _Patterns,
+    _FExpression,
+    ImportStatement,
+    ImportPrefix,
+    RelativeImport,
+    FutureImportStatement,
+    ImportFromStatement,
+    AliasedImport,
+    WildcardImport,
+    PrintStatement,
+    Chevron,
+    AssertStatement,
+    ExpressionStatement,
+    NamedExpression,
+    ReturnStatement,
+    DeleteStatement,
+    RaiseStatement,
+    PassStatement,
+    BreakStatement,
+    ContinueStatement,
+    IfStatement,
+    ElifClause,
+    ElseClause,
+    MatchStatement,
+    Block,
+    CaseClause,
+    ForStatement,
+    WhileStatement,
+    TryStatement,
+    ExceptClause,
+    ExceptGroupClause,
+    FinallyClause,
+    WithStatement,
+    WithClause,
+    WithItem,
+    FunctionDefinition,
+    Parameters,
+    LambdaParameters,
+    ListSplat,
+    DictionarySplat,
+    GlobalStatement,
+    NonlocalStatement,
+    ExecStatement,
+    TypeAliasStatement,
+    ClassDefinition,
+    TypeParameter,
+    ParenthesizedListSplat,
+    ArgumentList,
+    DecoratedDefinition,
+    Decorator,
+    ExpressionList,
+    DottedName,
+    CasePattern,
+    SplatPattern,
+    AsPattern,
+    UnionPattern,
+    DictPattern,
+    KeywordPattern,
+    ListPattern,
+    TuplePattern,
+    ClassPattern,
+    ComplexPattern,
+    Parameter,
+    DefaultParameter,
+    TypedDefaultParameter,
+    ListSplatPattern,
+    DictionarySplatPattern,
+    TypedParameter,
+    Type,
+    SplatType,
+    GenericType,
+    UnionType,
+    ConstrainedType,
+    MemberType,
+    KeywordSeparator,
+    PositionalSeparator,
+    Pattern,
+    Expression,
+    PrimaryExpression,
+    NotOperator,
+    BooleanOperator,
+    BinaryOperator,
+    UnaryOperator,
+    ComparisonOperator,
+    Lambda,
+    Assignment,
+    AugmentedAssignment,
+    PatternList,
+    Yield,
+    Attribute,
+    Subscript,
+    Slice,
+    Ellipsis,
+    Call,
+    KeywordArgument,
+    List,
+    Set,
+    Tuple,
+    Dictionary,
+    Pair,
+    ListComprehension,
+    DictionaryComprehension,
+    SetComprehension,
+    GeneratorExpression,
+    ForInClause,
+    IfClause,
+    ConditionalExpression,
+    ConcatenatedString,
+    String,
+    Interpolation,
+    FormatSpecifier,
+    TypeConversion,
+    Await,
+    ParenthesizedExpression,
+    ModuleRepeat1,
+    _SimpleStatementsRepeat1,
+    ImportPrefixRepeat1,
+    PrintStatementRepeat1,
+    AssertStatementRepeat1,
+    IfStatementRepeat1,
+    MatchStatementRepeat1,
+    _MatchBlockRepeat1,
+    CaseClauseRepeat1,
+    TryStatementRepeat1,
+    TryStatementRepeat2,
+    WithClauseRepeat1,
+    GlobalStatementRepeat1,
+    TypeParameterRepeat1,
+    ArgumentListRepeat1,
+    DecoratedDefinitionRepeat1,
+    DottedNameRepeat1,
+    UnionPatternRepeat1,
+    DictPatternRepeat1,
+    _ParametersRepeat1,
+    _PatternsRepeat1,
+    ComparisonOperatorRepeat1,
+    SubscriptRepeat1,
+    DictionaryRepeat1,
+    _ComprehensionClausesRepeat1,
+    _CollectionElementsRepeat1,
+    ForInClauseRepeat1,
+    ConcatenatedStringRepeat1,
+    StringRepeat1,
+    StringContentRepeat1,
+    FormatSpecifierRepeat1,
+    Spaces,
+    Directory,
+    ERROR,
+}
+impl Type {
+    pub fn from_u16(t: u16) -> Type {
+        match t {
+            0u16 => Type::End,
+            1u16 => Type::Identifier,
+            2u16 => Type::Import,
+            3u16 => Type::Dot,
+            4u16 => Type::From,
+            5u16 => Type::Future,
+            6u16 => Type::LParen,
+            7u16 => Type::RParen,
+            8u16 => Type::Comma,
+            9u16 => Type::As,
+            10u16 => Type::Star,
+            11u16 => Type::Print,
+            12u16 => Type::GtGt,
+            13u16 => Type::Assert,
+            14u16 => Type::ColonEq,
+            15u16 => Type::Return,
+            16u16 => Type::Del,
+            17u16 => Type::Raise,
+            18u16 => Type::Pass,
+            19u16 => Type::Break,
+            20u16 => Type::Continue,
+            21u16 => Type::If,
+            22u16 => Type::Colon,
+            23u16 => Type::Elif,
+            24u16 => Type::Else,
+            25u16 => Type::Match,
+            26u16 => Type::Case,
+            27u16 => Type::Async,
+            28u16 => Type::For,
+            29u16 => Type::In,
+            30u16 => Type::While,
+            31u16 => Type::Try,
+            32u16 => Type::Except,
+            33u16 => Type::ExceptStar,
+            34u16 => Type::Finally,
+            35u16 => Type::With,
+            36u16 => Type::Def,
+            37u16 => Type::DashGt,
+            38u16 => Type::StarStar,
+            39u16 => Type::Global,
+            40u16 => Type::Nonlocal,
+            41u16 => Type::Exec,
+            42u16 => Type::TS0,
+            43u16 => Type::Eq,
+            44u16 => Type::Class,
+            45u16 => Type::LBracket,
+            46u16 => Type::RBracket,
+            47u16 => Type::At,
+            48u16 => Type::Dash,
+            49u16 => Type::Underscore,
+            50u16 => Type::Pipe,
+            51u16 => Type::LBrace,
+            52u16 => Type::RBrace,
+            53u16 => Type::Plus,
+            54u16 => Type::Not,
+            55u16 => Type::And,
+            56u16 => Type::Or,
+            57u16 => Type::Slash,
+            58u16 => Type::Percent,
+            59u16 => Type::SlashSlash,
+            60u16 => Type::Amp,
+            61u16 => Type::Caret,
+            62u16 => Type::LtLt,
+            63u16 => Type::Tilde,
+            64u16 => Type::Is,
+            65u16 => Type::LT,
+            66u16 => Type::LTEq,
+            67u16 => Type::EqEq,
+            68u16 => Type::BangEq,
+            69u16 => Type::GTEq,
+            70u16 => Type::GT,
+            71u16 => Type::LTGT,
+            72u16 => Type::NotIn,
+            73u16 => Type::IsNot,
+            74u16 => Type::TS1,
+            75u16 => Type::PlusEq,
+            76u16 => Type::DashEq,
+            77u16 => Type::StarEq,
+            78u16 => Type::SlashEq,
+            79u16 => Type::AtEq,
+            80u16 => Type::SlashSlashEq,
+            81u16 => Type::PercentEq,
+            82u16 => Type::StarStarEq,
+            83u16 => Type::GtGtEq,
+            84u16 => Type::LtLtEq,
+            85u16 => Type::AmpEq,
+            86u16 => Type::CaretEq,
+            87u16 => Type::PipeEq,
+            88u16 => Type::TS2,
+            89u16 => Type::SemiColon,
+            90u16 => Type::TS3,
+            91u16 => Type::EscapeSequence,
+            92u16 => Type::Integer,
+            93u16 => Type::Float,
+            94u16 => Type::True,
+            95u16 => Type::False,
+            96u16 => Type::None,
+            97u16 => Type::Comment,
+            98u16 => Type::LineContinuation,
+            99u16 => Type::StringStart,
+            100u16 => Type::StringContent,
+            101u16 => Type::EscapeInterpolation,
+            102u16 => Type::StringEnd,
+            103u16 => Type::Module,
+            104u16 => Type::_Newline,
+            105u16 => Type::_Indent,
+            106u16 => Type::_Dedent,
+            107u16 => Type::_Statement,
+            108u16 => Type::_SimpleStatements,
+            109u16 => Type::_SimpleStatement,
+            110u16 => Type::_CompoundStatement,
+            111u16 => Type::_ImportList,
+            112u16 => Type::_Expressions,
+            113u16 => Type::_LeftHandSide,
+            114u16 => Type::_RightHandSide,
+            115u16 => Type::_CollectionElements,
+            116u16 => Type::_ComprehensionClauses,
+            117u16 => Type::_Parameters,
+            118u16 => Type::_Patterns,
+            119u16 => Type::_FExpression,
+            120u16 => Type::ImportStatement,
+            121u16 => Type::ImportPrefix,
+            122u16 => Type::RelativeImport,
+            123u16 => Type::FutureImportStatement,
+            124u16 => Type::ImportFromStatement,
+            125u16 => Type::AliasedImport,
+            126u16 => Type::WildcardImport,
+            127u16 => Type::PrintStatement,
+            128u16 => Type::Chevron,
+            129u16 => Type::AssertStatement,
+            130u16 => Type::ExpressionStatement,
+            131u16 => Type::NamedExpression,
+            132u16 => Type::ReturnStatement,
+            133u16 => Type::DeleteStatement,
+            134u16 => Type::RaiseStatement,
+            135u16 => Type::PassStatement,
+            136u16 => Type::BreakStatement,
+            137u16 => Type::ContinueStatement,
+            138u16 => Type::IfStatement,
+            139u16 => Type::ElifClause,
+            140u16 => Type::ElseClause,
+            141u16 => Type::MatchStatement,
+            142u16 => Type::Block,
+            143u16 => Type::CaseClause,
+            144u16 => Type::ForStatement,
+            145u16 => Type::WhileStatement,
+            146u16 => Type::TryStatement,
+            147u16 => Type::ExceptClause,
+            148u16 => Type::ExceptGroupClause,
+            149u16 => Type::FinallyClause,
+            150u16 => Type::WithStatement,
+            151u16 => Type::WithClause,
+            152u16 => Type::WithItem,
+            153u16 => Type::FunctionDefinition,
+            154u16 => Type::Parameters,
+            155u16 => Type::LambdaParameters,
+            156u16 => Type::ListSplat,
+            157u16 => Type::DictionarySplat,
+            158u16 => Type::GlobalStatement,
+            159u16 => Type::NonlocalStatement,
+            160u16 => Type::ExecStatement,
+            161u16 => Type::TypeAliasStatement,
+            162u16 => Type::ClassDefinition,
+            163u16 => Type::TypeParameter,
+            164u16 => Type::ParenthesizedListSplat,
+            165u16 => Type::ArgumentList,
+            166u16 => Type::DecoratedDefinition,
+            167u16 => Type::Decorator,
+            168u16 => Type::ExpressionList,
+            169u16 => Type::DottedName,
+            170u16 => Type::CasePattern,
+            171u16 => Type::SplatPattern,
+            172u16 => Type::AsPattern,
+            173u16 => Type::UnionPattern,
+            174u16 => Type::DictPattern,
+            175u16 => Type::KeywordPattern,
+            176u16 => Type::ListPattern,
+            177u16 => Type::TuplePattern,
+            178u16 => Type::ClassPattern,
+            179u16 => Type::ComplexPattern,
+            180u16 => Type::Parameter,
+            181u16 => Type::DefaultParameter,
+            182u16 => Type::TypedDefaultParameter,
+            183u16 => Type::ListSplatPattern,
+            184u16 => Type::DictionarySplatPattern,
+            185u16 => Type::TypedParameter,
+            186u16 => Type::Type,
+            187u16 => Type::SplatType,
+            188u16 => Type::GenericType,
+            189u16 => Type::UnionType,
+            190u16 => Type::ConstrainedType,
+            191u16 => Type::MemberType,
+            192u16 => Type::KeywordSeparator,
+            193u16 => Type::PositionalSeparator,
+            194u16 => Type::Pattern,
+            195u16 => Type::Expression,
+            196u16 => Type::PrimaryExpression,
+            197u16 => Type::NotOperator,
+            198u16 => Type::BooleanOperator,
+            199u16 => Type::BinaryOperator,
+            200u16 => Type::UnaryOperator,
+            201u16 => Type::ComparisonOperator,
+            202u16 => Type::Lambda,
+            203u16 => Type::Assignment,
+            204u16 => Type::AugmentedAssignment,
+            205u16 => Type::PatternList,
+            206u16 => Type::Yield,
+            207u16 => Type::Attribute,
+            208u16 => Type::Subscript,
+            209u16 => Type::Slice,
+            210u16 => Type::Ellipsis,
+            211u16 => Type::Call,
+            212u16 => Type::KeywordArgument,
+            213u16 => Type::List,
+            214u16 => Type::Set,
+            215u16 => Type::Tuple,
+            216u16 => Type::Dictionary,
+            217u16 => Type::Pair,
+            218u16 => Type::ListComprehension,
+            219u16 => Type::DictionaryComprehension,
+            220u16 => Type::SetComprehension,
+            221u16 => Type::GeneratorExpression,
+            222u16 => Type::ForInClause,
+            223u16 => Type::IfClause,
+            224u16 => Type::ConditionalExpression,
+            225u16 => Type::ConcatenatedString,
+            226u16 => Type::String,
+            227u16 => Type::Interpolation,
+            228u16 => Type::FormatSpecifier,
+            229u16 => Type::TypeConversion,
+            230u16 => Type::Await,
+            231u16 => Type::ParenthesizedExpression,
+            232u16 => Type::ModuleRepeat1,
+            233u16 => Type::_SimpleStatementsRepeat1,
+            234u16 => Type::ImportPrefixRepeat1,
+            235u16 => Type::PrintStatementRepeat1,
+            236u16 => Type::AssertStatementRepeat1,
+            237u16 => Type::IfStatementRepeat1,
+            238u16 => Type::MatchStatementRepeat1,
+            239u16 => Type::_MatchBlockRepeat1,
+            240u16 => Type::CaseClauseRepeat1,
+            241u16 => Type::TryStatementRepeat1,
+            242u16 => Type::TryStatementRepeat2,
+            243u16 => Type::WithClauseRepeat1,
+            244u16 => Type::GlobalStatementRepeat1,
+            245u16 => Type::TypeParameterRepeat1,
+            246u16 => Type::ArgumentListRepeat1,
+            247u16 => Type::DecoratedDefinitionRepeat1,
+            248u16 => Type::DottedNameRepeat1,
+            249u16 => Type::UnionPatternRepeat1,
+            250u16 => Type::DictPatternRepeat1,
+            251u16 => Type::_ParametersRepeat1,
+            252u16 => Type::_PatternsRepeat1,
+            253u16 => Type::ComparisonOperatorRepeat1,
+            254u16 => Type::SubscriptRepeat1,
+            255u16 => Type::DictionaryRepeat1,
+            256u16 => Type::_ComprehensionClausesRepeat1,
+            257u16 => Type::_CollectionElementsRepeat1,
+            258u16 => Type::ForInClauseRepeat1,
+            259u16 => Type::ConcatenatedStringRepeat1,
+            260u16 => Type::StringRepeat1,
+            261u16 => Type::StringContentRepeat1,
+            262u16 => Type::FormatSpecifierRepeat1,
+            263u16 => Type::Spaces,
+            264u16 => Type::Directory,
+            265u16 => Type::ERROR,
+            x => panic!("{}", x),
+        }
+    }
+    pub fn from_str(t: &str) -> Option<Type> {
+        Some(match t {
+            "end" => Type::End,
+            "identifier" => Type::Identifier,
+            "import" => Type::Import,
+            "." => Type::Dot,
+            "from" => Type::From,
+            "__future__" => Type::Future,
+            "(" => Type::LParen,
+            ")" => Type::RParen,
+            "," => Type::Comma,
+            "as" => Type::As,
+            "*" => Type::Star,
+            "print" => Type::Print,
+            ">>" => Type::GtGt,
+            "assert" => Type::Assert,
+            ":=" => Type::ColonEq,
+            "return" => Type::Return,
+            "del" => Type::Del,
+            "raise" => Type::Raise,
+            "pass" => Type::Pass,
+            "break" => Type::Break,
+            "continue" => Type::Continue,
+            "if" => Type::If,
+            ":" => Type::Colon,
+            "elif" => Type::Elif,
+            "else" => Type::Else,
+            "match" => Type::Match,
+            "case" => Type::Case,
+            "async" => Type::Async,
+            "for" => Type::For,
+            "in" => Type::In,
+            "while" => Type::While,
+            "try" => Type::Try,
+            "except" => Type::Except,
+            "except*" => Type::ExceptStar,
+            "finally" => Type::Finally,
+            "with" => Type::With,
+            "def" => Type::Def,
+            "->" => Type::DashGt,
+            "**" => Type::StarStar,
+            "global" => Type::Global,
+            "nonlocal" => Type::Nonlocal,
+            "exec" => Type::Exec,
+            "=" => Type::Eq,
+            "class" => Type::Class,
+            "[" => Type::LBracket,
+            "]" => Type::RBracket,
+            "@" => Type::At,
+            "-" => Type::Dash,
+            "_" => Type::Underscore,
+            "|" => Type::Pipe,
+            "{" => Type::LBrace,
+            "}" => Type::RBrace,
+            "+" => Type::Plus,
+            "not" => Type::Not,
+            "and" => Type::And,
+            "or" => Type::Or,
+            "/" => Type::Slash,
+            "%" => Type::Percent,
+            "//" => Type::SlashSlash,
+            "&" => Type::Amp,
+            "^" => Type::Caret,
+            "<<" => Type::LtLt,
+            "~" => Type::Tilde,
+            "is" => Type::Is,
+            "<" => Type::LT,
+            "<=" => Type::LTEq,
+            "==" => Type::EqEq,
+            "!=" => Type::BangEq,
+            ">=" => Type::GTEq,
+            ">" => Type::GT,
+            "<>" => Type::LTGT,
+            "not in" => Type::NotIn,
+            "is not" => Type::IsNot,
+            "+=" => Type::PlusEq,
+            "-=" => Type::DashEq,
+            "*=" => Type::StarEq,
+            "/=" => Type::SlashEq,
+            "@=" => Type::AtEq,
+            "//=" => Type::SlashSlashEq,
+            "%=" => Type::PercentEq,
+            "**=" => Type::StarStarEq,
+            ">>=" => Type::GtGtEq,
+            "<<=" => Type::LtLtEq,
+            "&=" => Type::AmpEq,
+            "^=" => Type::CaretEq,
+            "|=" => Type::PipeEq,
+            ";" => Type::SemiColon,
+            "escape_sequence" => Type::EscapeSequence,
+            "integer" => Type::Integer,
+            "float" => Type::Float,
+            "true" => Type::True,
+            "false" => Type::False,
+            "none" => Type::None,
+            "comment" => Type::Comment,
+            "line_continuation" => Type::LineContinuation,
+            "string_start" => Type::StringStart,
+            "string_content" => Type::StringContent,
+            "escape_interpolation" => Type::EscapeInterpolation,
+            "string_end" => Type::StringEnd,
+            "module" => Type::Module,
+            "_newline" => Type::_Newline,
+            "_indent" => Type::_Indent,
+            "_dedent" => Type::_Dedent,
+            "_statement" => Type::_Statement,
+            "_simple_statements" => Type::_SimpleStatements,
+            "_simple_statement" => Type::_SimpleStatement,
+            "_compound_statement" => Type::_CompoundStatement,
+            "_import_list" => Type::_ImportList,
+            "_expressions" => Type::_Expressions,
+            "_left_hand_side" => Type::_LeftHandSide,
+            "_right_hand_side" => Type::_RightHandSide,
+            "_collection_elements" => Type::_CollectionElements,
+            "_comprehension_clauses" => Type::_ComprehensionClauses,
+            "_parameters" => Type::_Parameters,
+            "_patterns" => Type::_Patterns,
+            "_f_expression" => Type::_FExpression,
+            "import_statement" => Type::ImportStatement,
+            "import_prefix" => Type::ImportPrefix,
+            "relative_import" => Type::RelativeImport,
+            "future_import_statement" => Type::FutureImportStatement,
+            "import_from_statement" => Type::ImportFromStatement,
+            "aliased_import" => Type::AliasedImport,
+            "wildcard_import" => Type::WildcardImport,
+            "print_statement" => Type::PrintStatement,
+            "chevron" => Type::Chevron,
+            "assert_statement" => Type::AssertStatement,
+            "expression_statement" => Type::ExpressionStatement,
+            "named_expression" => Type::NamedExpression,
+            "return_statement" => Type::ReturnStatement,
+            "delete_statement" => Type::DeleteStatement,
+            "raise_statement" => Type::RaiseStatement,
+            "pass_statement" => Type::PassStatement,
+            "break_statement" => Type::BreakStatement,
+            "continue_statement" => Type::ContinueStatement,
+            "if_statement" => Type::IfStatement,
+            "elif_clause" => Type::ElifClause,
+            "else_clause" => Type::ElseClause,
+            "match_statement" => Type::MatchStatement,
+            "block" => Type::Block,
+            "case_clause" => Type::CaseClause,
+            "for_statement" => Type::ForStatement,
+            "while_statement" => Type::WhileStatement,
+            "try_statement" => Type::TryStatement,
+            "except_clause" => Type::ExceptClause,
+            "except_group_clause" => Type::ExceptGroupClause,
+            "finally_clause" => Type::FinallyClause,
+            "with_statement" => Type::WithStatement,
+            "with_clause" => Type::WithClause,
+            "with_item" => Type::WithItem,
+            "function_definition" => Type::FunctionDefinition,
+            "parameters" => Type::Parameters,
+            "lambda_parameters" => Type::LambdaParameters,
+            "list_splat" => Type::ListSplat,
+            "dictionary_splat" => Type::DictionarySplat,
+            "global_statement" => Type::GlobalStatement,
+            "nonlocal_statement" => Type::NonlocalStatement,
+            "exec_statement" => Type::ExecStatement,
+            "type_alias_statement" => Type::TypeAliasStatement,
+            "class_definition" => Type::ClassDefinition,
+            "type_parameter" => Type::TypeParameter,
+            "parenthesized_list_splat" => Type::ParenthesizedListSplat,
+            "argument_list" => Type::ArgumentList,
+            "decorated_definition" => Type::DecoratedDefinition,
+            "decorator" => Type::Decorator,
+            "expression_list" => Type::ExpressionList,
+            "dotted_name" => Type::DottedName,
+            "case_pattern" => Type::CasePattern,
+            "splat_pattern" => Type::SplatPattern,
+            "as_pattern" => Type::AsPattern,
+            "union_pattern" => Type::UnionPattern,
+            "dict_pattern" => Type::DictPattern,
+            "keyword_pattern" => Type::KeywordPattern,
+            "list_pattern" => Type::ListPattern,
+            "tuple_pattern" => Type::TuplePattern,
+            "class_pattern" => Type::ClassPattern,
+            "complex_pattern" => Type::ComplexPattern,
+            "parameter" => Type::Parameter,
+            "default_parameter" => Type::DefaultParameter,
+            "typed_default_parameter" => Type::TypedDefaultParameter,
+            "list_splat_pattern" => Type::ListSplatPattern,
+            "dictionary_splat_pattern" => Type::DictionarySplatPattern,
+            "typed_parameter" => Type::TypedParameter,
+            "type" => Type::Type,
+            "splat_type" => Type::SplatType,
+            "generic_type" => Type::GenericType,
+            "union_type" => Type::UnionType,
+            "constrained_type" => Type::ConstrainedType,
+            "member_type" => Type::MemberType,
+            "keyword_separator" => Type::KeywordSeparator,
+            "positional_separator" => Type::PositionalSeparator,
+            "pattern" => Type::Pattern,
+            "expression" => Type::Expression,
+            "primary_expression" => Type::PrimaryExpression,
+            "not_operator" => Type::NotOperator,
+            "boolean_operator" => Type::BooleanOperator,
+            "binary_operator" => Type::BinaryOperator,
+            "unary_operator" => Type::UnaryOperator,
+            "comparison_operator" => Type::ComparisonOperator,
+            "lambda" => Type::Lambda,
+            "assignment" => Type::Assignment,
+            "augmented_assignment" => Type::AugmentedAssignment,
+            "pattern_list" => Type::PatternList,
+            "yield" => Type::Yield,
+            "attribute" => Type::Attribute,
+            "subscript" => Type::Subscript,
+            "slice" => Type::Slice,
+            "ellipsis" => Type::Ellipsis,
+            "call" => Type::Call,
+            "keyword_argument" => Type::KeywordArgument,
+            "list" => Type::List,
+            "set" => Type::Set,
+            "tuple" => Type::Tuple,
+            "dictionary" => Type::Dictionary,
+            "pair" => Type::Pair,
+            "list_comprehension" => Type::ListComprehension,
+            "dictionary_comprehension" => Type::DictionaryComprehension,
+            "set_comprehension" => Type::SetComprehension,
+            "generator_expression" => Type::GeneratorExpression,
+            "for_in_clause" => Type::ForInClause,
+            "if_clause" => Type::IfClause,
+            "conditional_expression" => Type::ConditionalExpression,
+            "concatenated_string" => Type::ConcatenatedString,
+            "string" => Type::String,
+            "interpolation" => Type::Interpolation,
+            "format_specifier" => Type::FormatSpecifier,
+            "type_conversion" => Type::TypeConversion,
+            "await" => Type::Await,
+            "parenthesized_expression" => Type::ParenthesizedExpression,
+            "module_repeat1" => Type::ModuleRepeat1,
+            "_simple_statements_repeat1" => Type::_SimpleStatementsRepeat1,
+            "import_prefix_repeat1" => Type::ImportPrefixRepeat1,
+            "print_statement_repeat1" => Type::PrintStatementRepeat1,
+            "assert_statement_repeat1" => Type::AssertStatementRepeat1,
+            "if_statement_repeat1" => Type::IfStatementRepeat1,
+            "match_statement_repeat1" => Type::MatchStatementRepeat1,
+            "_match_block_repeat1" => Type::_MatchBlockRepeat1,
+            "case_clause_repeat1" => Type::CaseClauseRepeat1,
+            "try_statement_repeat1" => Type::TryStatementRepeat1,
+            "try_statement_repeat2" => Type::TryStatementRepeat2,
+            "with_clause_repeat1" => Type::WithClauseRepeat1,
+            "global_statement_repeat1" => Type::GlobalStatementRepeat1,
+            "type_parameter_repeat1" => Type::TypeParameterRepeat1,
+            "argument_list_repeat1" => Type::ArgumentListRepeat1,
+            "decorated_definition_repeat1" => Type::DecoratedDefinitionRepeat1,
+            "dotted_name_repeat1" => Type::DottedNameRepeat1,
+            "union_pattern_repeat1" => Type::UnionPatternRepeat1,
+            "dict_pattern_repeat1" => Type::DictPatternRepeat1,
+            "_parameters_repeat1" => Type::_ParametersRepeat1,
+            "_patterns_repeat1" => Type::_PatternsRepeat1,
+            "comparison_operator_repeat1" => Type::ComparisonOperatorRepeat1,
+            "subscript_repeat1" => Type::SubscriptRepeat1,
+            "dictionary_repeat1" => Type::DictionaryRepeat1,
+            "_comprehension_clauses_repeat1" => Type::_ComprehensionClausesRepeat1,
+            "_collection_elements_repeat1" => Type::_CollectionElementsRepeat1,
+            "for_in_clause_repeat1" => Type::ForInClauseRepeat1,
+            "concatenated_string_repeat1" => Type::ConcatenatedStringRepeat1,
+            "string_repeat1" => Type::StringRepeat1,
+            "string_content_repeat1" => Type::StringContentRepeat1,
+            "format_specifier_repeat1" => Type::FormatSpecifierRepeat1,
+            "Spaces" => Type::Spaces,
+            "Directory" => Type::Directory,
+            "ERROR" => Type::ERROR,
+            _x => return None,
+        })
+    }
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Type::End => "end",
+            Type::Identifier => "identifier",
+            Type::Import => "import",
+            Type::Dot => ".",
+            Type::From => "from",
+            Type::Future => "__future__",
+            Type::LParen => "(",
+            Type::RParen => ")",
+            Type::Comma => ",",
+            Type::As => "as",
+            Type::Star => "*",
+            Type::Print => "print",
+            Type::GtGt => ">>",
+            Type::Assert => "assert",
+            Type::ColonEq => ":=",
+            Type::Return => "return",
+            Type::Del => "del",
+            Type::Raise => "raise",
+            Type::Pass => "pass",
+            Type::Break => "break",
+            Type::Continue => "continue",
+            Type::If => "if",
+            Type::Colon => ":",
+            Type::Elif => "elif",
+            Type::Else => "else",
+            Type::Match => "match",
+            Type::Case => "case",
+            Type::Async => "async",
+            Type::For => "for",
+            Type::In => "in",
+            Type::While => "while",
+            Type::Try => "try",
+            Type::Except => "except",
+            Type::ExceptStar => "except*",
+            Type::Finally => "finally",
+            Type::With => "with",
+            Type::Def => "def",
+            Type::DashGt => "->",
+            Type::StarStar => "**",
+            Type::Global => "global",
+            Type::Nonlocal => "nonlocal",
+            Type::Exec => "exec",
+            Type::TS0 => "type",
+            Type::Eq => "=",
+            Type::Class => "class",
+            Type::LBracket => "[",
+            Type::RBracket => "]",
+            Type::At => "@",
+            Type::Dash => "-",
+            Type::Underscore => "_",
+            Type::Pipe => "|",
+            Type::LBrace => "{",
+            Type::RBrace => "}",
+            Type::Plus => "+",
+            Type::Not => "not",
+            Type::And => "and",
+            Type::Or => "or",
+            Type::Slash => "/",
+            Type::Percent => "%",
+            Type::SlashSlash => "//",
+            Type::Amp => "&",
+            Type::Caret => "^",
+            Type::LtLt => "<<",
+            Type::Tilde => "~",
+            Type::Is => "is",
+            Type::LT => "<",
+            Type::LTEq => "<=",
+            Type::EqEq => "==",
+            Type::BangEq => "!=",
+            Type::GTEq => ">=",
+            Type::GT => ">",
+            Type::LTGT => "<>",
+            Type::NotIn => "not in",
+            Type::IsNot => "is not",
+            Type::TS1 => "lambda",
+            Type::PlusEq => "+=",
+            Type::DashEq => "-=",
+            Type::StarEq => "*=",
+            Type::SlashEq => "/=",
+            Type::AtEq => "@=",
+            Type::SlashSlashEq => "//=",
+            Type::PercentEq => "%=",
+            Type::StarStarEq => "**=",
+            Type::GtGtEq => ">>=",
+            Type::LtLtEq => "<<=",
+            Type::AmpEq => "&=",
+            Type::CaretEq => "^=",
+            Type::PipeEq => "|=",
+            Type::TS2 => "yield",
+            Type::SemiColon => ";",
+            Type::TS3 => "await",
+            Type::EscapeSequence => "escape_sequence",
+            Type::Integer => "integer",
+            Type::Float => "float",
+            Type::True => "true",
+            Type::False => "false",
+            Type::None => "none",
+            Type::Comment => "comment",
+            Type::LineContinuation => "line_continuation",
+            Type::StringStart => "string_start",
+            Type::StringContent => "string_content",
+            Type::EscapeInterpolation => "escape_interpolation",
+            Type::StringEnd => "string_end",
+            Type::Module => "module",
+            Type::_Newline => "_newline",
+            Type::_Indent => "_indent",
+            Type::_Dedent => "_dedent",
+            Type::_Statement => "_statement",
+            Type::_SimpleStatements => "_simple_statements",
+            Type::_SimpleStatement => "_simple_statement",
+            Type::_CompoundStatement => "_compound_statement",
+            Type::_ImportList => "_import_list",
+            Type::_Expressions => "_expressions",
+            Type::_LeftHandSide => "_left_hand_side",
+            Type::_RightHandSide => "_right_hand_side",
+            Type::_CollectionElements => "_collection_elements",
+            Type::_ComprehensionClauses => "_comprehension_clauses",
+            Type::_Parameters => "_parameters",
+            Type::_Patterns => "_patterns",
+            Type::_FExpression => "_f_expression",
+            Type::ImportStatement => "import_statement",
+            Type::ImportPrefix => "import_prefix",
+            Type::RelativeImport => "relative_import",
+            Type::FutureImportStatement => "future_import_statement",
+            Type::ImportFromStatement => "import_from_statement",
+            Type::AliasedImport => "aliased_import",
+            Type::WildcardImport => "wildcard_import",
+            Type::PrintStatement => "print_statement",
+            Type::Chevron => "chevron",
+            Type::AssertStatement => "assert_statement",
+            Type::ExpressionStatement => "expression_statement",
+            Type::NamedExpression => "named_expression",
+            Type::ReturnStatement => "return_statement",
+            Type::DeleteStatement => "delete_statement",
+            Type::RaiseStatement => "raise_statement",
+            Type::PassStatement => "pass_statement",
+            Type::BreakStatement => "break_statement",
+            Type::ContinueStatement => "continue_statement",
+            Type::IfStatement => "if_statement",
+            Type::ElifClause => "elif_clause",
+            Type::ElseClause => "else_clause",
+            Type::MatchStatement => "match_statement",
+            Type::Block => "block",
+            Type::CaseClause => "case_clause",
+            Type::ForStatement => "for_statement",
+            Type::WhileStatement => "while_statement",
+            Type::TryStatement => "try_statement",
+            Type::ExceptClause => "except_clause",
+            Type::ExceptGroupClause => "except_group_clause",
+            Type::FinallyClause => "finally_clause",
+            Type::WithStatement => "with_statement",
+            Type::WithClause => "with_clause",
+            Type::WithItem => "with_item",
+            Type::FunctionDefinition => "function_definition",
+            Type::Parameters => "parameters",
+            Type::LambdaParameters => "lambda_parameters",
+            Type::ListSplat => "list_splat",
+            Type::DictionarySplat => "dictionary_splat",
+            Type::GlobalStatement => "global_statement",
+            Type::NonlocalStatement => "nonlocal_statement",
+            Type::ExecStatement => "exec_statement",
+            Type::TypeAliasStatement => "type_alias_statement",
+            Type::ClassDefinition => "class_definition",
+            Type::TypeParameter => "type_parameter",
+            Type::ParenthesizedListSplat => "parenthesized_list_splat",
+            Type::ArgumentList => "argument_list",
+            Type::DecoratedDefinition => "decorated_definition",
+            Type::Decorator => "decorator",
+            Type::ExpressionList => "expression_list",
+            Type::DottedName => "dotted_name",
+            Type::CasePattern => "case_pattern",
+            Type::SplatPattern => "splat_pattern",
+            Type::AsPattern => "as_pattern",
+            Type::UnionPattern => "union_pattern",
+            Type::DictPattern => "dict_pattern",
+            Type::KeywordPattern => "keyword_pattern",
+            Type::ListPattern => "list_pattern",
+            Type::TuplePattern => "tuple_pattern",
+            Type::ClassPattern => "class_pattern",
+            Type::ComplexPattern => "complex_pattern",
+            Type::Parameter => "parameter",
+            Type::DefaultParameter => "default_parameter",
+            Type::TypedDefaultParameter => "typed_default_parameter",
+            Type::ListSplatPattern => "list_splat_pattern",
+            Type::DictionarySplatPattern => "dictionary_splat_pattern",
+            Type::TypedParameter => "typed_parameter",
+            Type::Type => "type",
+            Type::SplatType => "splat_type",
+            Type::GenericType => "generic_type",
+            Type::UnionType => "union_type",
+            Type::ConstrainedType => "constrained_type",
+            Type::MemberType => "member_type",
+            Type::KeywordSeparator => "keyword_separator",
+            Type::PositionalSeparator => "positional_separator",
+            Type::Pattern => "pattern",
+            Type::Expression => "expression",
+            Type::PrimaryExpression => "primary_expression",
+            Type::NotOperator => "not_operator",
+            Type::BooleanOperator => "boolean_operator",
+            Type::BinaryOperator => "binary_operator",
+            Type::UnaryOperator => "unary_operator",
+            Type::ComparisonOperator => "comparison_operator",
+            Type::Lambda => "lambda",
+            Type::Assignment => "assignment",
+            Type::AugmentedAssignment => "augmented_assignment",
+            Type::PatternList => "pattern_list",
+            Type::Yield => "yield",
+            Type::Attribute => "attribute",
+            Type::Subscript => "subscript",
+            Type::Slice => "slice",
+            Type::Ellipsis => "ellipsis",
+            Type::Call => "call",
+            Type::KeywordArgument => "keyword_argument",
+            Type::List => "list",
+            Type::Set => "set",
+            Type::Tuple => "tuple",
+            Type::Dictionary => "dictionary",
+            Type::Pair => "pair",
+            Type::ListComprehension => "list_comprehension",
+            Type::DictionaryComprehension => "dictionary_comprehension",
+            Type::SetComprehension => "set_comprehension",
+            Type::GeneratorExpression => "generator_expression",
+            Type::ForInClause => "for_in_clause",
+            Type::IfClause => "if_clause",
+            Type::ConditionalExpression => "conditional_expression",
+            Type::ConcatenatedString => "concatenated_string",
+            Type::String => "string",
+            Type::Interpolation => "interpolation",
+            Type::FormatSpecifier => "format_specifier",
+            Type::TypeConversion => "type_conversion",
+            Type::Await => "await",
+            Type::ParenthesizedExpression => "parenthesized_expression",
+            Type::ModuleRepeat1 => "module_repeat1",
+            Type::_SimpleStatementsRepeat1 => "_simple_statements_repeat1",
+            Type::ImportPrefixRepeat1 => "import_prefix_repeat1",
+            Type::PrintStatementRepeat1 => "print_statement_repeat1",
+            Type::AssertStatementRepeat1 => "assert_statement_repeat1",
+            Type::IfStatementRepeat1 => "if_statement_repeat1",
+            Type::MatchStatementRepeat1 => "match_statement_repeat1",
+            Type::_MatchBlockRepeat1 => "_match_block_repeat1",
+            Type::CaseClauseRepeat1 => "case_clause_repeat1",
+            Type::TryStatementRepeat1 => "try_statement_repeat1",
+            Type::TryStatementRepeat2 => "try_statement_repeat2",
+            Type::WithClauseRepeat1 => "with_clause_repeat1",
+            Type::GlobalStatementRepeat1 => "global_statement_repeat1",
+            Type::TypeParameterRepeat1 => "type_parameter_repeat1",
+            Type::ArgumentListRepeat1 => "argument_list_repeat1",
+            Type::DecoratedDefinitionRepeat1 => "decorated_definition_repeat1",
+            Type::DottedNameRepeat1 => "dotted_name_repeat1",
+            Type::UnionPatternRepeat1 => "union_pattern_repeat1",
+            Type::DictPatternRepeat1 => "dict_pattern_repeat1",
+            Type::_ParametersRepeat1 => "_parameters_repeat1",
+            Type::_PatternsRepeat1 => "_patterns_repeat1",
+            Type::ComparisonOperatorRepeat1 => "comparison_operator_repeat1",
+            Type::SubscriptRepeat1 => "subscript_repeat1",
+            Type::DictionaryRepeat1 => "dictionary_repeat1",
+            Type::_ComprehensionClausesRepeat1 => "_comprehension_clauses_repeat1",
+            Type::_CollectionElementsRepeat1 => "_collection_elements_repeat1",
+            Type::ForInClauseRepeat1 => "for_in_clause_repeat1",
+            Type::ConcatenatedStringRepeat1 => "concatenated_string_repeat1",
+            Type::StringRepeat1 => "string_repeat1",
+            Type::StringContentRepeat1 => "string_content_repeat1",
+            Type::FormatSpecifierRepeat1 => "format_specifier_repeat1",
+            Type::Spaces => "Spaces",
+            Type::Directory => "Directory",
+            Type::ERROR => "ERROR",
+        }
+    }
+}
+
+const S_T_L: &'static [Type] = &[
+    Type::End,
+    Type::Identifier,
+    Type::Import,
+    Type::Dot,
+    Type::From,
+    Type::Future,
+    Type::LParen,
+    Type::RParen,
+    Type::Comma,
+    Type::As,
+    Type::Star,
+    Type::Print,
+    Type::GtGt,
+    Type::Assert,
+    Type::ColonEq,
+    Type::Return,
+    Type::Del,
+    Type::Raise,
+    Type::Pass,
+    Type::Break,
+    Type::Continue,
+    Type::If,
+    Type::Colon,
+    Type::Elif,
+    Type::Else,
+    Type::Match,
+    Type::Case,
+    Type::Async,
+    Type::For,
+    Type::In,
+    Type::While,
+    Type::Try,
+    Type::Except,
+    Type::ExceptStar,
+    Type::Finally,
+    Type::With,
+    Type::Def,
+    Type::DashGt,
+    Type::StarStar,
+    Type::Global,
+    Type::Nonlocal,
+    Type::Exec,
+    Type::TS0,
+    Type::Eq,
+    Type::Class,
+    Type::LBracket,
+    Type::RBracket,
+    Type::At,
+    Type::Dash,
+    Type::Underscore,
+    Type::Pipe,
+    Type::LBrace,
+    Type::RBrace,
+    Type::Plus,
+    Type::Not,
+    Type::And,
+    Type::Or,
+    Type::Slash,
+    Type::Percent,
+    Type::SlashSlash,
+    Type::Amp,
+    Type::Caret,
+    Type::LtLt,
+    Type::Tilde,
+    Type::Is,
+    Type::LT,
+    Type::LTEq,
+    Type::EqEq,
+    Type::BangEq,
+    Type::GTEq,
+    Type::GT,
+    Type::LTGT,
+    Type::NotIn,
+    Type::IsNot,
+    Type::TS1,
+    Type::PlusEq,
+    Type::DashEq,
+    Type::StarEq,
+    Type::SlashEq,
+    Type::AtEq,
+    Type::SlashSlashEq,
+    Type::PercentEq,
+    Type::StarStarEq,
+    Type::GtGtEq,
+    Type::LtLtEq,
+    Type::AmpEq,
+    Type::CaretEq,
+    Type::PipeEq,
+    Type::TS2,
+    Type::SemiColon,
+    Type::TS3,
+    Type::EscapeSequence,
+    Type::Integer,
+    Type::Float,
+    Type::True,
+    Type::False,
+    Type::None,
+    Type::Comment,
+    Type::LineContinuation,
+    Type::StringStart,
+    Type::StringContent,
+    Type::EscapeInterpolation,
+    Type::StringEnd,
+    Type::Module,
+    Type::_Newline,
+    Type::_Indent,
+    Type::_Dedent,
+    Type::_Statement,
+    Type::_SimpleStatements,
+    Type::_SimpleStatement,
+    Type::_CompoundStatement,
+    Type::_ImportList,
+    Type::_Expressions,
+    Type::_LeftHandSide,
+    Type::_RightHandSide,
+    Type::_CollectionElements,
+    Type::_ComprehensionClauses,
+    Type::_Parameters,
+    Type::_Patterns,
+    Type::_FExpression,
+    Type::ImportStatement,
+    Type::ImportPrefix,
+    Type::RelativeImport,
+    Type::FutureImportStatement,
+    Type::ImportFromStatement,
+    Type::AliasedImport,
+    Type::WildcardImport,
+    Type::PrintStatement,
+    Type::Chevron,
+    Type::AssertStatement,
+    Type::ExpressionStatement,
+    Type::NamedExpression,
+    Type::ReturnStatement,
+    Type::DeleteStatement,
+    Type::RaiseStatement,
+    Type::PassStatement,
+    Type::BreakStatement,
+    Type::ContinueStatement,
+    Type::IfStatement,
+    Type::ElifClause,
+    Type::ElseClause,
+    Type::MatchStatement,
+    Type::Block,
+    Type::CaseClause,
+    Type::ForStatement,
+    Type::WhileStatement,
+    Type::TryStatement,
+    Type::ExceptClause,
+    Type::ExceptGroupClause,
+    Type::FinallyClause,
+    Type::WithStatement,
+    Type::WithClause,
+    Type::WithItem,
+    Type::FunctionDefinition,
+    Type::Parameters,
+    Type::LambdaParameters,
+    Type::ListSplat,
+    Type::DictionarySplat,
+    Type::GlobalStatement,
+    Type::NonlocalStatement,
+    Type::ExecStatement,
+    Type::TypeAliasStatement,
+    Type::ClassDefinition,
+    Type::TypeParameter,
+    Type::ParenthesizedListSplat,
+    Type::ArgumentList,
+    Type::DecoratedDefinition,
+    Type::Decorator,
+    Type::ExpressionList,
+    Type::DottedName,
+    Type::CasePattern,
+    Type::SplatPattern,
+    Type::AsPattern,
+    Type::UnionPattern,
+    Type::DictPattern,
+    Type::KeywordPattern,
+    Type::ListPattern,
+    Type::TuplePattern,
+    Type::ClassPattern,
+    Type::ComplexPattern,
+    Type::Parameter,
+    Type::DefaultParameter,
+    Type::TypedDefaultParameter,
+    Type::ListSplatPattern,
+    Type::DictionarySplatPattern,
+    Type::TypedParameter,
+    Type::Type,
+    Type::SplatType,
+    Type::GenericType,
+    Type::UnionType,
+    Type::ConstrainedType,
+    Type::MemberType,
+    Type::KeywordSeparator,
+    Type::PositionalSeparator,
+    Type::Pattern,
+    Type::Expression,
+    Type::PrimaryExpression,
+    Type::NotOperator,
+    Type::BooleanOperator,
+    Type::BinaryOperator,
+    Type::UnaryOperator,
+    Type::ComparisonOperator,
+    Type::Lambda,
+    Type::Assignment,
+    Type::AugmentedAssignment,
+    Type::PatternList,
+    Type::Yield,
+    Type::Attribute,
+    Type::Subscript,
+    Type::Slice,
+    Type::Ellipsis,
+    Type::Call,
+    Type::KeywordArgument,
+    Type::List,
+    Type::Set,
+    Type::Tuple,
+    Type::Dictionary,
+    Type::Pair,
+    Type::ListComprehension,
+    Type::DictionaryComprehension,
+    Type::SetComprehension,
+    Type::GeneratorExpression,
+    Type::ForInClause,
+    Type::IfClause,
+    Type::ConditionalExpression,
+    Type::ConcatenatedString,
+    Type::String,
+    Type::Interpolation,
+    Type::FormatSpecifier,
+    Type::TypeConversion,
+    Type::Await,
+    Type::ParenthesizedExpression,
+    Type::ModuleRepeat1,
+    Type::_SimpleStatementsRepeat1,
+    Type::ImportPrefixRepeat1,
+    Type::PrintStatementRepeat1,
+    Type::AssertStatementRepeat1,
+    Type::IfStatementRepeat1,
+    Type::MatchStatementRepeat1,
+    Type::_MatchBlockRepeat1,
+    Type::CaseClauseRepeat1,
+    Type::TryStatementRepeat1,
+    Type::TryStatementRepeat2,
+    Type::WithClauseRepeat1,
+    Type::GlobalStatementRepeat1,
+    Type::TypeParameterRepeat1,
+    Type::ArgumentListRepeat1,
+    Type::DecoratedDefinitionRepeat1,
+    Type::DottedNameRepeat1,
+    Type::UnionPatternRepeat1,
+    Type::DictPatternRepeat1,
+    Type::_ParametersRepeat1,
+    Type::_PatternsRepeat1,
+    Type::ComparisonOperatorRepeat1,
+    Type::SubscriptRepeat1,
+    Type::DictionaryRepeat1,
+    Type::_ComprehensionClausesRepeat1,
+    Type::_CollectionElementsRepeat1,
+    Type::ForInClauseRepeat1,
+    Type::ConcatenatedStringRepeat1,
+    Type::StringRepeat1,
+    Type::StringContentRepeat1,
+    Type::FormatSpecifierRepeat1,
+    Type::Spaces,
+    Type::Directory,
+    Type::ERROR,
+];
@@ -43,3 +43,13 @@ mod tnode {
 
 #[cfg(feature = "legion")]
 pub use tnode::TNode;
+
+#[cfg(feature = "impl")]
+pub fn language() -> tree_sitter::Language {
+    tree_sitter_typescript::language_typescript()
+}
+
+#[cfg(feature = "impl")]
+pub fn node_types() -> &'static str {
+    tree_sitter_typescript::TYPESCRIPT_NODE_TYPES
+}
@@ -12,9 +12,19 @@ mod legion_impls {
     use crate::TNode;
 
     impl<'a> TNode<'a> {
+        /// Types are the kinds of the typescript grammar,
+        /// nodes parsed with the tsx grammar are matched by kind name,
+        /// the jsx kinds that typescript does not have become [`Type::ERROR`].
         pub fn obtain_type(&self) -> Type {
             let t = self.kind_id();
-            Type::from_u16(t)
+            let language = tree_sitter_typescript::language_typescript();
+            if language.node_kind_for_id(t) == Some(self.0.kind()) {
+                return Type::from_u16(t);
+            }
+            match language.id_for_node_kind(self.0.kind(), self.0.is_named()) {
+                0 => Type::ERROR,
+                t => Type::from_u16(t),
+            }
         }
     }
 
@@ -29,6 +39,30 @@ mod legion_impls {
             t.e()
         }
     }
+
+    use hyper_ast::types::{LangWrapper, RoleStore};
+
+    impl RoleStore for TStore {
+        type IdF = u16;
+
+        type Role = hyper_ast::types::Role;
+
+        fn resolve_field(_lang: LangWrapper<Self::Ty>, field_id: Self::IdF) -> Self::Role {
+            let s = tree_sitter_typescript::language_typescript()
+                .field_name_for_id(field_id)
+                .ok_or_else(|| format!("{}", field_id))
+                .unwrap();
+            hyper_ast::types::Role::try_from(s).expect(s)
+        }
+
+        fn intern_role(_lang: LangWrapper<Self::Ty>, role: Self::Role) -> Self::IdF {
+            let field_name = role.to_string();
+            tree_sitter_typescript::language_typescript()
+                .field_id_for_name(field_name)
+                .unwrap()
+                .into()
+        }
+    }
 }
 
 pub trait TsEnabledTypeStore: TypeStore {
@@ -41,6 +75,34 @@ impl Type {
         assert!(t < COUNT);
         unsafe { std::mem::transmute(t) }
     }
+
+    /// hidden rules start with an underscore, auxiliary ones end with eg. `_repeat1` or `_token1`
+    pub fn is_hidden(&self) -> bool {
+        let s = self.to_str();
+        let aux = s.rsplit_once('_').map_or(false, |(_, x)| {
+            let x = x.trim_end_matches(|c: char| c.is_ascii_digit());
+            x == "repeat" || x == "token"
+        });
+        self == &Type::End || s.starts_with('_') || aux
+    }
+
+    pub fn is_supertype(&self) -> bool {
+        match self {
+            Type::Declaration => true,
+            Type::Expression => true,
+            Type::PrimaryExpression => true,
+            Type::Pattern => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_named(&self) -> bool {
+        match self {
+            Type::Spaces | Type::Directory => false,
+            Type::ERROR => true,
+            t => id_for_node_kind(t.to_str(), true) != 0,
+        }
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -75,7 +137,7 @@ impl<IdN: Clone + Eq + NodeId> TypedNodeId for TIdN<IdN> {
     type Ty = Type;
 }
 
-pub(crate) struct TStore;
+pub struct TStore;
 
 impl Default for TStore {
     fn default() -> Self {
@@ -174,7 +236,7 @@ impl HyperType for Type {
     }
 
     fn is_file(&self) -> bool {
-        todo!()
+        self == &Type::Program
     }
 
     fn is_spaces(&self) -> bool {
@@ -221,15 +283,15 @@ impl HyperType for Type {
     }
 
     fn is_hidden(&self) -> bool {
-        todo!()
+        self.is_hidden()
     }
 
     fn is_supertype(&self) -> bool {
-        todo!()
+        self.is_supertype()
     }
 
     fn is_named(&self) -> bool {
-        todo!()
+        self.is_named()
     }
 
     fn get_lang(&self) -> hyper_ast::types::LangWrapper<Self>
@@ -239,7 +301,7 @@ impl HyperType for Type {
         From::<&'static (dyn LangRef<Self>)>::from(&Lang)
     }
     fn lang_ref(&self) -> hyper_ast::types::LangWrapper<AnyType> {
-        todo!()
+        hyper_ast::types::LangWrapper::from(&Lang as &(dyn LangRef<AnyType> + 'static))
     }
 }
 impl TypeTrait for Type {
@@ -335,7 +397,7 @@ impl Display for Type {
     }
 }
 
-type TType = hyper_ast::types::TypeU16<Lang>;
+pub type TType = hyper_ast::types::TypeU16<Lang>;
 
 impl hyper_ast::types::LLang<TType> for Ts {
     type I = u16;
@@ -25,6 +25,7 @@ use super::{
     utils_results_batched::{self, show_long_result, ComputeResults},
     Sharing,
 };
+mod analysis;
 pub(crate) mod example_queries;
 
 const INFO_QUERY: EditorInfo<&'static str> = EditorInfo {
@@ -445,7 +446,8 @@ pub(super) fn show_querying(
     let is_portrait = ui.available_rect_before_wrap().aspect_ratio() < 1.0;
     if is_portrait {
         egui::ScrollArea::vertical().show(ui, |ui| {
-            show_scripts_edition(ui, api_endpoint, query_editors, query);
+            let editor = show_scripts_edition(ui, api_endpoint, query_editors, query);
+            show_query_analysis(ui, api_addr, query_editors, query, editor);
             handle_interactions(ui, query_editors, querying_result, query, trigger_compute);
             show_long_result(&*querying_result, ui);
         });
@@ -454,7 +456,8 @@ pub(super) fn show_querying(
             .ratio(0.7)
             .show(ui, |ui1, ui2| {
                 ui1.push_id(ui1.id().with("input"), |ui| {
-                    show_scripts_edition(ui, api_endpoint, query_editors, query);
+                    let editor = show_scripts_edition(ui, api_endpoint, query_editors, query);
+                    show_query_analysis(ui, api_addr, query_editors, query, editor);
                 });
                 let ui = ui2;
                 handle_interactions(ui, query_editors, querying_result, query, trigger_compute);
@@ -463,6 +466,81 @@ pub(super) fn show_querying(
     }
 }
 
+/// Shows the analysis of the query, and applies the completion picked in the popup below its `editor`
+fn show_query_analysis(
+    ui: &mut egui::Ui,
+    api_addr: &str,
+    query_editors: &mut QueryingContext,
+    single: &mut Sharing<ComputeConfigQuery>,
+    editor: Option<egui::Response>,
+) {
+    use egui_addon::code_editor::{generic_text_edit::TextEdit, EditAwareString};
+    let query = match &query_editors.current {
+        EditStatus::Shared(_, shared_script) | EditStatus::Sharing(shared_script) => {
+            shared_script.lock().unwrap().query.code().to_string()
+        }
+        EditStatus::Local { name: _, content } | EditStatus::Example { i: _, content } => {
+            content.query.code().to_string()
+        }
+    };
+    // the cursor is kept by the state of the text edit, in chars
+    let cursor = editor.as_ref().and_then(|editor| {
+        let state = TextEdit::<EditAwareString>::load_state(ui.ctx(), editor.id)?;
+        let index = state.ccursor_range()?.primary.index;
+        let byte = query
+            .char_indices()
+            .nth(index)
+            .map_or(query.len(), |(i, _)| i);
+        Some((editor, byte))
+    });
+    let picked = egui::CollapsingHeader::new("Analysis")
+        .default_open(true)
+        .show(ui, |ui| {
+            let language = single.content.config.language();
+            analysis::show_analysis(ui, api_addr, language, &query, cursor)
+        })
+        .body_returned
+        .flatten();
+    let (Some(completion), Some(editor)) = (picked, editor) else {
+        return;
+    };
+    // text buffers are edited with char indices
+    let chars = |byte: usize| query.get(..byte).map_or(0, |s| s.chars().count());
+    let replaced = chars(completion.replace.start)..chars(completion.replace.end);
+    let start = replaced.start;
+    match &mut query_editors.current {
+        EditStatus::Shared(_, shared_script) | EditStatus::Sharing(shared_script) => {
+            let mut shared_script = shared_script.lock().unwrap();
+            replace_chars(&mut shared_script.query.code, replaced, &completion.label);
+            // sync the shared query as if it was typed
+            if let Some(ws) = &mut single.ws {
+                if ws.timer == 0.0 {
+                    ws.timer = 0.01;
+                }
+            }
+        }
+        EditStatus::Local { name: _, content } | EditStatus::Example { i: _, content } => {
+            replace_chars(&mut content.query.code, replaced, &completion.label);
+        }
+    }
+    // put the cursor after the completion, back in the editor
+    let mut state =
+        TextEdit::<EditAwareString>::load_state(ui.ctx(), editor.id).unwrap_or_default();
+    let after = egui::text::CCursor::new(start + completion.label.chars().count());
+    state.set_ccursor_range(Some(egui::text_selection::CCursorRange::one(after)));
+    TextEdit::<EditAwareString>::store_state(ui.ctx(), editor.id, state);
+    ui.memory_mut(|mem| mem.request_focus(editor.id));
+}
+
+fn replace_chars(
+    code: &mut impl egui_addon::code_editor::generic_text_buffer::TextBuffer,
+    range: std::ops::Range<usize>,
+    text: &str,
+) {
+    code.delete_char_range(range.clone());
+    code.insert_text(text, range.start);
+}
+
 pub(crate) fn end_point(api_addr: &str) -> String {
     format!("{}/sharing-queries", api_addr)
 }
@@ -502,12 +580,13 @@ pub(crate) fn handle_interactions(
     }
 }
 
+/// Returns the response of the query editor, if it is shown
 pub(crate) fn show_scripts_edition(
     ui: &mut egui::Ui,
     api_endpoint: &str,
     querying_context: &mut QueryingContext,
     single: &mut Sharing<ComputeConfigQuery>,
-) {
+) -> Option<egui::Response> {
     egui::warn_if_debug_build(ui);
     egui::CollapsingHeader::new("Examples")
         .default_open(true)
@@ -523,11 +602,13 @@ pub(crate) fn show_scripts_edition(
     }
     show_available_remote_docs(ui, api_endpoint, single, querying_context);
     let local = querying_context
-        .when_local(|code_editors| code_editors.iter_editors_mut().for_each(|c| c.ui(ui)));
+        .when_local(|code_editors| code_editors.iter_editors_mut().map(|c| c.ui(ui)).collect());
     let shared = querying_context.when_shared(|query_editors| {
         utils_edition::show_shared_code_edition(ui, query_editors, single)
     });
-    assert!(local.or(shared).is_some());
+    let mut responses: Vec<_> = local.or(shared).unwrap();
+    // the query comes after its description
+    responses.pop().flatten()
 }
 
 fn show_examples(
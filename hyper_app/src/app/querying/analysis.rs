@@ -0,0 +1,195 @@
+//! Diagnostics and completions on the query being edited, computed by the `/query-analysis/:language` route.
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Range,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use poll_promise::Promise;
+
+/// delay without edits before analysing the query again, the route is rate limited
+const DEBOUNCE: f64 = 0.5;
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+pub(crate) struct Diagnostic {
+    /// in bytes
+    pub(crate) range: Range<usize>,
+    pub(crate) severity: Severity,
+    pub(crate) message: String,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CompletionKind {
+    NodeKind,
+    Supertype,
+    Subtype,
+    Token,
+    Field,
+    Predicate,
+    Capture,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+pub(crate) struct Completion {
+    pub(crate) label: String,
+    pub(crate) kind: CompletionKind,
+    /// bytes of the query replaced by `label`
+    pub(crate) replace: Range<usize>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+pub(crate) struct Analysis {
+    pub(crate) diagnostics: Vec<Diagnostic>,
+    #[serde(default)]
+    pub(crate) completions: Vec<Completion>,
+}
+
+#[derive(serde::Serialize)]
+struct Content<'a> {
+    query: &'a str,
+    /// byte offset where completions are requested
+    cursor: Option<usize>,
+}
+
+#[derive(Default)]
+pub(crate) struct QueryAnalysis {
+    /// hash of the language, query and cursor being edited, and when it last changed
+    edited: (u64, f64),
+    /// hash of the language, query and cursor of `promise`
+    requested: u64,
+    promise: Option<Promise<Result<Analysis, String>>>,
+}
+
+/// Shows the diagnostics of `query`, requesting them again once the query stops changing.
+///
+/// Given the `editor` of the query and the byte offset of its cursor,
+/// the completions at the cursor are proposed in a popup below the editor, opened while typing.
+/// Returns the completion that was picked.
+pub(crate) fn show_analysis(
+    ui: &mut egui::Ui,
+    api_addr: &str,
+    language: &str,
+    query: &str,
+    editor: Option<(&egui::Response, usize)>,
+) -> Option<Completion> {
+    if language.is_empty() {
+        ui.label("select a configuration with a language to analyse the query");
+        return None;
+    }
+    let cursor = editor.map(|(_, cursor)| cursor);
+    let id = ui.id().with("query_analysis");
+    let analysis = ui.data_mut(|d| {
+        d.get_temp_mut_or_default::<Arc<Mutex<QueryAnalysis>>>(id)
+            .clone()
+    });
+    let mut analysis = analysis.lock().unwrap();
+    let key = {
+        let mut hasher = DefaultHasher::new();
+        (language, query, cursor).hash(&mut hasher);
+        hasher.finish()
+    };
+    let now = ui.input(|i| i.time);
+    if analysis.edited.0 != key {
+        analysis.edited = (key, now);
+    }
+    if analysis.requested != key {
+        if now - analysis.edited.1 >= DEBOUNCE {
+            analysis.requested = key;
+            analysis.promise = Some(remote_analysis(ui.ctx(), api_addr, language, query, cursor));
+        } else {
+            ui.ctx()
+                .request_repaint_after(Duration::from_secs_f64(DEBOUNCE));
+        }
+    }
+    let Some(promise) = &analysis.promise else {
+        return None;
+    };
+    let mut picked = None;
+    if let (Some((editor, _)), Some(Ok(result))) = (editor, promise.ready()) {
+        let popup_id = id.with("completions");
+        if editor.changed() {
+            ui.memory_mut(|mem| mem.open_popup(popup_id));
+        }
+        // the replaced ranges of outdated completions would be wrong
+        if analysis.requested == key && !result.completions.is_empty() {
+            egui::popup_below_widget(
+                ui,
+                popup_id,
+                editor,
+                egui::PopupCloseBehavior::CloseOnClickOutside,
+                |ui| {
+                    egui::ScrollArea::vertical()
+                        .max_height(200.0)
+                        .show(ui, |ui| {
+                            for c in &result.completions {
+                                let text = format!("{} ({:?})", c.label, c.kind);
+                                if ui.selectable_label(false, text).clicked() {
+                                    picked = Some(c.clone());
+                                    ui.memory_mut(|mem| mem.close_popup());
+                                }
+                            }
+                        });
+                },
+            );
+        }
+    }
+    match promise.ready() {
+        None => {
+            ui.spinner();
+        }
+        Some(Err(err)) => {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        }
+        Some(Ok(result)) if result.diagnostics.is_empty() => {
+            ui.label("no issue found");
+        }
+        Some(Ok(result)) => {
+            for d in &result.diagnostics {
+                let color = match d.severity {
+                    Severity::Error => ui.visuals().error_fg_color,
+                    Severity::Warning => ui.visuals().warn_fg_color,
+                };
+                // the query might have changed since the request
+                let text = query.get(d.range.clone()).unwrap_or_default();
+                ui.colored_label(color, format!("{:?} `{}`: {}", d.range, text, d.message));
+            }
+        }
+    }
+    picked
+}
+
+fn remote_analysis(
+    ctx: &egui::Context,
+    api_addr: &str,
+    language: &str,
+    query: &str,
+    cursor: Option<usize>,
+) -> Promise<Result<Analysis, String>> {
+    let ctx = ctx.clone();
+    let (sender, promise) = Promise::new();
+    let url = format!("http://{}/query-analysis/{}", api_addr, language);
+    let content = Content { query, cursor };
+    let mut request = ehttp::Request::post(&url, serde_json::to_vec(&content).unwrap());
+    request.headers.insert(
+        "Content-Type".to_string(),
+        "application/json; charset=utf-8".to_string(),
+    );
+    ehttp::fetch(request, move |response| {
+        ctx.request_repaint(); // will wake up UI thread
+        let analysis = response.and_then(|response| {
+            if response.status != 200 {
+                return Err(response.text().unwrap_or_default().to_string());
+            }
+            serde_json::from_slice(&response.bytes).map_err(|e| e.to_string())
+        });
+        sender.send(analysis);
+    });
+    promise
+}
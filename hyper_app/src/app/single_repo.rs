@@ -324,7 +324,7 @@ fn show_scripts_edition(
     }
     show_available_remote_docs(ui, api_endpoint, single, scripting_context);
     let local = scripting_context
-        .when_local(|code_editors| code_editors.iter_editors_mut().for_each(|e| e.ui(ui)));
+        .when_local(|code_editors| code_editors.iter_editors_mut().map(|e| e.ui(ui)).collect());
     let shared = scripting_context
         .when_shared(|code_editors| show_shared_code_edition(ui, code_editors, single));
     assert!(local.or(shared).is_some());
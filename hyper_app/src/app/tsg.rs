@@ -307,7 +307,7 @@ fn show_scripts_edition(
     }
     show_available_remote_docs(ui, api_endpoint, single, querying_context);
     let local = querying_context
-        .when_local(|code_editors| code_editors.iter_editors_mut().for_each(|c| c.ui(ui)));
+        .when_local(|code_editors| code_editors.iter_editors_mut().map(|c| c.ui(ui)).collect());
     let shared = querying_context.when_shared(|query_editors| {
         utils_edition::show_shared_code_edition(ui, query_editors, single)
     });
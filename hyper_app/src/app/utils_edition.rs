@@ -82,11 +82,13 @@ impl<L: Default, S> Default for EditStatus<L, S> {
     }
 }
 
+/// Returns the responses of the editors, in the order of [`super::types::EditorHolder::iter_editors_mut`]
 pub(crate) fn show_shared_code_edition<T, U>(
     ui: &mut egui::Ui,
     query_editors: &mut SharedCodeEditors<T>,
     single: &mut Sharing<U>,
-) where
+) -> Vec<Option<egui::Response>>
+where
     T: autosurgeon::Reconcile,
     T: super::types::EditorHolder<Item = code_editor_automerge::CodeEditor>,
     // for<'a> &'a mut T: IntoIterator<Item = &'a mut code_editor_automerge::CodeEditor>,
@@ -98,7 +100,7 @@ pub(crate) fn show_shared_code_edition<T, U>(
     };
 
     let Some(ws) = &mut single.ws else {
-        return;
+        return resps;
     };
     if resps.iter().filter_map(|x| x.as_ref()).any(|x| x.changed()) {
         let timer = if ws.timer != 0.0 {
@@ -115,6 +117,7 @@ pub(crate) fn show_shared_code_edition<T, U>(
         let rt = &single.rt;
         timed_updater(ui, timer, ws, query_editors, rt);
     }
+    resps
 }
 
 fn timed_updater<T: autosurgeon::Reconcile>(
@@ -48,6 +48,11 @@ impl Mcc {
         is_cyclomatic_persisted(kind)
     }
 
+    /// The complexity of a node where it is persisted, ie. its number of forks + 1
+    pub fn complexity(&self) -> u32 {
+        self.value + 1
+    }
+
     // pub fn persist(&self, kind: &Type) -> Option<Self> {
     //     if is_cyclomatic_persisted(kind) {
     //         Some(Self {
@@ -59,6 +64,17 @@ impl Mcc {
     // }
 }
 
+impl crate::store::persist::Persist for Mcc {
+    fn persist(&self, w: &mut dyn std::io::Write) -> std::io::Result<()> {
+        crate::store::persist::Persist::persist(&self.value, w)
+    }
+    fn restore(r: &mut dyn std::io::Read) -> std::io::Result<Self> {
+        Ok(Self {
+            value: crate::store::persist::Persist::restore(r)?,
+        })
+    }
+}
+
 impl<T: Typed + WithMetaData<Mcc>> MetaData<T> for Mcc
 where
     T::Type: TypeTrait,
@@ -69,7 +85,7 @@ where
         let kind = node.get_type();
         if Mcc::persist(&kind) {
             node.get_metadata()
-                .map(|x| x.value + 1)
+                .map(Mcc::complexity)
                 .expect("missing mcc")
         } else {
             0
@@ -299,3 +299,76 @@ mod bloom_64 {
         }
     }
 }
+
+#[cfg(feature = "legion")]
+mod persist {
+    use std::io::{self, Read, Write};
+
+    use bitvec::{array::BitArray, view::BitViewSized};
+
+    use super::{Bloom, BloomSize};
+    use crate::store::persist::{invalid_data, ComponentRegistry, Persist};
+
+    impl Persist for BloomSize {
+        fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+            let x: u8 = match self {
+                BloomSize::None => 0,
+                BloomSize::B16 => 1,
+                BloomSize::B32 => 2,
+                BloomSize::B64 => 3,
+                BloomSize::B128 => 4,
+                BloomSize::B256 => 5,
+                BloomSize::B512 => 6,
+                BloomSize::B1024 => 7,
+                BloomSize::B2048 => 8,
+                BloomSize::B4096 => 9,
+                BloomSize::Much => 10,
+            };
+            x.persist(w)
+        }
+        fn restore(r: &mut dyn Read) -> io::Result<Self> {
+            Ok(match u8::restore(r)? {
+                0 => BloomSize::None,
+                1 => BloomSize::B16,
+                2 => BloomSize::B32,
+                3 => BloomSize::B64,
+                4 => BloomSize::B128,
+                5 => BloomSize::B256,
+                6 => BloomSize::B512,
+                7 => BloomSize::B1024,
+                8 => BloomSize::B2048,
+                9 => BloomSize::B4096,
+                10 => BloomSize::Much,
+                _ => return Err(invalid_data("bad bloom size")),
+            })
+        }
+    }
+
+    impl<T, V: BitViewSized + Persist> Persist for Bloom<T, V> {
+        fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+            self.bits.data.persist(w)
+        }
+        fn restore(r: &mut dyn Read) -> io::Result<Self> {
+            Ok(Self {
+                bits: BitArray::new(V::restore(r)?),
+                _phantom: Default::default(),
+            })
+        }
+    }
+
+    /// Blooms are generic over their size, register each one that can be attached to nodes.
+    pub(crate) fn register_blooms(registry: &mut ComponentRegistry) {
+        registry
+            .register::<Bloom<&'static [u8], u16>>("bloom_16")
+            .register::<Bloom<&'static [u8], u32>>("bloom_32")
+            .register::<Bloom<&'static [u8], u64>>("bloom_64")
+            .register::<Bloom<&'static [u8], [u64; 2]>>("bloom_128")
+            .register::<Bloom<&'static [u8], [u64; 4]>>("bloom_256")
+            .register::<Bloom<&'static [u8], [u64; 8]>>("bloom_512")
+            .register::<Bloom<&'static [u8], [u64; 16]>>("bloom_1024")
+            .register::<Bloom<&'static [u8], [u64; 32]>>("bloom_2048")
+            .register::<Bloom<&'static [u8], [u64; 64]>>("bloom_4096");
+    }
+}
+#[cfg(feature = "legion")]
+pub(crate) use persist::register_blooms;
@@ -69,6 +69,10 @@ impl crate::types::LabelStore<DefaultLabelValue> for &LabelStore {
 }
 
 impl LabelStore {
+    pub fn len(&self) -> usize {
+        self.internal.len()
+    }
+
     pub fn new() -> Self {
         let mut r = Self {
             count: 1,
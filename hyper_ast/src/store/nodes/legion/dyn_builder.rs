@@ -107,6 +107,16 @@ use super::*;
 /// A builder of entities for a archetypal store, here legion.
 pub struct BuiltEntity {
     inner: Common<fn() -> Box<dyn UnknownComponentStorage>>,
+    id: Option<Entity>,
+}
+
+impl BuiltEntity {
+    /// Insert the entity with the given identifier instead of allocating a new one,
+    /// the caller must make sure it is not already used.
+    pub fn with_id(mut self, id: Entity) -> Self {
+        self.id = Some(id);
+        self
+    }
 }
 
 #[derive(Default)]
@@ -119,7 +129,10 @@ impl EntityBuilder {
         Self::default()
     }
     pub fn build(self) -> BuiltEntity {
-        BuiltEntity { inner: self.inner }
+        BuiltEntity {
+            inner: self.inner,
+            id: None,
+        }
     }
 }
 
@@ -206,7 +219,10 @@ impl ComponentSource for BuiltEntity {
         writer: &mut ArchetypeWriter<'a>,
         mut entities: impl Iterator<Item = Entity>,
     ) {
-        let entity = entities.next().unwrap();
+        let entity = match self.id {
+            Some(id) => id,
+            None => entities.next().unwrap(),
+        };
         writer.push(entity);
 
         // let v = unsafe { Vec::from_raw_parts(self.inner.storage.as_ptr(), self.inner.cursor, 4) };
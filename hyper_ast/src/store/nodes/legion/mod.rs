@@ -287,6 +287,67 @@ impl NodeStore {
     }
 }
 
+impl NodeStore {
+    /// Identifiers greater than `since`, in allocation order.
+    pub(crate) fn ids_after(&self, since: u64) -> Vec<NodeIdentifier> {
+        use crate::store::persist::id_to_u64;
+        let mut ids: Vec<_> = self
+            .dedup
+            .keys()
+            .copied()
+            .filter(|x| id_to_u64(*x) > since)
+            .collect();
+        ids.sort_unstable_by_key(|x| id_to_u64(*x));
+        ids
+    }
+
+    pub(crate) fn entry_ref(&self, id: NodeIdentifier) -> EntryRef<'_> {
+        self.internal.entry_ref(id).unwrap()
+    }
+
+    /// Inserts a node with a given identifier, e.g. one read from a snapshot.
+    pub(crate) fn insert_restored(
+        &mut self,
+        id: NodeIdentifier,
+        components: dyn_builder::BuiltEntity,
+    ) -> Result<(), crate::store::persist::SnapshotError> {
+        if self.internal.contains(id) {
+            return Err(crate::store::persist::SnapshotError::OutOfSync);
+        }
+        self.internal.extend(components.with_id(id));
+        let Self {
+            dedup,
+            internal,
+            hasher,
+            ..
+        } = self;
+        let node: HashedNodeRef<'_, NodeIdentifier> =
+            HashedNodeRef::new(internal.entry_ref(id).unwrap());
+        let hash = make_hash(hasher, &node);
+        match dedup.raw_entry_mut().from_hash(hash, |x| *x == id) {
+            hashbrown::hash_map::RawEntryMut::Vacant(vacant) => {
+                vacant.insert_with_hasher(hash, id, (), |id| {
+                    let node: HashedNodeRef<'_, NodeIdentifier> = internal
+                        .entry_ref(*id)
+                        .map(|x| HashedNodeRef::new(x))
+                        .unwrap();
+                    make_hash(hasher, &node)
+                });
+            }
+            hashbrown::hash_map::RawEntryMut::Occupied(_) => unreachable!(),
+        }
+        Ok(())
+    }
+
+    /// legion allocates identifiers from a process wide counter,
+    /// make sure it gets past identifiers restored from a snapshot.
+    pub(crate) fn skip_allocated(last: u64) {
+        use crate::store::persist::id_to_u64;
+        let mut allocator = legion::world::Allocate::new();
+        while allocator.next().map_or(false, |x| id_to_u64(x) <= last) {}
+    }
+}
+
 impl NodeStore {
     pub fn new() -> Self {
         Self {
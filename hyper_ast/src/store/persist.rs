@@ -0,0 +1,728 @@
+//! Versioned on-disk snapshots of [`SimpleStores`].
+//!
+//! A snapshot starts with a small header followed by a sequence of segments.
+//! Each segment contains the labels and the nodes added since the previous segment,
+//! and an opaque payload left to the caller (e.g. a table of processed commits).
+//! It makes it possible to append newly built subtrees to a snapshot without rewriting it.
+//!
+//! Identifiers are written as is,
+//! thus loading a snapshot in an empty store gives back the exact same [`NodeIdentifier`]s and label identifiers.
+//!
+//! Components are identified by the names given on registration in a [`ComponentRegistry`],
+//! because [`std::any::TypeId`]s are not stable between builds.
+//!
+//! ```
+//! use hyper_ast::store::persist::{ComponentRegistry, SnapshotReader, SnapshotWriter};
+//! use hyper_ast::store::SimpleStores;
+//! let registry = ComponentRegistry::new();
+//! let stores: SimpleStores<()> = SimpleStores::default();
+//! let mut writer = SnapshotWriter::create(vec![]).unwrap();
+//! writer.write_segment(&stores, &registry, b"payload").unwrap();
+//! let bytes = writer.into_inner();
+//!
+//! let mut loaded: SimpleStores<()> = SimpleStores::default();
+//! let mut reader = SnapshotReader::open(&bytes[..]).unwrap();
+//! let payload = reader.read_segment(&mut loaded, &registry).unwrap();
+//! assert_eq!(payload.as_deref(), Some(&b"payload"[..]));
+//! assert!(reader.read_segment(&mut loaded, &registry).unwrap().is_none());
+//! ```
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    io::{self, Read, Write},
+};
+
+use legion::storage::{Component, ComponentTypeId};
+
+use super::{
+    labels::{DefaultLabelIdentifier, LabelStore},
+    nodes::{
+        legion::{compo, dyn_builder, EntryRef, NodeIdentifier, NodeStore},
+        EntityBuilder as _,
+    },
+    SimpleStores,
+};
+
+pub const MAGIC: [u8; 4] = *b"HAST";
+/// Incremented on each breaking change of the layout of snapshots.
+pub const FORMAT_VERSION: u32 = 1;
+
+const SEGMENT_TAG: u8 = b'S';
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    NotASnapshot,
+    UnsupportedVersion(u32),
+    /// A node holds a component that was not registered, thus it cannot be written.
+    UnregisteredComponent(ComponentTypeId),
+    /// The snapshot contains a component that was not registered, thus it cannot be read.
+    UnknownComponent(String),
+    /// The store was modified since the snapshot was written or loaded,
+    /// so identifiers would not be preserved.
+    OutOfSync,
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(value: io::Error) -> Self {
+        SnapshotError::Io(value)
+    }
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "{}", err),
+            SnapshotError::NotASnapshot => write!(f, "not a HyperAST snapshot"),
+            SnapshotError::UnsupportedVersion(v) => write!(
+                f,
+                "unsupported snapshot version {}, expected {}",
+                v, FORMAT_VERSION
+            ),
+            SnapshotError::UnregisteredComponent(ty) => {
+                write!(f, "component {:?} is not registered", ty)
+            }
+            SnapshotError::UnknownComponent(name) => {
+                write!(f, "component '{}' is not registered", name)
+            }
+            SnapshotError::OutOfSync => {
+                write!(f, "the store diverged from the snapshot")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// Binary encoding of values written in snapshots, little endian.
+pub trait Persist: Sized {
+    fn persist(&self, w: &mut dyn Write) -> io::Result<()>;
+    fn restore(r: &mut dyn Read) -> io::Result<Self>;
+}
+
+pub(crate) fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+macro_rules! persist_int {
+    ( $( $t:ty ),* ) => {
+        $(
+            impl Persist for $t {
+                fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+                    w.write_all(&self.to_le_bytes())
+                }
+                fn restore(r: &mut dyn Read) -> io::Result<Self> {
+                    let mut b = [0; std::mem::size_of::<$t>()];
+                    r.read_exact(&mut b)?;
+                    Ok(<$t>::from_le_bytes(b))
+                }
+            }
+        )*
+    };
+}
+
+persist_int!(u8, u16, u32, u64, u128, i64);
+
+impl Persist for bool {
+    fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+        (*self as u8).persist(w)
+    }
+    fn restore(r: &mut dyn Read) -> io::Result<Self> {
+        Ok(u8::restore(r)? != 0)
+    }
+}
+
+impl Persist for String {
+    fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+        (self.len() as u32).persist(w)?;
+        w.write_all(self.as_bytes())
+    }
+    fn restore(r: &mut dyn Read) -> io::Result<Self> {
+        let len = u32::restore(r)? as usize;
+        let mut b = vec![0; len];
+        r.read_exact(&mut b)?;
+        String::from_utf8(b).map_err(|_| invalid_data("label is not utf8"))
+    }
+}
+
+impl<T: Persist> Persist for Box<[T]> {
+    fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+        (self.len() as u32).persist(w)?;
+        self.iter().try_for_each(|x| x.persist(w))
+    }
+    fn restore(r: &mut dyn Read) -> io::Result<Self> {
+        let len = u32::restore(r)? as usize;
+        (0..len).map(|_| T::restore(r)).collect()
+    }
+}
+
+impl<T: Persist> Persist for Vec<T> {
+    fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+        (self.len() as u32).persist(w)?;
+        self.iter().try_for_each(|x| x.persist(w))
+    }
+    fn restore(r: &mut dyn Read) -> io::Result<Self> {
+        let len = u32::restore(r)? as usize;
+        (0..len).map(|_| T::restore(r)).collect()
+    }
+}
+
+impl<T: Persist + Copy + Default, const N: usize> Persist for [T; N] {
+    fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+        self.iter().try_for_each(|x| x.persist(w))
+    }
+    fn restore(r: &mut dyn Read) -> io::Result<Self> {
+        let mut a = [T::default(); N];
+        for x in a.iter_mut() {
+            *x = T::restore(r)?;
+        }
+        Ok(a)
+    }
+}
+
+pub(crate) fn id_to_u64(id: NodeIdentifier) -> u64 {
+    unsafe { std::mem::transmute(id) }
+}
+
+pub(crate) fn id_from_u64(id: u64) -> io::Result<NodeIdentifier> {
+    if id == 0 {
+        return Err(invalid_data("null node identifier"));
+    }
+    Ok(unsafe { std::mem::transmute(id) })
+}
+
+impl Persist for NodeIdentifier {
+    fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+        id_to_u64(*self).persist(w)
+    }
+    fn restore(r: &mut dyn Read) -> io::Result<Self> {
+        id_from_u64(u64::restore(r)?)
+    }
+}
+
+impl Persist for DefaultLabelIdentifier {
+    fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+        use string_interner::Symbol;
+        (self.to_usize() as u32).persist(w)
+    }
+    fn restore(r: &mut dyn Read) -> io::Result<Self> {
+        let id = u32::restore(r)? as usize;
+        super::labels::label_id_from_usize(id).ok_or_else(|| invalid_data("bad label identifier"))
+    }
+}
+
+macro_rules! persist_newtype {
+    ( $( $t:ident ),* ) => {
+        $(
+            impl Persist for compo::$t {
+                fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+                    self.0.persist(w)
+                }
+                fn restore(r: &mut dyn Read) -> io::Result<Self> {
+                    Ok(compo::$t(Persist::restore(r)?))
+                }
+            }
+        )*
+    };
+}
+
+persist_newtype!(
+    Size,
+    SizeNoSpaces,
+    Height,
+    BytesLen,
+    LineCount,
+    HStruct,
+    HLabel,
+    CSStaticCount,
+    RoleOffsets
+);
+
+impl<T: Persist> Persist for compo::CS<T> {
+    fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+        self.0.persist(w)
+    }
+    fn restore(r: &mut dyn Read) -> io::Result<Self> {
+        Ok(compo::CS(Persist::restore(r)?))
+    }
+}
+
+impl<T: Persist + Eq> Persist for compo::NoSpacesCS<T> {
+    fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+        self.0.persist(w)
+    }
+    fn restore(r: &mut dyn Read) -> io::Result<Self> {
+        Ok(compo::NoSpacesCS(Persist::restore(r)?))
+    }
+}
+
+impl<T: Persist> Persist for compo::Precomp<T> {
+    fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+        self.0.persist(w)
+    }
+    fn restore(r: &mut dyn Read) -> io::Result<Self> {
+        Ok(compo::Precomp(Persist::restore(r)?))
+    }
+}
+
+impl Persist for compo::PrecompFlag {
+    fn persist(&self, _w: &mut dyn Write) -> io::Result<()> {
+        Ok(())
+    }
+    fn restore(_r: &mut dyn Read) -> io::Result<Self> {
+        Ok(compo::PrecompFlag)
+    }
+}
+
+impl Persist for crate::hashed::SyntaxNodeHashs<u32> {
+    fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+        self.structt.persist(w)?;
+        self.label.persist(w)?;
+        self.syntax.persist(w)
+    }
+    fn restore(r: &mut dyn Read) -> io::Result<Self> {
+        Ok(Self {
+            structt: Persist::restore(r)?,
+            label: Persist::restore(r)?,
+            syntax: Persist::restore(r)?,
+        })
+    }
+}
+
+impl Persist for crate::types::Role {
+    fn persist(&self, w: &mut dyn Write) -> io::Result<()> {
+        self.to_string().persist(w)
+    }
+    fn restore(r: &mut dyn Read) -> io::Result<Self> {
+        let s = String::restore(r)?;
+        crate::types::Role::try_from(s.as_str()).map_err(|_| invalid_data("unknown role"))
+    }
+}
+
+type WriteFn = fn(&EntryRef, &mut dyn Write) -> io::Result<()>;
+type ReadFn = fn(&mut dyn Read, &mut dyn_builder::EntityBuilder) -> io::Result<()>;
+
+struct Codec {
+    name: &'static str,
+    write: WriteFn,
+    read: ReadFn,
+}
+
+/// Maps components attached to nodes to stable names and to their encoding.
+///
+/// Each language registers its own components (e.g. its type),
+/// writing a node with an unregistered component fails rather than silently dropping data.
+#[derive(Default)]
+pub struct ComponentRegistry {
+    codecs: Vec<Codec>,
+    by_type: HashMap<ComponentTypeId, usize>,
+    by_name: HashMap<&'static str, usize>,
+}
+
+impl ComponentRegistry {
+    /// A registry that already knows about the components shared by all languages,
+    /// i.e. labels, children, hashes and basic metrics.
+    pub fn new() -> Self {
+        let mut r = Self::default();
+        r.register::<DefaultLabelIdentifier>("label")
+            .register::<compo::CS<NodeIdentifier>>("children")
+            .register::<compo::CS<DefaultLabelIdentifier>>("children_names")
+            .register::<compo::NoSpacesCS<NodeIdentifier>>("children_no_spaces")
+            .register::<crate::hashed::SyntaxNodeHashs<u32>>("hashs")
+            .register::<compo::Size>("size")
+            .register::<compo::SizeNoSpaces>("size_no_spaces")
+            .register::<compo::Height>("height")
+            .register::<compo::BytesLen>("bytes_len")
+            .register::<compo::LineCount>("line_count")
+            .register::<compo::HStruct>("hash_struct")
+            .register::<compo::HLabel>("hash_label")
+            .register::<compo::CSStaticCount>("children_static_count")
+            .register::<compo::RoleOffsets>("role_offsets")
+            .register::<Box<[crate::types::Role]>>("roles")
+            .register::<compo::Precomp<u16>>("precomp")
+            .register::<compo::PrecompFlag>("precomp_flag")
+            .register::<crate::filter::BloomSize>("bloom_size")
+            .register::<crate::cyclomatic::Mcc>("mcc");
+        crate::filter::register_blooms(&mut r);
+        r
+    }
+
+    /// Register `T` under `name`, using its [`Persist`] implementation.
+    pub fn register<T: Component + Persist>(&mut self, name: &'static str) -> &mut Self {
+        fn write<T: Component + Persist>(e: &EntryRef, w: &mut dyn Write) -> io::Result<()> {
+            e.get_component::<T>()
+                .map_err(|_| invalid_data("missing component"))?
+                .persist(w)
+        }
+        fn read<T: Component + Persist>(
+            r: &mut dyn Read,
+            builder: &mut dyn_builder::EntityBuilder,
+        ) -> io::Result<()> {
+            builder.add(T::restore(r)?);
+            Ok(())
+        }
+        self.register_raw::<T>(name, write::<T>, read::<T>)
+    }
+
+    /// Register a component with explicit encoding functions,
+    /// useful when [`Persist`] cannot be implemented on `T` because of coherence rules.
+    pub fn register_raw<T: Component>(
+        &mut self,
+        name: &'static str,
+        write: WriteFn,
+        read: ReadFn,
+    ) -> &mut Self {
+        assert!(
+            !self.by_name.contains_key(name),
+            "{} is already registered",
+            name
+        );
+        let i = self.codecs.len();
+        self.codecs.push(Codec { name, write, read });
+        self.by_type.insert(ComponentTypeId::of::<T>(), i);
+        self.by_name.insert(name, i);
+        self
+    }
+
+    pub(crate) fn index_of(&self, ty: &ComponentTypeId) -> Option<usize> {
+        self.by_type.get(ty).copied()
+    }
+
+    pub(crate) fn write(&self, i: usize, e: &EntryRef, w: &mut dyn Write) -> io::Result<()> {
+        (self.codecs[i].write)(e, w)
+    }
+
+    pub(crate) fn read(
+        &self,
+        i: usize,
+        r: &mut dyn Read,
+        builder: &mut dyn_builder::EntityBuilder,
+    ) -> io::Result<()> {
+        (self.codecs[i].read)(r, builder)
+    }
+
+    fn persist_names(&self, w: &mut dyn Write) -> io::Result<()> {
+        (self.codecs.len() as u16).persist(w)?;
+        self.codecs
+            .iter()
+            .try_for_each(|c| c.name.to_string().persist(w))
+    }
+
+    /// Maps the component indexes used in a segment to the ones of this registry.
+    fn restore_names(&self, r: &mut dyn Read) -> io::Result<Vec<Result<usize, String>>> {
+        let len = u16::restore(r)?;
+        (0..len)
+            .map(|_| {
+                let name = String::restore(r)?;
+                Ok(self.by_name.get(name.as_str()).copied().ok_or(name))
+            })
+            .collect()
+    }
+}
+
+/// How far a snapshot goes, i.e. what was already written to or read from it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Watermark {
+    labels: usize,
+    nodes: usize,
+    last_node: u64,
+}
+
+impl Watermark {
+    fn check<TS>(&self, stores: &SimpleStores<TS>) -> Result<(), SnapshotError> {
+        if stores.label_store.len() < self.labels || stores.node_store.len() < self.nodes {
+            return Err(SnapshotError::OutOfSync);
+        }
+        Ok(())
+    }
+}
+
+fn write_header(w: &mut dyn Write) -> io::Result<()> {
+    w.write_all(&MAGIC)?;
+    FORMAT_VERSION.persist(w)
+}
+
+/// Writes segments of a snapshot.
+pub struct SnapshotWriter<W: Write> {
+    w: W,
+    watermark: Watermark,
+}
+
+impl<W: Write> SnapshotWriter<W> {
+    /// Starts a new snapshot.
+    pub fn create(mut w: W) -> io::Result<Self> {
+        write_header(&mut w)?;
+        Ok(Self {
+            w,
+            watermark: Default::default(),
+        })
+    }
+
+    /// Continues a snapshot, `w` should be positioned at its end,
+    /// and `watermark` is the one of the [`SnapshotWriter`] or [`SnapshotReader`] that last handled it.
+    pub fn append(w: W, watermark: Watermark) -> Self {
+        Self { w, watermark }
+    }
+
+    pub fn watermark(&self) -> Watermark {
+        self.watermark
+    }
+
+    /// Writes the labels and nodes added to `stores` since the last segment, followed by `payload`.
+    pub fn write_segment<TS>(
+        &mut self,
+        stores: &SimpleStores<TS>,
+        registry: &ComponentRegistry,
+        payload: &[u8],
+    ) -> Result<(), SnapshotError> {
+        self.watermark.check(stores)?;
+        let w = &mut self.w as &mut dyn Write;
+        SEGMENT_TAG.persist(w)?;
+        let labels = stores.label_store.persist_labels(self.watermark.labels, w)?;
+        registry.persist_names(w)?;
+        let (last_node, nodes) =
+            stores
+                .node_store
+                .persist_nodes(self.watermark.last_node, registry, w)?;
+        (payload.len() as u64).persist(w)?;
+        w.write_all(payload)?;
+        w.flush()?;
+        self.watermark = Watermark {
+            labels: self.watermark.labels + labels,
+            nodes: self.watermark.nodes + nodes,
+            last_node,
+        };
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.w
+    }
+}
+
+/// Reads segments of a snapshot.
+pub struct SnapshotReader<R: Read> {
+    r: R,
+    watermark: Watermark,
+}
+
+impl<R: Read> SnapshotReader<R> {
+    pub fn open(mut r: R) -> Result<Self, SnapshotError> {
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)
+            .map_err(|_| SnapshotError::NotASnapshot)?;
+        if magic != MAGIC {
+            return Err(SnapshotError::NotASnapshot);
+        }
+        let version = u32::restore(&mut r)?;
+        if version != FORMAT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        Ok(Self {
+            r,
+            watermark: Default::default(),
+        })
+    }
+
+    pub fn watermark(&self) -> Watermark {
+        self.watermark
+    }
+
+    /// Loads the next segment in `stores` and returns its payload,
+    /// or `None` at the end of the snapshot.
+    ///
+    /// `stores` must only contain what was previously read from this snapshot.
+    pub fn read_segment<TS>(
+        &mut self,
+        stores: &mut SimpleStores<TS>,
+        registry: &ComponentRegistry,
+    ) -> Result<Option<Vec<u8>>, SnapshotError> {
+        if stores.label_store.len() != self.watermark.labels
+            || stores.node_store.len() != self.watermark.nodes
+        {
+            return Err(SnapshotError::OutOfSync);
+        }
+        let r = &mut self.r as &mut dyn Read;
+        let mut tag = [0; 1];
+        if r.read(&mut tag)? == 0 {
+            return Ok(None);
+        }
+        if tag[0] != SEGMENT_TAG {
+            return Err(invalid_data("bad segment tag").into());
+        }
+        let labels = stores.label_store.restore_labels(self.watermark.labels, r)?;
+        let names = registry.restore_names(r)?;
+        let (last_node, nodes) = stores.node_store.restore_nodes(&names, registry, r)?;
+        let len = u64::restore(r)? as usize;
+        let mut payload = vec![0; len];
+        r.read_exact(&mut payload)?;
+        self.watermark = Watermark {
+            labels: self.watermark.labels + labels,
+            nodes: self.watermark.nodes + nodes,
+            last_node: last_node.max(self.watermark.last_node),
+        };
+        Ok(Some(payload))
+    }
+}
+
+impl LabelStore {
+    /// Writes the labels interned from index `start`, returns how many were written.
+    fn persist_labels(&self, start: usize, w: &mut dyn Write) -> io::Result<usize> {
+        use crate::types::LabelStore as _;
+        let end = self.len();
+        (start as u64).persist(w)?;
+        ((end - start) as u64).persist(w)?;
+        for i in start..end {
+            let id = super::labels::label_id_from_usize(i).unwrap();
+            w.write_all(&(self.resolve(&id).len() as u32).to_le_bytes())?;
+            w.write_all(self.resolve(&id).as_bytes())?;
+        }
+        Ok(end - start)
+    }
+
+    /// Interns labels in the order they were written so that they get back their identifiers.
+    fn restore_labels(&mut self, start: usize, r: &mut dyn Read) -> Result<usize, SnapshotError> {
+        use crate::types::LabelStore as _;
+        use string_interner::Symbol;
+        if u64::restore(r)? as usize != start {
+            return Err(SnapshotError::OutOfSync);
+        }
+        let count = u64::restore(r)? as usize;
+        for i in start..start + count {
+            let s = String::restore(r)?;
+            let id = self.get_or_insert(s.as_str());
+            if id.to_usize() != i {
+                return Err(SnapshotError::OutOfSync);
+            }
+        }
+        Ok(count)
+    }
+}
+
+impl NodeStore {
+    /// Writes the nodes allocated after `since`, in allocation order,
+    /// returns the greatest identifier written and how many nodes were written.
+    fn persist_nodes(
+        &self,
+        since: u64,
+        registry: &ComponentRegistry,
+        w: &mut dyn Write,
+    ) -> Result<(u64, usize), SnapshotError> {
+        let ids = self.ids_after(since);
+        (ids.len() as u64).persist(w)?;
+        for id in &ids {
+            let entry = self.entry_ref(*id);
+            let types = entry.archetype().layout().component_types();
+            id.persist(w)?;
+            (types.len() as u16).persist(w)?;
+            for ty in types {
+                let i = registry
+                    .index_of(ty)
+                    .ok_or(SnapshotError::UnregisteredComponent(*ty))?;
+                (i as u16).persist(w)?;
+                registry.write(i, &entry, w)?;
+            }
+        }
+        let last = ids.last().map_or(since, |x| id_to_u64(*x));
+        Ok((last, ids.len()))
+    }
+
+    fn restore_nodes(
+        &mut self,
+        names: &[Result<usize, String>],
+        registry: &ComponentRegistry,
+        r: &mut dyn Read,
+    ) -> Result<(u64, usize), SnapshotError> {
+        let count = u64::restore(r)? as usize;
+        let mut last = 0;
+        for _ in 0..count {
+            let id = NodeIdentifier::restore(r)?;
+            let mut builder = dyn_builder::EntityBuilder::new();
+            for _ in 0..u16::restore(r)? {
+                let i = u16::restore(r)? as usize;
+                let i = match names.get(i) {
+                    Some(Ok(i)) => *i,
+                    Some(Err(name)) => return Err(SnapshotError::UnknownComponent(name.clone())),
+                    None => return Err(invalid_data("bad component index").into()),
+                };
+                registry.read(i, r, &mut builder)?;
+            }
+            self.insert_restored(id, builder.build())?;
+            last = last.max(id_to_u64(id));
+        }
+        NodeStore::skip_allocated(last);
+        Ok((last, count))
+    }
+}
+
+#[test]
+fn roundtrip_with_append() {
+    use crate::hashed::SyntaxNodeHashs;
+    use crate::store::nodes::legion::eq_node;
+    use crate::types::{IterableChildren as _, LabelStore as _, WithChildren as _};
+    type Stores = SimpleStores<()>;
+
+    fn insert(stores: &mut Stores, kind: u16, label: &str, cs: &[NodeIdentifier]) -> NodeIdentifier {
+        use string_interner::Symbol;
+        let label = stores.label_store.get_or_insert(label);
+        let hashs = SyntaxNodeHashs {
+            structt: kind as u32,
+            label: label.to_usize() as u32,
+            syntax: (kind as u32) << 16 | (label.to_usize() as u32) << 4 | cs.len() as u32,
+        };
+        let eq = eq_node(&kind, Some(&label), cs);
+        let insertion = stores.node_store.prepare_insertion(&hashs.syntax, eq);
+        if let Some(id) = insertion.occupied_id() {
+            return id;
+        }
+        let mut builder = dyn_builder::EntityBuilder::new();
+        builder.add(kind);
+        builder.add(label);
+        builder.add(hashs);
+        if !cs.is_empty() {
+            builder.add(compo::CS(cs.to_vec().into_boxed_slice()));
+        }
+        NodeStore::insert_built_after_prepare(insertion.vacant(), builder.build())
+    }
+
+    let mut registry = ComponentRegistry::new();
+    registry.register::<u16>("kind");
+
+    let mut stores = Stores::default();
+    let x = insert(&mut stores, 1, "x", &[]);
+    let y = insert(&mut stores, 1, "y", &[]);
+    let root = insert(&mut stores, 2, "root", &[x, y]);
+    let mut writer = SnapshotWriter::create(vec![]).unwrap();
+    writer.write_segment(&stores, &registry, b"first").unwrap();
+
+    let z = insert(&mut stores, 1, "z", &[]);
+    let root2 = insert(&mut stores, 2, "root", &[x, z]);
+    let watermark = writer.watermark();
+    let bytes = writer.into_inner();
+    let mut writer = SnapshotWriter::append(bytes, watermark);
+    writer.write_segment(&stores, &registry, b"second").unwrap();
+    let bytes = writer.into_inner();
+
+    let mut loaded = Stores::default();
+    let mut reader = SnapshotReader::open(&bytes[..]).unwrap();
+    let first = reader.read_segment(&mut loaded, &registry).unwrap();
+    assert_eq!(first.as_deref(), Some(&b"first"[..]));
+    assert_eq!(3, loaded.node_store.len());
+    let second = reader.read_segment(&mut loaded, &registry).unwrap();
+    assert_eq!(second.as_deref(), Some(&b"second"[..]));
+    assert!(reader.read_segment(&mut loaded, &registry).unwrap().is_none());
+    assert_eq!(reader.watermark(), writer.watermark());
+
+    assert_eq!(stores.node_store.len(), loaded.node_store.len());
+    assert_eq!(stores.label_store.len(), loaded.label_store.len());
+    let n = loaded.node_store.resolve(root2);
+    assert_eq!(n.children().unwrap().iter_children().copied().collect::<Vec<_>>(), vec![x, z]);
+    let n = loaded.node_store.resolve(root);
+    assert_eq!(n.children().unwrap().iter_children().copied().collect::<Vec<_>>(), vec![x, y]);
+    assert_eq!(loaded.label_store.resolve(n.get_component::<DefaultLabelIdentifier>().unwrap()), "root");
+
+    // deduplication still works and new nodes do not collide with restored ones
+    assert_eq!(y, insert(&mut loaded, 1, "y", &[]));
+    let w = insert(&mut loaded, 1, "w", &[]);
+    assert!(![x, y, z, root, root2].contains(&w));
+}
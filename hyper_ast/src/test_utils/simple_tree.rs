@@ -644,7 +644,7 @@ where
 pub struct LS<I: PrimInt> {
     // v: RefCell<Vec<crate::types::OwnedLabel>>,
     v: Vec<crate::types::OwnedLabel>,
-    phantom: PhantomData<*const I>,
+    phantom: PhantomData<fn() -> I>,
 }
 
 impl<'a, I: PrimInt> LabelStore<crate::types::SlicedLabel> for LS<I> {
@@ -75,6 +75,24 @@ role_impl!(
     Element => "element",
     Consequence => "consequence",
     Key => "key",
+    Function => "function",
+    Attribute => "attribute",
+    Argument => "argument",
+    Operator => "operator",
+    Operators => "operators",
+    ReturnType => "return_type",
+    Definition => "definition",
+    Alias => "alias",
+    ModuleName => "module_name",
+    Superclasses => "superclasses",
+    Subscript => "subscript",
+    Subject => "subject",
+    Guard => "guard",
+    Cause => "cause",
+    Code => "code",
+    Expression => "expression",
+    FormatSpecifier => "format_specifier",
+    TypeConversion => "type_conversion",
 );
 
 #[allow(unused)]
@@ -1208,6 +1226,20 @@ impl<L: LLang<Self, I = u16>> Display for TypeU16<L> {
     }
 }
 
+#[cfg(feature = "legion")]
+impl<L: LLang<Self, I = u16>> crate::store::persist::Persist for TypeU16<L> {
+    fn persist(&self, w: &mut dyn std::io::Write) -> std::io::Result<()> {
+        crate::store::persist::Persist::persist(&self.0, w)
+    }
+    fn restore(r: &mut dyn std::io::Read) -> std::io::Result<Self> {
+        let t: u16 = crate::store::persist::Persist::restore(r)?;
+        if t as usize >= L::TE.len() {
+            return Err(crate::store::persist::invalid_data("bad type"));
+        }
+        Ok(Self(t, std::marker::PhantomData))
+    }
+}
+
 impl<L: LLang<Self, I = u16>> TypeU16<L> {
     pub fn e(&self) -> L::E {
         debug_assert!(L::TE.len() <= u16::MAX as usize);
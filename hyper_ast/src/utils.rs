@@ -184,6 +184,17 @@ impl Bytes {
     }
 }
 
+#[cfg(feature = "legion")]
+impl crate::store::persist::Persist for Bytes {
+    fn persist(&self, w: &mut dyn std::io::Write) -> std::io::Result<()> {
+        crate::store::persist::Persist::persist(&(self.0 as i64), w)
+    }
+    fn restore(r: &mut dyn std::io::Read) -> std::io::Result<Self> {
+        let x: i64 = crate::store::persist::Persist::restore(r)?;
+        Ok(Bytes(x as isize))
+    }
+}
+
 impl fmt::Display for Bytes {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let bytes = self.0;
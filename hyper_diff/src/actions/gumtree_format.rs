@@ -0,0 +1,643 @@
+//! Exporting and importing edit scripts and mappings in the JSON and XML formats of GumTree.
+//!
+//! Nodes are referenced as GumTree prints them, i.e. `type: label [start,end]`,
+//! with byte offsets relative to their file, as computed by [`hyper_ast::position`].
+//! Like GumTree, it is meant for diffs of files,
+//! identical nodes in different files of a directory cannot be told apart.
+//!
+//! The `parent` of insertions and moves is a node of the destination on export,
+//! on import it can also be a matched node of the source, as GumTree prints it.
+//! Imported actions only have original paths, i.e. `path.mid` is the same as `path.ori`.
+use std::{collections::HashMap, fmt, ops::Range, str::FromStr};
+
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use hyper_ast::{
+    position::compute_position,
+    store::{
+        defaults::{LabelIdentifier, NodeIdentifier},
+        nodes::HashedNodeRef,
+    },
+    types::{
+        HyperAST, HyperType, IterableChildren, LabelStore, Labeled, NodeStore, WithChildren,
+        WithSerialization,
+    },
+};
+
+use crate::{
+    decompressed_tree_store::{DecompressedWithParent, ShallowDecompressedTreeStore},
+    matchers::mapping_store::{MappingStore, MonoMappingStore},
+    tree::tree_path::{CompressedTreePath, TreePath},
+};
+
+use super::{
+    action_vec::ActionsVec,
+    script_generator2::{Act, ApplicablePath, SimpleAction},
+};
+
+#[derive(Debug)]
+pub enum GtFormatError {
+    Json(serde_json::Error),
+    Xml(String),
+    /// a node reference that is not formatted as `type: label [start,end]`
+    BadTree(String),
+    /// a node reference that matches no node of the trees
+    UnknownTree(GtTree),
+    /// a new label that is not in the label store
+    UnknownLabel(String),
+    /// an insertion or a move without a parent or a position
+    MissingParent(GtTree),
+}
+
+impl fmt::Display for GtFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GtFormatError::Json(e) => write!(f, "bad json: {}", e),
+            GtFormatError::Xml(e) => write!(f, "bad xml: {}", e),
+            GtFormatError::BadTree(s) => write!(f, "bad node reference: {:?}", s),
+            GtFormatError::UnknownTree(t) => write!(f, "unknown node: {}", t),
+            GtFormatError::UnknownLabel(l) => write!(f, "unknown label: {:?}", l),
+            GtFormatError::MissingParent(t) => write!(f, "missing parent of: {}", t),
+        }
+    }
+}
+
+impl std::error::Error for GtFormatError {}
+
+impl From<serde_json::Error> for GtFormatError {
+    fn from(value: serde_json::Error) -> Self {
+        GtFormatError::Json(value)
+    }
+}
+
+/// A node, as referenced by GumTree, i.e. `type: label [start,end]`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct GtTree {
+    pub r#type: String,
+    /// missing when empty
+    pub label: Option<String>,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl GtTree {
+    fn new<'store, HAST>(stores: &'store HAST, x: NodeIdentifier, range: Range<usize>) -> Self
+    where
+        HAST: HyperAST<
+            'store,
+            T = HashedNodeRef<'store>,
+            IdN = NodeIdentifier,
+            Label = LabelIdentifier,
+        >,
+    {
+        let r = stores.node_store().resolve(&x);
+        GtTree {
+            r#type: stores.resolve_type(&x).to_string(),
+            label: r
+                .try_get_label()
+                .map(|l| stores.label_store().resolve(l).to_string())
+                .filter(|l| !l.is_empty()),
+            start: range.start,
+            end: range.end,
+        }
+    }
+
+    /// The node at `path` from `root`.
+    fn at<'store, HAST>(
+        stores: &'store HAST,
+        root: NodeIdentifier,
+        mut path: impl Iterator<Item = u16>,
+    ) -> Self
+    where
+        HAST: HyperAST<
+            'store,
+            T = HashedNodeRef<'store>,
+            IdN = NodeIdentifier,
+            Label = LabelIdentifier,
+        >,
+    {
+        let (pos, x) = compute_position(root, &mut path, stores);
+        Self::new(stores, x, pos.range())
+    }
+}
+
+impl fmt::Display for GtTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.r#type)?;
+        if let Some(label) = &self.label {
+            write!(f, ": {}", label)?;
+        }
+        write!(f, " [{},{}]", self.start, self.end)
+    }
+}
+
+impl FromStr for GtTree {
+    type Err = GtFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || GtFormatError::BadTree(s.to_string());
+        let (node, range) = s
+            .strip_suffix(']')
+            .and_then(|s| s.rsplit_once(" ["))
+            .ok_or_else(bad)?;
+        let (start, end) = range.split_once(',').ok_or_else(bad)?;
+        let (r#type, label) = match node.split_once(": ") {
+            Some((t, l)) => (t, Some(l.to_string())),
+            None => (node, None),
+        };
+        Ok(GtTree {
+            r#type: r#type.to_string(),
+            label,
+            start: start.trim().parse().map_err(|_| bad())?,
+            end: end.trim().parse().map_err(|_| bad())?,
+        })
+    }
+}
+
+impl TryFrom<String> for GtTree {
+    type Error = GtFormatError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<GtTree> for String {
+    fn from(value: GtTree) -> Self {
+        value.to_string()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GtMatch {
+    pub src: GtTree,
+    pub dest: GtTree,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GtActionKind {
+    #[serde(rename = "update-node")]
+    UpdateNode,
+    #[serde(rename = "move-tree")]
+    MoveTree,
+    #[serde(rename = "insert-node")]
+    InsertNode,
+    #[serde(rename = "insert-tree")]
+    InsertTree,
+    #[serde(rename = "delete-node")]
+    DeleteNode,
+    #[serde(rename = "delete-tree")]
+    DeleteTree,
+}
+
+impl GtActionKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GtActionKind::UpdateNode => "update-node",
+            GtActionKind::MoveTree => "move-tree",
+            GtActionKind::InsertNode => "insert-node",
+            GtActionKind::InsertTree => "insert-tree",
+            GtActionKind::DeleteNode => "delete-node",
+            GtActionKind::DeleteTree => "delete-tree",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "update-node" => GtActionKind::UpdateNode,
+            "move-tree" => GtActionKind::MoveTree,
+            "insert-node" => GtActionKind::InsertNode,
+            "insert-tree" => GtActionKind::InsertTree,
+            "delete-node" => GtActionKind::DeleteNode,
+            "delete-tree" => GtActionKind::DeleteTree,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GtAction {
+    pub action: GtActionKind,
+    pub tree: GtTree,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<GtTree>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub at: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// The output of `gumtree textdiff` in its JSON (`-f JSON`) or XML (`-f XML`) format.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtDiff {
+    #[serde(default)]
+    pub matches: Vec<GtMatch>,
+    #[serde(default)]
+    pub actions: Vec<GtAction>,
+}
+
+impl GtDiff {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, GtFormatError> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    pub fn to_xml(&self) -> String {
+        use quick_xml::escape::escape;
+        let mut s = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<diff>\n");
+        s += "  <matches>\n";
+        for m in &self.matches {
+            s += &format!(
+                "    <match src=\"{}\" dest=\"{}\"/>\n",
+                escape(&m.src.to_string()),
+                escape(&m.dest.to_string())
+            );
+        }
+        s += "  </matches>\n  <actions>\n";
+        for a in &self.actions {
+            s += &format!(
+                "    <{} tree=\"{}\"",
+                a.action.name(),
+                escape(&a.tree.to_string())
+            );
+            if let Some(parent) = &a.parent {
+                s += &format!(" parent=\"{}\"", escape(&parent.to_string()));
+            }
+            if let Some(at) = a.at {
+                s += &format!(" at=\"{}\"", at);
+            }
+            if let Some(label) = &a.label {
+                s += &format!(" label=\"{}\"", escape(label));
+            }
+            s += "/>\n";
+        }
+        s += "  </actions>\n</diff>\n";
+        s
+    }
+
+    pub fn from_xml(s: &str) -> Result<Self, GtFormatError> {
+        use quick_xml::events::Event;
+        let xml_err = |e: &dyn fmt::Display| GtFormatError::Xml(e.to_string());
+        let mut reader = quick_xml::Reader::from_str(s);
+        let mut diff = GtDiff::default();
+        loop {
+            let e = match reader.read_event().map_err(|e| xml_err(&e))? {
+                Event::Eof => break,
+                Event::Empty(e) | Event::Start(e) => e,
+                _ => continue,
+            };
+            let mut attrs: HashMap<String, String> = HashMap::new();
+            for a in e.attributes() {
+                let a = a.map_err(|e| xml_err(&e))?;
+                let key = String::from_utf8_lossy(a.key.as_ref()).to_string();
+                let value = a.unescape_value().map_err(|e| xml_err(&e))?;
+                attrs.insert(key, value.to_string());
+            }
+            let mut tree = |name: &str| -> Result<GtTree, GtFormatError> {
+                attrs
+                    .remove(name)
+                    .ok_or_else(|| GtFormatError::Xml(format!("missing attribute {}", name)))?
+                    .parse()
+            };
+            let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+            if name == "match" {
+                diff.matches.push(GtMatch {
+                    src: tree("src")?,
+                    dest: tree("dest")?,
+                });
+            } else if let Some(action) = GtActionKind::from_name(&name) {
+                let tree = tree("tree")?;
+                let parent = attrs.remove("parent").map(|p| p.parse()).transpose()?;
+                let at = attrs
+                    .remove("at")
+                    .map(|at| at.parse().map_err(|_| xml_err(&"bad position")))
+                    .transpose()?;
+                diff.actions.push(GtAction {
+                    action,
+                    tree,
+                    parent,
+                    at,
+                    label: attrs.remove("label"),
+                });
+            }
+        }
+        Ok(diff)
+    }
+}
+
+/// Exports the edit script `actions` going from `src` to `dst`.
+pub fn export_actions<'store, P, HAST>(
+    stores: &'store HAST,
+    src: NodeIdentifier,
+    dst: NodeIdentifier,
+    actions: &ActionsVec<SimpleAction<LabelIdentifier, P, NodeIdentifier>>,
+) -> Vec<GtAction>
+where
+    P: TreePath<Item = u16> + PartialEq,
+    HAST:
+        HyperAST<'store, T = HashedNodeRef<'store>, IdN = NodeIdentifier, Label = LabelIdentifier>,
+{
+    let label = |l: &LabelIdentifier| Some(stores.label_store().resolve(l).to_string());
+    // parent in the destination and position in it
+    let parent = |path: &P| {
+        let path: Vec<u16> = path.iter().collect();
+        let (at, parent) = path.split_last().unwrap();
+        (
+            Some(GtTree::at(stores, dst, parent.iter().copied())),
+            Some(*at as usize),
+        )
+    };
+    let moved = |from: &P, path: &P| {
+        let (parent, at) = parent(path);
+        GtAction {
+            action: GtActionKind::MoveTree,
+            tree: GtTree::at(stores, src, from.iter()),
+            parent,
+            at,
+            label: None,
+        }
+    };
+    let updated = |tree: GtTree, new: &LabelIdentifier| GtAction {
+        action: GtActionKind::UpdateNode,
+        tree,
+        parent: None,
+        at: None,
+        label: label(new),
+    };
+    let mut result = vec![];
+    let mut it = actions.iter().peekable();
+    while let Some(a) = it.next() {
+        match &a.action {
+            Act::Update { new } => match it.peek() {
+                // the label of a moved node changed, its path is in the destination
+                Some(SimpleAction {
+                    path,
+                    action: Act::Move { from },
+                }) if path.ori == a.path.ori => {
+                    it.next();
+                    let tree = GtTree::at(stores, src, from.ori.iter());
+                    result.push(updated(tree, new));
+                    result.push(moved(&from.ori, &a.path.ori));
+                }
+                _ => {
+                    let tree = GtTree::at(stores, src, a.path.ori.iter());
+                    result.push(updated(tree, new));
+                }
+            },
+            Act::MovUpd { from, new } => {
+                let tree = GtTree::at(stores, src, from.ori.iter());
+                result.push(updated(tree, new));
+                result.push(moved(&from.ori, &a.path.ori));
+            }
+            Act::Move { from } => result.push(moved(&from.ori, &a.path.ori)),
+            Act::Delete {} => result.push(GtAction {
+                action: GtActionKind::DeleteNode,
+                tree: GtTree::at(stores, src, a.path.ori.iter()),
+                parent: None,
+                at: None,
+                label: None,
+            }),
+            Act::Insert { .. } => {
+                let (parent, at) = parent(&a.path.ori);
+                result.push(GtAction {
+                    action: GtActionKind::InsertNode,
+                    tree: GtTree::at(stores, dst, a.path.ori.iter()),
+                    parent,
+                    at,
+                    label: None,
+                })
+            }
+        }
+    }
+    result
+}
+
+/// Exports the `mappings` between the decompressed `src_arena` and `dst_arena`,
+/// respectively decompressing `src` and `dst`.
+pub fn export_matches<'store, 'a, HAST, SD, DD, M>(
+    stores: &'store HAST,
+    src: NodeIdentifier,
+    dst: NodeIdentifier,
+    src_arena: &'a SD,
+    dst_arena: &'a DD,
+    mappings: &M,
+) -> Vec<GtMatch>
+where
+    HAST:
+        HyperAST<'store, T = HashedNodeRef<'store>, IdN = NodeIdentifier, Label = LabelIdentifier>,
+    M: MonoMappingStore,
+    SD: DecompressedWithParent<'a, HAST::T, M::Src>
+        + ShallowDecompressedTreeStore<'a, HAST::T, M::Src>,
+    DD: DecompressedWithParent<'a, HAST::T, M::Dst>
+        + ShallowDecompressedTreeStore<'a, HAST::T, M::Dst>,
+{
+    mappings
+        .iter()
+        .map(|(a, b)| GtMatch {
+            src: GtTree::at(stores, src, src_arena.path_rooted(&a).into_iter()),
+            dest: GtTree::at(stores, dst, dst_arena.path_rooted(&b).into_iter()),
+        })
+        .collect()
+}
+
+/// Paths of the nodes of a tree, by their GumTree reference.
+struct TreeIndex(HashMap<GtTree, Vec<u16>>);
+
+impl TreeIndex {
+    fn new<'store, HAST>(stores: &'store HAST, root: NodeIdentifier) -> Self
+    where
+        HAST: HyperAST<
+            'store,
+            T = HashedNodeRef<'store>,
+            IdN = NodeIdentifier,
+            Label = LabelIdentifier,
+        >,
+    {
+        let mut index = TreeIndex(HashMap::new());
+        index.visit(stores, root, 0, &mut vec![]);
+        index
+    }
+
+    fn visit<'store, HAST>(
+        &mut self,
+        stores: &'store HAST,
+        x: NodeIdentifier,
+        offset: usize,
+        path: &mut Vec<u16>,
+    ) where
+        HAST: HyperAST<
+            'store,
+            T = HashedNodeRef<'store>,
+            IdN = NodeIdentifier,
+            Label = LabelIdentifier,
+        >,
+    {
+        let len = |x: &NodeIdentifier| {
+            let b = stores.node_store().resolve(x);
+            b.try_bytes_len().unwrap_or(0).to_usize().unwrap()
+        };
+        let is_dir = stores.resolve_type(&x).is_directory();
+        let range = offset..offset + if is_dir { 0 } else { len(&x) };
+        // the outermost node wins, like when looking for them top-down
+        self.0
+            .entry(GtTree::new(stores, x, range))
+            .or_insert_with(|| path.clone());
+        let b = stores.node_store().resolve(&x);
+        let Some(cs) = b.children() else {
+            return;
+        };
+        let mut offset = offset;
+        for (i, c) in cs.iter_children().enumerate() {
+            path.push(i as u16);
+            self.visit(stores, *c, offset, path);
+            path.pop();
+            if !is_dir {
+                offset += len(c);
+            }
+        }
+    }
+
+    fn get(&self, tree: &GtTree) -> Result<&[u16], GtFormatError> {
+        self.0
+            .get(tree)
+            .map(|x| x.as_slice())
+            .ok_or_else(|| GtFormatError::UnknownTree(tree.clone()))
+    }
+}
+
+fn node_at<'store, HAST>(stores: &'store HAST, root: NodeIdentifier, path: &[u16]) -> NodeIdentifier
+where
+    HAST:
+        HyperAST<'store, T = HashedNodeRef<'store>, IdN = NodeIdentifier, Label = LabelIdentifier>,
+{
+    let mut x = root;
+    for i in path {
+        x = stores.node_store().resolve(&x).child(i).unwrap();
+    }
+    x
+}
+
+impl GtDiff {
+    /// Imports the edit script going from `src` to `dst`.
+    pub fn import_actions<'store, HAST>(
+        &self,
+        stores: &'store HAST,
+        src: NodeIdentifier,
+        dst: NodeIdentifier,
+    ) -> Result<
+        ActionsVec<SimpleAction<LabelIdentifier, CompressedTreePath<u16>, NodeIdentifier>>,
+        GtFormatError,
+    >
+    where
+        HAST: HyperAST<
+            'store,
+            T = HashedNodeRef<'store>,
+            IdN = NodeIdentifier,
+            Label = LabelIdentifier,
+        >,
+    {
+        let src_index = TreeIndex::new(stores, src);
+        let dst_index = TreeIndex::new(stores, dst);
+        let matches: HashMap<&GtTree, &GtTree> =
+            self.matches.iter().map(|m| (&m.src, &m.dest)).collect();
+        let path = |p: &[u16]| {
+            let p: CompressedTreePath<u16> = p.to_vec().into();
+            ApplicablePath {
+                ori: p.clone(),
+                mid: p,
+            }
+        };
+        // path in the destination, from the parent and the position in it
+        let dst_path = |a: &GtAction| -> Result<Vec<u16>, GtFormatError> {
+            let missing = || GtFormatError::MissingParent(a.tree.clone());
+            let parent = a.parent.as_ref().ok_or_else(missing)?;
+            let at = a.at.ok_or_else(missing)?;
+            let mut p = match dst_index.get(parent) {
+                Ok(p) => p.to_vec(),
+                Err(e) => match matches.get(parent) {
+                    Some(parent) => dst_index.get(parent)?.to_vec(),
+                    None => return Err(e),
+                },
+            };
+            p.push(at as u16);
+            Ok(p)
+        };
+        let mut actions = ActionsVec::new();
+        for a in &self.actions {
+            let action = match a.action {
+                GtActionKind::UpdateNode => {
+                    let label = a.label.as_deref().unwrap_or("");
+                    let new = stores
+                        .label_store()
+                        .get(label)
+                        .ok_or_else(|| GtFormatError::UnknownLabel(label.to_string()))?;
+                    SimpleAction {
+                        path: path(src_index.get(&a.tree)?),
+                        action: Act::Update { new },
+                    }
+                }
+                GtActionKind::MoveTree => SimpleAction {
+                    path: path(&dst_path(a)?),
+                    action: Act::Move {
+                        from: path(src_index.get(&a.tree)?),
+                    },
+                },
+                GtActionKind::InsertNode | GtActionKind::InsertTree => {
+                    let p = dst_index.get(&a.tree)?;
+                    SimpleAction {
+                        path: path(p),
+                        action: Act::Insert {
+                            sub: node_at(stores, dst, p),
+                        },
+                    }
+                }
+                GtActionKind::DeleteNode | GtActionKind::DeleteTree => SimpleAction {
+                    path: path(src_index.get(&a.tree)?),
+                    action: Act::Delete {},
+                },
+            };
+            actions.push(action);
+        }
+        Ok(actions)
+    }
+
+    /// Imports the matches in `mappings`,
+    /// between the decompressed `src_arena` and `dst_arena`, respectively decompressing `src` and `dst`.
+    ///
+    /// `mappings` must already be sized for the arenas.
+    pub fn import_matches<'store, 'a, HAST, SD, DD, M>(
+        &self,
+        stores: &'store HAST,
+        src: NodeIdentifier,
+        dst: NodeIdentifier,
+        src_arena: &'a SD,
+        dst_arena: &'a DD,
+        mappings: &mut M,
+    ) -> Result<(), GtFormatError>
+    where
+        HAST: HyperAST<
+            'store,
+            T = HashedNodeRef<'store>,
+            IdN = NodeIdentifier,
+            Label = LabelIdentifier,
+        >,
+        M: MappingStore,
+        SD: ShallowDecompressedTreeStore<'a, HAST::T, M::Src>,
+        DD: ShallowDecompressedTreeStore<'a, HAST::T, M::Dst>,
+    {
+        let src_index = TreeIndex::new(stores, src);
+        let dst_index = TreeIndex::new(stores, dst);
+        let node_store = stores.node_store();
+        for m in &self.matches {
+            let a = src_arena.child(node_store, &src_arena.root(), src_index.get(&m.src)?);
+            let b = dst_arena.child(node_store, &dst_arena.root(), dst_index.get(&m.dest)?);
+            mappings.link(a, b);
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,9 @@
 #[allow(unused)] // still very experimental
 pub mod action_tree;
 pub mod action_vec;
+#[cfg(feature = "serialize")]
+pub mod gumtree_format;
+pub mod render;
 pub mod script_generator;
 pub mod script_generator2;
 
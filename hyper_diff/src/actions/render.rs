@@ -0,0 +1,500 @@
+//! Git-style textual rendering of edit scripts.
+//!
+//! Each action is located in the files of the source and/or of the destination,
+//! then shown as a hunk of the file it applies to,
+//! either in a [`Unified`] layout or [`SideBySide`].
+//!
+//! Moved nodes are shown once, at their destination, annotated with their origin.
+//! Updated labels are shown inline, as in `git diff --word-diff`, i.e. `[-old-]{+new+}`.
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display},
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use num_traits::ToPrimitive;
+
+use hyper_ast::{
+    nodes::TextSerializer,
+    store::{
+        defaults::{LabelIdentifier, NodeIdentifier},
+        nodes::HashedNodeRef,
+    },
+    types::{
+        Children, HyperAST, HyperType, IterableChildren, LabelStore, Labeled, NodeStore,
+        WithChildren, WithSerialization,
+    },
+};
+
+use crate::tree::tree_path::TreePath;
+
+use super::{
+    action_vec::ActionsVec,
+    script_generator2::{Act, SimpleAction},
+};
+
+/// A range of bytes in a file, with the line and column (both starting at 1) of its start.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub file: PathBuf,
+    pub range: Range<usize>,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file.display(), self.line, self.col)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HunkKind {
+    Delete,
+    Insert,
+    Update,
+    Move,
+    MovUpd,
+}
+
+impl Display for HunkKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            HunkKind::Delete => "delete",
+            HunkKind::Insert => "insert",
+            HunkKind::Update => "update",
+            HunkKind::Move => "move",
+            HunkKind::MovUpd => "move+update",
+        })
+    }
+}
+
+/// An action of an edit script, located in the source and/or the destination.
+#[derive(Clone, Debug)]
+pub struct Hunk {
+    pub kind: HunkKind,
+    /// missing for insertions
+    pub src: Option<Location>,
+    /// missing for deletions and in-place updates
+    pub dst: Option<Location>,
+    /// old and new label of updates
+    pub label: Option<(String, String)>,
+    /// full lines of the source covering the node
+    pub src_lines: Vec<String>,
+    /// full lines of the destination covering the node,
+    /// for in-place updates they are the source lines with the new label
+    pub dst_lines: Vec<String>,
+    /// lines with the updated label marked inline
+    pub inline: Vec<String>,
+}
+
+impl Hunk {
+    /// Where the hunk is displayed, i.e. the destination for moves and insertions.
+    pub fn location(&self) -> &Location {
+        self.dst.as_ref().or(self.src.as_ref()).unwrap()
+    }
+
+    fn header(&self) -> String {
+        let mut s = "@@".to_string();
+        if let Some(src) = &self.src {
+            s += &format!(" -{},{}", src.line, self.src_lines.len());
+        }
+        if let Some(dst) = &self.dst {
+            s += &format!(" +{},{}", dst.line, self.dst_lines.len());
+        }
+        let loc = self.location();
+        s += &format!(" @@ {} [{}..{}]", self.kind, loc.range.start, loc.range.end);
+        if let (HunkKind::Move | HunkKind::MovUpd, Some(src)) = (self.kind, &self.src) {
+            s += &format!(" from a/{} [{}..{}]", src, src.range.start, src.range.end);
+        }
+        if let Some((old, new)) = &self.label {
+            s += &format!(" {:?} -> {:?}", old, new);
+        }
+        s
+    }
+}
+
+/// The hunks of an edit script, grouped per file and sorted by position.
+pub struct RenderedScript {
+    files: BTreeMap<PathBuf, Vec<Hunk>>,
+}
+
+impl RenderedScript {
+    /// Locates the actions of `actions`, going from `src` to `dst`.
+    pub fn new<'store, P, HAST>(
+        stores: &'store HAST,
+        src: NodeIdentifier,
+        dst: NodeIdentifier,
+        actions: &ActionsVec<SimpleAction<LabelIdentifier, P, NodeIdentifier>>,
+    ) -> Self
+    where
+        P: TreePath<Item = u16> + PartialEq,
+        HAST: HyperAST<
+            'store,
+            T = HashedNodeRef<'store>,
+            IdN = NodeIdentifier,
+            Label = LabelIdentifier,
+        >,
+    {
+        let mut locator = Locator {
+            stores,
+            texts: HashMap::new(),
+        };
+        let mut files: BTreeMap<PathBuf, Vec<Hunk>> = BTreeMap::new();
+        let mut it = actions.iter().peekable();
+        while let Some(a) = it.next() {
+            let hunk = match &a.action {
+                Act::Update { new } => match it.peek() {
+                    // the label of a moved node changed, its path is in the destination
+                    Some(SimpleAction {
+                        path,
+                        action: Act::Move { from },
+                    }) if path.ori == a.path.ori => {
+                        it.next();
+                        locator.moved(src, dst, &from.ori, &a.path.ori, Some(new))
+                    }
+                    _ => locator.updated(src, &a.path.ori, new),
+                },
+                Act::Delete {} => {
+                    let (src, src_lines) = locator.locate(src, &a.path.ori);
+                    Hunk {
+                        kind: HunkKind::Delete,
+                        src: Some(src),
+                        dst: None,
+                        label: None,
+                        src_lines,
+                        dst_lines: vec![],
+                        inline: vec![],
+                    }
+                }
+                Act::Insert { .. } => {
+                    let (dst, dst_lines) = locator.locate(dst, &a.path.ori);
+                    Hunk {
+                        kind: HunkKind::Insert,
+                        src: None,
+                        dst: Some(dst),
+                        label: None,
+                        src_lines: vec![],
+                        dst_lines,
+                        inline: vec![],
+                    }
+                }
+                Act::Move { from } => locator.moved(src, dst, &from.ori, &a.path.ori, None),
+                Act::MovUpd { from, new } => {
+                    locator.moved(src, dst, &from.ori, &a.path.ori, Some(new))
+                }
+            };
+            files
+                .entry(hunk.location().file.clone())
+                .or_default()
+                .push(hunk);
+        }
+        for hunks in files.values_mut() {
+            hunks.sort_by_key(|h| (h.location().range.start, h.location().range.end));
+        }
+        Self { files }
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = (&Path, &[Hunk])> {
+        self.files.iter().map(|(f, h)| (f.as_path(), h.as_slice()))
+    }
+
+    pub fn unified(&self) -> Unified<'_> {
+        Unified(self)
+    }
+
+    /// `width` is the width of each of the two columns
+    pub fn side_by_side(&self, width: usize) -> SideBySide<'_> {
+        SideBySide(self, width)
+    }
+}
+
+fn file_header(f: &mut fmt::Formatter<'_>, file: &Path) -> fmt::Result {
+    writeln!(f, "--- a/{}", file.display())?;
+    writeln!(f, "+++ b/{}", file.display())
+}
+
+/// Unified layout, lines are prefixed by `-` when deleted, `+` when inserted,
+/// `~` when their label is updated, and `>` when moved.
+pub struct Unified<'a>(&'a RenderedScript);
+
+impl<'a> Display for Unified<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (file, hunks) in self.0.files() {
+            file_header(f, file)?;
+            for h in hunks {
+                writeln!(f, "{}", h.header())?;
+                let (prefix, lines) = match h.kind {
+                    HunkKind::Delete => ('-', &h.src_lines),
+                    HunkKind::Insert => ('+', &h.dst_lines),
+                    HunkKind::Update => ('~', &h.inline),
+                    HunkKind::Move => ('>', &h.dst_lines),
+                    HunkKind::MovUpd => ('>', &h.inline),
+                };
+                for l in lines {
+                    writeln!(f, "{}{}", prefix, l)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Side by side layout, the source on the left and the destination on the right,
+/// separated by `<` when deleted, `>` when inserted, `|` when updated and `~` when moved.
+pub struct SideBySide<'a>(&'a RenderedScript, usize);
+
+impl<'a> Display for SideBySide<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.1;
+        for (file, hunks) in self.0.files() {
+            file_header(f, file)?;
+            for h in hunks {
+                writeln!(f, "{}", h.header())?;
+                let sep = match h.kind {
+                    HunkKind::Delete => '<',
+                    HunkKind::Insert => '>',
+                    HunkKind::Update => '|',
+                    HunkKind::Move | HunkKind::MovUpd => '~',
+                };
+                let len = h.src_lines.len().max(h.dst_lines.len());
+                for i in 0..len {
+                    let l = h.src_lines.get(i).map_or("", |x| x.as_str());
+                    let r = h.dst_lines.get(i).map_or("", |x| x.as_str());
+                    let l = column(l, width);
+                    let r = column(r, width);
+                    writeln!(f, "{} {} {}", l, sep, r.trim_end())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Pads or truncates `s` to `width` chars.
+pub(crate) fn column(s: &str, width: usize) -> String {
+    let s = s.replace('\t', "    ");
+    let mut s: String = s.chars().take(width).collect();
+    let n = s.chars().count();
+    s.extend(std::iter::repeat(' ').take(width - n));
+    s
+}
+
+/// Line and column, both starting at 1, of the byte `offset` of `text`.
+pub(crate) fn line_col(text: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(text.len());
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let line = before.matches('\n').count() + 1;
+    let col = before[line_start..].chars().count() + 1;
+    (line, col)
+}
+
+/// The full lines of `text` covering `range`.
+pub(crate) fn covering_lines(text: &str, range: Range<usize>) -> Range<usize> {
+    let start = range.start.min(text.len());
+    let end = range.end.min(text.len()).max(start);
+    let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = text[end..].find('\n').map_or(text.len(), |i| end + i);
+    line_start..line_end
+}
+
+fn split_lines(s: &str) -> Vec<String> {
+    s.split('\n')
+        .map(|x| x.trim_end_matches('\r').to_string())
+        .collect()
+}
+
+/// The full lines of `text` covering `range`,
+/// where the first occurrence of `from` in `range` is replaced by `to`.
+pub(crate) fn replaced_lines(text: &str, range: Range<usize>, from: &str, to: &str) -> Vec<String> {
+    let lines = covering_lines(text, range.clone());
+    let start = range.start.min(text.len());
+    let end = range.end.min(text.len()).max(start);
+    let Some(i) = text[start..end].find(from) else {
+        return split_lines(&text[lines]);
+    };
+    let i = start + i;
+    let mut s = text[lines.start..i].to_string();
+    s += to;
+    s += &text[i + from.len()..lines.end];
+    split_lines(&s)
+}
+
+struct Locator<'store, HAST> {
+    stores: &'store HAST,
+    /// text of the files already serialized
+    texts: HashMap<NodeIdentifier, String>,
+}
+
+impl<'store, HAST> Locator<'store, HAST>
+where
+    HAST:
+        HyperAST<'store, T = HashedNodeRef<'store>, IdN = NodeIdentifier, Label = LabelIdentifier>,
+{
+    /// Follows `path` from `root`, like [`hyper_ast::position::compute_position`],
+    /// but also keeping the file containing the node.
+    fn walk<P: TreePath<Item = u16>>(
+        &self,
+        root: NodeIdentifier,
+        path: &P,
+    ) -> (
+        PathBuf,
+        Option<NodeIdentifier>,
+        Range<usize>,
+        NodeIdentifier,
+    ) {
+        let mut file = PathBuf::new();
+        let mut file_node = None;
+        let mut offset = 0;
+        let mut x = root;
+        let mut it = path.iter();
+        loop {
+            let b = self.stores.node_store().resolve(&x);
+            let t = self.stores.resolve_type(&x);
+            if t.is_directory() || t.is_file() {
+                let l = self.stores.label_store().resolve(b.get_label_unchecked());
+                file.push(l);
+            }
+            if t.is_directory() {
+                file_node = None;
+            } else if t.is_file() || file_node.is_none() {
+                file_node = Some(x);
+            }
+            let Some(o) = it.next() else {
+                let len = if t.is_directory() {
+                    0
+                } else {
+                    b.try_bytes_len().unwrap_or(0).to_usize().unwrap()
+                };
+                return (file, file_node, offset..offset + len, x);
+            };
+            let Some(cs) = b.children() else {
+                return (file, file_node, offset..offset, x);
+            };
+            if !t.is_directory() {
+                for y in cs.before(o).iter_children() {
+                    let b = self.stores.node_store().resolve(y);
+                    offset += b.try_bytes_len().unwrap_or(0).to_usize().unwrap();
+                }
+            }
+            let Some(a) = cs.get(o) else {
+                return (file, file_node, offset..offset, x);
+            };
+            x = *a;
+        }
+    }
+
+    fn text(&mut self, file_node: Option<NodeIdentifier>) -> &str {
+        let Some(file_node) = file_node else {
+            return "";
+        };
+        let stores = self.stores;
+        self.texts
+            .entry(file_node)
+            .or_insert_with(|| TextSerializer::new(stores, file_node).to_string())
+    }
+
+    fn label(&self, id: NodeIdentifier) -> String {
+        let b = self.stores.node_store().resolve(&id);
+        b.try_get_label()
+            .map_or("", |l| self.stores.label_store().resolve(l))
+            .to_string()
+    }
+
+    /// Location of the node at `path` from `root`, and the lines covering it.
+    fn locate<P: TreePath<Item = u16>>(
+        &mut self,
+        root: NodeIdentifier,
+        path: &P,
+    ) -> (Location, Vec<String>) {
+        let (file, file_node, range, _) = self.walk(root, path);
+        self.locate_range(file, file_node, range)
+    }
+
+    fn locate_range(
+        &mut self,
+        file: PathBuf,
+        file_node: Option<NodeIdentifier>,
+        range: Range<usize>,
+    ) -> (Location, Vec<String>) {
+        let text = self.text(file_node);
+        let (line, col) = line_col(text, range.start);
+        let lines = if range.is_empty() {
+            vec![]
+        } else {
+            split_lines(&text[covering_lines(text, range.clone())])
+        };
+        let loc = Location {
+            file,
+            range,
+            line,
+            col,
+        };
+        (loc, lines)
+    }
+
+    /// In-place update of the label of the source node at `path`.
+    fn updated<P: TreePath<Item = u16>>(
+        &mut self,
+        src: NodeIdentifier,
+        path: &P,
+        new: &LabelIdentifier,
+    ) -> Hunk {
+        let (file, file_node, range, node) = self.walk(src, path);
+        let old = self.label(node);
+        let new = self.stores.label_store().resolve(new).to_string();
+        let (loc, src_lines) = self.locate_range(file, file_node, range.clone());
+        let text = self.text(file_node);
+        let dst_lines = replaced_lines(text, range.clone(), &old, &new);
+        let inline = replaced_lines(text, range, &old, &word_diff(&old, &new));
+        Hunk {
+            kind: HunkKind::Update,
+            src: Some(loc),
+            dst: None,
+            label: Some((old, new)),
+            src_lines,
+            dst_lines,
+            inline,
+        }
+    }
+
+    /// Move of the source node at `from` to the destination node at `to`,
+    /// possibly with a `new` label.
+    fn moved<P: TreePath<Item = u16>>(
+        &mut self,
+        src: NodeIdentifier,
+        dst: NodeIdentifier,
+        from: &P,
+        to: &P,
+        new: Option<&LabelIdentifier>,
+    ) -> Hunk {
+        let (file, file_node, src_range, node) = self.walk(src, from);
+        let old = self.label(node);
+        let (src, src_lines) = self.locate_range(file, file_node, src_range);
+        let (file, file_node, dst_range, _) = self.walk(dst, to);
+        let (dst, dst_lines) = self.locate_range(file, file_node, dst_range.clone());
+        let (kind, label, inline) = if let Some(new) = new {
+            let new = self.stores.label_store().resolve(new).to_string();
+            let text = self.text(file_node);
+            let inline = replaced_lines(text, dst_range, &new, &word_diff(&old, &new));
+            (HunkKind::MovUpd, Some((old, new)), inline)
+        } else {
+            (HunkKind::Move, None, vec![])
+        };
+        Hunk {
+            kind,
+            src: Some(src),
+            dst: Some(dst),
+            label,
+            src_lines,
+            dst_lines,
+            inline,
+        }
+    }
+}
+
+pub(crate) fn word_diff(old: &str, new: &str) -> String {
+    format!("[-{}-]{{+{}+}}", old, new)
+}
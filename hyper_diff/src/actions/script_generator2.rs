@@ -839,8 +839,10 @@ where
             for b in &s2 {
                 if self.ori_mappings.unwrap().has(&a, &b) && !lcs.contains(&(*a, *b)) {
                     let k = self.find_pos(&b, x);
+                    // like other moves, the original path is the one in the destination,
+                    // where the parent is not necessarily at the same place as in the source
                     let path = ApplicablePath {
-                        ori: self.orig_src(*w).extend(&[k]),
+                        ori: self.path_dst(&self.dst_arena.root(), b),
                         mid: self.path(*w),
                     };
                     let action = SimpleAction {
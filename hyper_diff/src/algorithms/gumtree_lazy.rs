@@ -41,6 +41,59 @@ pub fn diff<'store, HAST: HyperAST<'store>>(
     Mapper<'store, HAST, CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
     PreparedMappingDurations<2>,
 >
+where
+    HAST::IdN: Clone + Debug + Eq,
+    HAST::Label: Clone + Copy + Eq + Debug,
+    HAST::Idx: hyper_ast::PrimInt,
+    HAST::T: 'store + types::WithHashs + types::WithStats,
+{
+    diff_with(hyperast, src, dst, |mapper| {
+        LazyGreedySubtreeMatcher::<_, _, _, _>::match_it::<DefaultMultiMappingStore<_>>(mapper)
+    })
+}
+
+/// Same as [`diff`] but the subtree matcher runs on multiple threads.
+pub fn diff_par<'store, HAST: HyperAST<'store> + Sync>(
+    hyperast: &'store HAST,
+    src: &HAST::IdN,
+    dst: &HAST::IdN,
+) -> DiffResult<
+    SimpleAction<
+        HAST::Label,
+        CompressedTreePath<<HAST::T as types::WithChildren>::ChildIdx>,
+        HAST::IdN,
+    >,
+    Mapper<'store, HAST, CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+    PreparedMappingDurations<2>,
+>
+where
+    HAST::IdN: Clone + Debug + Eq + Sync,
+    HAST::Label: Clone + Copy + Eq + Debug,
+    HAST::Idx: hyper_ast::PrimInt,
+    HAST::T: 'store + types::WithHashs + types::WithStats,
+    <HAST::T as types::WithHashs>::HP: Send + Sync,
+{
+    diff_with(hyperast, src, dst, |mapper| {
+        LazyGreedySubtreeMatcher::<_, _, _, _>::match_it_par::<DefaultMultiMappingStore<_>>(mapper)
+    })
+}
+
+fn diff_with<'store, HAST: HyperAST<'store>>(
+    hyperast: &'store HAST,
+    src: &HAST::IdN,
+    dst: &HAST::IdN,
+    subtree_matcher: impl FnOnce(
+        Mapper<'store, HAST, DS<HAST::T>, DS<HAST::T>, VecStore<u32>>,
+    ) -> Mapper<'store, HAST, DS<HAST::T>, DS<HAST::T>, VecStore<u32>>,
+) -> DiffResult<
+    SimpleAction<
+        HAST::Label,
+        CompressedTreePath<<HAST::T as types::WithChildren>::ChildIdx>,
+        HAST::IdN,
+    >,
+    Mapper<'store, HAST, CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+    PreparedMappingDurations<2>,
+>
 where
     HAST::IdN: Clone + Debug + Eq,
     HAST::Label: Clone + Copy + Eq + Debug,
@@ -52,8 +105,7 @@ where
         hyperast.decompress_pair(src, dst).into();
     let subtree_prepare_t = now.elapsed().as_secs_f64();
     let now = Instant::now();
-    let mapper =
-        LazyGreedySubtreeMatcher::<_, _, _, _>::match_it::<DefaultMultiMappingStore<_>>(mapper);
+    let mapper = subtree_matcher(mapper);
     let subtree_matcher_t = now.elapsed().as_secs_f64();
     let subtree_mappings_s = mapper.mappings().len();
     dbg!(&subtree_matcher_t, &subtree_mappings_s);
@@ -6,6 +6,8 @@ use crate::{
 pub mod gumtree;
 pub mod gumtree_lazy;
 pub mod gumtree_partial_lazy;
+pub mod per_file;
+pub mod pipeline;
 
 #[derive(Debug, Clone)]
 pub struct MappingDurations<const N: usize>(pub [f64; N]);
@@ -0,0 +1,191 @@
+//! Diff two versions of a directory file by file.
+//!
+//! Files are paired by path. Identical subtrees share the same identifier in the HyperAST,
+//! so unchanged directories and files are skipped without being decompressed.
+use std::{collections::HashMap, fmt::Debug};
+
+use hyper_ast::types::{
+    self, HyperAST, HyperType, IterableChildren, LabelStore, Labeled, NodeStore, WithChildren,
+};
+
+use crate::{
+    actions::{action_vec::ActionsVec, script_generator2::SimpleAction},
+    tree::tree_path::CompressedTreePath,
+};
+
+use super::gumtree_lazy;
+
+/// A file that is not the same in both versions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileChange<IdN> {
+    Added { path: String, dst: IdN },
+    Deleted { path: String, src: IdN },
+    Modified { path: String, src: IdN, dst: IdN },
+}
+
+impl<IdN> FileChange<IdN> {
+    /// Path of the file, relative to the compared directories
+    pub fn path(&self) -> &str {
+        match self {
+            FileChange::Added { path, .. }
+            | FileChange::Deleted { path, .. }
+            | FileChange::Modified { path, .. } => path,
+        }
+    }
+}
+
+pub struct FileDiff<A, IdN> {
+    pub change: FileChange<IdN>,
+    /// The edit script of a modified file, relative to the root of the file.
+    pub actions: Option<ActionsVec<A>>,
+}
+
+/// Lists the files that differ between `src` and `dst`, in the order of the directories.
+pub fn changed_files<'store, HAST: HyperAST<'store>>(
+    hyperast: &'store HAST,
+    src: &HAST::IdN,
+    dst: &HAST::IdN,
+) -> Vec<FileChange<HAST::IdN>>
+where
+    HAST::IdN: Clone + Eq,
+{
+    let mut changes = vec![];
+    changed_files_aux(hyperast, "", src, dst, &mut changes);
+    changes
+}
+
+fn changed_files_aux<'store, HAST: HyperAST<'store>>(
+    hyperast: &'store HAST,
+    path: &str,
+    src: &HAST::IdN,
+    dst: &HAST::IdN,
+    changes: &mut Vec<FileChange<HAST::IdN>>,
+) where
+    HAST::IdN: Clone + Eq,
+{
+    if src == dst {
+        return;
+    }
+    let src_t = hyperast.resolve_type(src);
+    let dst_t = hyperast.resolve_type(dst);
+    if src_t.is_file() && dst_t.is_file() {
+        changes.push(FileChange::Modified {
+            path: path.to_owned(),
+            src: src.clone(),
+            dst: dst.clone(),
+        });
+    } else if src_t.is_directory() && dst_t.is_directory() {
+        let src_cs = named_children(hyperast, src);
+        let dst_cs = named_children(hyperast, dst);
+        let dst_by_name: HashMap<_, _> = dst_cs.iter().map(|(n, x)| (n.as_str(), x)).collect();
+        let src_by_name: HashMap<_, _> = src_cs.iter().map(|(n, x)| (n.as_str(), x)).collect();
+        for (name, src) in &src_cs {
+            let path = join(path, name);
+            match dst_by_name.get(name.as_str()) {
+                Some(dst) => changed_files_aux(hyperast, &path, src, dst, changes),
+                None => files(hyperast, path, src, &mut |path, src| {
+                    changes.push(FileChange::Deleted { path, src })
+                }),
+            }
+        }
+        for (name, dst) in &dst_cs {
+            if !src_by_name.contains_key(name.as_str()) {
+                files(hyperast, join(path, name), dst, &mut |path, dst| {
+                    changes.push(FileChange::Added { path, dst })
+                });
+            }
+        }
+    } else {
+        // a file replaced by a directory or the opposite
+        files(hyperast, path.to_owned(), src, &mut |path, src| {
+            changes.push(FileChange::Deleted { path, src })
+        });
+        files(hyperast, path.to_owned(), dst, &mut |path, dst| {
+            changes.push(FileChange::Added { path, dst })
+        });
+    }
+}
+
+/// Calls `f` on each file contained in `id`, or on `id` itself if it is a file.
+fn files<'store, HAST: HyperAST<'store>>(
+    hyperast: &'store HAST,
+    path: String,
+    id: &HAST::IdN,
+    f: &mut impl FnMut(String, HAST::IdN),
+) where
+    HAST::IdN: Clone,
+{
+    let t = hyperast.resolve_type(id);
+    if t.is_file() {
+        f(path, id.clone())
+    } else if t.is_directory() {
+        for (name, x) in named_children(hyperast, id) {
+            files(hyperast, join(&path, &name), &x, f)
+        }
+    }
+}
+
+fn named_children<'store, HAST: HyperAST<'store>>(
+    hyperast: &'store HAST,
+    id: &HAST::IdN,
+) -> Vec<(String, HAST::IdN)>
+where
+    HAST::IdN: Clone,
+{
+    let n = hyperast.node_store().resolve(id);
+    let Some(cs) = n.children() else {
+        return vec![];
+    };
+    cs.iter_children()
+        .filter_map(|x| {
+            let n = hyperast.node_store().resolve(x);
+            let l = n.try_get_label()?;
+            Some((hyperast.label_store().resolve(l).to_owned(), x.clone()))
+        })
+        .collect()
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{}/{}", path, name)
+    }
+}
+
+/// Computes the edit script of each modified file with [`gumtree_lazy::diff`].
+///
+/// Added and deleted files do not have an edit script.
+pub fn diff<'store, HAST: HyperAST<'store>>(
+    hyperast: &'store HAST,
+    src: &HAST::IdN,
+    dst: &HAST::IdN,
+) -> Vec<
+    FileDiff<
+        SimpleAction<
+            HAST::Label,
+            CompressedTreePath<<HAST::T as types::WithChildren>::ChildIdx>,
+            HAST::IdN,
+        >,
+        HAST::IdN,
+    >,
+>
+where
+    HAST::IdN: Clone + Debug + Eq,
+    HAST::Label: Clone + Copy + Eq + Debug,
+    HAST::Idx: hyper_ast::PrimInt,
+    HAST::T: 'store + types::WithHashs + types::WithStats,
+{
+    changed_files(hyperast, src, dst)
+        .into_iter()
+        .map(|change| {
+            let actions = match &change {
+                FileChange::Modified { src, dst, .. } => {
+                    gumtree_lazy::diff(hyperast, src, dst).actions
+                }
+                _ => None,
+            };
+            FileDiff { change, actions }
+        })
+        .collect()
+}
@@ -0,0 +1,589 @@
+//! Matcher pipelines configured at runtime.
+//!
+//! A pipeline is a sequence of matching stages, the first one usually being a subtree matcher,
+//! followed by bottom-up matchers and recovery stages, e.g.
+//! `lazy_greedy_subtree, min_height=2 -> simple_bottom_up, sim=0.5 -> zs, max_size=1000`
+//!
+//! Stages are separated by `->` (or `→`),
+//! a stage is its name followed by its parameters as comma separated `key=value` pairs.
+//! In stage names, spaces and dashes are equivalent to underscores.
+//!
+//! | stage | parameters | |
+//! |-|-|-|
+//! | `greedy_subtree` | `min_height` (1) | top-down matching of isomorphic subtrees |
+//! | `lazy_greedy_subtree` | `min_height` (1) | same as above, lazily decompressing subtrees, only as the first stage |
+//! | `greedy_bottom_up` | `sim` (0.5), `max_size` (1000) | bottom-up matching of containers, with a ZS recovery inside containers smaller than `max_size` |
+//! | `simple_bottom_up` | `sim` (0.5) | bottom-up matching of containers, recovering inside them with lcs and histograms of types |
+//! | `zs` | `max_size` (1000) | optimal ZS recovery inside mapped containers smaller than `max_size` |
+//! | `apted` | `max_size` (1000) | same as above with APTED, faster on unbalanced containers |
+//! | `hybrid_bottom_up` | `max_size` (20) | bottom-up matching of containers, with a threshold depending on their sizes, and a ZS recovery inside containers smaller than `max_size` |
+//! | `change_distiller_leaves` | `label_sim` (0.5) | matching of leaves with similar labels |
+//! | `change_distiller_bottom_up` | `max_leaves` (4), `sim_large` (0.6), `sim_small` (0.4) | bottom-up matching of containers, `sim_large` applying to the ones with more than `max_leaves` leaves |
+//!
+//! [`Pipeline::hybrid`] and [`Pipeline::change_distiller`] reproduce the corresponding configurations of GumTree.
+use std::{fmt, str::FromStr, time::Instant};
+
+use crate::{
+    actions::script_generator2::{ScriptGenerator, SimpleAction},
+    decompressed_tree_store::{
+        bfs_wrapper::SimpleBfsMapper, lazy_post_order::LazyPostOrder, CompletePostOrder,
+    },
+    matchers::{
+        heuristic::gt::{
+            change_distiller_matcher::{
+                ChangeDistillerBottomUpMatcher, ChangeDistillerLeavesMatcher,
+            },
+            greedy_bottom_up_matcher::GreedyBottomUpMatcher,
+            greedy_subtree_matcher::GreedySubtreeMatcher,
+            hybrid_bottom_up_matcher::HybridBottomUpMatcher,
+            lazy2_greedy_subtree_matcher::LazyGreedySubtreeMatcher,
+        },
+        mapping_store::{DefaultMultiMappingStore, MappingStore, VecStore},
+        Mapper,
+    },
+    tree::tree_path::CompressedTreePath,
+};
+use hyper_ast::types::{self, HyperAST};
+
+use super::{ComputeTime, DiffResult};
+
+type DS<T> = LazyPostOrder<T, u32>;
+type CDS<T> = CompletePostOrder<T, u32>;
+
+/// Min heights supported by subtree matchers, they are const generics of the matchers
+pub const MIN_HEIGHTS: std::ops::RangeInclusive<usize> = 1..=5;
+
+/// Max sizes supported by the recoveries, subtree sizes are compared in the ids of the mapping store
+pub const MAX_SIZES: std::ops::RangeInclusive<usize> = 0..=u32::MAX as usize;
+
+const DEFAULT_MIN_HEIGHT: usize = 1;
+const DEFAULT_SIM: f64 = 0.5;
+const DEFAULT_MAX_SIZE: usize = 1000;
+const DEFAULT_HYBRID_MAX_SIZE: usize = 20;
+const DEFAULT_LABEL_SIM: f64 = 0.5;
+const DEFAULT_MAX_LEAVES: usize = 4;
+const DEFAULT_SIM_LARGE: f64 = 0.6;
+const DEFAULT_SIM_SMALL: f64 = 0.4;
+
+fn default_min_height() -> usize {
+    DEFAULT_MIN_HEIGHT
+}
+fn default_sim() -> f64 {
+    DEFAULT_SIM
+}
+fn default_max_size() -> usize {
+    DEFAULT_MAX_SIZE
+}
+fn default_hybrid_max_size() -> usize {
+    DEFAULT_HYBRID_MAX_SIZE
+}
+fn default_label_sim() -> f64 {
+    DEFAULT_LABEL_SIM
+}
+fn default_max_leaves() -> usize {
+    DEFAULT_MAX_LEAVES
+}
+fn default_sim_large() -> f64 {
+    DEFAULT_SIM_LARGE
+}
+fn default_sim_small() -> f64 {
+    DEFAULT_SIM_SMALL
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "stage", rename_all = "snake_case")
+)]
+pub enum Stage {
+    GreedySubtree {
+        #[cfg_attr(feature = "serialize", serde(default = "default_min_height"))]
+        min_height: usize,
+    },
+    LazyGreedySubtree {
+        #[cfg_attr(feature = "serialize", serde(default = "default_min_height"))]
+        min_height: usize,
+    },
+    GreedyBottomUp {
+        #[cfg_attr(feature = "serialize", serde(default = "default_sim"))]
+        sim: f64,
+        #[cfg_attr(feature = "serialize", serde(default = "default_max_size"))]
+        max_size: usize,
+    },
+    SimpleBottomUp {
+        #[cfg_attr(feature = "serialize", serde(default = "default_sim"))]
+        sim: f64,
+    },
+    Zs {
+        #[cfg_attr(feature = "serialize", serde(default = "default_max_size"))]
+        max_size: usize,
+    },
+    Apted {
+        #[cfg_attr(feature = "serialize", serde(default = "default_max_size"))]
+        max_size: usize,
+    },
+    HybridBottomUp {
+        #[cfg_attr(feature = "serialize", serde(default = "default_hybrid_max_size"))]
+        max_size: usize,
+    },
+    ChangeDistillerLeaves {
+        #[cfg_attr(feature = "serialize", serde(default = "default_label_sim"))]
+        label_sim: f64,
+    },
+    ChangeDistillerBottomUp {
+        #[cfg_attr(feature = "serialize", serde(default = "default_max_leaves"))]
+        max_leaves: usize,
+        #[cfg_attr(feature = "serialize", serde(default = "default_sim_large"))]
+        sim_large: f64,
+        #[cfg_attr(feature = "serialize", serde(default = "default_sim_small"))]
+        sim_small: f64,
+    },
+}
+
+impl Stage {
+    fn name(&self) -> &'static str {
+        match self {
+            Stage::GreedySubtree { .. } => "greedy_subtree",
+            Stage::LazyGreedySubtree { .. } => "lazy_greedy_subtree",
+            Stage::GreedyBottomUp { .. } => "greedy_bottom_up",
+            Stage::SimpleBottomUp { .. } => "simple_bottom_up",
+            Stage::Zs { .. } => "zs",
+            Stage::Apted { .. } => "apted",
+            Stage::HybridBottomUp { .. } => "hybrid_bottom_up",
+            Stage::ChangeDistillerLeaves { .. } => "change_distiller_leaves",
+            Stage::ChangeDistillerBottomUp { .. } => "change_distiller_bottom_up",
+        }
+    }
+
+    fn is_subtree(&self) -> bool {
+        matches!(
+            self,
+            Stage::GreedySubtree { .. } | Stage::LazyGreedySubtree { .. }
+        )
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())?;
+        match self {
+            Stage::GreedySubtree { min_height } | Stage::LazyGreedySubtree { min_height } => {
+                write!(f, ", min_height={}", min_height)
+            }
+            Stage::GreedyBottomUp { sim, max_size } => {
+                write!(f, ", sim={}, max_size={}", sim, max_size)
+            }
+            Stage::SimpleBottomUp { sim } => write!(f, ", sim={}", sim),
+            Stage::Zs { max_size }
+            | Stage::Apted { max_size }
+            | Stage::HybridBottomUp { max_size } => {
+                write!(f, ", max_size={}", max_size)
+            }
+            Stage::ChangeDistillerLeaves { label_sim } => write!(f, ", label_sim={}", label_sim),
+            Stage::ChangeDistillerBottomUp {
+                max_leaves,
+                sim_large,
+                sim_small,
+            } => write!(
+                f,
+                ", max_leaves={}, sim_large={}, sim_small={}",
+                max_leaves, sim_large, sim_small
+            ),
+        }
+    }
+}
+
+impl FromStr for Stage {
+    type Err = PipelineError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(',').map(str::trim);
+        let name = parts
+            .next()
+            .unwrap_or_default()
+            .to_lowercase()
+            .replace([' ', '-'], "_");
+        let mut stage = match name.as_str() {
+            "greedy_subtree" => Stage::GreedySubtree {
+                min_height: DEFAULT_MIN_HEIGHT,
+            },
+            "lazy_greedy_subtree" => Stage::LazyGreedySubtree {
+                min_height: DEFAULT_MIN_HEIGHT,
+            },
+            "greedy_bottom_up" => Stage::GreedyBottomUp {
+                sim: DEFAULT_SIM,
+                max_size: DEFAULT_MAX_SIZE,
+            },
+            "simple_bottom_up" => Stage::SimpleBottomUp { sim: DEFAULT_SIM },
+            "zs" => Stage::Zs {
+                max_size: DEFAULT_MAX_SIZE,
+            },
+            "apted" => Stage::Apted {
+                max_size: DEFAULT_MAX_SIZE,
+            },
+            "hybrid_bottom_up" => Stage::HybridBottomUp {
+                max_size: DEFAULT_HYBRID_MAX_SIZE,
+            },
+            "change_distiller_leaves" => Stage::ChangeDistillerLeaves {
+                label_sim: DEFAULT_LABEL_SIM,
+            },
+            "change_distiller_bottom_up" => Stage::ChangeDistillerBottomUp {
+                max_leaves: DEFAULT_MAX_LEAVES,
+                sim_large: DEFAULT_SIM_LARGE,
+                sim_small: DEFAULT_SIM_SMALL,
+            },
+            _ => return Err(PipelineError::UnknownStage(name)),
+        };
+        for param in parts.filter(|x| !x.is_empty()) {
+            let (key, value) = param
+                .split_once('=')
+                .map(|(k, v)| (k.trim(), v.trim()))
+                .ok_or_else(|| PipelineError::BadParam(param.to_string()))?;
+            let bad_value = || PipelineError::BadParam(param.to_string());
+            match (&mut stage, key) {
+                (
+                    Stage::GreedySubtree { min_height } | Stage::LazyGreedySubtree { min_height },
+                    "min_height",
+                ) => *min_height = value.parse().map_err(|_| bad_value())?,
+                (Stage::GreedyBottomUp { sim, .. } | Stage::SimpleBottomUp { sim }, "sim") => {
+                    *sim = value.parse().map_err(|_| bad_value())?
+                }
+                (
+                    Stage::GreedyBottomUp { max_size, .. }
+                    | Stage::Zs { max_size }
+                    | Stage::Apted { max_size }
+                    | Stage::HybridBottomUp { max_size },
+                    "max_size",
+                ) => *max_size = value.parse().map_err(|_| bad_value())?,
+                (Stage::ChangeDistillerLeaves { label_sim }, "label_sim") => {
+                    *label_sim = value.parse().map_err(|_| bad_value())?
+                }
+                (Stage::ChangeDistillerBottomUp { max_leaves, .. }, "max_leaves") => {
+                    *max_leaves = value.parse().map_err(|_| bad_value())?
+                }
+                (Stage::ChangeDistillerBottomUp { sim_large, .. }, "sim_large") => {
+                    *sim_large = value.parse().map_err(|_| bad_value())?
+                }
+                (Stage::ChangeDistillerBottomUp { sim_small, .. }, "sim_small") => {
+                    *sim_small = value.parse().map_err(|_| bad_value())?
+                }
+                _ => return Err(PipelineError::BadParam(param.to_string())),
+            }
+        }
+        Ok(stage)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipelineError {
+    Empty,
+    UnknownStage(String),
+    /// a parameter that is malformed or that the stage does not take
+    BadParam(String),
+    /// a subtree matcher that is not the first stage
+    BadOrder(Stage),
+    /// a parameter out of its supported range
+    OutOfRange(Stage),
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipelineError::Empty => write!(f, "empty pipeline"),
+            PipelineError::UnknownStage(s) => write!(f, "unknown stage: {:?}", s),
+            PipelineError::BadParam(s) => write!(f, "bad parameter: {:?}", s),
+            PipelineError::BadOrder(s) => write!(f, "{} must be the first stage", s.name()),
+            PipelineError::OutOfRange(s) => write!(f, "parameter out of range: {}", s),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+/// A validated sequence of matching stages.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Vec<Stage>", into = "Vec<Stage>")
+)]
+pub struct Pipeline(Vec<Stage>);
+
+impl Pipeline {
+    pub fn new(stages: Vec<Stage>) -> Result<Self, PipelineError> {
+        if stages.is_empty() {
+            return Err(PipelineError::Empty);
+        }
+        for (i, stage) in stages.iter().enumerate() {
+            if stage.is_subtree() && i != 0 {
+                return Err(PipelineError::BadOrder(*stage));
+            }
+            let in_range =
+                match *stage {
+                    Stage::GreedySubtree { min_height }
+                    | Stage::LazyGreedySubtree { min_height } => MIN_HEIGHTS.contains(&min_height),
+                    Stage::GreedyBottomUp { sim, max_size } => {
+                        (0.0..=1.0).contains(&sim) && MAX_SIZES.contains(&max_size)
+                    }
+                    Stage::SimpleBottomUp { sim }
+                    | Stage::ChangeDistillerLeaves { label_sim: sim } => (0.0..=1.0).contains(&sim),
+                    Stage::ChangeDistillerBottomUp {
+                        sim_large,
+                        sim_small,
+                        ..
+                    } => (0.0..=1.0).contains(&sim_large) && (0.0..=1.0).contains(&sim_small),
+                    Stage::Zs { max_size }
+                    | Stage::Apted { max_size }
+                    | Stage::HybridBottomUp { max_size } => MAX_SIZES.contains(&max_size),
+                };
+            if !in_range {
+                return Err(PipelineError::OutOfRange(*stage));
+            }
+        }
+        Ok(Self(stages))
+    }
+
+    pub fn stages(&self) -> &[Stage] {
+        &self.0
+    }
+
+    /// GumTree's hybrid configuration
+    pub fn hybrid() -> Self {
+        Self(vec![
+            Stage::GreedySubtree {
+                min_height: DEFAULT_MIN_HEIGHT,
+            },
+            Stage::HybridBottomUp {
+                max_size: DEFAULT_HYBRID_MAX_SIZE,
+            },
+        ])
+    }
+
+    /// GumTree's ChangeDistiller configuration
+    pub fn change_distiller() -> Self {
+        Self(vec![
+            Stage::ChangeDistillerLeaves {
+                label_sim: DEFAULT_LABEL_SIM,
+            },
+            Stage::ChangeDistillerBottomUp {
+                max_leaves: DEFAULT_MAX_LEAVES,
+                sim_large: DEFAULT_SIM_LARGE,
+                sim_small: DEFAULT_SIM_SMALL,
+            },
+        ])
+    }
+}
+
+/// The pipeline of [`super::gumtree::diff`]
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self(vec![
+            Stage::GreedySubtree {
+                min_height: DEFAULT_MIN_HEIGHT,
+            },
+            Stage::GreedyBottomUp {
+                sim: DEFAULT_SIM,
+                max_size: DEFAULT_MAX_SIZE,
+            },
+        ])
+    }
+}
+
+impl TryFrom<Vec<Stage>> for Pipeline {
+    type Error = PipelineError;
+
+    fn try_from(value: Vec<Stage>) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<Pipeline> for Vec<Stage> {
+    fn from(value: Pipeline) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, stage) in self.0.iter().enumerate() {
+            if i != 0 {
+                write!(f, " -> ")?;
+            }
+            write!(f, "{}", stage)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Pipeline {
+    type Err = PipelineError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let stages = s
+            .split("->")
+            .flat_map(|x| x.split('→'))
+            .map(str::trim)
+            .filter(|x| !x.is_empty())
+            .map(Stage::from_str)
+            .collect::<Result<_, _>>()?;
+        Self::new(stages)
+    }
+}
+
+/// Durations of each stage of a pipeline, in seconds
+#[derive(Debug, Clone)]
+pub struct StagesDurations {
+    pub mappings: Vec<f64>,
+    pub preparation: Vec<f64>,
+}
+
+impl ComputeTime for StagesDurations {
+    fn time(&self) -> f64 {
+        self.preparation.iter().sum::<f64>() + self.mappings.iter().sum::<f64>()
+    }
+}
+
+/// Dispatches a runtime min height to the const generic of subtree matchers.
+macro_rules! with_min_height {
+    ($min_height:expr, $h:ident => $e:expr) => {
+        match $min_height {
+            1 => {
+                const $h: usize = 1;
+                $e
+            }
+            2 => {
+                const $h: usize = 2;
+                $e
+            }
+            3 => {
+                const $h: usize = 3;
+                $e
+            }
+            4 => {
+                const $h: usize = 4;
+                $e
+            }
+            5 => {
+                const $h: usize = 5;
+                $e
+            }
+            h => unreachable!("min height {} should have been validated", h),
+        }
+    };
+}
+
+pub fn diff<'store, HAST: HyperAST<'store>>(
+    hyperast: &'store HAST,
+    src: &HAST::IdN,
+    dst: &HAST::IdN,
+    pipeline: &Pipeline,
+) -> DiffResult<
+    SimpleAction<
+        HAST::Label,
+        CompressedTreePath<<HAST::T as types::WithChildren>::ChildIdx>,
+        HAST::IdN,
+    >,
+    Mapper<'store, HAST, CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+    StagesDurations,
+>
+where
+    HAST::IdN: Clone + std::fmt::Debug + Eq,
+    HAST::Label: Clone + Copy + Eq + std::fmt::Debug,
+    HAST::Idx: hyper_ast::PrimInt,
+    <HAST::TS as types::TypeStore>::Ty: Copy + Send + Sync + Eq + std::hash::Hash + std::fmt::Debug,
+    HAST::T: 'store + types::WithHashs + types::WithStats,
+{
+    let node_store = hyperast.node_store();
+    let mut durations = StagesDurations {
+        mappings: vec![],
+        preparation: vec![],
+    };
+    let mut stages = pipeline.stages().iter();
+
+    let now = Instant::now();
+    // the preparation time is attributed to the next stage
+    let (mut mapper, mut prepare_t): (Mapper<_, CDS<HAST::T>, CDS<HAST::T>, VecStore<_>>, _) =
+        if let Some(Stage::LazyGreedySubtree { min_height }) = pipeline.stages().first() {
+            stages.next();
+            let mapper: Mapper<_, DS<HAST::T>, DS<HAST::T>, VecStore<_>> =
+                hyperast.decompress_pair(src, dst).into();
+            durations.preparation.push(now.elapsed().as_secs_f64());
+            let now = Instant::now();
+            let mapper = with_min_height!(*min_height, H => {
+                LazyGreedySubtreeMatcher::<_, _, _, _, { H }>::match_it::<
+                    DefaultMultiMappingStore<_>,
+                >(mapper)
+            });
+            durations.mappings.push(now.elapsed().as_secs_f64());
+            log::debug!("lazy_greedy_subtree: {} mappings", mapper.mappings().len());
+            let now = Instant::now();
+            let mapper = mapper.map(
+                |src_arena| CompletePostOrder::from(src_arena.complete(node_store)),
+                |dst_arena| CompletePostOrder::from(dst_arena.complete(node_store)),
+            );
+            (mapper, now.elapsed().as_secs_f64())
+        } else {
+            let mapper = hyperast.decompress_pair(src, dst).into();
+            (mapper, now.elapsed().as_secs_f64())
+        };
+
+    for stage in stages {
+        let now = Instant::now();
+        mapper = match *stage {
+            Stage::GreedySubtree { min_height } => with_min_height!(min_height, H => {
+                GreedySubtreeMatcher::<_, _, _, _, _, { H }>::match_it::<
+                    DefaultMultiMappingStore<_>,
+                >(mapper)
+            }),
+            Stage::LazyGreedySubtree { .. } => unreachable!("validated as the first stage"),
+            Stage::GreedyBottomUp { sim, max_size } => {
+                GreedyBottomUpMatcher::<_, _, _, _, _>::match_it_with(mapper, max_size, sim)
+            }
+            Stage::SimpleBottomUp { sim } => {
+                SimpleBottomUpMatcher::<_, _, _, _, _>::match_it_with(mapper, sim)
+            }
+            Stage::Zs { max_size } => {
+                GreedyBottomUpMatcher::<_, _, _, _, _>::recover_it(mapper, max_size)
+            }
+            Stage::Apted { max_size } => {
+                GreedyBottomUpMatcher::<_, _, _, _, _>::recover_it_apted(mapper, max_size)
+            }
+            Stage::HybridBottomUp { max_size } => {
+                HybridBottomUpMatcher::<_, _, _, _, _>::match_it_with(mapper, max_size)
+            }
+            Stage::ChangeDistillerLeaves { label_sim } => {
+                ChangeDistillerLeavesMatcher::<_, _, _, _>::match_it_with(mapper, label_sim)
+            }
+            Stage::ChangeDistillerBottomUp {
+                max_leaves,
+                sim_large,
+                sim_small,
+            } => ChangeDistillerBottomUpMatcher::<_, _, _, _>::match_it_with(
+                mapper, max_leaves, sim_large, sim_small,
+            ),
+        };
+        durations.mappings.push(now.elapsed().as_secs_f64());
+        durations.preparation.push(prepare_t);
+        prepare_t = 0.0;
+        log::debug!("{}: {} mappings", stage.name(), mapper.mappings().len());
+    }
+
+    let now = Instant::now();
+    let mapper = mapper.map(
+        |x| x,
+        |dst_arena| SimpleBfsMapper::from(node_store, dst_arena),
+    );
+    let prepare_gen_t = now.elapsed().as_secs_f64();
+    let now = Instant::now();
+    let actions = ScriptGenerator::compute_actions(mapper.hyperast, &mapper.mapping).ok();
+    let gen_t = now.elapsed().as_secs_f64();
+    let mapper = mapper.map(|x| x, |dst_arena| dst_arena.back);
+    DiffResult {
+        mapping_durations: durations,
+        mapper,
+        actions,
+        prepare_gen_t,
+        gen_t,
+    }
+}
@@ -1,6 +1,6 @@
 use std::{collections::HashMap, fmt::Debug, hash::Hash, marker::PhantomData};
 
-use num_traits::{PrimInt, ToPrimitive, Zero};
+use num_traits::{PrimInt, ToPrimitive};
 
 use crate::{
     decompressed_tree_store::{DecompressedTreeStore, DecompressedWithParent, Shallow},
@@ -81,9 +81,11 @@ where
     pub fn last_chance_match_histogram(&mut self, src: &M::Src, dst: &M::Dst) {
         self.lcs_equal_matching(src, dst);
         self.lcs_structure_matching(src, dst);
-        if !src.is_zero() && !dst.is_zero() {
-            self.histogram_matching(src, dst); //self.histogramMaking(src, dst),
-        } else if !(src.is_zero() || dst.is_zero()) {
+        let src_is_root = self.src_arena.parent(src).is_none();
+        let dst_is_root = self.dst_arena.parent(dst).is_none();
+        if src_is_root && dst_is_root {
+            self.histogram_matching(src, dst);
+        } else if !(src_is_root || dst_is_root) {
             if self.stores.resolve_type(
                 &self
                     .src_arena
@@ -93,7 +95,7 @@ where
                     .dst_arena
                     .original(&self.dst_arena.parent(dst).unwrap()),
             ) {
-                self.histogram_matching(src, dst) //self.histogramMaking(src, dst),
+                self.histogram_matching(src, dst)
             }
         }
     }
@@ -105,6 +107,7 @@ where
             .descendants(self.stores.node_store(), src)
             .iter()
             .all(|x| !self.mappings.is_src(x))
+            && !self.mappings.is_src(src)
     }
     pub(super) fn are_dsts_unmapped(&self, dst: &M::Dst) -> bool {
         // look at descendants
@@ -113,9 +116,11 @@ where
             .descendants(self.stores.node_store(), dst)
             .iter()
             .all(|x| !self.mappings.is_dst(x))
+            && !self.mappings.is_dst(dst)
     }
 
     pub(crate) fn add_mapping_recursively(&mut self, src: &M::Src, dst: &M::Dst) {
+        self.mappings.link(*src, *dst);
         self.src_arena
             .descendants(self.stores.node_store(), src)
             .iter()
@@ -208,7 +213,6 @@ where
                 }
             }
         }
-        todo!()
     }
 }
 
@@ -283,9 +287,11 @@ where
     pub fn last_chance_match_histogram(&mut self, src: &M::Src, dst: &M::Dst) {
         self.lcs_equal_matching(src, dst);
         self.lcs_structure_matching(src, dst);
-        if !src.is_zero() && !dst.is_zero() {
-            self.histogram_matching(src, dst); //self.histogramMaking(src, dst),
-        } else if !(src.is_zero() || dst.is_zero()) {
+        let src_is_root = self.src_arena.parent(src).is_none();
+        let dst_is_root = self.dst_arena.parent(dst).is_none();
+        if src_is_root && dst_is_root {
+            self.histogram_matching(src, dst);
+        } else if !(src_is_root || dst_is_root) {
             if self.hyperast.resolve_type(
                 &self
                     .src_arena
@@ -295,7 +301,7 @@ where
                     .dst_arena
                     .original(&self.dst_arena.parent(dst).unwrap()),
             ) {
-                self.histogram_matching(src, dst) //self.histogramMaking(src, dst),
+                self.histogram_matching(src, dst)
             }
         }
     }
@@ -307,6 +313,7 @@ where
             .descendants(self.hyperast.node_store(), src)
             .iter()
             .all(|x| !self.mappings.is_src(x))
+            && !self.mappings.is_src(src)
     }
     pub(super) fn are_dsts_unmapped(&self, dst: &M::Dst) -> bool {
         // look at descendants
@@ -315,6 +322,7 @@ where
             .descendants(self.hyperast.node_store(), dst)
             .iter()
             .all(|x| !self.mappings.is_dst(x))
+            && !self.mappings.is_dst(dst)
     }
 
     // pub(crate) fn add_mapping_recursively(&mut self, src: &M::Src, dst: &M::Dst) {
@@ -406,6 +414,5 @@ where
                 }
             }
         }
-        todo!()
     }
 }
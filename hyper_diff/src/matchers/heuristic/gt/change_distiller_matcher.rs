@@ -0,0 +1,257 @@
+//! ChangeDistiller's matchers, as configured in GumTree.
+//!
+//! Leaves are matched first according to the similarity of their labels,
+//! then inner nodes are matched according to the proportion of their mapped leaves,
+//! with a threshold depending on the number of leaves they contain.
+use std::fmt::Debug;
+
+use num_traits::{cast, PrimInt, ToPrimitive};
+
+use crate::decompressed_tree_store::{
+    ContiguousDescendants, DecompressedTreeStore, PostOrder, PostOrderIterable,
+};
+use crate::matchers::mapping_store::MonoMappingStore;
+use crate::matchers::similarity_metrics;
+use crate::matchers::Mapper;
+use hyper_ast::types::{HyperAST, LabelStore, NodeStore, Tree};
+
+pub struct ChangeDistillerLeavesMatcher<
+    'a,
+    Dsrc,
+    Ddst,
+    HAST,
+    M: MonoMappingStore,
+    const LABEL_SIM_THRESHOLD_NUM: u64 = 1,
+    const LABEL_SIM_THRESHOLD_DEN: u64 = 2,
+> {
+    internal: Mapper<'a, HAST, Dsrc, Ddst, M>,
+}
+
+impl<
+        'a,
+        Dsrc: DecompressedTreeStore<'a, HAST::T, M::Src>
+            + PostOrder<'a, HAST::T, M::Src>
+            + PostOrderIterable<'a, HAST::T, M::Src>,
+        Ddst: DecompressedTreeStore<'a, HAST::T, M::Dst>
+            + PostOrder<'a, HAST::T, M::Dst>
+            + PostOrderIterable<'a, HAST::T, M::Dst>,
+        HAST: HyperAST<'a>,
+        M: MonoMappingStore,
+        const LABEL_SIM_THRESHOLD_NUM: u64,
+        const LABEL_SIM_THRESHOLD_DEN: u64,
+    >
+    ChangeDistillerLeavesMatcher<
+        'a,
+        Dsrc,
+        Ddst,
+        HAST,
+        M,
+        LABEL_SIM_THRESHOLD_NUM,
+        LABEL_SIM_THRESHOLD_DEN,
+    >
+where
+    HAST::T: 'a + Tree,
+    HAST::IdN: 'a + Clone + Eq + Debug,
+    M::Src: 'a + PrimInt + std::ops::SubAssign + Debug,
+    M::Dst: 'a + PrimInt + std::ops::SubAssign + Debug,
+{
+    pub fn match_it(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        Self::match_it_with(
+            mapping,
+            LABEL_SIM_THRESHOLD_NUM as f64 / LABEL_SIM_THRESHOLD_DEN as f64,
+        )
+    }
+
+    /// Same as [`Self::match_it`] but with the label similarity threshold given at runtime.
+    pub fn match_it_with(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+        label_sim_threshold: f64,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        let mut matcher = Self { internal: mapping };
+        matcher.internal.mapping.mappings.topit(
+            matcher.internal.mapping.src_arena.len(),
+            matcher.internal.mapping.dst_arena.len(),
+        );
+        Self::execute(&mut matcher.internal, label_sim_threshold);
+        matcher.internal
+    }
+
+    pub fn execute(internal: &mut Mapper<'a, HAST, Dsrc, Ddst, M>, label_sim_threshold: f64) {
+        let hyperast = internal.hyperast;
+        let dst_leaves: Vec<_> = internal
+            .dst_arena
+            .iter_df_post::<true>()
+            .filter(|x| internal.dst_arena.lld(x) == *x && !internal.mappings.is_dst(x))
+            .map(|x| {
+                let o = internal.dst_arena.original(&x);
+                (x, hyperast.resolve_type(&o), label(hyperast, &o))
+            })
+            .collect();
+        let mut candidates = vec![];
+        for src in internal.src_arena.iter_df_post::<true>() {
+            if internal.src_arena.lld(&src) != src || internal.mappings.is_src(&src) {
+                continue;
+            }
+            let o = internal.src_arena.original(&src);
+            let t = hyperast.resolve_type(&o);
+            let l = label(hyperast, &o);
+            for (dst, dst_t, dst_l) in &dst_leaves {
+                if &t != dst_t {
+                    continue;
+                }
+                let sim = similarity_metrics::label_similarity(l, dst_l);
+                if sim > label_sim_threshold {
+                    candidates.push((src, *dst, sim));
+                }
+            }
+        }
+        // stable, so ties are resolved in post order
+        candidates.sort_by(|a, b| b.2.total_cmp(&a.2));
+        for (src, dst, _) in candidates {
+            internal.mappings.link_if_both_unmapped(src, dst);
+        }
+    }
+}
+
+pub struct ChangeDistillerBottomUpMatcher<
+    'a,
+    Dsrc,
+    Ddst,
+    HAST,
+    M: MonoMappingStore,
+    const MAX_LEAVES: usize = 4,
+    const SIM_THRESHOLD_LARGE_NUM: u64 = 3,
+    const SIM_THRESHOLD_LARGE_DEN: u64 = 5,
+    const SIM_THRESHOLD_SMALL_NUM: u64 = 2,
+    const SIM_THRESHOLD_SMALL_DEN: u64 = 5,
+> {
+    internal: Mapper<'a, HAST, Dsrc, Ddst, M>,
+}
+
+impl<
+        'a,
+        Dsrc: DecompressedTreeStore<'a, HAST::T, M::Src>
+            + PostOrder<'a, HAST::T, M::Src>
+            + PostOrderIterable<'a, HAST::T, M::Src>
+            + ContiguousDescendants<'a, HAST::T, M::Src>,
+        Ddst: DecompressedTreeStore<'a, HAST::T, M::Dst>
+            + PostOrder<'a, HAST::T, M::Dst>
+            + PostOrderIterable<'a, HAST::T, M::Dst>
+            + ContiguousDescendants<'a, HAST::T, M::Dst>,
+        HAST: HyperAST<'a>,
+        M: MonoMappingStore,
+        const MAX_LEAVES: usize,
+        const SIM_THRESHOLD_LARGE_NUM: u64,
+        const SIM_THRESHOLD_LARGE_DEN: u64,
+        const SIM_THRESHOLD_SMALL_NUM: u64,
+        const SIM_THRESHOLD_SMALL_DEN: u64,
+    >
+    ChangeDistillerBottomUpMatcher<
+        'a,
+        Dsrc,
+        Ddst,
+        HAST,
+        M,
+        MAX_LEAVES,
+        SIM_THRESHOLD_LARGE_NUM,
+        SIM_THRESHOLD_LARGE_DEN,
+        SIM_THRESHOLD_SMALL_NUM,
+        SIM_THRESHOLD_SMALL_DEN,
+    >
+where
+    HAST::T: 'a + Tree,
+    HAST::IdN: 'a + Clone + Eq + Debug,
+    M::Src: 'a + PrimInt + std::ops::SubAssign + Debug,
+    M::Dst: 'a + PrimInt + std::ops::SubAssign + Debug,
+{
+    pub fn match_it(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        Self::match_it_with(
+            mapping,
+            MAX_LEAVES,
+            SIM_THRESHOLD_LARGE_NUM as f64 / SIM_THRESHOLD_LARGE_DEN as f64,
+            SIM_THRESHOLD_SMALL_NUM as f64 / SIM_THRESHOLD_SMALL_DEN as f64,
+        )
+    }
+
+    /// Same as [`Self::match_it`] but with the thresholds given at runtime.
+    ///
+    /// Nodes with more than `max_leaves` leaves are matched with `sim_threshold_large`,
+    /// the other ones with `sim_threshold_small`.
+    pub fn match_it_with(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+        max_leaves: usize,
+        sim_threshold_large: f64,
+        sim_threshold_small: f64,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        let mut matcher = Self { internal: mapping };
+        matcher.internal.mapping.mappings.topit(
+            matcher.internal.mapping.src_arena.len(),
+            matcher.internal.mapping.dst_arena.len(),
+        );
+        Self::execute(
+            &mut matcher.internal,
+            max_leaves,
+            sim_threshold_large,
+            sim_threshold_small,
+        );
+        matcher.internal
+    }
+
+    pub fn execute(
+        internal: &mut Mapper<'a, HAST, Dsrc, Ddst, M>,
+        max_leaves: usize,
+        sim_threshold_large: f64,
+        sim_threshold_small: f64,
+    ) {
+        let hyperast = internal.hyperast;
+        let dst_inners: Vec<_> = internal
+            .dst_arena
+            .iter_df_post::<true>()
+            .filter(|x| internal.dst_arena.lld(x) != *x)
+            .map(|x| (x, hyperast.resolve_type(&internal.dst_arena.original(&x))))
+            .collect();
+        for src in internal.src_arena.iter_df_post::<true>() {
+            if internal.src_arena.lld(&src) == src || internal.mappings.is_src(&src) {
+                continue;
+            }
+            let t = hyperast.resolve_type(&internal.src_arena.original(&src));
+            let src_range = internal.src_arena.descendants_range(&src);
+            let leaves = (src_range.start.to_usize().unwrap()..src_range.end.to_usize().unwrap())
+                .map(|x| cast::<_, M::Src>(x).unwrap())
+                .filter(|x| internal.src_arena.lld(x) == *x)
+                .count();
+            let threshold = if leaves > max_leaves {
+                sim_threshold_large
+            } else {
+                sim_threshold_small
+            };
+            for (dst, dst_t) in &dst_inners {
+                if &t != dst_t || internal.mappings.is_dst(dst) {
+                    continue;
+                }
+                let sim = similarity_metrics::SimilarityMeasure::range(
+                    &src_range,
+                    &internal.dst_arena.descendants_range(dst),
+                    &internal.mappings,
+                )
+                .chawathe();
+                if sim >= threshold {
+                    internal.mappings.link(src, *dst);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+fn label<'a, HAST: HyperAST<'a>>(hyperast: &'a HAST, id: &HAST::IdN) -> &'a str {
+    let n = hyperast.node_store().resolve(id);
+    match n.try_get_label() {
+        Some(l) => hyperast.label_store().resolve(l),
+        None => "",
+    }
+}
@@ -7,7 +7,10 @@ use crate::decompressed_tree_store::{
     PostOrderIterable, PostOrderKeyRoots,
 };
 use crate::matchers::mapping_store::MonoMappingStore;
-use crate::matchers::{optimal::zs::ZsMatcher, similarity_metrics};
+use crate::matchers::{
+    optimal::{apted::AptedMatcher, zs::ZsMatcher},
+    similarity_metrics,
+};
 use hyper_ast::types::{DecompressedSubtree, HyperAST, NodeId, NodeStore, Tree, WithHashs};
 
 use super::bottom_up_matcher::BottomUpMatcher;
@@ -27,7 +30,7 @@ pub struct GreedyBottomUpMatcher<
     const SIM_THRESHOLD_NUM: u64 = 1,
     const SIM_THRESHOLD_DEN: u64 = 2,
 > {
-    internal: BottomUpMatcher<'a, Dsrc, Ddst, T, HAST, M>,
+    pub(super) internal: BottomUpMatcher<'a, Dsrc, Ddst, T, HAST, M>,
 }
 
 /// Enable using a slice instead of recreating a ZsTree for each call to ZsMatch, see last_chance_match
@@ -126,26 +129,80 @@ where
     pub fn match_it(
         mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
     ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
-        let mut matcher = Self {
-            internal: BottomUpMatcher {
-                stores: mapping.hyperast,
-                src_arena: mapping.mapping.src_arena,
-                dst_arena: mapping.mapping.dst_arena,
-                mappings: mapping.mapping.mappings,
-                _phantom: PhantomData,
-            },
-        };
+        let mut matcher = Self::from_mapper(mapping);
+        Self::execute(&mut matcher);
+        matcher.into_mapper()
+    }
+
+    /// Same as [`Self::match_it`] but with thresholds given at runtime instead of const generics.
+    ///
+    /// A `size_threshold` of 0 disables the last chance recovery.
+    pub fn match_it_with(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+        size_threshold: usize,
+        sim_threshold: f64,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        let mut matcher = Self::from_mapper(mapping);
+        matcher.execute_with(size_threshold, sim_threshold);
+        matcher.into_mapper()
+    }
+
+    /// Only applies the last chance recovery, with the optimal ZS matcher,
+    /// on already mapped pairs of nodes where one of the subtrees is smaller than `size_threshold`.
+    pub fn recover_it(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+        size_threshold: usize,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        Self::recover_with(mapping, size_threshold, Self::last_chance_match_zs_with)
+    }
+
+    /// Same as [`Self::recover_it`] but with the optimal APTED matcher,
+    /// which is faster than ZS on unbalanced subtrees.
+    pub fn recover_it_apted(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+        size_threshold: usize,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        Self::recover_with(mapping, size_threshold, Self::last_chance_match_apted_with)
+    }
+
+    fn recover_with(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+        size_threshold: usize,
+        last_chance_match: fn(&mut Self, M::Src, M::Dst, usize),
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        let mut matcher = Self::from_mapper(mapping);
+        let srcs: Vec<_> = matcher.internal.src_arena.iter_df_post::<true>().collect();
+        for a in srcs {
+            if !matcher.internal.mappings.is_src(&a) || !matcher.src_has_children(a) {
+                continue;
+            }
+            let b = matcher.internal.mappings.get_dst_unchecked(&a);
+            last_chance_match(&mut matcher, a, b, size_threshold);
+        }
+        matcher.into_mapper()
+    }
+
+    pub(super) fn from_mapper(mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>) -> Self {
+        let mut matcher = Self::new(
+            mapping.hyperast,
+            mapping.mapping.src_arena,
+            mapping.mapping.dst_arena,
+            mapping.mapping.mappings,
+        );
         matcher.internal.mappings.topit(
             matcher.internal.src_arena.len(),
             matcher.internal.dst_arena.len(),
         );
-        Self::execute(&mut matcher);
+        matcher
+    }
+
+    pub(super) fn into_mapper(self) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
         crate::matchers::Mapper {
-            hyperast: mapping.hyperast,
+            hyperast: self.internal.stores,
             mapping: crate::matchers::Mapping {
-                src_arena: matcher.internal.src_arena,
-                dst_arena: matcher.internal.dst_arena,
-                mappings: matcher.internal.mappings,
+                src_arena: self.internal.src_arena,
+                dst_arena: self.internal.dst_arena,
+                mappings: self.internal.mappings,
             },
         }
     }
@@ -171,6 +228,13 @@ where
     }
 
     pub fn execute<'b>(&mut self) {
+        self.execute_with(
+            SIZE_THRESHOLD,
+            SIM_THRESHOLD_NUM as f64 / SIM_THRESHOLD_DEN as f64,
+        )
+    }
+
+    pub fn execute_with(&mut self, size_threshold: usize, sim_threshold: f64) {
         assert_eq!(
             // TODO move it inside the arena ...
             self.internal.src_arena.root(),
@@ -196,14 +260,14 @@ where
                         &self.internal.mappings,
                     )
                     .dice();
-                    if sim > max && sim >= SIM_THRESHOLD_NUM as f64 / SIM_THRESHOLD_DEN as f64 {
+                    if sim > max && sim >= sim_threshold {
                         max = sim;
                         best = Some(cand);
                     }
                 }
 
                 if let Some(best) = best {
-                    self.last_chance_match_zs(a, best);
+                    self.last_chance_match_zs_with(a, best, size_threshold);
                     self.internal.mappings.link(a, best);
                 }
             }
@@ -213,15 +277,16 @@ where
             self.internal.src_arena.root(),
             self.internal.dst_arena.root(),
         );
-        self.last_chance_match_zs(
+        self.last_chance_match_zs_with(
             self.internal.src_arena.root(),
             self.internal.dst_arena.root(),
+            size_threshold,
         );
         // println!("nodes:{}", c);
         // println!("nodes:{}", c2);
     }
 
-    fn src_has_children(&mut self, src: M::Src) -> bool {
+    pub(super) fn src_has_children(&mut self, src: M::Src) -> bool {
         use num_traits::ToPrimitive;
         let r = self
             .internal
@@ -240,16 +305,17 @@ where
     }
 
     pub(crate) fn last_chance_match_zs(&mut self, src: M::Src, dst: M::Dst) {
+        self.last_chance_match_zs_with(src, dst, SIZE_THRESHOLD)
+    }
+
+    pub(super) fn last_chance_match_zs_with(
+        &mut self,
+        src: M::Src,
+        dst: M::Dst,
+        size_threshold: usize,
+    ) {
         // WIP https://blog.rust-lang.org/2022/10/28/gats-stabilization.html#implied-static-requirement-from-higher-ranked-trait-bounds
-        let src_s = self
-            .internal
-            .src_arena
-            .descendants_count(self.internal.stores.node_store(), &src);
-        let dst_s = self
-            .internal
-            .dst_arena
-            .descendants_count(self.internal.stores.node_store(), &dst);
-        if !(src_s < cast(SIZE_THRESHOLD).unwrap() || dst_s < cast(SIZE_THRESHOLD).unwrap()) {
+        if !self.is_small_enough(src, dst, size_threshold) {
             return;
         }
         let node_store = self.internal.stores.node_store();
@@ -300,6 +366,41 @@ where
         };
         let dst_offset = self.internal.dst_arena.first_descendant(&dst);
         assert_eq!(self.internal.src_arena.first_descendant(&src), src_offset);
+        self.link_recovered(src_offset, dst_offset, mappings);
+    }
+
+    pub(super) fn last_chance_match_apted_with(
+        &mut self,
+        src: M::Src,
+        dst: M::Dst,
+        size_threshold: usize,
+    ) {
+        if !self.is_small_enough(src, dst, size_threshold) {
+            return;
+        }
+        let src_arena = self.internal.src_arena.slice_po(&src);
+        let dst_arena = self.internal.dst_arena.slice_po(&dst);
+        let mappings: M = AptedMatcher::match_with(self.internal.stores, src_arena, dst_arena);
+        let src_offset = self.internal.src_arena.first_descendant(&src);
+        let dst_offset = self.internal.dst_arena.first_descendant(&dst);
+        self.link_recovered(src_offset, dst_offset, mappings);
+    }
+
+    fn is_small_enough(&self, src: M::Src, dst: M::Dst, size_threshold: usize) -> bool {
+        let src_s = self
+            .internal
+            .src_arena
+            .descendants_count(self.internal.stores.node_store(), &src);
+        let dst_s = self
+            .internal
+            .dst_arena
+            .descendants_count(self.internal.stores.node_store(), &dst);
+        src_s < cast(size_threshold).unwrap() || dst_s < cast(size_threshold).unwrap()
+    }
+
+    /// Adds the mappings found in the slices starting at `src_offset` and `dst_offset`,
+    /// if compatible with the existing ones.
+    fn link_recovered(&mut self, src_offset: M::Src, dst_offset: M::Dst, mappings: M) {
         for (i, t) in mappings.iter() {
             //remapping
             let src: M::Src = src_offset + cast(i).unwrap();
@@ -0,0 +1,122 @@
+//! GumTree's hybrid bottom-up matcher.
+//!
+//! Like the greedy bottom-up matcher, but the similarity threshold of each candidate depends on the sizes
+//! of the compared subtrees, and the optimal ZS matcher only recovers mappings inside small subtrees.
+use std::fmt::Debug;
+
+use num_traits::{PrimInt, ToPrimitive};
+
+use crate::decompressed_tree_store::{
+    ContiguousDescendants, DecompressedTreeStore, DecompressedWithParent, POBorrowSlice, PostOrder,
+    PostOrderIterable,
+};
+use crate::matchers::mapping_store::MonoMappingStore;
+use crate::matchers::similarity_metrics;
+use hyper_ast::types::{DecompressedSubtree, HyperAST, NodeId, Tree, WithHashs};
+
+use super::greedy_bottom_up_matcher::GreedyBottomUpMatcher;
+
+pub struct HybridBottomUpMatcher<
+    'a,
+    Dsrc,
+    Ddst,
+    T: 'a + Tree + WithHashs,
+    HAST,
+    M: MonoMappingStore,
+    const SIZE_THRESHOLD: usize = 20,
+> {
+    internal: GreedyBottomUpMatcher<'a, Dsrc, Ddst, T, HAST, M, SIZE_THRESHOLD>,
+}
+
+impl<
+        'a,
+        Dsrc: 'a
+            + DecompressedTreeStore<'a, T, M::Src>
+            + DecompressedWithParent<'a, T, M::Src>
+            + PostOrder<'a, T, M::Src>
+            + PostOrderIterable<'a, T, M::Src>
+            + DecompressedSubtree<'a, T, Out = Dsrc>
+            + ContiguousDescendants<'a, T, M::Src>
+            + POBorrowSlice<'a, T, M::Src>,
+        Ddst: 'a
+            + DecompressedTreeStore<'a, T, M::Dst>
+            + DecompressedWithParent<'a, T, M::Dst>
+            + PostOrder<'a, T, M::Dst>
+            + PostOrderIterable<'a, T, M::Dst>
+            + DecompressedSubtree<'a, T, Out = Ddst>
+            + ContiguousDescendants<'a, T, M::Dst>
+            + POBorrowSlice<'a, T, M::Dst>,
+        T: 'a + Tree + WithHashs,
+        HAST: HyperAST<'a, IdN = T::TreeId, T = T, Label = T::Label>,
+        M: MonoMappingStore + Default,
+        const SIZE_THRESHOLD: usize,
+    > HybridBottomUpMatcher<'a, Dsrc, Ddst, T, HAST, M, SIZE_THRESHOLD>
+where
+    T::TreeId: 'a + Clone + Debug + NodeId<IdN = T::TreeId>,
+    M::Src: 'a + PrimInt + std::ops::SubAssign + Debug,
+    M::Dst: 'a + PrimInt + std::ops::SubAssign + Debug,
+{
+    pub fn match_it(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        Self::match_it_with(mapping, SIZE_THRESHOLD)
+    }
+
+    /// Same as [`Self::match_it`] but with the size threshold given at runtime.
+    pub fn match_it_with(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+        size_threshold: usize,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        let mut matcher = Self {
+            internal: GreedyBottomUpMatcher::from_mapper(mapping),
+        };
+        matcher.execute_with(size_threshold);
+        matcher.internal.into_mapper()
+    }
+
+    pub fn execute(&mut self) {
+        self.execute_with(SIZE_THRESHOLD)
+    }
+
+    fn execute_with(&mut self, size_threshold: usize) {
+        let matcher = &mut self.internal;
+        assert!(matcher.internal.src_arena.len() > 0);
+        for a in matcher.internal.src_arena.iter_df_post::<true>() {
+            if matcher.internal.src_arena.parent(&a).is_none() {
+                break;
+            }
+            if matcher.internal.mappings.is_src(&a) || !matcher.src_has_children(a) {
+                continue;
+            }
+            let candidates = matcher.internal.get_dst_candidates(&a);
+            let src_range = matcher.internal.src_arena.descendants_range(&a);
+            let src_size = (src_range.end - src_range.start).to_usize().unwrap();
+            let mut best = None;
+            let mut max: f64 = -1.;
+            for cand in candidates {
+                let dst_range = matcher.internal.dst_arena.descendants_range(&cand);
+                let dst_size = (dst_range.end - dst_range.start).to_usize().unwrap();
+                let threshold = 1.0 / (1.0 + ((src_size + dst_size) as f64).ln());
+                let sim = similarity_metrics::SimilarityMeasure::range(
+                    &src_range,
+                    &dst_range,
+                    &matcher.internal.mappings,
+                )
+                .chawathe();
+                if sim > max && sim >= threshold {
+                    max = sim;
+                    best = Some(cand);
+                }
+            }
+            if let Some(best) = best {
+                matcher.last_chance_match_zs_with(a, best, size_threshold);
+                matcher.internal.mappings.link(a, best);
+            }
+        }
+        // for root
+        let src = matcher.internal.src_arena.root();
+        let dst = matcher.internal.dst_arena.root();
+        matcher.internal.mappings.link(src, dst);
+        matcher.last_chance_match_zs_with(src, dst, size_threshold);
+    }
+}
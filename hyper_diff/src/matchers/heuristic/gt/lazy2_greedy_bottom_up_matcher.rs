@@ -238,7 +238,10 @@ where
         // println!("nodes:{}", c2);
     }
 
-    fn src_has_children(internal: &Mapper<'a, HAST, Dsrc, Ddst, M>, src: Dsrc::IdD) -> bool {
+    pub(super) fn src_has_children(
+        internal: &Mapper<'a, HAST, Dsrc, Ddst, M>,
+        src: Dsrc::IdD,
+    ) -> bool {
         let o = internal.src_arena.original(&src);
         let r = internal.hyperast.node_store().resolve(&o).has_children();
 
@@ -257,6 +260,15 @@ where
         internal: &mut Mapper<'a, HAST, Dsrc, Ddst, M>,
         src: Dsrc::IdD,
         dst: Ddst::IdD,
+    ) {
+        Self::last_chance_match_zs_with(internal, src, dst, SIZE_THRESHOLD)
+    }
+
+    pub(crate) fn last_chance_match_zs_with(
+        internal: &mut Mapper<'a, HAST, Dsrc, Ddst, M>,
+        src: Dsrc::IdD,
+        dst: Ddst::IdD,
+        size_threshold: usize,
     ) {
         // allow using another internal mapping store
         // WIP https://blog.rust-lang.org/2022/10/28/gats-stabilization.html#implied-static-requirement-from-higher-ranked-trait-bounds
@@ -266,7 +278,7 @@ where
         let dst_arena = &mut mapping.dst_arena;
         let src_s = src_arena.descendants_count(node_store, &src);
         let dst_s = dst_arena.descendants_count(node_store, &dst);
-        if !(src_s < cast(SIZE_THRESHOLD).unwrap() || dst_s < cast(SIZE_THRESHOLD).unwrap()) {
+        if !(src_s < cast(size_threshold).unwrap() || dst_s < cast(size_threshold).unwrap()) {
             // dbg!(src_s, dst_s);
             return;
         }
@@ -63,6 +63,28 @@ where
         matcher.internal
     }
 
+    /// Same as [`Self::match_it`] but the top-down phase runs in parallel,
+    /// see [`Mapper::compute_multimapping_par`].
+    pub fn match_it_par<MM>(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>
+    where
+        Self: 'a,
+        MM: MultiMappingStore<Src = Dsrc::IdD, Dst = Ddst::IdD> + Default,
+        HAST: Sync,
+        HAST::IdN: Sync,
+        <HAST::T as WithHashs>::HP: Send + Sync,
+    {
+        let mut matcher = Self { internal: mapping };
+        matcher.internal.mapping.mappings.topit(
+            matcher.internal.mapping.src_arena.len(),
+            matcher.internal.mapping.dst_arena.len(),
+        );
+        let mm: MM = Self::compute_multi_mapping_par(&mut matcher.internal);
+        Self::filter_mappings(&mut matcher.internal, &mm);
+        matcher.internal
+    }
+
     // pub fn matchh<MM: MultiMappingStore<Src = Dsrc::IdD, Dst = Ddst::IdD>>(
     //     node_store: &'a S,
     //     src: &'a T::TreeId,
@@ -107,6 +129,27 @@ where
         );
         mm
     }
+
+    pub fn compute_multi_mapping_par<
+        MM: MultiMappingStore<Src = Dsrc::IdD, Dst = Ddst::IdD> + Default,
+    >(
+        internal: &mut Mapper<'a, HAST, Dsrc, Ddst, M>,
+    ) -> MM
+    where
+        HAST: Sync,
+        HAST::IdN: Sync,
+        <HAST::T as WithHashs>::HP: Send + Sync,
+    {
+        let mut mm: MM = Default::default();
+        mm.topit(internal.src_arena.len(), internal.dst_arena.len());
+        Mapper::<HAST, Dsrc, Ddst, M>::compute_multimapping_par::<_, MIN_HEIGHT>(
+            internal.hyperast,
+            &mut internal.mapping.src_arena,
+            &mut internal.mapping.dst_arena,
+            &mut mm,
+        );
+        mm
+    }
 }
 
 impl<
@@ -495,6 +538,27 @@ where
         src_arena: &mut Dsrc,
         dst_arena: &mut Ddst,
         multi_mappings: &mut MM,
+    ) {
+        Self::compute_multimapping_with::<_, MIN_HEIGHT>(
+            hyperast,
+            src_arena,
+            dst_arena,
+            multi_mappings,
+            Self::isomorphic_pairs,
+        )
+    }
+
+    /// Top-down phase, where subtrees of the same height are compared using `isomorphic_pairs`.
+    /// Subtrees that are not matched are then opened.
+    fn compute_multimapping_with<
+        MM: MultiMappingStore<Src = Dsrc::IdD, Dst = Ddst::IdD>,
+        const MIN_HEIGHT: usize,
+    >(
+        hyperast: &'a HAST,
+        src_arena: &mut Dsrc,
+        dst_arena: &mut Ddst,
+        multi_mappings: &mut MM,
+        isomorphic_pairs: impl Fn(&'a HAST, &[HAST::IdN], &[HAST::IdN]) -> Vec<(usize, usize)>,
     ) {
         let now = std::time::Instant::now();
         let mut src_trees = PriorityTreeList::<
@@ -540,21 +604,20 @@ where
             let mut marks_for_src_trees = bitvec::bitbox![0;current_height_src_trees.len()];
             let mut marks_for_dst_trees = bitvec::bitbox![0;current_height_dst_trees.len()];
 
-            for i in 0..current_height_src_trees.len() {
-                for j in 0..current_height_dst_trees.len() {
-                    let src = current_height_src_trees[i].clone();
-                    let dst = current_height_dst_trees[j].clone();
-                    let is_iso = {
-                        let src = src_trees.arena.original(&src);
-                        let dst = dst_trees.arena.original(&dst);
-                        Self::isomorphic_aux::<true>(hyperast, &src, &dst)
-                    };
-                    if is_iso {
-                        multi_mappings.link(src, dst);
-                        marks_for_src_trees.set(i, true);
-                        marks_for_dst_trees.set(j, true);
-                    }
-                }
+            let src_originals: Vec<_> = current_height_src_trees
+                .iter()
+                .map(|x| src_trees.arena.original(x))
+                .collect();
+            let dst_originals: Vec<_> = current_height_dst_trees
+                .iter()
+                .map(|x| dst_trees.arena.original(x))
+                .collect();
+            for (i, j) in isomorphic_pairs(hyperast, &src_originals, &dst_originals) {
+                let src = current_height_src_trees[i].clone();
+                let dst = current_height_dst_trees[j].clone();
+                multi_mappings.link(src, dst);
+                marks_for_src_trees.set(i, true);
+                marks_for_dst_trees.set(j, true);
             }
             for i in 0..marks_for_src_trees.len() {
                 if marks_for_src_trees[i] == false {
@@ -572,6 +635,23 @@ where
         }
     }
 
+    /// Compares every src subtree with every dst subtree.
+    fn isomorphic_pairs(
+        hyperast: &'a HAST,
+        srcs: &[HAST::IdN],
+        dsts: &[HAST::IdN],
+    ) -> Vec<(usize, usize)> {
+        let mut pairs = vec![];
+        for i in 0..srcs.len() {
+            for j in 0..dsts.len() {
+                if Self::isomorphic_aux::<true>(hyperast, &srcs[i], &dsts[j]) {
+                    pairs.push((i, j));
+                }
+            }
+        }
+        pairs
+    }
+
     /// if H then test the hash otherwise do not test it,
     /// considering hash colisions testing it should only be useful once.
     pub(crate) fn isomorphic_aux<const H: bool>(
@@ -623,6 +703,88 @@ where
     }
 }
 
+impl<
+        'a,
+        HAST: 'a + HyperAST<'a> + Sync,
+        Dsrc: DecompressedWithParent<'a, HAST::T, Dsrc::IdD>
+            + LazyDecompressedTreeStore<'a, HAST::T, M::Src>,
+        Ddst: DecompressedWithParent<'a, HAST::T, Ddst::IdD>
+            + LazyDecompressedTreeStore<'a, HAST::T, M::Dst>,
+        M: MonoMappingStore,
+    > crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>
+where
+    HAST::T: 'a + Tree + WithHashs + WithStats,
+    <HAST::T as WithHashs>::HP: Send + Sync,
+    HAST::IdN: Clone + Eq + Sync,
+    HAST::Label: Eq,
+    Dsrc::IdD: Clone,
+    Ddst::IdD: Clone,
+    M::Src: Debug + Copy,
+    M::Dst: Debug + Copy,
+{
+    /// Same as [`Self::compute_multimapping`] but subtrees of the same height are compared in parallel.
+    ///
+    /// The multi mappings are linked in the same order as the sequential version,
+    /// so the result does not depend on the number of threads.
+    pub fn compute_multimapping_par<
+        MM: MultiMappingStore<Src = Dsrc::IdD, Dst = Ddst::IdD>,
+        const MIN_HEIGHT: usize,
+    >(
+        hyperast: &'a HAST,
+        src_arena: &mut Dsrc,
+        dst_arena: &mut Ddst,
+        multi_mappings: &mut MM,
+    ) {
+        Self::compute_multimapping_with::<_, MIN_HEIGHT>(
+            hyperast,
+            src_arena,
+            dst_arena,
+            multi_mappings,
+            Self::isomorphic_pairs_par,
+        )
+    }
+
+    /// Buckets subtrees by hash, then compares each src subtree with the dst subtrees of its bucket.
+    fn isomorphic_pairs_par(
+        hyperast: &'a HAST,
+        srcs: &[HAST::IdN],
+        dsts: &[HAST::IdN],
+    ) -> Vec<(usize, usize)> {
+        use rayon::prelude::{
+            IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator, ParallelSliceMut,
+        };
+        let hash = |x: &HAST::IdN| {
+            let n = hyperast.node_store().resolve(x);
+            WithHashs::hash(&n, &<HAST::T as WithHashs>::HK::label())
+        };
+        let src_hashes: Vec<_> = srcs.par_iter().map(hash).collect();
+        let mut dst_buckets: Vec<_> = dsts
+            .par_iter()
+            .map(hash)
+            .enumerate()
+            .map(|(j, h)| (h, j))
+            .collect();
+        // within a bucket, dsts stay in the order of the sequential version
+        dst_buckets.par_sort_unstable();
+        let dst_buckets = &dst_buckets;
+        src_hashes
+            .par_iter()
+            .enumerate()
+            .flat_map_iter(move |(i, h)| {
+                let start = dst_buckets.partition_point(|(x, _)| x < h);
+                dst_buckets[start..]
+                    .iter()
+                    .take_while(move |(x, _)| x == h)
+                    .filter(move |(_, j)| {
+                        // hashes are already known to be equal
+                        Self::isomorphic_aux::<false>(hyperast, &srcs[i], &dsts[*j])
+                    })
+                    .map(move |(_, j)| (i, *j))
+            })
+            .collect()
+    }
+}
+
 struct PriorityTreeList<'a, 'b, D, IdS, IdD, T: Tree, S, const MIN_HEIGHT: usize> {
     trees: Vec<Option<Vec<IdD>>>,
 
@@ -0,0 +1,298 @@
+//! makes change_distiller_matcher lazy
+//!
+//! ChangeDistiller has to look at every leaf, so both sides end up decompressed,
+//! but only as they are reached.
+use std::fmt::Debug;
+
+use num_traits::{cast, PrimInt, ToPrimitive};
+
+use crate::decompressed_tree_store::{
+    ContiguousDescendants, DecompressedTreeStore, LazyDecompressedTreeStore, PostOrder,
+    PostOrderIterable, ShallowDecompressedTreeStore,
+};
+use crate::matchers::mapping_store::MonoMappingStore;
+use crate::matchers::similarity_metrics;
+use crate::matchers::Mapper;
+use hyper_ast::types::{HyperAST, LabelStore, NodeStore, Tree, WithStats};
+
+pub struct LazyChangeDistillerLeavesMatcher<
+    'a,
+    Dsrc,
+    Ddst,
+    HAST,
+    M: MonoMappingStore,
+    const LABEL_SIM_THRESHOLD_NUM: u64 = 1,
+    const LABEL_SIM_THRESHOLD_DEN: u64 = 2,
+> {
+    internal: Mapper<'a, HAST, Dsrc, Ddst, M>,
+}
+
+impl<
+        'a,
+        Dsrc: DecompressedTreeStore<'a, HAST::T, Dsrc::IdD, M::Src>
+            + PostOrder<'a, HAST::T, Dsrc::IdD, M::Src>
+            + PostOrderIterable<'a, HAST::T, Dsrc::IdD, M::Src>
+            + ShallowDecompressedTreeStore<'a, HAST::T, Dsrc::IdD, M::Src>
+            + LazyDecompressedTreeStore<'a, HAST::T, M::Src>,
+        Ddst: DecompressedTreeStore<'a, HAST::T, Ddst::IdD, M::Dst>
+            + PostOrder<'a, HAST::T, Ddst::IdD, M::Dst>
+            + PostOrderIterable<'a, HAST::T, Ddst::IdD, M::Dst>
+            + ShallowDecompressedTreeStore<'a, HAST::T, Ddst::IdD, M::Dst>
+            + LazyDecompressedTreeStore<'a, HAST::T, M::Dst>,
+        HAST: HyperAST<'a>,
+        M: MonoMappingStore,
+        const LABEL_SIM_THRESHOLD_NUM: u64,
+        const LABEL_SIM_THRESHOLD_DEN: u64,
+    >
+    LazyChangeDistillerLeavesMatcher<
+        'a,
+        Dsrc,
+        Ddst,
+        HAST,
+        M,
+        LABEL_SIM_THRESHOLD_NUM,
+        LABEL_SIM_THRESHOLD_DEN,
+    >
+where
+    HAST::T: 'a + Tree + WithStats,
+    HAST::IdN: 'a + Clone + Eq + Debug,
+    Dsrc::IdD: 'a + PrimInt + std::ops::SubAssign + Debug,
+    Ddst::IdD: 'a + PrimInt + std::ops::SubAssign + Debug,
+    M::Src: 'a + PrimInt + std::ops::SubAssign + Debug,
+    M::Dst: 'a + PrimInt + std::ops::SubAssign + Debug,
+{
+    pub fn match_it(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        Self::match_it_with(
+            mapping,
+            LABEL_SIM_THRESHOLD_NUM as f64 / LABEL_SIM_THRESHOLD_DEN as f64,
+        )
+    }
+
+    /// Same as [`Self::match_it`] but with the label similarity threshold given at runtime.
+    pub fn match_it_with(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+        label_sim_threshold: f64,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        let mut matcher = Self { internal: mapping };
+        matcher.internal.mapping.mappings.topit(
+            matcher.internal.mapping.src_arena.len(),
+            matcher.internal.mapping.dst_arena.len(),
+        );
+        Self::execute(&mut matcher.internal, label_sim_threshold);
+        matcher.internal
+    }
+
+    pub fn execute(internal: &mut Mapper<'a, HAST, Dsrc, Ddst, M>, label_sim_threshold: f64) {
+        let hyperast = internal.hyperast;
+        let mut dst_leaves = vec![];
+        for x in internal.dst_arena.iter_df_post::<true>() {
+            if internal.mappings.is_dst(&x) {
+                continue;
+            }
+            let d = internal
+                .mapping
+                .dst_arena
+                .decompress_to(hyperast.node_store(), &x);
+            if internal.dst_arena.lld(&d) != x {
+                continue;
+            }
+            let o = internal.dst_arena.original(&d);
+            dst_leaves.push((x, hyperast.resolve_type(&o), label(hyperast, &o)));
+        }
+        let mut candidates = vec![];
+        for src in internal.src_arena.iter_df_post::<true>() {
+            if internal.mappings.is_src(&src) {
+                continue;
+            }
+            let s = internal
+                .mapping
+                .src_arena
+                .decompress_to(hyperast.node_store(), &src);
+            if internal.src_arena.lld(&s) != src {
+                continue;
+            }
+            let o = internal.src_arena.original(&s);
+            let t = hyperast.resolve_type(&o);
+            let l = label(hyperast, &o);
+            for (dst, dst_t, dst_l) in &dst_leaves {
+                if &t != dst_t {
+                    continue;
+                }
+                let sim = similarity_metrics::label_similarity(l, dst_l);
+                if sim > label_sim_threshold {
+                    candidates.push((src, *dst, sim));
+                }
+            }
+        }
+        // stable, so ties are resolved in post order
+        candidates.sort_by(|a, b| b.2.total_cmp(&a.2));
+        for (src, dst, _) in candidates {
+            internal.mappings.link_if_both_unmapped(src, dst);
+        }
+    }
+}
+
+pub struct LazyChangeDistillerBottomUpMatcher<
+    'a,
+    Dsrc,
+    Ddst,
+    HAST,
+    M: MonoMappingStore,
+    const MAX_LEAVES: usize = 4,
+    const SIM_THRESHOLD_LARGE_NUM: u64 = 3,
+    const SIM_THRESHOLD_LARGE_DEN: u64 = 5,
+    const SIM_THRESHOLD_SMALL_NUM: u64 = 2,
+    const SIM_THRESHOLD_SMALL_DEN: u64 = 5,
+> {
+    internal: Mapper<'a, HAST, Dsrc, Ddst, M>,
+}
+
+impl<
+        'a,
+        Dsrc: DecompressedTreeStore<'a, HAST::T, Dsrc::IdD, M::Src>
+            + PostOrder<'a, HAST::T, Dsrc::IdD, M::Src>
+            + PostOrderIterable<'a, HAST::T, Dsrc::IdD, M::Src>
+            + ContiguousDescendants<'a, HAST::T, Dsrc::IdD, M::Src>
+            + ShallowDecompressedTreeStore<'a, HAST::T, Dsrc::IdD, M::Src>
+            + LazyDecompressedTreeStore<'a, HAST::T, M::Src>,
+        Ddst: DecompressedTreeStore<'a, HAST::T, Ddst::IdD, M::Dst>
+            + PostOrder<'a, HAST::T, Ddst::IdD, M::Dst>
+            + PostOrderIterable<'a, HAST::T, Ddst::IdD, M::Dst>
+            + ContiguousDescendants<'a, HAST::T, Ddst::IdD, M::Dst>
+            + ShallowDecompressedTreeStore<'a, HAST::T, Ddst::IdD, M::Dst>
+            + LazyDecompressedTreeStore<'a, HAST::T, M::Dst>,
+        HAST: HyperAST<'a>,
+        M: MonoMappingStore,
+        const MAX_LEAVES: usize,
+        const SIM_THRESHOLD_LARGE_NUM: u64,
+        const SIM_THRESHOLD_LARGE_DEN: u64,
+        const SIM_THRESHOLD_SMALL_NUM: u64,
+        const SIM_THRESHOLD_SMALL_DEN: u64,
+    >
+    LazyChangeDistillerBottomUpMatcher<
+        'a,
+        Dsrc,
+        Ddst,
+        HAST,
+        M,
+        MAX_LEAVES,
+        SIM_THRESHOLD_LARGE_NUM,
+        SIM_THRESHOLD_LARGE_DEN,
+        SIM_THRESHOLD_SMALL_NUM,
+        SIM_THRESHOLD_SMALL_DEN,
+    >
+where
+    HAST::T: 'a + Tree + WithStats,
+    HAST::IdN: 'a + Clone + Eq + Debug,
+    Dsrc::IdD: 'a + PrimInt + std::ops::SubAssign + Debug,
+    Ddst::IdD: 'a + PrimInt + std::ops::SubAssign + Debug,
+    M::Src: 'a + PrimInt + std::ops::SubAssign + Debug,
+    M::Dst: 'a + PrimInt + std::ops::SubAssign + Debug,
+{
+    pub fn match_it(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        Self::match_it_with(
+            mapping,
+            MAX_LEAVES,
+            SIM_THRESHOLD_LARGE_NUM as f64 / SIM_THRESHOLD_LARGE_DEN as f64,
+            SIM_THRESHOLD_SMALL_NUM as f64 / SIM_THRESHOLD_SMALL_DEN as f64,
+        )
+    }
+
+    /// Same as [`Self::match_it`] but with the thresholds given at runtime.
+    pub fn match_it_with(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+        max_leaves: usize,
+        sim_threshold_large: f64,
+        sim_threshold_small: f64,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        let mut matcher = Self { internal: mapping };
+        matcher.internal.mapping.mappings.topit(
+            matcher.internal.mapping.src_arena.len(),
+            matcher.internal.mapping.dst_arena.len(),
+        );
+        Self::execute(
+            &mut matcher.internal,
+            max_leaves,
+            sim_threshold_large,
+            sim_threshold_small,
+        );
+        matcher.internal
+    }
+
+    pub fn execute(
+        internal: &mut Mapper<'a, HAST, Dsrc, Ddst, M>,
+        max_leaves: usize,
+        sim_threshold_large: f64,
+        sim_threshold_small: f64,
+    ) {
+        let hyperast = internal.hyperast;
+        let mut dst_inners = vec![];
+        for x in internal.dst_arena.iter_df_post::<true>() {
+            let d = internal
+                .mapping
+                .dst_arena
+                .decompress_to(hyperast.node_store(), &x);
+            if internal.dst_arena.lld(&d) != x {
+                let t = hyperast.resolve_type(&internal.dst_arena.original(&d));
+                dst_inners.push((x, d, t));
+            }
+        }
+        for src in internal.src_arena.iter_df_post::<true>() {
+            if internal.mappings.is_src(&src) {
+                continue;
+            }
+            let s = internal
+                .mapping
+                .src_arena
+                .decompress_to(hyperast.node_store(), &src);
+            if internal.src_arena.lld(&s) == src {
+                continue;
+            }
+            let t = hyperast.resolve_type(&internal.src_arena.original(&s));
+            let src_range = internal.src_arena.descendants_range(&s);
+            let mut leaves = 0;
+            for x in src_range.start.to_usize().unwrap()..src_range.end.to_usize().unwrap() {
+                let x = cast::<_, M::Src>(x).unwrap();
+                let d = internal
+                    .mapping
+                    .src_arena
+                    .decompress_to(hyperast.node_store(), &x);
+                if internal.src_arena.lld(&d) == x {
+                    leaves += 1;
+                }
+            }
+            let threshold = if leaves > max_leaves {
+                sim_threshold_large
+            } else {
+                sim_threshold_small
+            };
+            for (dst, d, dst_t) in &dst_inners {
+                if &t != dst_t || internal.mappings.is_dst(dst) {
+                    continue;
+                }
+                let sim = similarity_metrics::SimilarityMeasure::range(
+                    &src_range,
+                    &internal.dst_arena.descendants_range(d),
+                    &internal.mappings,
+                )
+                .chawathe();
+                if sim >= threshold {
+                    internal.mappings.link(src, *dst);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+fn label<'a, HAST: HyperAST<'a>>(hyperast: &'a HAST, id: &HAST::IdN) -> &'a str {
+    let n = hyperast.node_store().resolve(id);
+    match n.try_get_label() {
+        Some(l) => hyperast.label_store().resolve(l),
+        None => "",
+    }
+}
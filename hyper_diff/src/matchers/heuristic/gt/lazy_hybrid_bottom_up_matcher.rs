@@ -0,0 +1,144 @@
+//! makes hybrid_bottom_up_matcher lazy
+use std::{fmt::Debug, marker::PhantomData};
+
+use num_traits::{PrimInt, ToPrimitive};
+
+use crate::decompressed_tree_store::{
+    ContiguousDescendants, DecompressedTreeStore, DecompressedWithParent,
+    LazyDecompressedTreeStore, LazyPOBorrowSlice, PostOrder, PostOrderIterable, Shallow,
+    ShallowDecompressedTreeStore,
+};
+use crate::matchers::mapping_store::MonoMappingStore;
+use crate::matchers::similarity_metrics;
+use crate::matchers::Mapper;
+use hyper_ast::types::{DecompressedSubtree, HyperAST, Tree, WithHashs, WithStats};
+
+use super::lazy2_greedy_bottom_up_matcher::GreedyBottomUpMatcher;
+
+pub struct LazyHybridBottomUpMatcher<
+    'a,
+    Dsrc,
+    Ddst,
+    HAST: HyperAST<'a>,
+    M: MonoMappingStore,
+    MZs: MonoMappingStore = M,
+    const SIZE_THRESHOLD: usize = 20,
+> {
+    internal: Mapper<'a, HAST, Dsrc, Ddst, M>,
+    _phantom: PhantomData<*const MZs>,
+}
+
+impl<
+        'a,
+        Dsrc: DecompressedTreeStore<'a, HAST::T, Dsrc::IdD, M::Src>
+            + DecompressedWithParent<'a, HAST::T, Dsrc::IdD>
+            + PostOrder<'a, HAST::T, Dsrc::IdD, M::Src>
+            + PostOrderIterable<'a, HAST::T, Dsrc::IdD, M::Src>
+            + DecompressedSubtree<'a, HAST::T>
+            + ContiguousDescendants<'a, HAST::T, Dsrc::IdD, M::Src>
+            + LazyPOBorrowSlice<'a, HAST::T, Dsrc::IdD, M::Src>
+            + ShallowDecompressedTreeStore<'a, HAST::T, Dsrc::IdD, M::Src>
+            + LazyDecompressedTreeStore<'a, HAST::T, M::Src>,
+        Ddst: DecompressedTreeStore<'a, HAST::T, Ddst::IdD, M::Dst>
+            + DecompressedWithParent<'a, HAST::T, Ddst::IdD>
+            + PostOrder<'a, HAST::T, Ddst::IdD, M::Dst>
+            + PostOrderIterable<'a, HAST::T, Ddst::IdD, M::Dst>
+            + DecompressedSubtree<'a, HAST::T>
+            + ContiguousDescendants<'a, HAST::T, Ddst::IdD, M::Dst>
+            + LazyPOBorrowSlice<'a, HAST::T, Ddst::IdD, M::Dst>
+            + ShallowDecompressedTreeStore<'a, HAST::T, Ddst::IdD, M::Dst>
+            + LazyDecompressedTreeStore<'a, HAST::T, M::Dst>,
+        HAST: HyperAST<'a>,
+        M: MonoMappingStore,
+        MZs: MonoMappingStore<Src = Dsrc::IdD, Dst = Ddst::IdD> + Default,
+        const SIZE_THRESHOLD: usize,
+    > LazyHybridBottomUpMatcher<'a, Dsrc, Ddst, HAST, M, MZs, SIZE_THRESHOLD>
+where
+    HAST::T: 'a + Tree + WithHashs + WithStats,
+    HAST::IdN: 'a + Clone + Eq + Debug,
+    Dsrc::IdD: 'a + PrimInt + std::ops::SubAssign + Debug,
+    Ddst::IdD: 'a + PrimInt + std::ops::SubAssign + Debug,
+    M::Src: 'a + PrimInt + std::ops::SubAssign + Debug,
+    M::Dst: 'a + PrimInt + std::ops::SubAssign + Debug,
+{
+    pub fn match_it(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        Self::match_it_with(mapping, SIZE_THRESHOLD)
+    }
+
+    /// Same as [`Self::match_it`] but with the size threshold given at runtime.
+    pub fn match_it_with(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+        size_threshold: usize,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        let mut matcher = Self {
+            internal: mapping,
+            _phantom: PhantomData,
+        };
+        matcher.internal.mapping.mappings.topit(
+            matcher.internal.mapping.src_arena.len(),
+            matcher.internal.mapping.dst_arena.len(),
+        );
+        Self::execute(&mut matcher.internal, size_threshold);
+        matcher.internal
+    }
+
+    pub fn execute(internal: &mut Mapper<'a, HAST, Dsrc, Ddst, M>, size_threshold: usize) {
+        assert!(internal.src_arena.len() > 0);
+        for a in internal.src_arena.iter_df_post::<false>() {
+            if internal.mappings.is_src(&a) {
+                continue;
+            }
+            let a = internal
+                .mapping
+                .src_arena
+                .decompress_to(internal.hyperast.node_store(), &a);
+            if !GreedyBottomUpMatcher::<Dsrc, Ddst, HAST, M, MZs>::src_has_children(internal, a) {
+                continue;
+            }
+            let candidates = internal.get_dst_candidates_lazily(&a);
+            let src_range = internal.src_arena.descendants_range(&a);
+            let src_size = (src_range.end - src_range.start).to_usize().unwrap();
+            let mut best = None;
+            let mut max: f64 = -1.;
+            for cand in candidates {
+                let dst_range = internal.dst_arena.descendants_range(&cand);
+                let dst_size = (dst_range.end - dst_range.start).to_usize().unwrap();
+                let threshold = 1.0 / (1.0 + ((src_size + dst_size) as f64).ln());
+                let sim = similarity_metrics::SimilarityMeasure::range(
+                    &src_range,
+                    &dst_range,
+                    &internal.mappings,
+                )
+                .chawathe();
+                if sim > max && sim >= threshold {
+                    max = sim;
+                    best = Some(cand);
+                }
+            }
+            if let Some(best) = best {
+                GreedyBottomUpMatcher::<Dsrc, Ddst, HAST, M, MZs>::last_chance_match_zs_with(
+                    internal,
+                    a,
+                    best,
+                    size_threshold,
+                );
+                internal.mappings.link(*a.shallow(), *best.shallow());
+            }
+        }
+        // for root
+        internal.mapping.mappings.link(
+            internal.mapping.src_arena.root(),
+            internal.mapping.dst_arena.root(),
+        );
+        let src = internal.src_arena.starter();
+        let dst = internal.dst_arena.starter();
+        GreedyBottomUpMatcher::<Dsrc, Ddst, HAST, M, MZs>::last_chance_match_zs_with(
+            internal,
+            src,
+            dst,
+            size_threshold,
+        );
+    }
+}
@@ -1,17 +1,20 @@
 use hyper_ast::types::{IterableChildren, NodeId, NodeStore, WithChildren};
 
 pub mod bottom_up_matcher;
+pub mod change_distiller_matcher;
 pub mod greedy_bottom_up_matcher;
 pub mod greedy_subtree_matcher;
-#[allow(unused)] // TODO finish simple bottom up matcher
+pub mod hybrid_bottom_up_matcher;
 pub mod simple_bottom_up_matcher;
 
 // lazy versions, that do not decompress directly subtrees
 pub mod lazy2_greedy_bottom_up_matcher;
 pub mod lazy2_greedy_subtree_matcher;
 pub mod lazy_bottom_up_matcher;
+pub mod lazy_change_distiller_matcher;
 pub mod lazy_greedy_bottom_up_matcher;
 pub mod lazy_greedy_subtree_matcher;
+pub mod lazy_hybrid_bottom_up_matcher;
 // pub mod simple_bottom_up_matcher2;
 
 pub fn size<'a, IdC: Clone + NodeId<IdN = IdC>, S>(store: &'a S, x: &IdC) -> usize
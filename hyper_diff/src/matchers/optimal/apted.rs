@@ -0,0 +1,473 @@
+//! APTED, the all path tree edit distance algorithm of Pawlik and Augsten, 2016
+//!
+//! Zhang and Shasha always decompose both trees along their left paths,
+//! which is quadratic in the number of subproblems on trees leaning to the right, e.g. long sequences of statements.
+//! Here each pair of subtrees is decomposed along the left or the right path of one of them,
+//! whichever minimizes the number of subproblems, as given by a strategy computed beforehand.
+//!
+//! Only left and right paths are considered, heavy paths are not supported.
+//! The distances between subtrees are the same as with Zhang and Shasha,
+//! so mappings are extracted the same way, see [`super::zs`].
+use std::fmt::Debug;
+
+use num_traits::{cast, PrimInt, ToPrimitive};
+
+use super::zs::{MatcherImpl, ZsMatcherDist};
+use crate::decompressed_tree_store::{PostOrder, PostOrderKeyRoots};
+use crate::matchers::mapping_store::MonoMappingStore;
+use hyper_ast::types::{DecompressedSubtree, HyperAST, NodeId, Tree, WithChildren};
+
+pub struct AptedMatcher<M, SD, DD = SD> {
+    pub mappings: M,
+    pub src_arena: SD,
+    pub dst_arena: DD,
+}
+
+impl<SD, DD, M: MonoMappingStore + Default> AptedMatcher<M, SD, DD> {
+    pub fn matchh<'store: 'b, 'b, T, HAST>(
+        stores: &'store HAST,
+        src: T::TreeId,
+        dst: T::TreeId,
+    ) -> Self
+    where
+        T::TreeId: Clone + NodeId<IdN = T::TreeId>,
+        M::Src: PrimInt + std::ops::SubAssign + Debug,
+        M::Dst: PrimInt + std::ops::SubAssign + Debug,
+        SD: 'b + PostOrderKeyRoots<'b, T, M::Src> + DecompressedSubtree<'store, T, Out = SD>,
+        DD: 'b + PostOrderKeyRoots<'b, T, M::Dst> + DecompressedSubtree<'store, T, Out = DD>,
+        T: 'store + Tree,
+        HAST: HyperAST<'store, IdN = T::TreeId, T = T, Label = T::Label>,
+    {
+        let src_arena = SD::decompress(stores.node_store(), &src);
+        let dst_arena = DD::decompress(stores.node_store(), &dst);
+        let mappings = Self::mappings(stores, &src_arena, &dst_arena);
+        Self {
+            mappings,
+            src_arena,
+            dst_arena,
+        }
+    }
+
+    pub fn match_with<'store: 'b, 'b, T, HAST>(
+        stores: &'store HAST,
+        src_arena: SD,
+        dst_arena: DD,
+    ) -> M
+    where
+        T::TreeId: Clone + NodeId<IdN = T::TreeId>,
+        M::Src: PrimInt + std::ops::SubAssign + Debug,
+        M::Dst: PrimInt + std::ops::SubAssign + Debug,
+        SD: 'b + PostOrderKeyRoots<'b, T, M::Src>,
+        DD: 'b + PostOrderKeyRoots<'b, T, M::Dst>,
+        T: 'store + Tree,
+        HAST: HyperAST<'store, IdN = T::TreeId, T = T, Label = T::Label>,
+    {
+        Self::mappings(stores, &src_arena, &dst_arena)
+    }
+
+    /// Edit distance between the two trees, with unit costs except for the update of labels
+    pub fn distance<'store: 'b, 'b: 'c, 'c, T, HAST>(
+        stores: &'store HAST,
+        src_arena: &'c SD,
+        dst_arena: &'c DD,
+    ) -> f64
+    where
+        T::TreeId: Clone,
+        M::Src: PrimInt + std::ops::SubAssign + Debug,
+        M::Dst: PrimInt + std::ops::SubAssign + Debug,
+        SD: 'b + PostOrderKeyRoots<'b, T, M::Src>,
+        DD: 'b + PostOrderKeyRoots<'b, T, M::Dst>,
+        T: 'store + Tree,
+        HAST: HyperAST<'store, IdN = T::TreeId, T = T, Label = T::Label>,
+    {
+        let base =
+            MatcherImpl::<'store, 'b, '_, SD, DD, T, HAST, M>::new(stores, src_arena, dst_arena);
+        let delta = subtree_distances(&base);
+        delta[delta.len() - 1]
+    }
+
+    fn mappings<'store: 'b, 'b: 'c, 'c, T, HAST>(
+        stores: &'store HAST,
+        src_arena: &'c SD,
+        dst_arena: &'c DD,
+    ) -> M
+    where
+        T::TreeId: Clone,
+        M::Src: PrimInt + std::ops::SubAssign + Debug,
+        M::Dst: PrimInt + std::ops::SubAssign + Debug,
+        SD: 'b + PostOrderKeyRoots<'b, T, M::Src>,
+        DD: 'b + PostOrderKeyRoots<'b, T, M::Dst>,
+        T: 'store + Tree,
+        HAST: HyperAST<'store, IdN = T::TreeId, T = T, Label = T::Label>,
+    {
+        let mut mappings = M::default();
+        mappings.topit(src_arena.len() + 1, dst_arena.len() + 1);
+        let base =
+            MatcherImpl::<'store, 'b, '_, SD, DD, T, HAST, M>::new(stores, src_arena, dst_arena);
+        let delta = subtree_distances(&base);
+        let m = dst_arena.len();
+        let mut dist = ZsMatcherDist::with_tree_dist(src_arena.len(), m, |i, j| delta[i * m + j]);
+        // the mapping extraction expects the forest distances of the roots
+        base.forest_dist(&mut dist, &src_arena.root(), &dst_arena.root());
+        base.compute_mappings(&mut mappings, &mut dist);
+        mappings
+    }
+}
+
+/// Distances between all pairs of subtrees, indexed by `src * dst_len + dst`
+fn subtree_distances<'store: 'b, 'b: 'c, 'c, SD, DD, T, HAST, M>(
+    base: &MatcherImpl<'store, 'b, 'c, SD, DD, T, HAST, M>,
+) -> Vec<f64>
+where
+    T::TreeId: Clone,
+    M::Src: PrimInt + std::ops::SubAssign + Debug,
+    M::Dst: PrimInt + std::ops::SubAssign + Debug,
+    SD: 'c + PostOrderKeyRoots<'b, T, M::Src>,
+    DD: 'c + PostOrderKeyRoots<'b, T, M::Dst>,
+    T: 'store + Tree,
+    HAST: HyperAST<'store, IdN = T::TreeId, T = T, Label = T::Label>,
+    M: MonoMappingStore,
+{
+    let src_arena = base.src_arena;
+    let dst_arena = base.dst_arena;
+    let src_tree = |i: usize| src_arena.tree(&cast(i).unwrap());
+    let dst_tree = |j: usize| dst_arena.tree(&cast(j).unwrap());
+    let src = Layout::new(src_arena);
+    let dst = Layout::new(dst_arena);
+    let del = (0..src.len())
+        .map(|i| base.get_deletion_cost(&src_tree(i)))
+        .collect();
+    let ins = (0..dst.len())
+        .map(|j| base.get_insertion_cost(&dst_tree(j)))
+        .collect();
+    let mut ren = |i, j| base.get_update_cost(&src_tree(i), &dst_tree(j));
+    Apted::new(src, dst, del, ins, &mut ren).run()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Path {
+    Left = 0,
+    Right = 1,
+}
+
+const PATHS: [Path; 2] = [Path::Left, Path::Right];
+
+/// Decomposition of a pair of subtrees
+#[derive(Clone, Copy, Debug)]
+struct Strategy {
+    /// along a path in the src subtree, otherwise in the dst subtree
+    in_src: bool,
+    path: Path,
+}
+
+/// The shape of a tree in post order
+struct Layout {
+    lld: Vec<usize>,
+    children: Vec<Vec<usize>>,
+}
+
+impl Layout {
+    fn new<'b, T: WithChildren, IdD: PrimInt, D: PostOrder<'b, T, IdD>>(arena: &D) -> Self {
+        let lld: Vec<usize> = (0..arena.len())
+            .map(|i| arena.lld(&cast(i).unwrap()).to_usize().unwrap())
+            .collect();
+        let children = (0..lld.len())
+            .map(|i| {
+                let mut cs = vec![];
+                let mut c = i;
+                while c > lld[i] {
+                    c -= 1;
+                    cs.push(c);
+                    c = lld[c];
+                }
+                cs.reverse();
+                cs
+            })
+            .collect();
+        Self { lld, children }
+    }
+
+    fn len(&self) -> usize {
+        self.lld.len()
+    }
+
+    fn size(&self, x: usize) -> usize {
+        x - self.lld[x] + 1
+    }
+
+    fn path_child(&self, x: usize, path: Path) -> Option<usize> {
+        match path {
+            Path::Left => self.children[x].first().copied(),
+            Path::Right => self.children[x].last().copied(),
+        }
+    }
+
+    /// Roots of the subtrees hanging off the path going down from `x`
+    fn hanging(&self, mut x: usize, path: Path) -> Vec<usize> {
+        let mut r = vec![];
+        while let Some(c) = self.path_child(x, path) {
+            r.extend(self.children[x].iter().filter(|y| **y != c));
+            x = c;
+        }
+        r
+    }
+
+    /// For each subtree, the sum of the sizes of its keyroots,
+    /// i.e. the number of subproblems of a single path function decomposing the other tree
+    fn keyroots_sizes(&self, path: Path) -> Vec<u64> {
+        let mut r = vec![0; self.len()];
+        for x in 0..self.len() {
+            r[x] = self.size(x) as u64;
+            for c in &self.children[x] {
+                r[x] += r[*c];
+            }
+            if let Some(c) = self.path_child(x, path) {
+                r[x] -= self.size(c) as u64;
+            }
+        }
+        r
+    }
+
+    /// The post order of the tree where the `path` children come first
+    fn view(&self, path: Path) -> View {
+        let n = self.len();
+        let mut view = View {
+            to_orig: Vec::with_capacity(n),
+            from_orig: vec![0; n],
+            lld: Vec::with_capacity(n),
+        };
+        let mut stack = vec![(n - 1, 0)];
+        while let Some((x, i)) = stack.pop() {
+            let cs = &self.children[x];
+            if i < cs.len() {
+                stack.push((x, i + 1));
+                let c = match path {
+                    Path::Left => cs[i],
+                    Path::Right => cs[cs.len() - 1 - i],
+                };
+                stack.push((c, 0));
+                continue;
+            }
+            let id = view.to_orig.len();
+            view.to_orig.push(x);
+            view.from_orig[x] = id;
+            let lld = match self.path_child(x, path) {
+                Some(c) => view.lld[view.from_orig[c]],
+                None => id,
+            };
+            view.lld.push(lld);
+        }
+        view
+    }
+}
+
+struct View {
+    to_orig: Vec<usize>,
+    from_orig: Vec<usize>,
+    lld: Vec<usize>,
+}
+
+struct Apted<'a> {
+    src: Layout,
+    dst: Layout,
+    /// indexed by [`Path`]
+    src_views: [View; 2],
+    dst_views: [View; 2],
+    del: Vec<f64>,
+    ins: Vec<f64>,
+    ren: &'a mut dyn FnMut(usize, usize) -> f64,
+    /// NaN until computed
+    ren_cache: Vec<f64>,
+    delta: Vec<f64>,
+}
+
+impl<'a> Apted<'a> {
+    fn new(
+        src: Layout,
+        dst: Layout,
+        del: Vec<f64>,
+        ins: Vec<f64>,
+        ren: &'a mut dyn FnMut(usize, usize) -> f64,
+    ) -> Self {
+        let len = src.len() * dst.len();
+        Self {
+            src_views: PATHS.map(|p| src.view(p)),
+            dst_views: PATHS.map(|p| dst.view(p)),
+            src,
+            dst,
+            del,
+            ins,
+            ren,
+            ren_cache: vec![f64::NAN; len],
+            delta: vec![0.0; len],
+        }
+    }
+
+    fn run(mut self) -> Vec<f64> {
+        let m = self.dst.len();
+        let strategies = self.strategies();
+        let mut stack = vec![(self.src.len() - 1, m - 1, false)];
+        while let Some((v, w, ready)) = stack.pop() {
+            let s = strategies[v * m + w];
+            if ready {
+                self.spf(s, v, w);
+                continue;
+            }
+            stack.push((v, w, true));
+            // the subtrees hanging off the path must be solved first
+            if s.in_src {
+                stack.extend(
+                    self.src
+                        .hanging(v, s.path)
+                        .into_iter()
+                        .map(|u| (u, w, false)),
+                );
+            } else {
+                stack.extend(
+                    self.dst
+                        .hanging(w, s.path)
+                        .into_iter()
+                        .map(|u| (v, u, false)),
+                );
+            }
+        }
+        self.delta
+    }
+
+    /// Computes the cheapest strategy of each pair of subtrees, counting subproblems as in RTED
+    fn strategies(&self) -> Vec<Strategy> {
+        let n = self.src.len();
+        let m = self.dst.len();
+        let src_kr = PATHS.map(|p| self.src.keyroots_sizes(p));
+        let dst_kr = PATHS.map(|p| self.dst.keyroots_sizes(p));
+        let mut cost = vec![0u64; n * m];
+        // costs of the subtrees hanging off each path
+        let mut src_hanging = [vec![0u64; n * m], vec![0u64; n * m]];
+        let mut dst_hanging = [vec![0u64; n * m], vec![0u64; n * m]];
+        let mut strategies = vec![
+            Strategy {
+                in_src: true,
+                path: Path::Left,
+            };
+            n * m
+        ];
+        for v in 0..n {
+            for w in 0..m {
+                let i = v * m + w;
+                let mut best = u64::MAX;
+                for path in PATHS {
+                    let p = path as usize;
+                    if let Some(c) = self.src.path_child(v, path) {
+                        src_hanging[p][i] = src_hanging[p][c * m + w]
+                            + self.src.children[v]
+                                .iter()
+                                .filter(|x| **x != c)
+                                .map(|x| cost[x * m + w])
+                                .sum::<u64>();
+                    }
+                    let x = self.src.size(v) as u64 * dst_kr[p][w] + src_hanging[p][i];
+                    if x < best {
+                        best = x;
+                        strategies[i] = Strategy { in_src: true, path };
+                    }
+                }
+                for path in PATHS {
+                    let p = path as usize;
+                    if let Some(c) = self.dst.path_child(w, path) {
+                        dst_hanging[p][i] = dst_hanging[p][v * m + c]
+                            + self.dst.children[w]
+                                .iter()
+                                .filter(|x| **x != c)
+                                .map(|x| cost[v * m + x])
+                                .sum::<u64>();
+                    }
+                    let x = self.dst.size(w) as u64 * src_kr[p][v] + dst_hanging[p][i];
+                    if x < best {
+                        best = x;
+                        strategies[i] = Strategy {
+                            in_src: false,
+                            path,
+                        };
+                    }
+                }
+                cost[i] = best;
+            }
+        }
+        strategies
+    }
+
+    /// Single path function, a Zhang and Shasha forest distance where one side is restricted to the path.
+    ///
+    /// Computes the distances between the subtrees rooted on the path and all the subtrees of the other side,
+    /// the distances of the subtrees hanging off the path must already be known.
+    fn spf(&mut self, strategy: Strategy, v: usize, w: usize) {
+        let Self {
+            src_views,
+            dst_views,
+            del,
+            ins,
+            ren,
+            ren_cache,
+            delta,
+            ..
+        } = self;
+        let p = strategy.path as usize;
+        let m = dst_views[p].lld.len();
+        // f is decomposed along the path, g along its keyroots
+        let (f, g, f_root, g_root, f_cost, g_cost) = if strategy.in_src {
+            let (f, g) = (&src_views[p], &dst_views[p]);
+            (f, g, f.from_orig[v], g.from_orig[w], &*del, &*ins)
+        } else {
+            let (f, g) = (&dst_views[p], &src_views[p]);
+            (f, g, f.from_orig[w], g.from_orig[v], &*ins, &*del)
+        };
+        let l1 = f.lld[f_root];
+        let rows = f_root - l1 + 2;
+        let l_g = g.lld[g_root];
+        let mut keyroots = vec![];
+        let mut seen = vec![false; g_root - l_g + 1];
+        for k in (l_g..=g_root).rev() {
+            if !seen[g.lld[k] - l_g] {
+                seen[g.lld[k] - l_g] = true;
+                keyroots.push(k);
+            }
+        }
+        let mut fd = vec![0.0; rows * (g_root - l_g + 2)];
+        for k in keyroots.into_iter().rev() {
+            let l2 = g.lld[k];
+            let cols = k - l2 + 2;
+            let at = |a: usize, b: usize| a * cols + b;
+            fd[0] = 0.0;
+            for a in 1..rows {
+                fd[at(a, 0)] = fd[at(a - 1, 0)] + f_cost[f.to_orig[l1 + a - 1]];
+            }
+            for b in 1..cols {
+                fd[at(0, b)] = fd[at(0, b - 1)] + g_cost[g.to_orig[l2 + b - 1]];
+            }
+            for a in 1..rows {
+                let x = l1 + a - 1;
+                let ox = f.to_orig[x];
+                for b in 1..cols {
+                    let y = l2 + b - 1;
+                    let oy = g.to_orig[y];
+                    let i = if strategy.in_src {
+                        ox * m + oy
+                    } else {
+                        oy * m + ox
+                    };
+                    let d = f64::min(fd[at(a - 1, b)] + f_cost[ox], fd[at(a, b - 1)] + g_cost[oy]);
+                    fd[at(a, b)] = if f.lld[x] == l1 && g.lld[y] == l2 {
+                        if ren_cache[i].is_nan() {
+                            ren_cache[i] = (ren)(i / m, i % m);
+                        }
+                        let d = f64::min(d, fd[at(a - 1, b - 1)] + ren_cache[i]);
+                        delta[i] = d;
+                        d
+                    } else {
+                        f64::min(d, fd[at(f.lld[x] - l1, g.lld[y] - l2)] + delta[i])
+                    };
+                }
+            }
+        }
+    }
+}
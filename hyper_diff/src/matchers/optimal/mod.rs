@@ -1 +1,2 @@
+pub mod apted;
 pub mod zs;
@@ -92,6 +92,26 @@ impl<SD, DD, M: MonoMappingStore + Default> ZsMatcher<M, SD, DD> {
         base.compute_mappings(&mut mappings, &mut dist);
         mappings
     }
+
+    /// Edit distance between the two trees, with unit costs except for the update of labels
+    pub fn distance<'store: 'b, 'b: 'c, 'c, T, HAST>(
+        stores: &'store HAST,
+        src_arena: &'c SD,
+        dst_arena: &'c DD,
+    ) -> f64
+    where
+        T::TreeId: Clone,
+        M::Src: PrimInt + std::ops::SubAssign + Debug,
+        M::Dst: PrimInt + std::ops::SubAssign + Debug,
+        SD: 'b + PostOrderKeyRoots<'b, T, M::Src>,
+        DD: 'b + PostOrderKeyRoots<'b, T, M::Dst>,
+        T: 'store + Tree,
+        HAST: HyperAST<'store, IdN = T::TreeId, T = T, Label = T::Label>,
+    {
+        let base = MatcherImpl::<'store, 'b, '_, SD, DD, T, HAST, M>::new(stores, src_arena, dst_arena);
+        let dist = base.compute_dist();
+        dist.tree[src_arena.len()][dst_arena.len()]
+    }
 }
 
 // TODO use the Mapper struct
@@ -121,15 +141,24 @@ where
     M::Src: PrimInt + std::ops::SubAssign + Debug,
     M::Dst: PrimInt + std::ops::SubAssign + Debug,
 {
-    fn get_deletion_cost(&self, _di: &T::TreeId) -> f64 {
+    pub(crate) fn new(stores: &'store HAST, src_arena: &'c SD, dst_arena: &'c DD) -> Self {
+        Self {
+            stores,
+            src_arena,
+            dst_arena,
+            phantom: PhantomData,
+        }
+    }
+
+    pub(crate) fn get_deletion_cost(&self, _di: &T::TreeId) -> f64 {
         1.0
     }
 
-    fn get_insertion_cost(&self, _dj: &T::TreeId) -> f64 {
+    pub(crate) fn get_insertion_cost(&self, _dj: &T::TreeId) -> f64 {
         1.0
     }
 
-    fn get_update_cost(
+    pub(crate) fn get_update_cost(
         &self, //cache: &mut Cache<LS::I>,
         r1: &T::TreeId,
         r2: &T::TreeId,
@@ -159,34 +188,37 @@ where
         if s1.len() == 0 || s2.len() == 0 {
             return 1.;
         }
-        const S_LEN: usize = 3;
-        let s1 = s1.as_bytes();
-        let s2 = s2.as_bytes();
-        if s1.len() > 30 || s2.len() > 30 {
-            debug_assert_eq!(S_LEN, 3);
-            qgrams::qgram_distance_hash_opti(s1, s2)
-        } else {
-            const S: &[u8] = b"##";
-            debug_assert_eq!(S_LEN, 3);
-            // TODO find a way to repeat at compile time
-            //format!("{empty:#>width$}", empty = "", width = 3-1);
-            //"#".repeat(3 - 1)
-
-            let s1 = {
-                let mut tmp = S.to_vec();
-                tmp.extend_from_slice(&s1);
-                tmp.extend_from_slice(S);
-                tmp
-            };
-            let s2 = {
-                let mut tmp = S.to_vec();
-                tmp.extend_from_slice(&s2);
-                tmp.extend_from_slice(S);
-                tmp
-            };
-            let d = str_distance_patched::QGram::new(S_LEN).normalized(s1, s2);
-            d
-        }
+        qgram_distance(s1.as_bytes(), s2.as_bytes())
+    }
+}
+
+/// Normalized distance between the 3-grams of two labels
+pub fn qgram_distance(s1: &[u8], s2: &[u8]) -> f64 {
+    const S_LEN: usize = 3;
+    if s1.len() > 30 || s2.len() > 30 {
+        debug_assert_eq!(S_LEN, 3);
+        qgrams::qgram_distance_hash_opti(s1, s2)
+    } else {
+        const S: &[u8] = b"##";
+        debug_assert_eq!(S_LEN, 3);
+        // TODO find a way to repeat at compile time
+        //format!("{empty:#>width$}", empty = "", width = 3-1);
+        //"#".repeat(3 - 1)
+
+        let s1 = {
+            let mut tmp = S.to_vec();
+            tmp.extend_from_slice(&s1);
+            tmp.extend_from_slice(S);
+            tmp
+        };
+        let s2 = {
+            let mut tmp = S.to_vec();
+            tmp.extend_from_slice(&s2);
+            tmp.extend_from_slice(S);
+            tmp
+        };
+        let d = str_distance_patched::QGram::new(S_LEN).normalized(s1, s2);
+        d
     }
 }
 
@@ -197,6 +229,20 @@ pub struct ZsMatcherDist {
 
 // TODO make a fully typed interface to each dist
 impl ZsMatcherDist {
+    /// Initializes the distances between subtrees with `tree(i, j)`, indexes being offset by one.
+    pub(crate) fn with_tree_dist(src_len: usize, dst_len: usize, tree: impl Fn(usize, usize) -> f64) -> Self {
+        let mut dist = Self {
+            tree: vec![vec![0.0; dst_len + 1]; src_len + 1],
+            forest: vec![vec![0.0; dst_len + 1]; src_len + 1],
+        };
+        for i in 0..src_len {
+            for j in 0..dst_len {
+                dist.tree[i + 1][j + 1] = tree(i, j);
+            }
+        }
+        dist
+    }
+
     fn f_dist<IdD1: PrimInt, IdD2: PrimInt>(&self, row: IdD1, col: IdD2) -> f64 {
         self.forest[row.to_usize().unwrap()][col.to_usize().unwrap()]
     }
@@ -111,6 +111,17 @@ pub fn jaccard_similarity<
     num / den
 }
 
+/// Similarity of two labels, i.e. the complement of the normalized distance between their 3-grams
+pub fn label_similarity(s1: &str, s2: &str) -> f64 {
+    if s1 == s2 {
+        return 1.;
+    }
+    if s1.is_empty() || s2.is_empty() {
+        return 0.;
+    }
+    1. - super::optimal::zs::qgram_distance(s1.as_bytes(), s2.as_bytes())
+}
+
 pub fn number_of_common_descendants<
     Id1: PrimInt,
     Id2: PrimInt,
@@ -0,0 +1,63 @@
+use std::marker::PhantomData;
+
+use crate::{
+    decompressed_tree_store::{ShallowDecompressedTreeStore, SimpleZsTree},
+    matchers::{
+        mapping_store::{DefaultMappingStore, MonoMappingStore},
+        optimal::{apted::AptedMatcher, zs::ZsMatcher},
+    },
+    tests::examples::*,
+};
+
+use hyper_ast::test_utils::simple_tree::{vpair_to_stores, SimpleTree, TStore};
+use hyper_ast::types::{DecompressedSubtree, SimpleHyperAST};
+
+/// APTED must give the same distances and the same mappings as ZS
+fn assert_same_as_zs(example: (SimpleTree<u8>, SimpleTree<u8>)) {
+    let (label_store, node_store, src, dst) = vpair_to_stores(example);
+    let stores = SimpleHyperAST::<_, TStore, _, _> {
+        node_store,
+        label_store,
+        _phantom: PhantomData,
+    };
+    let src_arena = SimpleZsTree::<_, u16>::decompress(&stores.node_store, &src);
+    let dst_arena = SimpleZsTree::<_, u16>::decompress(&stores.node_store, &dst);
+    assert_eq!(
+        ZsMatcher::<DefaultMappingStore<u16>, SimpleZsTree<_, u16>>::distance(
+            &stores, &src_arena, &dst_arena
+        ),
+        AptedMatcher::<DefaultMappingStore<u16>, SimpleZsTree<_, u16>>::distance(
+            &stores, &src_arena, &dst_arena
+        ),
+    );
+
+    let zs = ZsMatcher::<DefaultMappingStore<u16>, SimpleZsTree<_, u16>>::matchh(&stores, src, dst);
+    let apted =
+        AptedMatcher::<DefaultMappingStore<u16>, SimpleZsTree<_, u16>>::matchh(&stores, src, dst);
+    assert_eq!(zs.src_arena.len(), apted.src_arena.len());
+    assert_eq!(
+        zs.mappings.iter().collect::<Vec<_>>(),
+        apted.mappings.iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_same_as_zs() {
+    assert_same_as_zs(example_zs_paper());
+    assert_same_as_zs(example_gt_java_code());
+    assert_same_as_zs(example_gt_slides());
+    assert_same_as_zs(example_gumtree());
+    assert_same_as_zs(example_gumtree_ambiguous());
+    assert_same_as_zs(example_bottom_up());
+    assert_same_as_zs(example_action());
+    assert_same_as_zs(example_action2());
+    assert_same_as_zs(example_move());
+    assert_same_as_zs(example_move1());
+    assert_same_as_zs(example_move2());
+    assert_same_as_zs(example_move3());
+    assert_same_as_zs(example_simple());
+    assert_same_as_zs(example_simple1());
+    assert_same_as_zs(example_single());
+    assert_same_as_zs(example_eq_simple_class_rename());
+    assert_same_as_zs(example_very_simple_post_order());
+}
@@ -0,0 +1,153 @@
+use std::marker::PhantomData;
+
+use crate::{
+    algorithms::pipeline::{self, Pipeline},
+    decompressed_tree_store::{lazy_post_order::LazyPostOrder, ShallowDecompressedTreeStore},
+    matchers::{
+        heuristic::gt::{
+            lazy2_greedy_subtree_matcher::LazyGreedySubtreeMatcher,
+            lazy_change_distiller_matcher::{
+                LazyChangeDistillerBottomUpMatcher, LazyChangeDistillerLeavesMatcher,
+            },
+            lazy_hybrid_bottom_up_matcher::LazyHybridBottomUpMatcher,
+        },
+        mapping_store::{DefaultMultiMappingStore, MonoMappingStore, VecStore},
+        Mapper,
+    },
+    tests::examples::*,
+};
+
+use hyper_ast::test_utils::simple_tree::{vpair_to_stores, SimpleTree, TStore};
+use hyper_ast::types::{HyperAST, SimpleHyperAST};
+
+type DS<T> = LazyPostOrder<T, u32>;
+
+type Example = fn() -> (SimpleTree<u8>, SimpleTree<u8>);
+
+const EXAMPLES: &[Example] = &[
+    example_zs_paper,
+    example_gt_java_code,
+    example_gt_slides,
+    example_gumtree,
+    example_gumtree_ambiguous,
+    example_bottom_up,
+    example_action,
+    example_action2,
+    example_move,
+    example_move1,
+    example_move2,
+    example_move3,
+    example_simple,
+    example_simple1,
+    example_single,
+    example_eq_simple_class_rename,
+    example_very_simple_post_order,
+];
+
+/// Mappings found by `pipeline` on `example`, along with the pair of roots
+fn mappings(
+    example: (SimpleTree<u8>, SimpleTree<u8>),
+    pipeline: &Pipeline,
+) -> (Vec<(u32, u32)>, (u32, u32)) {
+    let (label_store, node_store, src, dst) = vpair_to_stores(example);
+    let stores = SimpleHyperAST::<_, TStore, _, _> {
+        node_store,
+        label_store,
+        _phantom: PhantomData,
+    };
+    let diff = pipeline::diff(&stores, &src, &dst, pipeline);
+    let mapping = &diff.mapper.mapping;
+    let roots = (mapping.src_arena.root(), mapping.dst_arena.root());
+    (mapping.mappings.iter().collect(), roots)
+}
+
+/// On identical trees, hybrid and ChangeDistiller must map every node to itself, like GumTree
+#[test]
+fn test_identical_trees() {
+    for example in EXAMPLES {
+        for side in [0, 1] {
+            let tree = || {
+                let (src, dst) = example();
+                if side == 0 {
+                    src
+                } else {
+                    dst
+                }
+            };
+            let (gumtree, (root, _)) = mappings((tree(), tree()), &Pipeline::default());
+            assert_eq!(gumtree.len(), root as usize + 1);
+            assert!(gumtree.iter().all(|(s, d)| s == d));
+            for p in [Pipeline::hybrid(), Pipeline::change_distiller()] {
+                let (other, _) = mappings((tree(), tree()), &p);
+                assert_eq!(gumtree, other, "{}", p);
+            }
+        }
+    }
+}
+
+/// Bottom-up phases must keep the mappings of the top-down phase, and map the roots
+#[test]
+fn test_bottom_up_extends_subtree_mappings() {
+    let subtree: Pipeline = "greedy_subtree".parse().unwrap();
+    for example in EXAMPLES {
+        let (top_down, (src_root, _)) = mappings(example(), &subtree);
+        for p in [Pipeline::default(), Pipeline::hybrid()] {
+            let (bottom_up, roots) = mappings(example(), &p);
+            assert!(bottom_up.contains(&roots), "{}", p);
+            // the root may be remapped to the other root
+            for m in top_down.iter().filter(|(s, _)| *s != src_root) {
+                assert!(bottom_up.contains(m), "{}: {:?}", p, m);
+            }
+        }
+    }
+}
+
+/// Mappings found by the lazy variants of hybrid, or of ChangeDistiller, on `example`
+fn lazy_mappings(example: (SimpleTree<u8>, SimpleTree<u8>), hybrid: bool) -> Vec<(u32, u32)> {
+    let (label_store, node_store, src, dst) = vpair_to_stores(example);
+    let stores = SimpleHyperAST::<_, TStore, _, _> {
+        node_store,
+        label_store,
+        _phantom: PhantomData,
+    };
+    let mapper: Mapper<_, DS<_>, DS<_>, VecStore<u32>> = stores.decompress_pair(&src, &dst).into();
+    let mapper = if hybrid {
+        let mapper =
+            LazyGreedySubtreeMatcher::<_, _, _, _>::match_it::<DefaultMultiMappingStore<_>>(mapper);
+        LazyHybridBottomUpMatcher::<_, _, _, _>::match_it(mapper)
+    } else {
+        let mapper = LazyChangeDistillerLeavesMatcher::<_, _, _, _, _>::match_it(mapper);
+        LazyChangeDistillerBottomUpMatcher::<_, _, _, _, _>::match_it(mapper)
+    };
+    mapper.mapping.mappings.iter().collect()
+}
+
+/// Mappings of GumTree on a renamed container (ids are in post order):
+/// the subtree `s(x, y)` is matched top-down, then `foo` is matched to `bar` bottom-up.
+/// Only hybrid recovers `u` to `v` with ZS, ChangeDistiller only maps leaves with similar labels.
+#[test]
+fn test_renamed_container() {
+    let hybrid = vec![(0, 0), (1, 1), (2, 2), (3, 3), (4, 4), (5, 5)];
+    let change_distiller = vec![(0, 0), (1, 1), (2, 2), (4, 4), (5, 5)];
+
+    let (eager, _) = mappings(example_renamed_container(), &Pipeline::hybrid());
+    assert_eq!(eager, hybrid);
+    assert_eq!(lazy_mappings(example_renamed_container(), true), hybrid);
+
+    let (eager, _) = mappings(example_renamed_container(), &Pipeline::change_distiller());
+    assert_eq!(eager, change_distiller);
+    assert_eq!(
+        lazy_mappings(example_renamed_container(), false),
+        change_distiller
+    );
+}
+
+/// Mappings of GumTree's ChangeDistiller on a moved pair of leaves:
+/// `d` and `e` are matched as leaves, then `g` is matched to `h`, that now contains them.
+#[test]
+fn test_change_distiller_move() {
+    let expected = vec![(0, 1), (1, 2), (2, 3), (4, 4)];
+    let (eager, _) = mappings(example_move(), &Pipeline::change_distiller());
+    assert_eq!(eager, expected);
+    assert_eq!(lazy_mappings(example_move(), false), expected);
+}
@@ -0,0 +1,96 @@
+use std::marker::PhantomData;
+
+use crate::{
+    algorithms,
+    decompressed_tree_store::lazy_post_order::LazyPostOrder,
+    matchers::{
+        heuristic::gt::lazy2_greedy_subtree_matcher::LazyGreedySubtreeMatcher,
+        mapping_store::{DefaultMultiMappingStore, MonoMappingStore, VecStore},
+        Mapper,
+    },
+    tests::{examples::*, java_pair},
+};
+
+use hyper_ast::test_utils::simple_tree::{vpair_to_stores, SimpleTree, TStore};
+use hyper_ast::types::{HyperAST, SimpleHyperAST};
+
+type DS<T> = LazyPostOrder<T, u16>;
+
+/// The parallel top-down phase must give the same mappings as the sequential one
+fn assert_same_as_sequential(example: (SimpleTree<u8>, SimpleTree<u8>)) {
+    let (label_store, node_store, src, dst) = vpair_to_stores(example);
+    let stores = SimpleHyperAST::<_, TStore, _, _> {
+        node_store,
+        label_store,
+        _phantom: PhantomData,
+    };
+    let mapper: Mapper<_, DS<_>, DS<_>, VecStore<u16>> = stores.decompress_pair(&src, &dst).into();
+    let seq =
+        LazyGreedySubtreeMatcher::<_, _, _, _>::match_it::<DefaultMultiMappingStore<_>>(mapper);
+    let mapper: Mapper<_, DS<_>, DS<_>, VecStore<u16>> = stores.decompress_pair(&src, &dst).into();
+    let par =
+        LazyGreedySubtreeMatcher::<_, _, _, _>::match_it_par::<DefaultMultiMappingStore<_>>(mapper);
+    assert_eq!(
+        seq.mappings.iter().collect::<Vec<_>>(),
+        par.mappings.iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_same_as_sequential() {
+    assert_same_as_sequential(example_zs_paper());
+    assert_same_as_sequential(example_gt_java_code());
+    assert_same_as_sequential(example_gt_slides());
+    assert_same_as_sequential(example_gumtree());
+    assert_same_as_sequential(example_gumtree_ambiguous());
+    assert_same_as_sequential(example_bottom_up());
+    assert_same_as_sequential(example_action());
+    assert_same_as_sequential(example_action2());
+    assert_same_as_sequential(example_move());
+    assert_same_as_sequential(example_move1());
+    assert_same_as_sequential(example_move2());
+    assert_same_as_sequential(example_move3());
+    assert_same_as_sequential(example_simple());
+    assert_same_as_sequential(example_simple1());
+    assert_same_as_sequential(example_single());
+    assert_same_as_sequential(example_eq_simple_class_rename());
+    assert_same_as_sequential(example_very_simple_post_order());
+}
+
+/// A class with `n` methods, every third one having another body if `changed`
+fn java_class(n: usize, changed: bool) -> String {
+    let mut s = "class A {\n".to_string();
+    for i in 0..n {
+        let body = if changed && i % 3 == 0 {
+            format!("return x * {};", i)
+        } else {
+            format!("int y = x + {};\n        return y;", i)
+        };
+        s += &format!("    int f{}(int x) {{\n        {}\n    }}\n", i, body);
+    }
+    s += "}\n";
+    s
+}
+
+/// On generated java trees, the parallel diff must be the same as the sequential one, whatever the number of threads
+#[test]
+fn test_diff_par_same_as_diff_on_java() {
+    let (stores, src, dst) = java_pair(&java_class(60, false), &java_class(60, true));
+    let seq = algorithms::gumtree_lazy::diff(&stores, &src, &dst);
+    let seq_mappings: Vec<_> = seq.mapper.mappings().iter().collect();
+    let seq_actions = seq.actions.unwrap().0;
+    assert!(!seq_actions.is_empty());
+    for threads in [1, 2, 3, 8] {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        let (mappings, actions) = pool.install(|| {
+            let par = algorithms::gumtree_lazy::diff_par(&stores, &src, &dst);
+            let mappings: Vec<_> = par.mapper.mappings().iter().collect();
+            (mappings, par.actions.unwrap().0)
+        });
+        assert_eq!(seq_mappings, mappings, "{} threads", threads);
+        assert_eq!(seq_actions, actions, "{} threads", threads);
+    }
+}
@@ -2,4 +2,10 @@
 // #[cfg(test)]
 // pub mod gumtree_tests;
 #[cfg(test)]
+pub mod apted_tests;
+#[cfg(test)]
+pub mod lazy_subtree_par_tests;
+#[cfg(test)]
 pub mod zs_tests;
+#[cfg(test)]
+pub mod bottom_up_tests;
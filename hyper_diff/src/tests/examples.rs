@@ -432,3 +432,28 @@ pub(crate) fn example_very_simple_post_order() -> (ST<u8>, ST<u8>) {
     ]);
     (src, dst)
 }
+
+/// A renamed container, keeping a subtree and a renamed leaf
+pub(crate) fn example_renamed_container() -> (ST<u8>, ST<u8>) {
+    let src = tree!(
+        0, "r"; [
+            tree!(1, "foo"; [
+                tree!(2, "s"; [
+                    tree!(3, "x"),
+                    tree!(3, "y"),
+                ]),
+                tree!(3, "u"),
+            ]),
+    ]);
+    let dst = tree!(
+        0, "r"; [
+            tree!(1, "bar"; [
+                tree!(2, "s"; [
+                    tree!(3, "x"),
+                    tree!(3, "y"),
+                ]),
+                tree!(3, "v"),
+            ]),
+    ]);
+    (src, dst)
+}
@@ -0,0 +1,179 @@
+use crate::actions::gumtree_format::{
+    export_actions, export_matches, GtAction, GtActionKind, GtDiff, GtMatch, GtTree,
+};
+use crate::algorithms;
+use crate::decompressed_tree_store::ShallowDecompressedTreeStore;
+use crate::matchers::mapping_store::{MappingStore, VecStore};
+use crate::tests::java_pair;
+
+fn diff() -> GtDiff {
+    let tree = |s: &str| -> GtTree { s.parse().unwrap() };
+    GtDiff {
+        matches: vec![GtMatch {
+            src: tree("program [0,20]"),
+            dest: tree("program [0,22]"),
+        }],
+        actions: vec![
+            GtAction {
+                action: GtActionKind::UpdateNode,
+                tree: tree("identifier: a [6,7]"),
+                parent: None,
+                at: None,
+                label: Some("b<\"&\">".to_string()),
+            },
+            GtAction {
+                action: GtActionKind::InsertNode,
+                tree: tree("string_literal: \"x: y\" [10,16]"),
+                parent: Some(tree("argument_list [9,17]")),
+                at: Some(1),
+                label: None,
+            },
+            GtAction {
+                action: GtActionKind::DeleteNode,
+                tree: tree("; [19,20]"),
+                parent: None,
+                at: None,
+                label: None,
+            },
+        ],
+    }
+}
+
+#[test]
+fn test_tree_ref() {
+    let t: GtTree = "string_literal: \"x: y\" [10,16]".parse().unwrap();
+    assert_eq!(t.r#type, "string_literal");
+    assert_eq!(t.label.as_deref(), Some("\"x: y\""));
+    assert_eq!((t.start, t.end), (10, 16));
+    assert_eq!(t.to_string(), "string_literal: \"x: y\" [10,16]");
+    let t: GtTree = "program [0,20]".parse().unwrap();
+    assert_eq!(t.label, None);
+    assert_eq!(t.to_string(), "program [0,20]");
+    assert!("program".parse::<GtTree>().is_err());
+    assert!("program [0]".parse::<GtTree>().is_err());
+}
+
+#[test]
+fn test_json_roundtrip() {
+    let diff = diff();
+    let json = diff.to_json();
+    assert!(json.contains(r#""action": "update-node""#));
+    assert!(json.contains(r#""tree": "identifier: a [6,7]""#));
+    assert_eq!(GtDiff::from_json(&json).unwrap(), diff);
+}
+
+#[test]
+fn test_xml_roundtrip() {
+    let diff = diff();
+    let xml = diff.to_xml();
+    assert!(xml.contains(r#"<match src="program [0,20]" dest="program [0,22]"/>"#));
+    assert!(xml.contains("<delete-node "));
+    assert_eq!(GtDiff::from_xml(&xml).unwrap(), diff);
+}
+
+static SRC: &str = "class A {\n    int f() {\n        return 1;\n    }\n}\n";
+static DST: &str = "class A {\n    int f() {\n        return 2;\n    }\n}\n";
+
+/// `gumtree textdiff -f JSON` on `SRC` and `DST`, parsed with tree-sitter-java,
+/// keeping the matches of named nodes, the HyperAST also has keywords, punctuation and spaces.
+static REFERENCE: &str = r#"{
+  "matches": [
+    {
+      "src": "program [0,50]",
+      "dest": "program [0,50]"
+    },
+    {
+      "src": "class_declaration [0,49]",
+      "dest": "class_declaration [0,49]"
+    },
+    {
+      "src": "identifier: A [6,7]",
+      "dest": "identifier: A [6,7]"
+    },
+    {
+      "src": "class_body [8,49]",
+      "dest": "class_body [8,49]"
+    },
+    {
+      "src": "method_declaration [14,47]",
+      "dest": "method_declaration [14,47]"
+    },
+    {
+      "src": "integral_type [14,17]",
+      "dest": "integral_type [14,17]"
+    },
+    {
+      "src": "identifier: f [18,19]",
+      "dest": "identifier: f [18,19]"
+    },
+    {
+      "src": "formal_parameters [19,21]",
+      "dest": "formal_parameters [19,21]"
+    },
+    {
+      "src": "block [22,47]",
+      "dest": "block [22,47]"
+    },
+    {
+      "src": "return_statement [32,41]",
+      "dest": "return_statement [32,41]"
+    },
+    {
+      "src": "decimal_integer_literal: 1 [39,40]",
+      "dest": "decimal_integer_literal: 2 [39,40]"
+    }
+  ],
+  "actions": [
+    {
+      "action": "update-node",
+      "tree": "decimal_integer_literal: 1 [39,40]",
+      "label": "2"
+    }
+  ]
+}"#;
+
+#[test]
+fn test_java_export_import() {
+    let (stores, src, dst) = java_pair(SRC, DST);
+    let diff = algorithms::gumtree::diff(&stores, &src, &dst);
+    let actions = diff.actions.unwrap();
+    let mapping = &diff.mapper.mapping;
+    let (src_arena, dst_arena) = (&mapping.src_arena, &mapping.dst_arena);
+    let exported = GtDiff {
+        matches: export_matches(&stores, src, dst, src_arena, dst_arena, &mapping.mappings),
+        actions: export_actions(&stores, src, dst, &actions),
+    };
+
+    let reference = GtDiff::from_json(REFERENCE).unwrap();
+    assert_eq!(exported.actions, reference.actions);
+    for m in &reference.matches {
+        assert!(
+            exported.matches.contains(m),
+            "missing match {} -> {}",
+            m.src,
+            m.dest
+        );
+    }
+    assert!(exported
+        .to_xml()
+        .contains(r#"<update-node tree="decimal_integer_literal: 1 [39,40]" label="2"/>"#));
+
+    // importing the export gives back the same script and mappings
+    let imported = GtDiff::from_json(&exported.to_json()).unwrap();
+    let actions = imported.import_actions(&stores, src, dst).unwrap();
+    assert_eq!(
+        export_actions(&stores, src, dst, &actions),
+        exported.actions
+    );
+    let mut mappings = VecStore::<u32>::default();
+    mappings.topit(src_arena.len(), dst_arena.len());
+    imported
+        .import_matches(&stores, src, dst, src_arena, dst_arena, &mut mappings)
+        .unwrap();
+    let mut matches = export_matches(&stores, src, dst, src_arena, dst_arena, &mappings);
+    let mut expected = exported.matches;
+    let key = |m: &GtMatch| (m.src.start, m.src.end, m.src.to_string());
+    matches.sort_by_key(key);
+    expected.sort_by_key(key);
+    assert_eq!(matches, expected);
+}
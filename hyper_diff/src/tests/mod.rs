@@ -12,15 +12,43 @@ macro_rules! tree {
         SimpleTree::new($k, None, vec![$($x),+])
     };
 }
-pub(crate) use tree; 
+pub(crate) use tree;
+
+use hyper_ast::store::{defaults::NodeIdentifier, SimpleStores};
+use hyper_ast_gen_ts_java::{legion_with_refs::JavaTreeGen, types::TStore};
+
+/// Generates both java files, named `A.java`, in the same stores.
+pub(crate) fn java_pair(
+    src: &str,
+    dst: &str,
+) -> (SimpleStores<TStore>, NodeIdentifier, NodeIdentifier) {
+    let mut stores = SimpleStores::<TStore>::default();
+    let mut md_cache = Default::default();
+    let (src, dst) = {
+        let mut gen = JavaTreeGen::new(&mut stores, &mut md_cache);
+        let mut generate = |text: &str| {
+            let tree = hyper_ast_gen_ts_java::legion_with_refs::tree_sitter_parse(text.as_bytes())
+                .unwrap_or_else(|t| t);
+            gen.generate_file(b"A.java", text.as_bytes(), tree.walk())
+                .local
+                .compressed_node
+        };
+        (generate(src), generate(dst))
+    };
+    (stores, src, dst)
+}
 
 pub mod action_generator2_simple_tests;
 pub mod action_generator2_tests;
 pub mod action_generator_tests;
 #[cfg(test)]
 pub mod examples;
+#[cfg(feature = "serialize")]
+pub mod gumtree_format_tests;
 pub mod hungarian_tests;
 #[cfg(test)]
 pub mod lazy_decompression_tests;
 pub mod pair_tests;
-pub mod simple_examples;
\ No newline at end of file
+pub mod pipeline_tests;
+pub mod render_tests;
+pub mod simple_examples;
@@ -0,0 +1,217 @@
+use crate::actions::script_generator2::Act;
+use crate::algorithms::{
+    self,
+    pipeline::{Pipeline, PipelineError, Stage},
+};
+use crate::decompressed_tree_store::ShallowDecompressedTreeStore;
+use crate::matchers::mapping_store::{MappingStore, MonoMappingStore};
+use crate::tests::{
+    java_pair,
+    render_tests::{DST, SRC},
+};
+
+#[test]
+fn parse_pipeline() {
+    let p: Pipeline = "lazy greedy subtree, min_height=2 → simple-bottom-up, sim=0.4 -> zs"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        p.stages(),
+        &[
+            Stage::LazyGreedySubtree { min_height: 2 },
+            Stage::SimpleBottomUp { sim: 0.4 },
+            Stage::Zs { max_size: 1000 },
+        ]
+    );
+    assert_eq!(
+        p.to_string(),
+        "lazy_greedy_subtree, min_height=2 -> simple_bottom_up, sim=0.4 -> zs, max_size=1000"
+    );
+    assert_eq!(p.to_string().parse::<Pipeline>().unwrap(), p);
+}
+
+#[test]
+fn default_pipeline() {
+    let p: Pipeline = "greedy_subtree -> greedy_bottom_up".parse().unwrap();
+    assert_eq!(p, Pipeline::default());
+}
+
+#[test]
+fn gumtree_configurations() {
+    let p: Pipeline = "greedy_subtree -> hybrid_bottom_up".parse().unwrap();
+    assert_eq!(p, Pipeline::hybrid());
+    let p: Pipeline = "change_distiller_leaves -> change_distiller_bottom_up"
+        .parse()
+        .unwrap();
+    assert_eq!(p, Pipeline::change_distiller());
+    assert_eq!(
+        p.to_string(),
+        "change_distiller_leaves, label_sim=0.5 -> change_distiller_bottom_up, max_leaves=4, sim_large=0.6, sim_small=0.4"
+    );
+    assert_eq!(
+        "change_distiller_bottom_up, sim_small=2".parse::<Pipeline>(),
+        Err(PipelineError::OutOfRange(Stage::ChangeDistillerBottomUp {
+            max_leaves: 4,
+            sim_large: 0.6,
+            sim_small: 2.
+        }))
+    );
+}
+
+#[test]
+fn label_similarity() {
+    use crate::matchers::similarity_metrics::label_similarity;
+    assert_eq!(label_similarity("", ""), 1.);
+    assert_eq!(label_similarity("foo", "foo"), 1.);
+    assert_eq!(label_similarity("foo", ""), 0.);
+    let close = label_similarity("getName", "getNames");
+    let far = label_similarity("getName", "size");
+    assert!(0. < close && close < 1.);
+    assert!(far < close);
+}
+
+#[test]
+fn invalid_pipelines() {
+    assert_eq!("".parse::<Pipeline>(), Err(PipelineError::Empty));
+    assert_eq!(
+        "rted".parse::<Pipeline>(),
+        Err(PipelineError::UnknownStage("rted".into()))
+    );
+    assert_eq!(
+        "zs, sim=0.5".parse::<Pipeline>(),
+        Err(PipelineError::BadParam("sim=0.5".into()))
+    );
+    assert_eq!(
+        "greedy_bottom_up -> greedy_subtree".parse::<Pipeline>(),
+        Err(PipelineError::BadOrder(Stage::GreedySubtree {
+            min_height: 1
+        }))
+    );
+    assert_eq!(
+        "greedy_subtree, min_height=9".parse::<Pipeline>(),
+        Err(PipelineError::OutOfRange(Stage::GreedySubtree {
+            min_height: 9
+        }))
+    );
+    let max_size = u32::MAX as usize + 1;
+    for stage in [
+        Stage::GreedyBottomUp { sim: 0.5, max_size },
+        Stage::Zs { max_size },
+        Stage::Apted { max_size },
+        Stage::HybridBottomUp { max_size },
+    ] {
+        assert_eq!(
+            Pipeline::new(vec![stage]),
+            Err(PipelineError::OutOfRange(stage))
+        );
+    }
+    assert!(Pipeline::new(vec![Stage::Zs {
+        max_size: u32::MAX as usize
+    }])
+    .is_ok());
+}
+
+#[test]
+fn simple_bottom_up_recovers_renamed_leaf() {
+    let src = "class A { void f() { a(); b(); } }";
+    let dst = "class A { void f() { a(); c(); } }";
+    let (stores, src, dst) = java_pair(src, dst);
+    let p: Pipeline = "greedy_subtree, min_height=2 -> simple_bottom_up"
+        .parse()
+        .unwrap();
+    let diff = algorithms::pipeline::diff(&stores, &src, &dst, &p);
+    // same shapes, the lcs and histogram recovery map every node, including b to c
+    let mapping = &diff.mapper.mapping;
+    assert_eq!(mapping.src_arena.len(), mapping.dst_arena.len());
+    assert_eq!(diff.mapper.mappings().len(), mapping.src_arena.len());
+    let actions = diff.actions.unwrap().0;
+    assert_eq!(actions.len(), 1, "{:?}", actions);
+    assert!(matches!(actions[0].action, Act::Update { .. }));
+
+    // without recovery, the renamed leaf stays unmapped
+    let p: Pipeline = "greedy_subtree, min_height=2 -> greedy_bottom_up, max_size=0"
+        .parse()
+        .unwrap();
+    let greedy = algorithms::pipeline::diff(&stores, &src, &dst, &p);
+    assert!(greedy.mapper.mappings().len() < diff.mapper.mappings().len());
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn serde_pipeline() {
+    let p: Pipeline = serde_json::from_str(
+        r#"[{"stage":"greedy_subtree","min_height":2},{"stage":"greedy_bottom_up","sim":0.3}]"#,
+    )
+    .unwrap();
+    assert_eq!(
+        p.stages(),
+        &[
+            Stage::GreedySubtree { min_height: 2 },
+            Stage::GreedyBottomUp {
+                sim: 0.3,
+                max_size: 1000
+            },
+        ]
+    );
+    let s = serde_json::to_string(&p).unwrap();
+    assert_eq!(serde_json::from_str::<Pipeline>(&s).unwrap(), p);
+    assert!(serde_json::from_str::<Pipeline>("[]").is_err());
+}
+
+#[test]
+fn default_pipeline_diff() {
+    let (stores, src, dst) = java_pair(SRC, DST);
+    let gumtree = algorithms::gumtree::diff(&stores, &src, &dst);
+    let pipeline = algorithms::pipeline::diff(&stores, &src, &dst, &Pipeline::default());
+    assert_eq!(pipeline.mapping_durations.mappings.len(), 2);
+    let mappings = |m: &crate::matchers::mapping_store::VecStore<u32>| -> Vec<(u32, u32)> {
+        m.iter().collect()
+    };
+    assert_eq!(
+        mappings(pipeline.mapper.mappings()),
+        mappings(gumtree.mapper.mappings())
+    );
+    assert_eq!(pipeline.actions.unwrap().0, gumtree.actions.unwrap().0);
+}
+
+#[test]
+fn subtree_min_heights() {
+    let (stores, src, dst) = java_pair(SRC, DST);
+    let mut subtree_mappings = vec![];
+    for min_height in 1..=5 {
+        let p: Pipeline = format!("greedy_subtree, min_height={}", min_height)
+            .parse()
+            .unwrap();
+        let diff = algorithms::pipeline::diff(&stores, &src, &dst, &p);
+        subtree_mappings.push(diff.mapper.mappings().len());
+
+        let p: Pipeline = format!(
+            "greedy_subtree, min_height={} -> greedy_bottom_up",
+            min_height
+        )
+        .parse()
+        .unwrap();
+        let diff = algorithms::pipeline::diff(&stores, &src, &dst, &p);
+        // the roots are always matched by the bottom-up stage
+        assert!(diff.mapper.mappings().len() > 0);
+        assert!(diff.actions.is_some());
+
+        let p: Pipeline = format!(
+            "lazy_greedy_subtree, min_height={} -> greedy_bottom_up",
+            min_height
+        )
+        .parse()
+        .unwrap();
+        let diff = algorithms::pipeline::diff(&stores, &src, &dst, &p);
+        assert!(diff.mapper.mappings().len() > 0);
+        assert!(diff.actions.is_some());
+    }
+    // the swapped methods are matched as whole subtrees
+    assert!(subtree_mappings[0] > 0);
+    // higher subtrees are a subset of the candidates
+    assert!(
+        subtree_mappings.windows(2).all(|w| w[0] >= w[1]),
+        "{:?}",
+        subtree_mappings
+    );
+}
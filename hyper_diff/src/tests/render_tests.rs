@@ -0,0 +1,141 @@
+use crate::actions::render::{
+    column, covering_lines, line_col, replaced_lines, word_diff, HunkKind, RenderedScript,
+};
+use crate::algorithms;
+use crate::tests::java_pair;
+
+static TEXT: &str = "class A {\n    int f() {\n        return x;\n    }\n}\n";
+
+#[test]
+fn test_line_col() {
+    assert_eq!(line_col(TEXT, 0), (1, 1));
+    assert_eq!(line_col(TEXT, 14), (2, 5));
+    let x = TEXT.find("x;").unwrap();
+    assert_eq!(line_col(TEXT, x), (3, 16));
+    assert_eq!(line_col(TEXT, TEXT.len() + 10), (6, 1));
+}
+
+#[test]
+fn test_covering_lines() {
+    let start = TEXT.find("int").unwrap();
+    let end = TEXT.find("    }\n}").unwrap() + 5;
+    let lines = covering_lines(TEXT, start..end);
+    assert_eq!(&TEXT[lines], "    int f() {\n        return x;\n    }");
+}
+
+#[test]
+fn test_inline_update() {
+    let x = TEXT.find("x;").unwrap();
+    assert_eq!(
+        replaced_lines(TEXT, x..x + 1, "x", &word_diff("x", "y")),
+        vec!["        return [-x-]{+y+};"]
+    );
+    // label not found in the range, lines are kept as is
+    assert_eq!(
+        replaced_lines(TEXT, x..x + 1, "z", "y"),
+        vec!["        return x;"]
+    );
+}
+
+#[test]
+fn test_column() {
+    assert_eq!(column("\tab", 6), "    ab");
+    assert_eq!(column("abc", 5), "abc  ");
+    assert_eq!(column("abcdef", 3), "abc");
+}
+
+pub(crate) static SRC: &str = r#"class A {
+    int f() {
+        return 1;
+    }
+
+    int g() {
+        return 2;
+    }
+}
+"#;
+
+/// `g` is swapped with `f`, and the class is shifted by an import,
+/// so the moved method does not have the same path in the source and in the destination.
+pub(crate) static DST: &str = r#"import java.util.List;
+
+class A {
+    int g() {
+        return 2;
+    }
+
+    int f() {
+        return 1;
+    }
+}
+"#;
+
+fn line_of(text: &str, line: &str) -> usize {
+    text.lines().position(|l| l == line).unwrap() + 1
+}
+
+#[test]
+fn test_render_moved_method() {
+    let (stores, src, dst) = java_pair(SRC, DST);
+    let actions = algorithms::gumtree::diff(&stores, &src, &dst)
+        .actions
+        .unwrap();
+    let script = RenderedScript::new(&stores, src, dst, &actions);
+
+    let files: Vec<_> = script.files().collect();
+    assert_eq!(files.len(), 1);
+    let (file, hunks) = files[0];
+    assert_eq!(file.to_str(), Some("A.java"));
+
+    let inserted = hunks
+        .iter()
+        .find(|h| h.kind == HunkKind::Insert && h.dst_lines == ["import java.util.List;"])
+        .expect("the import should be inserted");
+    assert_eq!(inserted.location().line, 1);
+    assert!(inserted.src.is_none());
+
+    let moved: Vec<_> = hunks
+        .iter()
+        .filter(|h| h.kind == HunkKind::Move)
+        .filter(|h| h.src_lines[0].trim_start().starts_with("int "))
+        .collect();
+    assert_eq!(moved.len(), 1);
+    let moved = moved[0];
+    // the method is shown at its destination, unchanged
+    assert_eq!(moved.src_lines, moved.dst_lines);
+    assert_eq!(moved.src_lines.len(), 3);
+    let header = &moved.src_lines[0];
+    let src_loc = moved.src.as_ref().unwrap();
+    let dst_loc = moved.dst.as_ref().unwrap();
+    assert_eq!(src_loc.line, line_of(SRC, header));
+    assert_eq!(dst_loc.line, line_of(DST, header));
+    assert_eq!(&DST[dst_loc.range.clone()][..4], "int ");
+    assert_eq!(dst_loc.col, 5);
+
+    let unified = script.unified().to_string();
+    assert!(unified.starts_with("--- a/A.java\n+++ b/A.java\n"));
+    assert!(unified.contains("+import java.util.List;\n"));
+    assert!(unified.contains(&format!(
+        " @@ move [{}..{}] from a/{} [{}..{}]",
+        dst_loc.range.start, dst_loc.range.end, src_loc, src_loc.range.start, src_loc.range.end
+    )));
+    assert!(unified.contains(&format!(">{}\n", header)));
+
+    let side_by_side = script.side_by_side(30).to_string();
+    assert!(side_by_side.starts_with("--- a/A.java\n+++ b/A.java\n"));
+    assert!(side_by_side.contains(&format!("{} ~ {}\n", column(header, 30), header)));
+    assert!(side_by_side.contains(&format!("{} > import java.util.List;\n", column("", 30))));
+}
+
+#[test]
+fn test_render_nothing() {
+    let (stores, src, dst) = java_pair(SRC, SRC);
+    assert_eq!(src, dst);
+    let actions = algorithms::gumtree::diff(&stores, &src, &dst)
+        .actions
+        .unwrap();
+    let script = RenderedScript::new(&stores, src, dst, &actions);
+    assert_eq!(script.files().count(), 0);
+    assert_eq!(script.unified().to_string(), "");
+    assert_eq!(script.side_by_side(30).to_string(), "");
+}
@@ -0,0 +1,228 @@
+//! Building files and directories from the disk, each file with the generator of its language.
+use std::{fs, io, path::Path};
+
+use hyper_ast::{
+    hashed::{IndexingHashBuilder, MetaDataHashsBuilder},
+    store::{
+        defaults::{LabelIdentifier, NodeIdentifier},
+        nodes::legion::{compo::CS, dyn_builder, eq_node},
+        nodes::EntityBuilder as _,
+    },
+    tree_gen::SubTreeMetrics,
+    types::LabelStore as _,
+};
+use hyper_ast_gen_ts_java::types::JavaEnabledTypeStore as _;
+
+use crate::types::TStore;
+
+pub type SimpleStores = hyper_ast::store::SimpleStores<TStore>;
+
+type Metrics = SubTreeMetrics<hyper_ast::hashed::SyntaxNodeHashs<u32>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lang {
+    Java,
+    Cpp,
+    Ts,
+    Xml,
+    Query,
+}
+
+impl Lang {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Some(match path.extension()?.to_str()? {
+            "java" => Lang::Java,
+            "cpp" | "cc" | "cxx" | "c" | "h" | "hpp" | "hh" | "hxx" => Lang::Cpp,
+            "ts" => Lang::Ts,
+            "xml" => Lang::Xml,
+            "scm" => Lang::Query,
+            _ => return None,
+        })
+    }
+}
+
+/// Metadata caches of the generators.
+#[derive(Default)]
+pub struct Caches {
+    java: hyper_ast_gen_ts_java::legion_with_refs::MDCache,
+    cpp: hyper_ast_gen_ts_cpp::legion::MDCache,
+    ts: hyper_ast_gen_ts_ts::legion::MDCache,
+    query: hyper_ast_gen_ts_tsquery::legion::MDCache,
+}
+
+#[derive(Default)]
+pub struct Builder {
+    pub stores: SimpleStores,
+    caches: Caches,
+}
+
+fn line_break(text: &[u8]) -> Vec<u8> {
+    if text.contains(&b'\r') {
+        b"\r\n".to_vec()
+    } else {
+        b"\n".to_vec()
+    }
+}
+
+impl Builder {
+    /// Builds the file or the directory at `path`.
+    ///
+    /// In directories, hidden entries and files of unsupported languages are skipped.
+    pub fn build(&mut self, path: &Path) -> io::Result<NodeIdentifier> {
+        if path.is_dir() {
+            Ok(self.build_dir(path)?.0)
+        } else {
+            let lang = Lang::from_path(path).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("unsupported language: {}", path.display()),
+                )
+            })?;
+            Ok(self.build_file(path, lang)?.0)
+        }
+    }
+
+    fn build_dir(&mut self, path: &Path) -> io::Result<(NodeIdentifier, Metrics)> {
+        let mut entries = fs::read_dir(path)?.collect::<io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|e| e.file_name());
+        let mut children = vec![];
+        let mut children_names = vec![];
+        let mut metrics = Metrics::default();
+        for e in entries {
+            let name = e.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                continue;
+            }
+            let path = e.path();
+            let (id, m) = if e.file_type()?.is_dir() {
+                self.build_dir(&path)?
+            } else if let Some(lang) = Lang::from_path(&path) {
+                self.build_file(&path, lang)?
+            } else {
+                continue;
+            };
+            children.push(id);
+            children_names.push(self.stores.label_store.get_or_insert(name));
+            metrics.acc(m);
+        }
+        let name = path
+            .file_name()
+            .map_or(String::new(), |x| x.to_string_lossy().to_string());
+        Ok(self.make_dir(name, children, children_names, metrics))
+    }
+
+    fn make_dir(
+        &mut self,
+        name: String,
+        children: Vec<NodeIdentifier>,
+        children_names: Vec<LabelIdentifier>,
+        metrics: Metrics,
+    ) -> (NodeIdentifier, Metrics) {
+        let kind = hyper_ast_gen_ts_java::types::Type::Directory;
+        let interned_kind = hyper_ast_gen_ts_java::types::TStore::intern(kind);
+        let label_id = self.stores.label_store.get_or_insert(name);
+        let metrics = metrics.finalize(&interned_kind, &label_id, 0);
+        let hashable = metrics.hashs.most_discriminating();
+        let eq = eq_node(&interned_kind, Some(&label_id), &children);
+        let insertion = self.stores.node_store.prepare_insertion(&hashable, eq);
+        if let Some(id) = insertion.occupied_id() {
+            let metrics = metrics.map_hashs(|h| h.build());
+            return (id, metrics);
+        }
+
+        let mut dyn_builder = dyn_builder::EntityBuilder::new();
+        let children_is_empty = children.is_empty();
+        dyn_builder.add(interned_kind);
+        dyn_builder.add(label_id);
+        if !children_is_empty {
+            dyn_builder.add(CS(children_names.into_boxed_slice()));
+            dyn_builder.add(CS(children.into_boxed_slice()));
+        }
+        let metrics = metrics.map_hashs(|h| h.build());
+        let hashs = metrics.add_md_metrics(&mut dyn_builder, children_is_empty);
+        hashs.persist(&mut dyn_builder);
+
+        let vacant = insertion.vacant();
+        let id = hyper_ast::store::nodes::legion::NodeStore::insert_built_after_prepare(
+            vacant,
+            dyn_builder.build(),
+        );
+        (id, metrics)
+    }
+
+    fn build_file(&mut self, path: &Path, lang: Lang) -> io::Result<(NodeIdentifier, Metrics)> {
+        let text = fs::read(path)?;
+        let name = path
+            .file_name()
+            .map_or(String::new(), |x| x.to_string_lossy().to_string());
+        Ok(self.generate(lang, name.as_bytes(), &text))
+    }
+
+    /// Generates the subtree of `text`, even if it does not parse without errors.
+    pub fn generate(&mut self, lang: Lang, name: &[u8], text: &[u8]) -> (NodeIdentifier, Metrics) {
+        use hyper_ast_gen_ts_cpp::legion::CppTreeGen;
+        use hyper_ast_gen_ts_java::legion_with_refs::JavaTreeGen;
+        use hyper_ast_gen_ts_ts::legion::TsTreeGen;
+        use hyper_ast_gen_ts_tsquery::legion::TsQueryTreeGen;
+        use hyper_ast_gen_ts_xml::legion::XmlTreeGen;
+        let line_break = line_break(text);
+        match lang {
+            Lang::Java => {
+                let tree = hyper_ast_gen_ts_java::legion_with_refs::tree_sitter_parse(text)
+                    .unwrap_or_else(|t| t);
+                let stores = self
+                    .stores
+                    .mut_with_ts::<hyper_ast_gen_ts_java::types::TStore>();
+                let mut gen = JavaTreeGen::new(stores, &mut self.caches.java);
+                gen.line_break = line_break;
+                let n = gen.generate_file(name, text, tree.walk()).local;
+                (n.compressed_node, n.metrics)
+            }
+            Lang::Cpp => {
+                let tree =
+                    CppTreeGen::<hyper_ast_gen_ts_cpp::types::TStore>::tree_sitter_parse(text)
+                        .unwrap_or_else(|t| t);
+                let stores = self
+                    .stores
+                    .mut_with_ts::<hyper_ast_gen_ts_cpp::types::TStore>();
+                let mut gen = CppTreeGen::new(stores, &mut self.caches.cpp);
+                gen.line_break = line_break;
+                let n = gen.generate_file(name, text, tree.walk()).local;
+                (n.compressed_node, n.metrics)
+            }
+            Lang::Ts => {
+                let tree = TsTreeGen::<hyper_ast_gen_ts_ts::types::TStore>::tree_sitter_parse(text)
+                    .unwrap_or_else(|t| t);
+                let stores = self
+                    .stores
+                    .mut_with_ts::<hyper_ast_gen_ts_ts::types::TStore>();
+                let mut gen = TsTreeGen::new(stores, &mut self.caches.ts);
+                gen.line_break = line_break;
+                let n = gen.generate_file(name, text, tree.walk()).local;
+                (n.compressed_node, n.metrics)
+            }
+            Lang::Xml => {
+                let tree =
+                    hyper_ast_gen_ts_xml::legion::tree_sitter_parse_xml(text).unwrap_or_else(|t| t);
+                let stores = self
+                    .stores
+                    .mut_with_ts::<hyper_ast_gen_ts_xml::types::TStore>();
+                let mut gen = XmlTreeGen::new(stores);
+                gen.line_break = line_break;
+                let n = gen.generate_file(name, text, tree.walk()).local;
+                (n.compressed_node, n.metrics)
+            }
+            Lang::Query => {
+                let tree =
+                    hyper_ast_gen_ts_tsquery::legion::tree_sitter_parse(text).unwrap_or_else(|t| t);
+                let stores = self
+                    .stores
+                    .mut_with_ts::<hyper_ast_gen_ts_tsquery::types::TStore>();
+                let mut gen = TsQueryTreeGen::new(stores, &mut self.caches.query);
+                gen.line_break = line_break;
+                let n = gen.generate_file(name, text, tree.walk()).local;
+                (n.compressed_node, n.metrics)
+            }
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Diffs two files or two directories, without needing a git repository.
+//!
+//! Each file is generated according to its extension,
+//! both sides are built in the same stores, then matched and the resulting edit script is printed.
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+use hyper_diff::{
+    actions::{
+        gumtree_format::{export_actions, export_matches, GtDiff},
+        render::RenderedScript,
+    },
+    algorithms::{self, pipeline::Pipeline},
+};
+
+mod building;
+mod types;
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Matcher {
+    /// the classic gumtree, fully decompressing both sides
+    Gumtree,
+    /// gumtree, lazily decompressing subtrees
+    Lazy,
+    /// gumtree, lazily decompressing subtrees and skipping the ones already identical
+    PartialLazy,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Unified,
+    SideBySide,
+    /// gumtree's json format
+    Json,
+    /// gumtree's xml format
+    Xml,
+}
+
+#[derive(Parser)]
+#[clap(version, about, long_about = None)]
+struct Options {
+    /// Increase verbosity, and can be used multiple times
+    #[clap(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// the original file or directory
+    src: PathBuf,
+
+    /// the modified file or directory
+    dst: PathBuf,
+
+    #[clap(short, long, value_enum, default_value_t = Matcher::Gumtree)]
+    matcher: Matcher,
+
+    /// a matcher pipeline, replacing the matcher,
+    /// e.g. "greedy_subtree, min_height=2 -> greedy_bottom_up, sim=0.5, max_size=1000"
+    #[clap(short, long)]
+    pipeline: Option<Pipeline>,
+
+    #[clap(short, long, value_enum, default_value_t = Format::Unified)]
+    format: Format,
+
+    /// width of each column in the side-by-side format
+    #[clap(short, long, default_value_t = 80)]
+    width: usize,
+}
+
+fn main() {
+    let opts = Options::parse();
+    let level = match opts.verbose {
+        0 => log::LevelFilter::Warn,
+        1 => log::LevelFilter::Info,
+        2 => log::LevelFilter::Debug,
+        _ => log::LevelFilter::Trace,
+    };
+    env_logger::Builder::from_default_env()
+        .filter_level(level)
+        .init();
+
+    if opts.src.is_dir() != opts.dst.is_dir() {
+        eprintln!("cannot diff a file with a directory");
+        std::process::exit(2);
+    }
+
+    let mut builder = building::Builder::default();
+    let now = std::time::Instant::now();
+    let (src, dst) = match (builder.build(&opts.src), builder.build(&opts.dst)) {
+        (Ok(src), Ok(dst)) => (src, dst),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    log::info!("built both sides in {:?}", now.elapsed());
+    let stores = &builder.stores;
+
+    let now = std::time::Instant::now();
+    macro_rules! parts {
+        ($diff:expr) => {{
+            let diff = $diff;
+            (diff.mapper, diff.actions)
+        }};
+    }
+    let (mapper, actions) = match (&opts.pipeline, opts.matcher) {
+        (Some(p), _) => parts!(algorithms::pipeline::diff(stores, &src, &dst, p)),
+        (None, Matcher::Gumtree) => parts!(algorithms::gumtree::diff(stores, &src, &dst)),
+        (None, Matcher::Lazy) => parts!(algorithms::gumtree_lazy::diff(stores, &src, &dst)),
+        (None, Matcher::PartialLazy) => {
+            parts!(algorithms::gumtree_partial_lazy::diff(stores, &src, &dst))
+        }
+    };
+    log::info!("diffed in {:?}", now.elapsed());
+    let actions = actions.unwrap_or_default();
+
+    match opts.format {
+        Format::Unified => {
+            let script = RenderedScript::new(stores, src, dst, &actions);
+            print!("{}", script.unified());
+        }
+        Format::SideBySide => {
+            let script = RenderedScript::new(stores, src, dst, &actions);
+            print!("{}", script.side_by_side(opts.width));
+        }
+        Format::Json | Format::Xml => {
+            let mapping = &mapper.mapping;
+            let gt = GtDiff {
+                matches: export_matches(
+                    stores,
+                    src,
+                    dst,
+                    &mapping.src_arena,
+                    &mapping.dst_arena,
+                    &mapping.mappings,
+                ),
+                actions: export_actions(stores, src, dst, &actions),
+            };
+            match opts.format {
+                Format::Json => println!("{}", gt.to_json()),
+                _ => print!("{}", gt.to_xml()),
+            }
+        }
+    }
+
+    if !actions.0.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use hyper_ast::types::{
+        HyperAST as _, HyperType as _, IterableChildren as _, LangRef as _, WithChildren as _,
+    };
+
+    use super::*;
+
+    static JAVA_SRC: &str = "class A {\n    int f() {\n        return 1;\n    }\n}\n";
+    static JAVA_DST: &str = "class A {\n    int f() {\n        return 2;\n    }\n}\n";
+    static TS_SRC: &str = "let x: number = 1;\nconsole.log(x);\n";
+    static TS_DST: &str = "let y: number = 1;\nconsole.log(y);\n";
+
+    fn write_project(side: &str, java: &str, ts: &str) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join(format!("hyperdiff_{}_{}", side, std::process::id()))
+            .join("proj");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        fs::write(path.join("A.java"), java).unwrap();
+        fs::write(path.join("b.ts"), ts).unwrap();
+        path
+    }
+
+    /// The hunks of `file` in the unified output.
+    fn hunks_of<'a>(unified: &'a str, file: &Path) -> &'a str {
+        let header = format!("--- a/{}\n", file.display());
+        let start = unified.find(&header).expect("the file should have hunks") + header.len();
+        let end = unified[start..]
+            .find("--- a/")
+            .map_or(unified.len(), |i| start + i);
+        &unified[start..end]
+    }
+
+    #[test]
+    fn diff_java_and_ts_directories() {
+        let src = write_project("src", JAVA_SRC, TS_SRC);
+        let dst = write_project("dst", JAVA_DST, TS_DST);
+        let mut builder = building::Builder::default();
+        let src = builder.build(&src).unwrap();
+        let dst = builder.build(&dst).unwrap();
+        let stores = &builder.stores;
+
+        // both languages are in the same tree, each with its own types
+        let files: Vec<_> = stores
+            .node_store
+            .resolve(src)
+            .children()
+            .unwrap()
+            .iter_children()
+            .copied()
+            .collect();
+        assert_eq!(files.len(), 2);
+        for file in &files {
+            assert!(stores.resolve_type(file).is_file());
+        }
+        assert_ne!(
+            stores.resolve_type(&files[0]).get_lang().name(),
+            stores.resolve_type(&files[1]).get_lang().name()
+        );
+
+        let actions = algorithms::gumtree::diff(stores, &src, &dst)
+            .actions
+            .unwrap();
+        let script = RenderedScript::new(stores, src, dst, &actions);
+        let unified = script.unified().to_string();
+        let changed = |l: &&str| l.starts_with('~') || l.starts_with('+');
+
+        let java = hunks_of(&unified, Path::new("proj/A.java"));
+        assert!(java
+            .lines()
+            .filter(changed)
+            .any(|l| l.contains("return") && l.contains('2')));
+        assert!(!java.contains("class A"));
+
+        let ts = hunks_of(&unified, Path::new("proj/b.ts"));
+        assert!(ts.lines().filter(changed).any(|l| l.contains('y')));
+    }
+}
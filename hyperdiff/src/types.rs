@@ -0,0 +1,46 @@
+use hyper_ast::types::{AnyType, HyperType, TypeStore, TypeU16};
+
+/// Type store of trees mixing all the supported languages,
+/// each file being generated with the type store of its language.
+#[derive(Clone, Default)]
+pub struct TStore;
+
+impl hyper_ast::store::TyDown<hyper_ast_gen_ts_java::types::TStore> for TStore {}
+impl hyper_ast::store::TyDown<hyper_ast_gen_ts_cpp::types::TStore> for TStore {}
+impl hyper_ast::store::TyDown<hyper_ast_gen_ts_xml::types::TStore> for TStore {}
+impl hyper_ast::store::TyDown<hyper_ast_gen_ts_tsquery::types::TStore> for TStore {}
+impl hyper_ast::store::TyDown<hyper_ast_gen_ts_ts::types::TStore> for TStore {}
+
+macro_rules! unerase {
+    ($erazed:expr, $t:ty) => {
+        unsafe { $erazed.unerase_ref::<$t>(std::any::TypeId::of::<$t>()) }
+            .map(|t| t.as_static().into())
+    };
+}
+
+impl TStore {
+    /// The type of a node generated with any of the supported languages,
+    /// `None` if it was generated with another type store.
+    pub(crate) fn try_decompress_type(
+        erazed: &impl hyper_ast::types::ErasedHolder,
+    ) -> Option<AnyType> {
+        unerase!(erazed, hyper_ast_gen_ts_java::types::TType)
+            .or_else(|| unerase!(erazed, hyper_ast_gen_ts_cpp::types::TType))
+            .or_else(|| unerase!(erazed, hyper_ast_gen_ts_xml::types::TType))
+            .or_else(|| unerase!(erazed, TypeU16<hyper_ast_gen_ts_ts::types::Ts>))
+            .or_else(|| unerase!(erazed, TypeU16<hyper_ast_gen_ts_tsquery::types::TsQuery>))
+    }
+}
+
+impl TypeStore for TStore {
+    type Ty = AnyType;
+
+    /// Nodes are only built by [`crate::building::Builder`], with the languages above.
+    fn decompress_type(
+        erazed: &impl hyper_ast::types::ErasedHolder,
+        _tid: std::any::TypeId,
+    ) -> Self::Ty {
+        Self::try_decompress_type(erazed)
+            .expect("the node should be generated with the type store of a supported language")
+    }
+}
@@ -3,6 +3,7 @@
 pub mod generate_types;
 mod keywords;
 pub mod preprocess;
+pub mod query_analysis;
 pub mod ts_metadata;
 
 use std::{ops::Not, path::Path};
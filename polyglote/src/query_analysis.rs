@@ -0,0 +1,612 @@
+//! Analysis of tree-sitter queries against the types of a grammar,
+//! to report misspelled node kinds or fields before compiling a query,
+//! and to suggest completions while it is being written.
+//!
+//! The [`Grammar`] is serializable, so it can be computed once by a server and sent to an editor.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{preprocess::TypeSys, preprocess_aux, ts_metadata, LanguageCompo};
+
+/// Kinds, fields and supertypes of a grammar, as needed to analyse queries.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Grammar {
+    pub named: BTreeSet<String>,
+    /// kinds matched with strings, eg. `"if"`
+    pub anonymous: BTreeSet<String>,
+    /// fields of each concrete kind
+    pub fields: BTreeMap<String, BTreeSet<String>>,
+    pub supertypes: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Grammar {
+    pub fn new(types: &TypeSys) -> Self {
+        let mut grammar = Grammar::default();
+        for (t, named) in types.leafs() {
+            if named {
+                grammar.named.insert(t);
+            } else {
+                grammar.anonymous.insert(t);
+            }
+        }
+        for (t, _) in types.concrete() {
+            grammar.named.insert(t);
+        }
+        for (t, fields) in types.concrete_fields() {
+            let fields = fields.into_iter().map(|(role, _)| role);
+            grammar.fields.entry(t).or_default().extend(fields);
+        }
+        for (t, subtypes) in types.r#abstract_subtypes() {
+            grammar.supertypes.insert(t, subtypes.into_iter().collect());
+        }
+        grammar
+    }
+
+    pub fn from_language(lang: &impl LanguageCompo) -> Result<Self, std::io::Error> {
+        Ok(Self::new(&preprocess_aux(lang)?))
+    }
+
+    fn is_kind(&self, kind: &str) -> bool {
+        self.named.contains(kind) || self.supertypes.contains_key(kind)
+    }
+
+    /// Fields that can be used in a node of `kind`, `None` if it could be any field
+    fn fields_of(&self, kind: &str) -> Option<BTreeSet<&str>> {
+        if self.named.contains(kind) && !self.supertypes.contains_key(kind) {
+            let fields = self.fields.get(kind).into_iter().flatten();
+            Some(fields.map(|x| x.as_str()).collect())
+        } else {
+            None
+        }
+    }
+
+    fn all_fields(&self) -> BTreeSet<&str> {
+        self.fields.values().flatten().map(|x| x.as_str()).collect()
+    }
+
+    /// Subtypes of `supertype`, including the ones of its subtypes
+    fn subtypes_of(&self, supertype: &str) -> BTreeSet<&str> {
+        let mut result = BTreeSet::new();
+        let mut stack = vec![supertype];
+        while let Some(t) = stack.pop() {
+            for sub in self.supertypes.get(t).into_iter().flatten() {
+                if result.insert(sub.as_str()) {
+                    stack.push(sub);
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Predicates handled by tree-sitter and the HyperAST query engines
+pub const PREDICATES: &[&str] = &[
+    "eq?",
+    "not-eq?",
+    "any-eq?",
+    "any-not-eq?",
+    "match?",
+    "not-match?",
+    "any-match?",
+    "any-not-match?",
+    "any-of?",
+    "not-any-of?",
+    "is?",
+    "is-not?",
+    "set!",
+    "select-adjacent!",
+    "strip!",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+    Error,
+    /// the query might still compile, eg. with a custom predicate
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// in bytes
+    pub range: Range<usize>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(node: tree_sitter::Node, message: String) -> Self {
+        Self {
+            range: node.byte_range(),
+            severity: Severity::Error,
+            message,
+        }
+    }
+}
+
+/// Checks the syntax of `query`, and its node kinds, fields and supertypes against `grammar`.
+///
+/// Diagnostics are given in the order of the query.
+pub fn validate(grammar: &Grammar, query: &str) -> Vec<Diagnostic> {
+    let tree = ts_metadata::ts_query_tree_from_str(query);
+    let text = |n: tree_sitter::Node| &query[n.byte_range()];
+    let mut diagnostics = vec![];
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if node.is_missing() {
+            let message = format!("missing `{}`", node.kind());
+            diagnostics.push(Diagnostic::error(node, message));
+        } else if node.is_error() {
+            let message = format!("invalid syntax `{}`", text(node).trim());
+            diagnostics.push(Diagnostic::error(node, message));
+        }
+        match node.kind() {
+            "named_node" => validate_named_node(grammar, query, node, &mut diagnostics),
+            "anonymous_node" => {
+                if let Some(name) = named_child_of_kind(node, "string") {
+                    let token = unescape(text(name));
+                    if !grammar.anonymous.contains(&token) {
+                        let message = format!("unknown token `{}`", token);
+                        let message = suggest(message, &token, grammar.anonymous.iter());
+                        diagnostics.push(Diagnostic::error(name, message));
+                    }
+                }
+            }
+            "field_definition" => {
+                if let Some(name) = node.child_by_field_name("name") {
+                    validate_field(grammar, query, node, name, &mut diagnostics);
+                }
+            }
+            "negated_field" => {
+                if let Some(name) = named_child_of_kind(node, "identifier") {
+                    validate_field(grammar, query, node, name, &mut diagnostics);
+                }
+            }
+            "predicate" => {
+                let name = named_child_of_kind(node, "identifier");
+                let ty = named_child_of_kind(node, "predicate_type");
+                if let (Some(name), Some(ty)) = (name, ty) {
+                    let predicate = format!("{}{}", text(name), text(ty));
+                    if !PREDICATES.contains(&predicate.as_str()) {
+                        diagnostics.push(Diagnostic {
+                            range: name.start_byte()..ty.end_byte(),
+                            severity: Severity::Warning,
+                            message: format!("unknown predicate `#{}`", predicate),
+                        });
+                    }
+                }
+            }
+            _ => (),
+        }
+        let mut cursor = node.walk();
+        let children: Vec<_> = node.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    diagnostics
+}
+
+/// Checks the kind of a node pattern like `(kind ...)` or `(supertype/subtype ...)`
+fn validate_named_node(
+    grammar: &Grammar,
+    query: &str,
+    node: tree_sitter::Node,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let Some((kind, subtype)) = node_kind(node) else {
+        return;
+    };
+    let name = &query[kind.byte_range()];
+    if name == "ERROR" || name == "MISSING" {
+        return;
+    }
+    if !grammar.is_kind(name) {
+        let message = format!("unknown node kind `{}`", name);
+        let kinds = grammar.named.iter().chain(grammar.supertypes.keys());
+        diagnostics.push(Diagnostic::error(kind, suggest(message, name, kinds)));
+        return;
+    }
+    let Some(subtype) = subtype else {
+        return;
+    };
+    let sub = &query[subtype.byte_range()];
+    if !grammar.supertypes.contains_key(name) {
+        let message = format!("`{}` is not a supertype", name);
+        diagnostics.push(Diagnostic::error(kind, message));
+        return;
+    }
+    let subtypes = grammar.subtypes_of(name);
+    if !subtypes.contains(sub) {
+        let message = format!("`{}` is not a subtype of `{}`", sub, name);
+        let message = suggest(message, sub, subtypes.into_iter());
+        diagnostics.push(Diagnostic::error(subtype, message));
+    }
+}
+
+/// Checks that the field `name` exists on the node pattern enclosing `node`
+fn validate_field(
+    grammar: &Grammar,
+    query: &str,
+    node: tree_sitter::Node,
+    name: tree_sitter::Node,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let field = &query[name.byte_range()];
+    let parent = enclosing_named_node(node)
+        .and_then(node_kind)
+        .map(|(kind, _)| &query[kind.byte_range()]);
+    match parent.and_then(|kind| Some((kind, grammar.fields_of(kind)?))) {
+        Some((kind, fields)) if !fields.contains(field) => {
+            let message = format!("`{}` has no field `{}`", kind, field);
+            let message = suggest(message, field, fields.into_iter());
+            diagnostics.push(Diagnostic::error(name, message));
+        }
+        Some(_) => (),
+        None => {
+            let fields = grammar.all_fields();
+            if !fields.contains(field) {
+                let message = format!("unknown field `{}`", field);
+                let message = suggest(message, field, fields.into_iter());
+                diagnostics.push(Diagnostic::error(name, message));
+            }
+        }
+    }
+}
+
+/// The kind of a `named_node` and its subtype if written `(kind/subtype)`
+fn node_kind(node: tree_sitter::Node) -> Option<(tree_sitter::Node, Option<tree_sitter::Node>)> {
+    let mut cursor = node.walk();
+    let mut children = node.children(&mut cursor);
+    // skip the opening parenthesis
+    children.next();
+    let kind = children.next().filter(|x| x.kind() == "identifier")?;
+    let subtype = match children.next() {
+        Some(slash) if slash.kind() == "/" => children.next().filter(|x| x.kind() == "identifier"),
+        _ => None,
+    };
+    Some((kind, subtype))
+}
+
+/// The node pattern a field applies to, going through lists and groupings
+fn enclosing_named_node(node: tree_sitter::Node) -> Option<tree_sitter::Node> {
+    let mut parent = node.parent()?;
+    loop {
+        match parent.kind() {
+            "named_node" => return Some(parent),
+            "list" | "grouping" => parent = parent.parent()?,
+            _ => return None,
+        }
+    }
+}
+
+fn named_child_of_kind<'t>(
+    node: tree_sitter::Node<'t>,
+    kind: &str,
+) -> Option<tree_sitter::Node<'t>> {
+    let mut cursor = node.walk();
+    let child = node.named_children(&mut cursor).find(|x| x.kind() == kind);
+    child
+}
+
+/// Content of a string literal of a query
+fn unescape(s: &str) -> String {
+    let s = s.strip_prefix('"').unwrap_or(s);
+    let s = s.strip_suffix('"').unwrap_or(s);
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some('0') => result.push('\0'),
+            Some(c) => result.push(c),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Adds to `message` the closest candidate to `name`, if it is close enough
+fn suggest(
+    message: String,
+    name: &str,
+    candidates: impl Iterator<Item = impl AsRef<str>>,
+) -> String {
+    let closest = candidates
+        .map(|x| (edit_distance(name, x.as_ref()), x))
+        .filter(|(d, _)| *d <= (name.len() / 3).max(2))
+        .min_by_key(|(d, _)| *d);
+    match closest {
+        Some((_, x)) => format!("{}, did you mean `{}`?", message, x.as_ref()),
+        None => message,
+    }
+}
+
+/// Levenshtein distance between `a` and `b`, counted in chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(cur).min(row[j])
+            };
+            prev = cur;
+        }
+    }
+    row[b.len()]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompletionKind {
+    NodeKind,
+    Supertype,
+    /// a subtype after `supertype/`
+    Subtype,
+    /// an anonymous node, inside a string
+    Token,
+    Field,
+    Predicate,
+    Capture,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    /// bytes of the query replaced by `label`
+    pub replace: Range<usize>,
+}
+
+/// Suggests what can be written at the byte offset `cursor` of `query`,
+/// completing the identifier right before the cursor.
+///
+/// The query is usually incomplete while being edited, so the context is found from the text,
+/// eg. fields are proposed inside a node pattern and subtypes after a `/`.
+/// Parentheses inside strings are not taken into account.
+pub fn complete(grammar: &Grammar, query: &str, cursor: usize) -> Vec<Completion> {
+    let mut cursor = cursor.min(query.len());
+    while !query.is_char_boundary(cursor) {
+        cursor -= 1;
+    }
+    let before = &query[..cursor];
+    if let Some(start) = string_start(before) {
+        let prefix = &before[start..];
+        return completions(
+            grammar.anonymous.iter().map(|x| x.as_str()),
+            prefix,
+            CompletionKind::Token,
+            start..cursor,
+        );
+    }
+    let start = before
+        .rfind(|c: char| !is_identifier_char(c))
+        .map_or(0, |i| i + 1);
+    let prefix = &before[start..];
+    let replace = start..cursor;
+    match before[..start].chars().next_back() {
+        Some('(') => kinds(grammar, prefix, replace),
+        Some('/') => {
+            let supertype = identifier_before(&before[..start - 1]);
+            let subtypes = grammar.subtypes_of(supertype);
+            completions(
+                subtypes.into_iter(),
+                prefix,
+                CompletionKind::Subtype,
+                replace,
+            )
+        }
+        Some('#') => completions(
+            PREDICATES.iter().copied(),
+            prefix,
+            CompletionKind::Predicate,
+            replace,
+        ),
+        Some('@') => {
+            let captures = captures(query, start..cursor);
+            completions(
+                captures.iter().copied(),
+                prefix,
+                CompletionKind::Capture,
+                replace,
+            )
+        }
+        Some('!') => fields(grammar, &before[..start], prefix, replace),
+        Some(c) if c.is_whitespace() => fields(grammar, &before[..start], prefix, replace),
+        _ => vec![],
+    }
+}
+
+fn completions<'a>(
+    candidates: impl Iterator<Item = &'a str>,
+    prefix: &str,
+    kind: CompletionKind,
+    replace: Range<usize>,
+) -> Vec<Completion> {
+    candidates
+        .filter(|x| x.starts_with(prefix))
+        .map(|label| Completion {
+            label: label.to_string(),
+            kind,
+            replace: replace.clone(),
+        })
+        .collect()
+}
+
+fn kinds(grammar: &Grammar, prefix: &str, replace: Range<usize>) -> Vec<Completion> {
+    let named = grammar
+        .named
+        .iter()
+        .filter(|x| !grammar.supertypes.contains_key(*x));
+    let named = named.map(|x| x.as_str());
+    let supertypes = grammar.supertypes.keys().map(|x| x.as_str());
+    let mut result = completions(named, prefix, CompletionKind::NodeKind, replace.clone());
+    result.extend(completions(
+        supertypes,
+        prefix,
+        CompletionKind::Supertype,
+        replace,
+    ));
+    result
+}
+
+/// Fields of the node pattern opened before `before`
+fn fields(grammar: &Grammar, before: &str, prefix: &str, replace: Range<usize>) -> Vec<Completion> {
+    let Some(open) = unclosed_parenthesis(before) else {
+        return vec![];
+    };
+    let kind = &before[open + 1..];
+    let end = kind
+        .find(|c: char| !is_identifier_char(c))
+        .unwrap_or(kind.len());
+    let kind = &kind[..end];
+    match grammar.fields_of(kind) {
+        Some(fields) => completions(fields.into_iter(), prefix, CompletionKind::Field, replace),
+        // eg. in a predicate or a supertype
+        None if !grammar.named.contains(kind) => vec![],
+        None => completions(
+            grammar.all_fields().into_iter(),
+            prefix,
+            CompletionKind::Field,
+            replace,
+        ),
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+fn identifier_before(s: &str) -> &str {
+    let start = s
+        .rfind(|c: char| !is_identifier_char(c))
+        .map_or(0, |i| i + 1);
+    &s[start..]
+}
+
+/// Start of the content of the string that is still open at the end of `s`, if any
+fn string_start(s: &str) -> Option<usize> {
+    let mut start = None;
+    let mut escaped = false;
+    let mut comment = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '\n' if comment => comment = false,
+            _ if comment => (),
+            _ if escaped => escaped = false,
+            '\\' if start.is_some() => escaped = true,
+            '"' if start.is_some() => start = None,
+            '"' => start = Some(i + 1),
+            ';' => comment = true,
+            _ => (),
+        }
+    }
+    start
+}
+
+/// Offset of the last parenthesis of `s` that is not closed
+fn unclosed_parenthesis(s: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' if depth == 0 => return Some(i),
+            '(' => depth -= 1,
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Names of the captures of `query`, except the one being written in `skip`
+fn captures(query: &str, skip: Range<usize>) -> BTreeSet<&str> {
+    let mut result = BTreeSet::new();
+    for (i, _) in query.match_indices('@') {
+        let name = &query[i + 1..];
+        let end = name
+            .find(|c: char| !is_identifier_char(c))
+            .unwrap_or(name.len());
+        if i + 1 != skip.start && end > 0 {
+            result.insert(&name[..end]);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grammar() -> Grammar {
+        let lang = crate::Lang {
+            language: tree_sitter_java::language(),
+            name: "java",
+            node_types: tree_sitter_java::NODE_TYPES,
+            highlights: "",
+            tags: "",
+            injects: "",
+        };
+        Grammar::from_language(&lang).unwrap()
+    }
+
+    #[test]
+    fn java_query_diagnostics() {
+        let grammar = grammar();
+        let query = r#"(method_declaration name: (identifier) body: (block)) @m"#;
+        assert_eq!(validate(&grammar, query), vec![]);
+
+        let query = r#"(methd_declaration) (class_declaration nme: (identifier)) "iff""#;
+        let diagnostics = validate(&grammar, query);
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "unknown node kind `methd_declaration`, did you mean `method_declaration`?",
+                "`class_declaration` has no field `nme`, did you mean `name`?",
+                "unknown token `iff`, did you mean `if`?",
+            ]
+        );
+        assert_eq!(diagnostics[0].range, 1..18);
+
+        let query = r#"(expression/method_invocation) (expression/block) (#foo? @x)"#;
+        let diagnostics = validate(&grammar, query);
+        assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[1].severity, Severity::Warning);
+
+        let query = r#"(block (identifier)"#;
+        let diagnostics = validate(&grammar, query);
+        assert!(!diagnostics.is_empty());
+    }
+
+    #[test]
+    fn java_query_completions() {
+        let grammar = grammar();
+        let labels = |query: &str| -> Vec<String> {
+            complete(&grammar, query, query.len())
+                .into_iter()
+                .map(|c| c.label)
+                .collect()
+        };
+        assert!(labels("(method_decl").contains(&"method_declaration".to_string()));
+        assert_eq!(labels("(class_declaration na"), vec!["name"]);
+        assert!(labels("(expression/method_").contains(&"method_invocation".to_string()));
+        assert!(labels("(identifier) @id (#eq").contains(&"eq?".to_string()));
+        assert_eq!(labels("(identifier) @id (#eq? @"), vec!["id"]);
+        assert!(labels("(if_statement \"el").contains(&"else".to_string()));
+        let completions = complete(&grammar, "(class_declaration na", 21);
+        assert_eq!(completions[0].replace, 19..21);
+    }
+}
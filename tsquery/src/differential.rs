@@ -0,0 +1,168 @@
+//! Follow the matches of a query along a sequence of versions of the same code.
+//!
+//! The matches of consecutive versions are paired using the mappings of [`hyper_diff`],
+//! so each version only reports the matches that appeared, disappeared or moved.
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Debug,
+};
+
+use hyper_ast::position::{position_accessors::WithPreOrderOffsets, StructuralPosition};
+use hyper_ast::types::{self, HyperAST, RoleStore, WithPrecompQueries, WithRoles};
+use hyper_diff::{
+    actions::script_generator2::Act,
+    algorithms::gumtree_lazy,
+    decompressed_tree_store::{DecompressedWithParent, ShallowDecompressedTreeStore},
+    matchers::mapping_store::MonoMappingStore,
+};
+
+use crate::{hyperast::TreeCursor, PatternId, Query};
+
+/// A match, located by the path from the root to its topmost captured node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocatedMatch<Idx> {
+    pub pattern_index: PatternId,
+    /// offsets of the children to go through, starting from the root
+    pub path: Vec<Idx>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchChange<Idx> {
+    Added(LocatedMatch<Idx>),
+    Removed(LocatedMatch<Idx>),
+    /// The match is still there but its topmost captured node, or one of its ancestors, was moved by the diff.
+    Moved {
+        src: LocatedMatch<Idx>,
+        dst: LocatedMatch<Idx>,
+    },
+}
+
+/// Lists the matches of `query` in `root`, in the order given by the query cursor.
+///
+/// Matches without captures cannot be located, so they are skipped.
+pub fn located_matches<'hast, HAST: HyperAST<'hast>>(
+    query: &Query,
+    stores: &'hast HAST,
+    root: HAST::IdN,
+) -> Vec<LocatedMatch<HAST::Idx>>
+where
+    HAST::IdN: Debug + Copy,
+    HAST::TS: RoleStore,
+    <HAST::TS as RoleStore>::IdF: Into<u16> + From<u16>,
+    HAST::T: WithRoles + WithPrecompQueries,
+{
+    let cursor = TreeCursor::new(stores, StructuralPosition::new(root));
+    query
+        .matches(cursor)
+        .filter_map(|m| {
+            let path = (&m.captures)
+                .into_iter()
+                .map(|c| c.node.pos.iter_offsets().collect::<Vec<_>>())
+                .min_by_key(|path| path.len())?;
+            Some(LocatedMatch {
+                pattern_index: m.pattern_index,
+                path,
+            })
+        })
+        .collect()
+}
+
+/// Pairs the matches of `query` in `src` and `dst` using the mappings of [`gumtree_lazy::diff`].
+///
+/// Removed and moved matches come first, in the order of `src`, then added ones, in the order of `dst`.
+/// Matches that are only shifted by insertions or deletions of siblings are not reported.
+pub fn match_changes<'hast, HAST: HyperAST<'hast>>(
+    query: &Query,
+    hyperast: &'hast HAST,
+    src: HAST::IdN,
+    dst: HAST::IdN,
+) -> Vec<MatchChange<HAST::Idx>>
+where
+    HAST::IdN: Debug + Copy + Eq,
+    HAST::TS: RoleStore,
+    <HAST::TS as RoleStore>::IdF: Into<u16> + From<u16>,
+    HAST::T: 'hast + WithRoles + WithPrecompQueries + types::WithHashs + types::WithStats,
+    HAST::Label: Clone + Copy + Eq + Debug,
+    HAST::Idx: hyper_ast::PrimInt,
+{
+    if src == dst {
+        return vec![];
+    }
+    let src_matches = located_matches(query, hyperast, src);
+    let dst_matches = located_matches(query, hyperast, dst);
+    let diff = gumtree_lazy::diff(hyperast, &src, &dst);
+    let mapper = diff.mapper;
+    let node_store = hyperast.node_store();
+    let src_arena = &mapper.mapping.src_arena;
+    let dst_arena = &mapper.mapping.dst_arena;
+    let moved: HashSet<_> = diff
+        .actions
+        .iter()
+        .flat_map(|actions| actions.iter())
+        .filter_map(|a| match &a.action {
+            Act::Move { from } | Act::MovUpd { from, .. } => {
+                let path: Vec<_> = from.ori.iter().collect();
+                Some(src_arena.child(node_store, &src_arena.root(), &path))
+            }
+            _ => None,
+        })
+        .collect();
+    // several matches of a pattern can share their topmost captured node
+    let mut dst_by_node: HashMap<_, VecDeque<usize>> = HashMap::default();
+    for (i, m) in dst_matches.iter().enumerate() {
+        let d = dst_arena.child(node_store, &dst_arena.root(), &m.path);
+        dst_by_node
+            .entry((d, m.pattern_index.to_usize()))
+            .or_default()
+            .push_back(i);
+    }
+    let mut paired = vec![false; dst_matches.len()];
+    let mut changes = vec![];
+    for m in src_matches {
+        let s = src_arena.child(node_store, &src_arena.root(), &m.path);
+        let i = mapper.mapping.mappings.get_dst(&s).and_then(|d| {
+            dst_by_node
+                .get_mut(&(d, m.pattern_index.to_usize()))?
+                .pop_front()
+        });
+        match i {
+            Some(i) => {
+                paired[i] = true;
+                let was_moved = std::iter::successors(Some(s), |x| src_arena.parent(x))
+                    .any(|x| moved.contains(&x));
+                if was_moved {
+                    changes.push(MatchChange::Moved {
+                        src: m,
+                        dst: dst_matches[i].clone(),
+                    });
+                }
+            }
+            None => changes.push(MatchChange::Removed(m)),
+        }
+    }
+    for (m, paired) in dst_matches.into_iter().zip(paired) {
+        if !paired {
+            changes.push(MatchChange::Added(m));
+        }
+    }
+    changes
+}
+
+/// Computes the [`match_changes`] of each version in `roots` with the one before it.
+pub fn differential<'a, 'hast: 'a, HAST: HyperAST<'hast>>(
+    query: &'a Query,
+    hyperast: &'hast HAST,
+    roots: &'a [HAST::IdN],
+) -> impl Iterator<Item = Vec<MatchChange<HAST::Idx>>> + 'a
+where
+    HAST::IdN: Debug + Copy + Eq,
+    HAST::TS: RoleStore,
+    <HAST::TS as RoleStore>::IdF: Into<u16> + From<u16>,
+    HAST::T: 'hast + WithRoles + WithPrecompQueries + types::WithHashs + types::WithStats,
+    HAST::Label: Clone + Copy + Eq + Debug,
+    HAST::Idx: hyper_ast::PrimInt,
+{
+    roots
+        .windows(2)
+        .map(move |w| match_changes(query, hyperast, w[0], w[1]))
+}
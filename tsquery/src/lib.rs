@@ -19,10 +19,16 @@ mod ffi_extra;
 mod ts_private_bypass;
 
 pub mod default_impls;
+#[cfg(feature = "differential")]
+pub mod differential;
 #[cfg(feature = "hyper_ast")]
 pub mod hyperast;
 #[cfg(feature = "hyper_ast")]
 pub mod hyperast_opt;
+#[cfg(feature = "hyper_ast")]
+pub mod memoized;
+#[cfg(feature = "hyper_ast")]
+pub mod metadata;
 pub mod stepped_query;
 pub mod tsg;
 
@@ -36,6 +42,7 @@ pub(crate) use ts_private_bypass::*;
 pub use indexed::CaptureId;
 pub use indexed::PatternId;
 pub use indexed::Symbol;
+pub use predicate::{CustomPredicates, PredicateArg};
 
 use std::{collections::VecDeque, usize};
 
@@ -106,9 +113,21 @@ impl Query {
             query: self,
             cursor,
             next_state_id: indexed::StateId::ZERO,
+            custom_predicates: None,
         }
     }
 
+    /// Same as [`Query::matches`] but also evaluates the predicates registered in `predicates`
+    pub fn matches_with<'query, Cursor: self::Cursor>(
+        &'query self,
+        cursor: Cursor,
+        predicates: &'query CustomPredicates<Cursor::Node>,
+    ) -> QueryCursor<'query, Cursor, Cursor::Node> {
+        let mut qcursor = self.matches(cursor);
+        qcursor.custom_predicates = Some(predicates);
+        qcursor
+    }
+
     /// Match all patterns that starts on cursor current node
     pub fn matches_immediate<'query, Cursor: self::Cursor>(
         &'query self,
@@ -157,6 +176,7 @@ pub struct QueryCursor<'query, Cursor, Node> {
     // only triggers when there is no more capture list available
     // not triggered by reaching max_start_depth
     did_exceed_match_limit: bool,
+    custom_predicates: Option<&'query CustomPredicates<Node>>,
 }
 
 #[derive(Clone)]
@@ -297,13 +317,25 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         loop {
             let result = self.next_match()?;
-            if result.satisfies_text_predicates(
+            if !result.satisfies_text_predicates(
                 self.cursor.text_provider(),
                 self.query
                     .text_predicates_for_pattern_id(result.pattern_index),
             ) {
-                return Some(result);
+                continue;
+            }
+            if let Some(custom_predicates) = self.custom_predicates {
+                let predicates = self
+                    .query
+                    .general_predicates_for_pattern_id(result.pattern_index);
+                let properties = self
+                    .query
+                    .property_predicates_for_pattern_id(result.pattern_index);
+                if !custom_predicates.satisfied_by(&result, predicates, properties) {
+                    continue;
+                }
             }
+            return Some(result);
         }
     }
 }
@@ -0,0 +1,352 @@
+//! Count the matches of a query over many versions of the same code.
+//!
+//! Identical subtrees share the same identifier in the HyperAST,
+//! so the counts of local patterns are memoized per directory and per file.
+//! Querying a new version then only goes through the files that changed.
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+use hyper_ast::cyclomatic::Mcc;
+use hyper_ast::hashed::SyntaxNodeHashsKinds;
+use hyper_ast::position::StructuralPosition;
+use hyper_ast::store::nodes::legion::compo;
+use hyper_ast::types::{
+    HyperAST, HyperType, IterableChildren, NodeStore, RoleStore, WithChildren, WithHashs,
+    WithMetaData, WithPrecompQueries, WithRoles, WithStats,
+};
+
+use crate::{
+    hyperast::{Node, TreeCursor},
+    metadata, CustomPredicates, PatternId, Query,
+};
+
+pub struct MatchCounts<IdN> {
+    query: Query,
+    /// only the local patterns of `query`, their matches can be counted per subtree
+    local: Query,
+    /// only the other patterns of `query`, if there are some
+    non_local: Option<Query>,
+    /// counts of local patterns in each visited directory and file,
+    /// indexed like the enabled patterns of `query`
+    cache: HashMap<IdN, Box<[u64]>>,
+    /// number of subtrees whose counts were taken from `cache`
+    hits: usize,
+}
+
+/// Why counting stopped before the end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interrupted {
+    TimeOut,
+    MaxMatches,
+}
+
+/// Limits of a count, checked each time a file or a memoized subtree is counted
+struct Budget {
+    start: Instant,
+    timeout: Duration,
+    max_matches: u64,
+    /// matches counted so far from the root
+    total: Vec<u64>,
+}
+
+impl Budget {
+    fn add(&mut self, counts: &[u64]) -> Result<(), Interrupted> {
+        for (x, y) in self.total.iter_mut().zip(counts) {
+            *x += y;
+        }
+        if self.start.elapsed() >= self.timeout {
+            Err(Interrupted::TimeOut)
+        } else if self.total.iter().any(|x| *x > self.max_matches) {
+            Err(Interrupted::MaxMatches)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<IdN> MatchCounts<IdN> {
+    pub fn new(query: Query) -> Self {
+        let mut local = query.clone();
+        let mut non_local = query.clone();
+        let mut all_local = true;
+        for pid in (0..query.pattern_count()).map(PatternId::new) {
+            if query.enabled_pattern_index(pid).is_none() {
+                continue;
+            }
+            if query.is_pattern_local(pid) {
+                non_local.disable_pattern(pid);
+            } else {
+                local.disable_pattern(pid);
+                all_local = false;
+            }
+        }
+        Self {
+            query,
+            local,
+            non_local: (!all_local).then_some(non_local),
+            cache: HashMap::default(),
+            hits: 0,
+        }
+    }
+
+    pub fn query(&self) -> &Query {
+        &self.query
+    }
+
+    /// Number of directories and files with memoized counts
+    pub fn cached_count(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Number of directories and files whose counts were reused instead of being recounted
+    pub fn cache_hits(&self) -> usize {
+        self.hits
+    }
+}
+
+impl<IdN: Debug + Copy + Hash + Eq> MatchCounts<IdN> {
+    /// Counts the matches of each enabled pattern in `root`,
+    /// reusing the counts of subtrees already seen in previous calls.
+    ///
+    /// The [`metadata::predicates`] are evaluated, they only depend on the subtree.
+    pub fn count<'hast, HAST>(&mut self, stores: &'hast HAST, root: IdN) -> Vec<u64>
+    where
+        HAST: HyperAST<'hast, IdN = IdN>,
+        HAST::TS: RoleStore,
+        <HAST::TS as RoleStore>::IdF: Into<u16> + From<u16>,
+        HAST::T: WithRoles
+            + WithPrecompQueries
+            + WithStats
+            + WithHashs<HK = SyntaxNodeHashsKinds>
+            + WithMetaData<compo::SizeNoSpaces>
+            + WithMetaData<Mcc>,
+    {
+        self.count_with_limits(stores, root, Duration::MAX, u64::MAX)
+            .unwrap_or_else(|(_, partial)| partial)
+    }
+
+    /// Same as [`Self::count`], but stops once `timeout` is elapsed
+    /// or a pattern has more than `max_matches` matches.
+    ///
+    /// Limits are checked after each file, so an interruption also gives the counts so far.
+    /// Subtrees that were fully counted stay memoized.
+    pub fn count_with_limits<'hast, HAST>(
+        &mut self,
+        stores: &'hast HAST,
+        root: IdN,
+        timeout: Duration,
+        max_matches: u64,
+    ) -> Result<Vec<u64>, (Interrupted, Vec<u64>)>
+    where
+        HAST: HyperAST<'hast, IdN = IdN>,
+        HAST::TS: RoleStore,
+        <HAST::TS as RoleStore>::IdF: Into<u16> + From<u16>,
+        HAST::T: WithRoles
+            + WithPrecompQueries
+            + WithStats
+            + WithHashs<HK = SyntaxNodeHashsKinds>
+            + WithMetaData<compo::SizeNoSpaces>
+            + WithMetaData<Mcc>,
+    {
+        let predicates = metadata::predicates();
+        let mut budget = Budget {
+            start: Instant::now(),
+            timeout,
+            max_matches,
+            total: vec![0; self.query.enabled_pattern_count()],
+        };
+        if let Err(e) = self.count_local(stores, &predicates, root, &mut budget) {
+            return Err((e, budget.total));
+        }
+        if let Some(non_local) = &self.non_local {
+            let cursor = TreeCursor::new(stores, StructuralPosition::new(root));
+            let mut one = vec![0; budget.total.len()];
+            for m in non_local.matches_with(cursor, &predicates) {
+                let i = self.query.enabled_pattern_index(m.pattern_index).unwrap();
+                one[i as usize] = 1;
+                if let Err(e) = budget.add(&one) {
+                    return Err((e, budget.total));
+                }
+                one[i as usize] = 0;
+            }
+        }
+        Ok(budget.total)
+    }
+
+    fn count_local<'hast, HAST>(
+        &mut self,
+        stores: &'hast HAST,
+        predicates: &CustomPredicates<Node<'hast, HAST>>,
+        id: IdN,
+        budget: &mut Budget,
+    ) -> Result<Box<[u64]>, Interrupted>
+    where
+        HAST: HyperAST<'hast, IdN = IdN>,
+        HAST::TS: RoleStore,
+        <HAST::TS as RoleStore>::IdF: Into<u16> + From<u16>,
+        HAST::T: WithRoles
+            + WithPrecompQueries
+            + WithStats
+            + WithHashs<HK = SyntaxNodeHashsKinds>
+            + WithMetaData<compo::SizeNoSpaces>
+            + WithMetaData<Mcc>,
+    {
+        if let Some(counts) = self.cache.get(&id) {
+            let counts = counts.clone();
+            self.hits += 1;
+            budget.add(&counts)?;
+            return Ok(counts);
+        }
+        let mut counts = vec![0; self.query.enabled_pattern_count()];
+        let cursor = TreeCursor::new(stores, StructuralPosition::new(id));
+        if stores.resolve_type(&id).is_directory() {
+            // matches starting on the directory itself,
+            // the ones starting deeper are counted with its children
+            let mut matches = self.local.matches_with(cursor, predicates);
+            matches.set_max_start_depth(0);
+            for m in matches {
+                let i = self.query.enabled_pattern_index(m.pattern_index).unwrap();
+                counts[i as usize] += 1;
+            }
+            budget.add(&counts)?;
+            let n = stores.node_store().resolve(&id);
+            for child in n.children().into_iter().flat_map(|cs| cs.iter_children()) {
+                if stores.resolve_type(child).is_spaces() {
+                    continue;
+                }
+                // already added to the budget
+                let child_counts = self.count_local(stores, predicates, *child, budget)?;
+                for (x, y) in counts.iter_mut().zip(child_counts.iter()) {
+                    *x += y;
+                }
+            }
+        } else {
+            for m in self.local.matches_with(cursor, predicates) {
+                let i = self.query.enabled_pattern_index(m.pattern_index).unwrap();
+                counts[i as usize] += 1;
+            }
+            let counts = counts.into_boxed_slice();
+            self.cache.insert(id, counts.clone());
+            // a file is complete even if it exceeds the limits
+            budget.add(&counts)?;
+            return Ok(counts);
+        }
+        // only complete counts reach the cache
+        let counts = counts.into_boxed_slice();
+        self.cache.insert(id, counts.clone());
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper_ast::hashed::{IndexingHashBuilder, MetaDataHashsBuilder, SyntaxNodeHashs};
+    use hyper_ast::store::nodes::legion::{dyn_builder, eq_node};
+    use hyper_ast::store::{defaults::NodeIdentifier, SimpleStores};
+    use hyper_ast::tree_gen::SubTreeMetrics;
+    use hyper_ast::types::LabelStore;
+    use hyper_ast_gen_ts_java::legion_with_refs::{tree_sitter_parse, JavaTreeGen};
+    use hyper_ast_gen_ts_java::types::{JavaEnabledTypeStore as _, TStore, Type};
+
+    type Stores = SimpleStores<TStore>;
+    type Full = (NodeIdentifier, SubTreeMetrics<SyntaxNodeHashs<u32>>);
+
+    fn file(stores: &mut Stores, text: &str) -> Full {
+        let mut md_cache = Default::default();
+        let mut gen = JavaTreeGen::new(stores, &mut md_cache);
+        let tree = tree_sitter_parse(text.as_bytes()).unwrap_or_else(|t| t);
+        let local = gen.generate_file(b"", text.as_bytes(), tree.walk()).local;
+        (local.compressed_node, local.metrics)
+    }
+
+    /// A directory built like the ones of the git processors, without their analysis
+    fn dir(stores: &mut Stores, name: &str, children: &[(&str, Full)]) -> Full {
+        use hyper_ast::store::nodes::EntityBuilder as _;
+        let kind = TStore::intern(Type::Directory);
+        let label = stores.label_store.get_or_insert(name);
+        let mut metrics = SubTreeMetrics::<SyntaxNodeHashs<u32>>::default();
+        let mut names = vec![];
+        let mut ids = vec![];
+        for (name, (id, m)) in children {
+            names.push(stores.label_store.get_or_insert(*name));
+            ids.push(*id);
+            metrics.acc(*m);
+        }
+        let metrics = metrics.finalize(&kind, &label, 0);
+        let hashable = metrics.hashs.most_discriminating();
+        let eq = eq_node(&kind, Some(&label), &ids);
+        let insertion = stores.node_store.prepare_insertion(&hashable, eq);
+        if let Some(id) = insertion.occupied_id() {
+            return (id, metrics.map_hashs(|h| MetaDataHashsBuilder::build(h)));
+        }
+        let mut builder = dyn_builder::EntityBuilder::new();
+        builder.add(kind);
+        builder.add(label);
+        builder.add(compo::CS(names.into_boxed_slice()));
+        builder.add(compo::CS(ids.into_boxed_slice()));
+        let metrics = metrics.map_hashs(|h| h.build());
+        let hashs = metrics.add_md_metrics(&mut builder, false);
+        hashs.persist(&mut builder);
+        let id = hyper_ast::store::nodes::legion::NodeStore::insert_built_after_prepare(
+            insertion.vacant(),
+            builder.build(),
+        );
+        (id, metrics)
+    }
+
+    /// Counts of a whole traversal of `root`, without memoization
+    fn traversal_counts(query: &Query, stores: &Stores, root: NodeIdentifier) -> Vec<u64> {
+        let mut counts = vec![0; query.enabled_pattern_count()];
+        let cursor = TreeCursor::new(stores, StructuralPosition::new(root));
+        for m in query.matches(cursor) {
+            let i = query.enabled_pattern_index(m.pattern_index).unwrap();
+            counts[i as usize] += 1;
+        }
+        counts
+    }
+
+    /// Over commits, unchanged directories and files are not recounted,
+    /// and the counts stay the same as the ones of a whole traversal
+    #[test]
+    fn memoized_commits() {
+        let mut stores = Stores::default();
+        let a = file(&mut stores, "class A { void f() {} }");
+        let b = file(&mut stores, "class B { void g() {} }");
+        let b2 = file(&mut stores, "class B { void g() {} void h() {} }");
+        let dir_a = dir(&mut stores, "a", &[("A.java", a)]);
+        let dir_b = dir(&mut stores, "b", &[("B.java", b)]);
+        let dir_b2 = dir(&mut stores, "b", &[("B.java", b2)]);
+        let dir_c = dir(&mut stores, "c", &[("A.java", a)]);
+        let commits = [
+            dir(&mut stores, "", &[("a", dir_a), ("b", dir_b)]).0,
+            // b/B.java changed
+            dir(&mut stores, "", &[("a", dir_a), ("b", dir_b2)]).0,
+            // c/A.java is a copy of a/A.java
+            dir(
+                &mut stores,
+                "",
+                &[("a", dir_a), ("b", dir_b2), ("c", dir_c)],
+            )
+            .0,
+        ];
+        let query = Query::new(
+            "(method_declaration) @m\n(class_declaration name: (identifier) @n)",
+            tree_sitter_java::language(),
+        )
+        .unwrap();
+        let mut counts = MatchCounts::new(query.clone());
+        let expected = [vec![2, 2], vec![3, 2], vec![4, 3]];
+        // (hits, cached) after each commit
+        let memo = [(0, 5), (1, 8), (4, 10)];
+        for ((root, expected), memo) in commits.into_iter().zip(expected).zip(memo) {
+            assert_eq!(traversal_counts(&query, &stores, root), expected);
+            assert_eq!(counts.count(&stores, root), expected);
+            assert_eq!((counts.cache_hits(), counts.cached_count()), memo);
+        }
+    }
+}
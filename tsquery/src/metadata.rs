@@ -0,0 +1,158 @@
+//! Query over the metadata of HyperAST nodes.
+//!
+//! With the [`predicates`] of this module,
+//! `((method_declaration) @m (#is? @m cyclomatic ">10"))` only matches complex methods.
+//! The values of captured nodes can also be requested with `(#set! @m size)`, see [`requested_metadata`].
+use hyper_ast::cyclomatic::Mcc;
+use hyper_ast::hashed::SyntaxNodeHashsKinds;
+use hyper_ast::position::TreePath;
+use hyper_ast::store::nodes::legion::compo;
+use hyper_ast::types::{HyperAST, NodeStore, WithHashs, WithMetaData, WithStats};
+use num::ToPrimitive;
+
+use crate::{hyperast::Node, CaptureId, CustomPredicates, PatternId, Query, QueryMatch};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metadata {
+    Size,
+    SizeNoSpaces,
+    Height,
+    StructHash,
+    LabelHash,
+    SyntaxHash,
+    /// only available on files, type declarations and executable members
+    Cyclomatic,
+}
+
+impl Metadata {
+    pub const ALL: [Metadata; 7] = [
+        Metadata::Size,
+        Metadata::SizeNoSpaces,
+        Metadata::Height,
+        Metadata::StructHash,
+        Metadata::LabelHash,
+        Metadata::SyntaxHash,
+        Metadata::Cyclomatic,
+    ];
+
+    /// The key used in queries
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Metadata::Size => "size",
+            Metadata::SizeNoSpaces => "size_no_spaces",
+            Metadata::Height => "height",
+            Metadata::StructHash => "struct_hash",
+            Metadata::LabelHash => "label_hash",
+            Metadata::SyntaxHash => "hash",
+            Metadata::Cyclomatic => "cyclomatic",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.as_str() == key)
+    }
+
+    /// Returns `None` if this metadata is not stored on the node
+    pub fn get<'hast, HAST: HyperAST<'hast>>(
+        &self,
+        stores: &'hast HAST,
+        id: &HAST::IdN,
+    ) -> Option<u64>
+    where
+        HAST::T: WithStats
+            + WithHashs<HK = SyntaxNodeHashsKinds>
+            + WithMetaData<compo::SizeNoSpaces>
+            + WithMetaData<Mcc>,
+    {
+        let n = stores.node_store().resolve(id);
+        match self {
+            Metadata::Size => n.size().to_u64(),
+            // like the size, leaves do not store it
+            Metadata::SizeNoSpaces => Some(
+                WithMetaData::<compo::SizeNoSpaces>::get_metadata(&n).map_or(1, |x| x.0 as u64),
+            ),
+            Metadata::Height => n.height().to_u64(),
+            Metadata::StructHash => n.hash(&SyntaxNodeHashsKinds::Struct).to_u64(),
+            Metadata::LabelHash => n.hash(&SyntaxNodeHashsKinds::Label).to_u64(),
+            Metadata::SyntaxHash => n.hash(&SyntaxNodeHashsKinds::Syntax).to_u64(),
+            Metadata::Cyclomatic => {
+                WithMetaData::<Mcc>::get_metadata(&n).map(|x| x.complexity() as u64)
+            }
+        }
+    }
+}
+
+/// Checks `value` against a constraint like `10`, `>10`, `>=10`, `<10` or `<=10`
+fn satisfies(value: u64, constraint: &str) -> bool {
+    let parse = |x: &str| x.trim().parse::<u64>().ok();
+    if let Some(x) = constraint.strip_prefix(">=").and_then(parse) {
+        value >= x
+    } else if let Some(x) = constraint.strip_prefix("<=").and_then(parse) {
+        value <= x
+    } else if let Some(x) = constraint.strip_prefix('>').and_then(parse) {
+        value > x
+    } else if let Some(x) = constraint.strip_prefix('<').and_then(parse) {
+        value < x
+    } else {
+        parse(constraint) == Some(value)
+    }
+}
+
+/// Properties to filter on each [`Metadata`], with `#is?` and `#is-not?`.
+///
+/// Without a value, eg. `(#is? @m cyclomatic)`, the property holds if the node stores this metadata.
+pub fn predicates<'hast, HAST: HyperAST<'hast>>() -> CustomPredicates<Node<'hast, HAST>>
+where
+    HAST::IdN: Copy,
+    HAST::T: WithStats
+        + WithHashs<HK = SyntaxNodeHashsKinds>
+        + WithMetaData<compo::SizeNoSpaces>
+        + WithMetaData<Mcc>,
+{
+    let mut predicates = CustomPredicates::default();
+    for md in Metadata::ALL {
+        predicates.register_property(md.as_str(), move |n: &Node<'hast, HAST>, constraint| {
+            let Some(value) = md.get(n.stores, n.pos.node().unwrap()) else {
+                return false;
+            };
+            constraint.map_or(true, |c| satisfies(value, c))
+        });
+    }
+    predicates
+}
+
+/// Whether an enabled pattern of `query` requests a [`Metadata`] with `(#set! @capture key)`
+pub fn requests_metadata(query: &Query) -> bool {
+    (0..query.pattern_count())
+        .map(PatternId::new)
+        .filter(|pid| query.enabled_pattern_index(*pid).is_some())
+        .flat_map(|pid| query.property_settings_for_pattern_id(pid))
+        .any(|p| p.capture_id.is_some() && Metadata::from_key(&p.key).is_some())
+}
+
+/// Values of the metadata requested with `(#set! @capture key)`, for each node captured in `m`.
+///
+/// Keys that are not a [`Metadata`] are skipped, they might be used for something else.
+pub fn requested_metadata<'hast, HAST: HyperAST<'hast>>(
+    query: &Query,
+    m: &QueryMatch<Node<'hast, HAST>>,
+) -> Vec<(CaptureId, Metadata, Option<u64>)>
+where
+    HAST::IdN: Copy,
+    HAST::T: WithStats
+        + WithHashs<HK = SyntaxNodeHashsKinds>
+        + WithMetaData<compo::SizeNoSpaces>
+        + WithMetaData<Mcc>,
+{
+    let mut result = vec![];
+    for p in query.property_settings_for_pattern_id(m.pattern_index) {
+        let (Some(md), Some(i)) = (Metadata::from_key(&p.key), p.capture_id) else {
+            continue;
+        };
+        let i = CaptureId::new(i as u32);
+        for n in m.captures.nodes_for_capture_index(i) {
+            result.push((i, md, md.get(n.stores, n.pos.node().unwrap())));
+        }
+    }
+    result
+}
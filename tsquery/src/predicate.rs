@@ -1,6 +1,6 @@
-use std::fmt::Debug;
+use std::{collections::HashMap, fmt::Debug};
 
-use super::TextPredicateCapture;
+use super::{indexed::CaptureId, QueryMatch, TextPredicateCapture};
 
 /// [`PerPattern`] Builder
 pub struct PerPatternBuilder<P> {
@@ -183,3 +183,107 @@ impl PartialEq for ImmediateTextPredicate {
         }
     }
 }
+
+/// Argument given to a custom predicate
+pub enum PredicateArg<'a, Node> {
+    /// The nodes captured with this name in the match
+    Capture(Vec<&'a Node>),
+    String(&'a str),
+}
+
+type PredicateFn<Node> = Box<dyn Fn(&[PredicateArg<'_, Node>]) -> bool + Send + Sync>;
+type PropertyFn<Node> = Box<dyn Fn(&Node, Option<&str>) -> bool + Send + Sync>;
+
+/// Registry of predicates implemented in Rust, eg. `#size-gt? @body 50`,
+/// and of node properties checked by `#is?` and `#is-not?`, eg. `#is? @body size ">50"`.
+///
+/// They are evaluated on the matches given by [`crate::Query::matches_with`].
+/// Like other unknown predicates, the ones that are not registered are ignored.
+pub struct CustomPredicates<Node> {
+    predicates: HashMap<Box<str>, PredicateFn<Node>>,
+    properties: HashMap<Box<str>, PropertyFn<Node>>,
+}
+
+impl<Node> Default for CustomPredicates<Node> {
+    fn default() -> Self {
+        Self {
+            predicates: Default::default(),
+            properties: Default::default(),
+        }
+    }
+}
+
+impl<Node> CustomPredicates<Node> {
+    /// `name` is the operator as written in queries without the `#`, eg. `is-public?`
+    pub fn register(
+        &mut self,
+        name: &str,
+        predicate: impl Fn(&[PredicateArg<'_, Node>]) -> bool + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.predicates.insert(name.into(), Box::new(predicate));
+        self
+    }
+
+    /// `key` is the property as written in `(#is? @capture key value)`,
+    /// `property` receives each captured node and the optional value.
+    ///
+    /// Properties given without a capture are ignored.
+    pub fn register_property(
+        &mut self,
+        key: &str,
+        property: impl Fn(&Node, Option<&str>) -> bool + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.properties.insert(key.into(), Box::new(property));
+        self
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.predicates.contains_key(name)
+    }
+
+    /// Operators used in `query` that are neither builtin nor registered
+    pub fn missing<'q>(&self, query: &'q crate::Query) -> Vec<&'q str> {
+        let mut missing: Vec<&str> = vec![];
+        for p in query.general_predicates.0.iter().flat_map(|x| x.iter()) {
+            if !self.contains(&p.operator) && !missing.contains(&&*p.operator) {
+                missing.push(&p.operator);
+            }
+        }
+        missing
+    }
+
+    pub(crate) fn satisfied_by<'a>(
+        &self,
+        m: &QueryMatch<Node>,
+        mut predicates: impl Iterator<Item = &'a QueryPredicate>,
+        mut properties: impl Iterator<Item = &'a (tree_sitter::QueryProperty, IsPositive)>,
+    ) -> bool {
+        let properties_hold = properties.all(|(p, is_positive)| {
+            let (Some(f), Some(i)) = (self.properties.get(&p.key), p.capture_id) else {
+                return true;
+            };
+            m.captures
+                .nodes_for_capture_index(CaptureId::new(i as u32))
+                .all(|n| f(n, p.value.as_deref()) == *is_positive)
+        });
+        properties_hold
+            && predicates.all(|p| {
+                let Some(f) = self.predicates.get(&p.operator) else {
+                    return true;
+                };
+                let args: Vec<_> = p
+                    .args
+                    .iter()
+                    .map(|arg| match arg {
+                        QueryPredicateArg::Capture(i) => PredicateArg::Capture(
+                            m.captures
+                                .nodes_for_capture_index(CaptureId::new(*i))
+                                .collect(),
+                        ),
+                        QueryPredicateArg::String(s) => PredicateArg::String(s),
+                    })
+                    .collect();
+                f(&args)
+            })
+    }
+}
@@ -758,6 +758,27 @@ impl Query {
     ) -> impl Iterator<Item = &'a TextPredicateCapture> {
         self.text_predicates.preds_for_patern_id(pattern_index)
     }
+
+    pub(crate) fn property_predicates_for_pattern_id<'a>(
+        &'a self,
+        pattern_index: indexed::PatternId,
+    ) -> impl Iterator<Item = &'a (QueryProperty, bool)> {
+        self.property_predicates.preds_for_patern_id(pattern_index)
+    }
+
+    pub(crate) fn property_settings_for_pattern_id<'a>(
+        &'a self,
+        pattern_index: indexed::PatternId,
+    ) -> impl Iterator<Item = &'a QueryProperty> {
+        self.property_settings.preds_for_patern_id(pattern_index)
+    }
+
+    pub(crate) fn general_predicates_for_pattern_id<'a>(
+        &'a self,
+        pattern_index: indexed::PatternId,
+    ) -> impl Iterator<Item = &'a crate::predicate::QueryPredicate> {
+        self.general_predicates.preds_for_patern_id(pattern_index)
+    }
 }
 
 impl Query {
@@ -1420,6 +1441,16 @@ impl Query {
         let i = self.enabled_pattern_map[pid.to_usize()];
         (i != u16::MAX).then_some(i)
     }
+    /// A pattern is local if each of its matches only depends on the subtree where it starts,
+    /// ie. it has a single root node and cannot span sibling nodes.
+    pub fn is_pattern_local(&self, pid: PatternId) -> bool {
+        !self.patterns[pid].is_non_local
+            && self
+                .pattern_map
+                .iter()
+                .filter(|p| p.pattern_index == pid)
+                .all(|p| p.is_rooted)
+    }
     pub fn get_each_pat_start_byte(&self) -> Vec<usize> {
         let mut r = vec![];
         for (i, j) in self.enabled_pattern_map.iter().enumerate() {